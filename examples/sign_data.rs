@@ -35,6 +35,7 @@ fn main() {
         signing_hash: CryptoHash::Sha2_512,
         ephemeral: false,
         non_exportable: false,
+        ..Default::default()
     };
 
     let key_pair_handle = provider.create_key_pair(key_pair_capabilities).unwrap();
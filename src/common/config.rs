@@ -1,11 +1,13 @@
 #![allow(dead_code)]
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::{
     cmp::{Eq, Ord, PartialEq, PartialOrd},
     collections::HashSet,
     future::Future,
+    ops::{BitAnd, BitOr, BitOrAssign},
     pin::Pin,
     sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use serde::{Deserialize, Serialize};
@@ -14,10 +16,10 @@ use strum::{EnumDiscriminants, EnumIter, EnumString, IntoStaticStr};
 use zeroize::Zeroize;
 
 use super::crypto::algorithms::{
-    encryption::{AsymmetricKeySpec, Cipher},
+    encryption::{AsymmetricKeySpec, Cipher, PointEncoding, RsaPadding, SignatureEncoding},
     hashes::CryptoHash,
 };
-use super::{KeyHandle, KeyPairHandle};
+use super::{error::CalError, KeyHandle, KeyPairHandle};
 
 /// A type alias for a pinned, heap-allocated, dynamically dispatched future that is `Send`.
 ///
@@ -108,6 +110,140 @@ pub struct KeySpec {
     ///
     /// Some providers do not allow exporting keys at all, even if set to `false`.
     pub non_exportable: bool,
+
+    /// Unix time in seconds before which the key cannot be used.
+    #[serde(default)]
+    pub not_before: Option<u64>,
+
+    /// Unix time in seconds after which the key cannot be used to protect data, e.g. encrypt.
+    #[serde(default)]
+    pub not_after: Option<u64>,
+
+    /// Unix time in seconds after which the key cannot be used to process data either, e.g. decrypt.
+    ///
+    /// If unset, data protected with the key can be processed after [KeySpec::not_after].
+    #[serde(default)]
+    pub process_not_after: Option<u64>,
+
+    /// Operations the key may be used for.
+    #[serde(default)]
+    pub key_usage: KeyUsage,
+}
+
+/// Operations a key or key pair may be used for, as a bitset.
+///
+/// Usages are combined with `|`, e.g. `KeyUsage::ENCRYPT | KeyUsage::DECRYPT`.
+/// The default allows all operations.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Zeroize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+/// flutter_rust_bridge:non_opaque
+pub struct KeyUsage(pub u32);
+
+impl KeyUsage {
+    pub const NONE: Self = Self(0);
+    /// Encrypting data.
+    pub const ENCRYPT: Self = Self(1);
+    /// Decrypting data.
+    pub const DECRYPT: Self = Self(1 << 1);
+    /// Signing data or calculating HMACs.
+    pub const SIGN: Self = Self(1 << 2);
+    /// Verifying signatures or HMACs.
+    pub const VERIFY: Self = Self(1 << 3);
+    /// Deriving further keys.
+    pub const DERIVE: Self = Self(1 << 4);
+    /// Wrapping and unwrapping other keys.
+    pub const WRAP: Self = Self(1 << 5);
+    /// Key agreement and key encapsulation.
+    pub const AGREE: Self = Self(1 << 6);
+    pub const ALL: Self = Self((1 << 7) - 1);
+    /// Protecting and processing data, given to keys that are derived, agreed or encapsulated.
+    pub(crate) const DATA: Self =
+        Self(Self::ENCRYPT.0 | Self::DECRYPT.0 | Self::SIGN.0 | Self::VERIFY.0);
+
+    const NAMES: [(KeyUsage, &'static str); 7] = [
+        (Self::ENCRYPT, "encrypt"),
+        (Self::DECRYPT, "decrypt"),
+        (Self::SIGN, "sign"),
+        (Self::VERIFY, "verify"),
+        (Self::DERIVE, "derive"),
+        (Self::WRAP, "wrap"),
+        (Self::AGREE, "agree"),
+    ];
+
+    /// Returns `true` if all usages in `other` are allowed.
+    pub const fn contains(self, other: KeyUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Fails if `usage` is not allowed for the key `key_id`.
+    pub(crate) fn require(self, usage: KeyUsage, key_id: &str) -> Result<(), CalError> {
+        if self.contains(usage) {
+            Ok(())
+        } else {
+            Err(CalError::key_usage_not_allowed(key_id, usage))
+        }
+    }
+}
+
+impl Default for KeyUsage {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl BitOr for KeyUsage {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for KeyUsage {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for KeyUsage {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl fmt::Display for KeyUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Self::NAMES
+            .iter()
+            .filter(|(usage, _)| self.contains(*usage))
+            .map(|(_, name)| *name)
+            .collect();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(" | "))
+        }
+    }
+}
+
+/// Encryptions done with a symmetric key and the safety limits of its cipher.
+///
+/// See [KeyHandle::encryption_usage](crate::common::KeyHandle::encryption_usage).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+/// flutter_rust_bridge:non_opaque
+pub struct EncryptionUsage {
+    /// Number of encryptions, which may include encryptions reserved but not used before a restart.
+    pub encryptions: u64,
+
+    /// Maximum number of encryptions with random nonces.
+    pub random_nonce_limit: u64,
+
+    /// Maximum number of encryptions with counter nonces.
+    pub counter_nonce_limit: u64,
 }
 
 /// Struct used to configure key pairs.
@@ -127,11 +263,145 @@ pub struct KeyPairSpec {
     /// Hash function used for signing and encrypting.
     pub signing_hash: CryptoHash,
 
+    /// Padding of signatures with `RSA` key pairs, ignored for other algorithms.
+    #[serde(default)]
+    pub rsa_padding: RsaPadding,
+
+    /// Encoding of signatures with ECDSA key pairs, ignored for other algorithms.
+    #[serde(default)]
+    pub signature_encoding: SignatureEncoding,
+
+    /// Encoding of the elliptic curve points returned as public key, ignored for other
+    /// algorithms. Imports accept both encodings. Providers that cannot compress points refuse
+    /// [PointEncoding::Compressed].
+    #[serde(default)]
+    pub point_encoding: PointEncoding,
+
     /// If set to true, the key pair will be discarded after the handle is dropped.
     pub ephemeral: bool,
 
     /// If set to true, the key can't be exported (also software keys)
     pub non_exportable: bool,
+
+    /// Unix time in seconds before which the key pair cannot be used.
+    #[serde(default)]
+    pub not_before: Option<u64>,
+
+    /// Unix time in seconds after which the key pair cannot be used to protect data, e.g. sign.
+    #[serde(default)]
+    pub not_after: Option<u64>,
+
+    /// Unix time in seconds after which the key pair cannot be used to process data either,
+    /// e.g. verify signatures.
+    ///
+    /// If unset, data protected with the key pair can be processed after [KeyPairSpec::not_after].
+    #[serde(default)]
+    pub process_not_after: Option<u64>,
+
+    /// Operations the key pair may be used for.
+    #[serde(default)]
+    pub key_usage: KeyUsage,
+}
+
+/// Use of a key, which is checked against its validity period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyUse {
+    /// Encrypting, signing or authenticating data.
+    Protect,
+    /// Decrypting or verifying data protected before.
+    Process,
+}
+
+/// Validity period of a key or key pair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Validity {
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+    process_not_after: Option<u64>,
+}
+
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+impl Validity {
+    /// Checks that the period is not empty and that processing ends after protecting.
+    pub(crate) fn check_period(&self) -> Result<(), CalError> {
+        let ordered = |start: Option<u64>, end: Option<u64>| match (start, end) {
+            (Some(start), Some(end)) => start <= end,
+            _ => true,
+        };
+        if !ordered(self.not_before, self.not_after)
+            || !ordered(self.not_before, self.process_not_after)
+            || !ordered(self.not_after, self.process_not_after)
+        {
+            return Err(CalError::bad_parameter(
+                "The validity period has to satisfy not_before <= not_after <= process_not_after"
+                    .to_owned(),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the key can be used for `key_use` at `now`.
+    pub(crate) fn check(
+        &self,
+        key_use: KeyUse,
+        now: u64,
+        key_id: impl FnOnce() -> String,
+    ) -> Result<(), CalError> {
+        if self.not_before.is_some_and(|not_before| now < not_before) {
+            return Err(CalError::key_not_yet_valid(key_id()));
+        }
+        let end = match key_use {
+            KeyUse::Protect => self.not_after,
+            KeyUse::Process => self.process_not_after,
+        };
+        if end.is_some_and(|end| now > end) {
+            return Err(CalError::key_expired(key_id()));
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the key cannot be used to protect data `within` seconds after `now`.
+    pub(crate) fn expires_within(&self, now: u64, within: u64) -> bool {
+        self.not_after
+            .is_some_and(|not_after| not_after < now.saturating_add(within))
+    }
+}
+
+impl KeySpec {
+    pub(crate) fn validity(&self) -> Validity {
+        Validity {
+            not_before: self.not_before,
+            not_after: self.not_after,
+            process_not_after: self.process_not_after,
+        }
+    }
+}
+
+impl KeyPairSpec {
+    pub(crate) fn validity(&self) -> Validity {
+        Validity {
+            not_before: self.not_before,
+            not_after: self.not_after,
+            process_not_after: self.process_not_after,
+        }
+    }
+}
+
+impl Spec {
+    pub(crate) fn validity(&self) -> Validity {
+        match self {
+            Spec::KeySpec(spec) => spec.validity(),
+            Spec::KeyPairSpec(spec) => spec.validity(),
+        }
+    }
 }
 
 /// Capabilities of a Provider
@@ -0,0 +1,138 @@
+//! `COSE_Encrypt0` messages with an implicit recipient.
+//!
+//! The plain text is encrypted with the AEAD cipher of a [KeyHandle], which has to match the
+//! algorithm. A random IV is generated for every message and put in the unprotected header,
+//! partial IVs are not supported.
+
+use coset::{CoseEncrypt0, CoseEncrypt0Builder};
+
+use super::{allowed_algorithm, decode, encode, invalid, protected_header, Headers};
+use crate::common::{crypto::algorithms::encryption::Cipher, error::CalError, KeyHandle, Provider};
+
+/// Supported content encryption algorithms (RFC 9053, section 4).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// AES-GCM with a 128 bit key and a 128 bit tag.
+    A128GCM,
+    /// AES-GCM with a 256 bit key and a 128 bit tag.
+    A256GCM,
+    ChaCha20Poly1305,
+}
+
+impl Algorithm {
+    fn cipher(self) -> Cipher {
+        match self {
+            Self::A128GCM => Cipher::AesGcm128,
+            Self::A256GCM => Cipher::AesGcm256,
+            Self::ChaCha20Poly1305 => Cipher::ChaCha20Poly1305,
+        }
+    }
+
+    fn check_key(self, key: &KeyHandle) -> Result<(), CalError> {
+        if key.spec().cipher != self.cipher() {
+            return Err(CalError::bad_parameter(
+                format!("Key does not match the COSE algorithm {self:?}"),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl super::Algorithm for Algorithm {
+    fn iana(self) -> super::iana::Algorithm {
+        use super::iana::Algorithm as Iana;
+        match self {
+            Self::A128GCM => Iana::A128GCM,
+            Self::A256GCM => Iana::A256GCM,
+            Self::ChaCha20Poly1305 => Iana::ChaCha20Poly1305,
+        }
+    }
+}
+
+/// Headers and plain text of a decrypted `COSE_Encrypt0` message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decrypted {
+    pub headers: Headers,
+    pub plaintext: Vec<u8>,
+}
+
+/// Encrypts `plaintext` and returns the `COSE_Encrypt0` message.
+///
+/// `alg` is added to the protected header and a random IV from `provider` to the unprotected
+/// header of `headers`, which must not contain an IV. `external_aad` is authenticated but not part
+/// of the message.
+pub fn encrypt0(
+    provider: &Provider,
+    key: &KeyHandle,
+    alg: Algorithm,
+    headers: &Headers,
+    plaintext: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    alg.check_key(key)?;
+    let protected = protected_header(headers, alg)?;
+    if [&protected, &headers.unprotected]
+        .iter()
+        .any(|header| !header.iv.is_empty() || !header.partial_iv.is_empty())
+    {
+        return Err(invalid("the IV is generated and must not be set"));
+    }
+
+    let mut unprotected = headers.unprotected.clone();
+    unprotected.iv = provider.get_random(alg.cipher().iv_len());
+    let encrypt0 = CoseEncrypt0Builder::new()
+        .protected(protected)
+        .unprotected(unprotected.clone())
+        .try_create_ciphertext(plaintext, external_aad, |plaintext, aad| {
+            key.encrypt_with_aad(plaintext, &unprotected.iv, aad)
+        })?
+        .build();
+    encode(encrypt0, headers.tagged)
+}
+
+/// Decrypts a tagged or untagged `COSE_Encrypt0` message.
+///
+/// Fails unless the protected algorithm is in `allowed` and matches the spec of `key`. The IV may
+/// be in the protected or unprotected header.
+pub fn decrypt0(
+    message: &[u8],
+    key: &KeyHandle,
+    allowed: &[Algorithm],
+    external_aad: &[u8],
+) -> Result<Decrypted, CalError> {
+    let (encrypt0, tagged) = decode::<CoseEncrypt0>(message)?;
+    let protected = &encrypt0.protected.header;
+    let alg = allowed_algorithm(protected, allowed)?;
+    alg.check_key(key)?;
+
+    if !protected.partial_iv.is_empty() || !encrypt0.unprotected.partial_iv.is_empty() {
+        return Err(CalError::unsupported_algorithm(
+            "COSE messages with partial IV".to_owned(),
+        ));
+    }
+    let iv = match (protected.iv.is_empty(), encrypt0.unprotected.iv.is_empty()) {
+        (false, true) => &protected.iv,
+        (true, false) => &encrypt0.unprotected.iv,
+        _ => return Err(invalid("expected exactly one IV")),
+    };
+    if iv.len() != alg.cipher().iv_len() {
+        return Err(invalid(format!("invalid IV length {}", iv.len())));
+    }
+    if encrypt0.ciphertext.is_none() {
+        return Err(invalid("the cipher text is detached"));
+    }
+
+    let plaintext = encrypt0.decrypt(external_aad, |ciphertext, aad| {
+        key.decrypt_with_aad(ciphertext, iv, aad)
+    })?;
+    Ok(Decrypted {
+        headers: Headers {
+            protected: encrypt0.protected.header.clone(),
+            unprotected: encrypt0.unprotected.clone(),
+            tagged,
+        },
+        plaintext,
+    })
+}
@@ -0,0 +1,324 @@
+//! Conversion of keys to and from `COSE_Key` maps.
+//!
+//! `COSE_Key`s are converted to and from [Jwk]s, which have the same members, and imported and
+//! exported like in [jwk]. `OKP`, `EC2`, `RSA` and `Symmetric` keys are supported, compressed
+//! `EC2` points are not. The base IV and parameters of other key types are ignored.
+//!
+//! [CoseKey] does not zeroize private parameters, drop keys with private material as early as
+//! possible.
+
+use std::collections::BTreeSet;
+
+use coset::{
+    cbor::Value,
+    iana::{self, EnumI64},
+    Algorithm as CoseAlgorithm, KeyOperation as CoseKeyOperation, KeyType as CoseKeyType, Label,
+};
+
+use super::CoseKey;
+use crate::common::{
+    config::{KeyPairSpec, KeySpec},
+    crypto::algorithms::encryption::Cipher,
+    error::CalError,
+    jose::jwk::{self, Curve, Jwk, KeyOperation, KeyType},
+    KeyHandle, KeyPairHandle, Provider,
+};
+
+/// Label of the curve of `OKP` and `EC2` keys.
+const CRV: i64 = -1;
+
+/// COSE algorithms with the name of the same JOSE algorithm.
+const ALGORITHMS: [(iana::Algorithm, &str); 15] = [
+    (iana::Algorithm::ES256, "ES256"),
+    (iana::Algorithm::ES384, "ES384"),
+    (iana::Algorithm::ES512, "ES512"),
+    (iana::Algorithm::EdDSA, "EdDSA"),
+    (iana::Algorithm::PS256, "PS256"),
+    (iana::Algorithm::PS384, "PS384"),
+    (iana::Algorithm::PS512, "PS512"),
+    (iana::Algorithm::RS256, "RS256"),
+    (iana::Algorithm::HMAC_256_256, "HS256"),
+    (iana::Algorithm::HMAC_384_384, "HS384"),
+    (iana::Algorithm::HMAC_512_512, "HS512"),
+    (iana::Algorithm::A128GCM, "A128GCM"),
+    (iana::Algorithm::A256GCM, "A256GCM"),
+    (iana::Algorithm::A128KW, "A128KW"),
+    (iana::Algorithm::A256KW, "A256KW"),
+];
+
+const CURVES: [(iana::EllipticCurve, Curve); 8] = [
+    (iana::EllipticCurve::P_256, Curve::P256),
+    (iana::EllipticCurve::P_384, Curve::P384),
+    (iana::EllipticCurve::P_521, Curve::P521),
+    (iana::EllipticCurve::Secp256k1, Curve::Secp256k1),
+    (iana::EllipticCurve::Ed25519, Curve::Ed25519),
+    (iana::EllipticCurve::X25519, Curve::X25519),
+    (iana::EllipticCurve::Ed448, Curve::Ed448),
+    (iana::EllipticCurve::X448, Curve::X448),
+];
+
+const KEY_TYPES: [(iana::KeyType, KeyType); 4] = [
+    (iana::KeyType::EC2, KeyType::Ec),
+    (iana::KeyType::OKP, KeyType::Okp),
+    (iana::KeyType::RSA, KeyType::Rsa),
+    (iana::KeyType::Symmetric, KeyType::Oct),
+];
+
+fn invalid(message: impl std::fmt::Display) -> CalError {
+    CalError::bad_parameter(format!("Invalid COSE key: {message}"), true, None)
+}
+
+/// Byte string parameters of a key type with their labels.
+fn parameters(jwk: &mut Jwk) -> Vec<(i64, &mut Option<Vec<u8>>)> {
+    match jwk.kty {
+        KeyType::Ec => vec![(-2, &mut jwk.x), (-3, &mut jwk.y), (-4, &mut jwk.d)],
+        KeyType::Okp => vec![(-2, &mut jwk.x), (-4, &mut jwk.d)],
+        KeyType::Rsa => vec![
+            (-1, &mut jwk.n),
+            (-2, &mut jwk.e),
+            (-3, &mut jwk.d),
+            (-4, &mut jwk.p),
+            (-5, &mut jwk.q),
+            (-6, &mut jwk.dp),
+            (-7, &mut jwk.dq),
+            (-8, &mut jwk.qi),
+        ],
+        KeyType::Oct => vec![(-1, &mut jwk.k)],
+    }
+}
+
+fn key_operation(operation: &CoseKeyOperation) -> Result<KeyOperation, CalError> {
+    use iana::KeyOperation as Iana;
+    let CoseKeyOperation::Assigned(assigned) = operation else {
+        return Err(invalid(format!("unknown key operation {operation:?}")));
+    };
+    Ok(match assigned {
+        Iana::Sign | Iana::MacCreate => KeyOperation::Sign,
+        Iana::Verify | Iana::MacVerify => KeyOperation::Verify,
+        Iana::Encrypt => KeyOperation::Encrypt,
+        Iana::Decrypt => KeyOperation::Decrypt,
+        Iana::WrapKey => KeyOperation::WrapKey,
+        Iana::UnwrapKey => KeyOperation::UnwrapKey,
+        Iana::DeriveKey => KeyOperation::DeriveKey,
+        Iana::DeriveBits => KeyOperation::DeriveBits,
+        _ => return Err(invalid(format!("unknown key operation {operation:?}"))),
+    })
+}
+
+fn cose_key_operation(operation: KeyOperation, kty: KeyType) -> CoseKeyOperation {
+    use iana::KeyOperation as Iana;
+    CoseKeyOperation::Assigned(match (operation, kty) {
+        (KeyOperation::Sign, KeyType::Oct) => Iana::MacCreate,
+        (KeyOperation::Verify, KeyType::Oct) => Iana::MacVerify,
+        (KeyOperation::Sign, _) => Iana::Sign,
+        (KeyOperation::Verify, _) => Iana::Verify,
+        (KeyOperation::Encrypt, _) => Iana::Encrypt,
+        (KeyOperation::Decrypt, _) => Iana::Decrypt,
+        (KeyOperation::WrapKey, _) => Iana::WrapKey,
+        (KeyOperation::UnwrapKey, _) => Iana::UnwrapKey,
+        (KeyOperation::DeriveKey, _) => Iana::DeriveKey,
+        (KeyOperation::DeriveBits, _) => Iana::DeriveBits,
+    })
+}
+
+/// Converts a `COSE_Key` to a JWK.
+///
+/// Fails for key ids which are not UTF-8 and for algorithms without JOSE equivalent.
+pub fn to_jwk(key: &CoseKey) -> Result<Jwk, CalError> {
+    let kty = KEY_TYPES
+        .iter()
+        .find(|(cose, _)| key.kty == CoseKeyType::Assigned(*cose))
+        .map(|(_, kty)| *kty)
+        .ok_or_else(|| {
+            CalError::unsupported_algorithm(format!("COSE keys of type {:?}", key.kty))
+        })?;
+
+    let mut jwk = Jwk::new(kty);
+    if !key.key_id.is_empty() {
+        jwk.kid = Some(
+            String::from_utf8(key.key_id.clone())
+                .map_err(|_| invalid("the key id is not UTF-8"))?,
+        );
+    }
+    if let Some(alg) = &key.alg {
+        let name = ALGORITHMS
+            .iter()
+            .find(|(cose, _)| *alg == CoseAlgorithm::Assigned(*cose))
+            .map(|(_, name)| *name)
+            .ok_or_else(|| CalError::unsupported_algorithm(format!("COSE keys for {alg:?}")))?;
+        jwk.alg = Some(name.to_owned());
+    }
+    if !key.key_ops.is_empty() {
+        let mut operations = Vec::new();
+        for operation in &key.key_ops {
+            let operation = key_operation(operation)?;
+            if !operations.contains(&operation) {
+                operations.push(operation);
+            }
+        }
+        jwk.key_ops = Some(operations);
+    }
+
+    for (label, value) in &key.params {
+        let Label::Int(label) = *label else {
+            continue;
+        };
+        if label == CRV && matches!(kty, KeyType::Ec | KeyType::Okp) {
+            let crv = value
+                .as_integer()
+                .and_then(|crv| i64::try_from(crv).ok())
+                .and_then(iana::EllipticCurve::from_i64)
+                .and_then(|crv| CURVES.iter().find(|(cose, _)| *cose == crv))
+                .ok_or_else(|| {
+                    CalError::unsupported_algorithm(format!("COSE keys with curve {value:?}"))
+                })?;
+            if jwk.crv.replace(crv.1).is_some() {
+                return Err(invalid("duplicate curve"));
+            }
+            continue;
+        }
+
+        let Some((_, member)) = parameters(&mut jwk)
+            .into_iter()
+            .find(|(member_label, _)| *member_label == label)
+        else {
+            continue;
+        };
+        let bytes = match value {
+            Value::Bytes(bytes) => bytes.clone(),
+            Value::Bool(_) if kty == KeyType::Ec && label == -3 => {
+                return Err(CalError::unsupported_algorithm(
+                    "COSE keys with compressed points".to_owned(),
+                ))
+            }
+            _ => return Err(invalid(format!("parameter {label} is not a byte string"))),
+        };
+        if member.replace(bytes).is_some() {
+            return Err(invalid(format!("duplicate parameter {label}")));
+        }
+    }
+
+    jwk.check()?;
+    Ok(jwk)
+}
+
+/// Converts a JWK to a `COSE_Key`.
+///
+/// Fails for algorithms without COSE equivalent.
+pub fn from_jwk(jwk: &Jwk) -> Result<CoseKey, CalError> {
+    jwk.check()?;
+    let mut key = CoseKey {
+        kty: KEY_TYPES
+            .iter()
+            .find(|(_, kty)| *kty == jwk.kty)
+            .map(|(cose, _)| CoseKeyType::Assigned(*cose))
+            .expect("All JWK key types have a COSE key type"),
+        key_id: jwk.kid.clone().unwrap_or_default().into_bytes(),
+        ..Default::default()
+    };
+    if let Some(alg) = &jwk.alg {
+        key.alg = Some(
+            ALGORITHMS
+                .iter()
+                .find(|(_, name)| name == alg)
+                .map(|(cose, _)| CoseAlgorithm::Assigned(*cose))
+                .ok_or_else(|| CalError::unsupported_algorithm(format!("COSE keys for {alg}")))?,
+        );
+    }
+    key.key_ops = jwk
+        .key_ops
+        .iter()
+        .flatten()
+        .map(|operation| cose_key_operation(*operation, jwk.kty))
+        .collect::<BTreeSet<_>>();
+
+    if let Some(crv) = jwk.crv {
+        let (crv, _) = CURVES
+            .iter()
+            .find(|(_, other)| *other == crv)
+            .expect("All JWK curves have a COSE curve");
+        key.params
+            .push((Label::Int(CRV), Value::from(crv.to_i64())));
+    }
+    let mut jwk = jwk.clone();
+    for (label, member) in parameters(&mut jwk) {
+        if let Some(value) = member.take() {
+            key.params.push((Label::Int(label), Value::Bytes(value)));
+        }
+    }
+    Ok(key)
+}
+
+/// Exports the public key of `key_pair` as `COSE_Key`.
+pub fn export_public_key(key_pair: &KeyPairHandle) -> Result<CoseKey, CalError> {
+    from_jwk(&jwk::export_public_key(key_pair)?)
+}
+
+/// Exports the private key of `key_pair` as `COSE_Key`.
+///
+/// Fails like [KeyPairHandle::extract_key] for non exportable keys.
+pub fn export_key_pair(key_pair: &KeyPairHandle) -> Result<CoseKey, CalError> {
+    from_jwk(&jwk::export_key_pair(key_pair)?)
+}
+
+/// Exports a symmetric key as `Symmetric` `COSE_Key`.
+///
+/// Fails like [KeyHandle::extract_key] for non exportable keys.
+pub fn export_key(key: &KeyHandle) -> Result<CoseKey, CalError> {
+    from_jwk(&jwk::export_key(key)?)
+}
+
+fn is_chacha(key: &CoseKey) -> bool {
+    key.alg == Some(CoseAlgorithm::Assigned(iana::Algorithm::ChaCha20Poly1305))
+}
+
+/// Converts `key` for import. The key id is not needed and may be binary, `ChaCha20/Poly1305`
+/// has no JOSE name and is handled by [import_key].
+fn import_jwk(key: &CoseKey) -> Result<Jwk, CalError> {
+    to_jwk(&CoseKey {
+        key_id: Vec::new(),
+        alg: key.alg.clone().filter(|_| !is_chacha(key)),
+        ..key.clone()
+    })
+}
+
+/// Imports an `OKP`, `EC2` or `RSA` `COSE_Key` with the spec inferred like
+/// [Jwk::key_pair_spec].
+///
+/// Public keys are imported as public keys.
+pub fn import_key_pair(provider: &mut Provider, key: &CoseKey) -> Result<KeyPairHandle, CalError> {
+    jwk::import_key_pair(provider, &import_jwk(key)?)
+}
+
+/// Imports an `OKP`, `EC2` or `RSA` `COSE_Key` with `spec`.
+pub fn import_key_pair_with_spec(
+    provider: &mut Provider,
+    key: &CoseKey,
+    spec: KeyPairSpec,
+) -> Result<KeyPairHandle, CalError> {
+    jwk::import_key_pair_with_spec(provider, &import_jwk(key)?, spec)
+}
+
+/// Imports a `Symmetric` `COSE_Key` with the spec inferred like [Jwk::key_spec].
+///
+/// The `ChaCha20/Poly1305` algorithm selects [Cipher::ChaCha20Poly1305].
+pub fn import_key(provider: &mut Provider, key: &CoseKey) -> Result<KeyHandle, CalError> {
+    let jwk = import_jwk(key)?;
+    let mut spec = jwk.key_spec()?;
+    if is_chacha(key) {
+        if jwk.k.as_ref().map(Vec::len) != Some(Cipher::ChaCha20Poly1305.len()) {
+            return Err(invalid("ChaCha20/Poly1305 keys are 32 bytes long"));
+        }
+        spec.cipher = Cipher::ChaCha20Poly1305;
+    }
+    jwk::import_key_with_spec(provider, &jwk, spec)
+}
+
+/// Imports a `Symmetric` `COSE_Key` with `spec`.
+pub fn import_key_with_spec(
+    provider: &mut Provider,
+    key: &CoseKey,
+    spec: KeySpec,
+) -> Result<KeyHandle, CalError> {
+    jwk::import_key_with_spec(provider, &import_jwk(key)?, spec)
+}
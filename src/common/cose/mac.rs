@@ -0,0 +1,110 @@
+//! `COSE_Mac0` messages with an implicit recipient.
+//!
+//! The `MAC_structure` is authenticated with the HMAC of a [KeyHandle], whose signing hash has to
+//! match the algorithm. Truncated tags like `HMAC 256/64` are not supported.
+
+use coset::{CoseMac0, CoseMac0Builder};
+
+use super::{allowed_algorithm, decode, encode, invalid, protected_header, Headers, Verified};
+use crate::common::{crypto::algorithms::hashes::CryptoHash, error::CalError, KeyHandle};
+
+/// Supported MAC algorithms (RFC 9053, section 3.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// HMAC with SHA-256 and a 256 bit tag.
+    HMAC256,
+    /// HMAC with SHA-384 and a 384 bit tag.
+    HMAC384,
+    /// HMAC with SHA-512 and a 512 bit tag.
+    HMAC512,
+}
+
+impl Algorithm {
+    fn hash(self) -> CryptoHash {
+        match self {
+            Self::HMAC256 => CryptoHash::Sha2_256,
+            Self::HMAC384 => CryptoHash::Sha2_384,
+            Self::HMAC512 => CryptoHash::Sha2_512,
+        }
+    }
+
+    fn check_key(self, key: &KeyHandle) -> Result<(), CalError> {
+        if key.spec().signing_hash != self.hash() {
+            return Err(CalError::bad_parameter(
+                format!("Key does not match the COSE algorithm {self:?}"),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl super::Algorithm for Algorithm {
+    fn iana(self) -> super::iana::Algorithm {
+        use super::iana::Algorithm as Iana;
+        match self {
+            Self::HMAC256 => Iana::HMAC_256_256,
+            Self::HMAC384 => Iana::HMAC_384_384,
+            Self::HMAC512 => Iana::HMAC_512_512,
+        }
+    }
+}
+
+/// Authenticates `payload` and returns the `COSE_Mac0` message.
+///
+/// `alg` is added to the protected header of `headers`. `external_aad` is authenticated but not
+/// part of the message.
+pub fn mac0(
+    key: &KeyHandle,
+    alg: Algorithm,
+    headers: &Headers,
+    payload: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    alg.check_key(key)?;
+    let mac0 = CoseMac0Builder::new()
+        .protected(protected_header(headers, alg)?)
+        .unprotected(headers.unprotected.clone())
+        .payload(payload.to_vec())
+        .try_create_tag(external_aad, |data| key.hmac(data))?
+        .build();
+    encode(mac0, headers.tagged)
+}
+
+/// Verifies a tagged or untagged `COSE_Mac0` message and returns its headers and payload.
+///
+/// Fails unless the protected algorithm is in `allowed` and matches the spec of `key`. Detached
+/// payloads are not supported.
+pub fn verify_mac0(
+    message: &[u8],
+    key: &KeyHandle,
+    allowed: &[Algorithm],
+    external_aad: &[u8],
+) -> Result<Verified, CalError> {
+    let (mac0, tagged) = decode::<CoseMac0>(message)?;
+    let alg = allowed_algorithm(&mac0.protected.header, allowed)?;
+    alg.check_key(key)?;
+    let Some(payload) = mac0.payload.clone() else {
+        return Err(invalid("the payload is detached"));
+    };
+
+    mac0.verify_tag(external_aad, |tag, data| {
+        if !key.verify_hmac(data, tag)? {
+            return Err(CalError::failed_operation(
+                "Invalid COSE tag".to_owned(),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    })?;
+    Ok(Verified {
+        headers: Headers {
+            protected: mac0.protected.header,
+            unprotected: mac0.unprotected,
+            tagged,
+        },
+        payload,
+    })
+}
@@ -0,0 +1,165 @@
+//! CBOR Object Signing and Encryption (COSE, RFC 9052 and RFC 9053) on top of a
+//! [Provider](super::Provider).
+//!
+//! `COSE_Sign1`, `COSE_Mac0` and `COSE_Encrypt0` messages are created and verified with
+//! [KeyPairHandle](super::KeyPairHandle)s and [KeyHandle](super::KeyHandle)s, keys are converted
+//! to and from `COSE_Key` maps. Messages and keys are encoded with [coset], whose [Header] and
+//! [CoseKey] types are part of this API.
+//!
+//! As for JOSE, a message is only accepted if its algorithm is in the allow-list passed to the
+//! verification or decryption and matches the key. The algorithm has to be in the protected
+//! header and critical header parameters are not supported.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{
+//!     common::cose::{self, sign::{self, Algorithm}, Headers, HeaderBuilder},
+//!     prelude::*,
+//! };
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::P256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     ..Default::default()
+//! };
+//! let key_pair = provider.create_key_pair(spec).unwrap();
+//!
+//! let headers = Headers {
+//!     unprotected: HeaderBuilder::new().key_id(b"11".to_vec()).build(),
+//!     tagged: true,
+//!     ..Default::default()
+//! };
+//! let message = sign::sign1(&key_pair, Algorithm::ES256, &headers, b"payload", b"").unwrap();
+//!
+//! let verified = sign::verify_sign1(&message, &key_pair, &[Algorithm::ES256], b"").unwrap();
+//! assert_eq!(verified.payload, b"payload");
+//! assert_eq!(verified.headers.unprotected.key_id, b"11");
+//! assert!(sign::verify_sign1(&message, &key_pair, &[Algorithm::EdDSA], b"").is_err());
+//!
+//! let cose_key = cose::key::export_public_key(&key_pair).unwrap();
+//! let public_key = cose::key::import_key_pair(&mut provider, &cose_key).unwrap();
+//! assert!(sign::verify_sign1(&message, &public_key, &[Algorithm::ES256], b"").is_ok());
+//! ```
+
+use anyhow::anyhow;
+pub use coset::{self, iana, CborSerializable, CoseKey, Header, HeaderBuilder, Label};
+use coset::{Algorithm as CoseAlgorithm, TaggedCborSerializable};
+
+use super::error::CalError;
+
+/// `COSE_Encrypt0` messages (RFC 9052, section 5.2).
+pub mod encrypt;
+/// Conversion of keys to and from `COSE_Key` maps (RFC 9052, section 7).
+pub mod key;
+/// `COSE_Mac0` messages (RFC 9052, section 6.2).
+pub mod mac;
+/// `COSE_Sign1` messages (RFC 9052, section 4.2).
+pub mod sign;
+
+/// Headers and encoding of a COSE message.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Headers {
+    /// Protected header, `alg` is set when creating a message.
+    pub protected: Header,
+    pub unprotected: Header,
+    /// Whether the message is wrapped in its CBOR tag, e.g. 18 for `COSE_Sign1`.
+    ///
+    /// ISO 18013-5 uses untagged messages, CWTs usually tagged ones. Both are accepted when
+    /// decoding.
+    pub tagged: bool,
+}
+
+/// Header and payload of a verified `COSE_Sign1` or `COSE_Mac0` message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verified {
+    pub headers: Headers,
+    pub payload: Vec<u8>,
+}
+
+/// Algorithms of a message type, which are identified by their IANA value.
+pub(crate) trait Algorithm: Copy {
+    fn iana(self) -> iana::Algorithm;
+}
+
+fn invalid(message: impl std::fmt::Display) -> CalError {
+    CalError::bad_parameter(format!("Invalid COSE message: {message}"), true, None)
+}
+
+/// Returns the protected header of a new message with `alg` set.
+fn protected_header(headers: &Headers, alg: impl Algorithm) -> Result<Header, CalError> {
+    if !headers.protected.crit.is_empty() {
+        return Err(invalid("critical header parameters are not supported"));
+    }
+    let alg = CoseAlgorithm::Assigned(alg.iana());
+    if headers
+        .protected
+        .alg
+        .as_ref()
+        .is_some_and(|other| *other != alg)
+        || headers.unprotected.alg.is_some()
+    {
+        return Err(invalid(
+            "the algorithm is set by the key and has to be protected",
+        ));
+    }
+
+    let mut protected = headers.protected.clone();
+    protected.alg = Some(alg);
+    Ok(protected)
+}
+
+/// Returns the algorithm of a received message if it is in `allowed`.
+fn allowed_algorithm<A: Algorithm>(protected: &Header, allowed: &[A]) -> Result<A, CalError> {
+    if !protected.crit.is_empty() {
+        return Err(invalid("critical header parameters are not supported"));
+    }
+    let Some(alg) = &protected.alg else {
+        return Err(invalid("missing protected algorithm"));
+    };
+    allowed
+        .iter()
+        .copied()
+        .find(|allowed| *alg == CoseAlgorithm::Assigned(allowed.iana()))
+        .ok_or_else(|| invalid(format!("algorithm {alg:?} is not allowed")))
+}
+
+/// Decodes a tagged or untagged message and returns whether it was tagged.
+fn decode<T: CborSerializable + TaggedCborSerializable>(
+    message: &[u8],
+) -> Result<(T, bool), CalError> {
+    if let Ok(decoded) = T::from_tagged_slice(message) {
+        return Ok((decoded, true));
+    }
+    T::from_slice(message)
+        .map(|decoded| (decoded, false))
+        .map_err(|e| {
+            CalError::bad_parameter(
+                "Invalid COSE message encoding".to_owned(),
+                true,
+                Some(anyhow!(e)),
+            )
+        })
+}
+
+fn encode<T: CborSerializable + TaggedCborSerializable>(
+    message: T,
+    tagged: bool,
+) -> Result<Vec<u8>, CalError> {
+    if tagged {
+        message.to_tagged_vec()
+    } else {
+        message.to_vec()
+    }
+    .map_err(|e| {
+        CalError::failed_operation(
+            "Failed to encode COSE message".to_owned(),
+            true,
+            Some(anyhow!(e)),
+        )
+    })
+}
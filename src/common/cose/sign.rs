@@ -0,0 +1,169 @@
+//! `COSE_Sign1` messages with a single signer.
+//!
+//! The `Sig_structure` is signed with a [KeyPairHandle], ECDSA signatures are encoded as fixed size
+//! `r || s` like in JWS. Detached payloads are encoded as `nil` and have to
+//! be passed to the verification.
+
+use coset::{CoseSign1, CoseSign1Builder};
+
+use super::{allowed_algorithm, decode, encode, invalid, protected_header, Headers, Verified};
+use crate::common::{
+    error::CalError,
+    jose::jws::{self, JwsKey},
+    KeyPairHandle,
+};
+
+/// Supported signature algorithms (RFC 9053, section 2).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// ECDSA with `P-256` and SHA-256.
+    ES256,
+    /// ECDSA with `P-384` and SHA-384.
+    ES384,
+    /// Ed25519.
+    EdDSA,
+    /// `RSASSA-PSS` with SHA-256 (RFC 8230).
+    PS256,
+    /// `RSASSA-PKCS1-v1_5` with SHA-256 (RFC 8812).
+    RS256,
+}
+
+impl Algorithm {
+    /// The JWS algorithm with the same signature scheme.
+    fn jws(self) -> jws::Algorithm {
+        match self {
+            Self::ES256 => jws::Algorithm::ES256,
+            Self::ES384 => jws::Algorithm::ES384,
+            Self::EdDSA => jws::Algorithm::EdDSA,
+            Self::PS256 => jws::Algorithm::PS256,
+            Self::RS256 => jws::Algorithm::RS256,
+        }
+    }
+}
+
+impl super::Algorithm for Algorithm {
+    fn iana(self) -> super::iana::Algorithm {
+        use super::iana::Algorithm as Iana;
+        match self {
+            Self::ES256 => Iana::ES256,
+            Self::ES384 => Iana::ES384,
+            Self::EdDSA => Iana::EdDSA,
+            Self::PS256 => Iana::PS256,
+            Self::RS256 => Iana::RS256,
+        }
+    }
+}
+
+fn builder(alg: Algorithm, headers: &Headers) -> Result<CoseSign1Builder, CalError> {
+    Ok(CoseSign1Builder::new()
+        .protected(protected_header(headers, alg)?)
+        .unprotected(headers.unprotected.clone()))
+}
+
+fn verify(
+    alg: Algorithm,
+    key_pair: &KeyPairHandle,
+    signature: &[u8],
+    data: &[u8],
+) -> Result<(), CalError> {
+    if !alg
+        .jws()
+        .verify(JwsKey::KeyPair(key_pair), data, signature)?
+    {
+        return Err(CalError::failed_operation(
+            "Invalid COSE signature".to_owned(),
+            true,
+            None,
+        ));
+    }
+    Ok(())
+}
+
+fn headers(sign1: &CoseSign1, tagged: bool) -> Headers {
+    Headers {
+        protected: sign1.protected.header.clone(),
+        unprotected: sign1.unprotected.clone(),
+        tagged,
+    }
+}
+
+/// Signs `payload` and returns the `COSE_Sign1` message.
+///
+/// `alg` is added to the protected header of `headers`. `external_aad` is signed but not part of
+/// the message, pass an empty slice if the application does not use it.
+pub fn sign1(
+    key_pair: &KeyPairHandle,
+    alg: Algorithm,
+    headers: &Headers,
+    payload: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let sign1 = builder(alg, headers)?
+        .payload(payload.to_vec())
+        .try_create_signature(external_aad, |data| {
+            alg.jws().sign(JwsKey::KeyPair(key_pair), data)
+        })?
+        .build();
+    encode(sign1, headers.tagged)
+}
+
+/// Signs `payload` and returns a `COSE_Sign1` message with detached payload.
+pub fn sign1_detached(
+    key_pair: &KeyPairHandle,
+    alg: Algorithm,
+    headers: &Headers,
+    payload: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let sign1 = builder(alg, headers)?
+        .try_create_detached_signature(payload, external_aad, |data| {
+            alg.jws().sign(JwsKey::KeyPair(key_pair), data)
+        })?
+        .build();
+    encode(sign1, headers.tagged)
+}
+
+/// Verifies a tagged or untagged `COSE_Sign1` message and returns its headers and payload.
+///
+/// Fails unless the protected algorithm is in `allowed` and matches the spec of `key_pair`, or if
+/// the payload is detached.
+pub fn verify_sign1(
+    message: &[u8],
+    key_pair: &KeyPairHandle,
+    allowed: &[Algorithm],
+    external_aad: &[u8],
+) -> Result<Verified, CalError> {
+    let (sign1, tagged) = decode::<CoseSign1>(message)?;
+    let alg = allowed_algorithm(&sign1.protected.header, allowed)?;
+    let Some(payload) = sign1.payload.clone() else {
+        return Err(invalid("the payload is detached"));
+    };
+
+    sign1.verify_signature(external_aad, |signature, data| {
+        verify(alg, key_pair, signature, data)
+    })?;
+    Ok(Verified {
+        headers: headers(&sign1, tagged),
+        payload,
+    })
+}
+
+/// Verifies a `COSE_Sign1` message with detached `payload` and returns its headers.
+pub fn verify_sign1_detached(
+    message: &[u8],
+    payload: &[u8],
+    key_pair: &KeyPairHandle,
+    allowed: &[Algorithm],
+    external_aad: &[u8],
+) -> Result<Headers, CalError> {
+    let (sign1, tagged) = decode::<CoseSign1>(message)?;
+    let alg = allowed_algorithm(&sign1.protected.header, allowed)?;
+    if sign1.payload.is_some() {
+        return Err(invalid("the payload is not detached"));
+    }
+
+    sign1.verify_detached_signature(payload, external_aad, |signature, data| {
+        verify(alg, key_pair, signature, data)
+    })?;
+    Ok(headers(&sign1, tagged))
+}
@@ -58,6 +58,198 @@ pub enum AsymmetricKeySpec {
     Curve448,
     /// `FRP256v1`, a French curve providing strong security and performance.
     Frp256v1,
+    /// `ML-KEM-512` (FIPS 203), a post-quantum key encapsulation mechanism.
+    MlKem512,
+    /// `ML-KEM-768` (FIPS 203), a post-quantum key encapsulation mechanism.
+    MlKem768,
+    /// `ML-KEM-1024` (FIPS 203), a post-quantum key encapsulation mechanism.
+    MlKem1024,
+    /// `ML-DSA-44` (FIPS 204), a post-quantum signature scheme.
+    MlDsa44,
+    /// `ML-DSA-65` (FIPS 204), a post-quantum signature scheme.
+    MlDsa65,
+    /// `ML-DSA-87` (FIPS 204), a post-quantum signature scheme.
+    MlDsa87,
+    /// `SLH-DSA-SHA2-128s` (FIPS 205), a stateless hash-based post-quantum signature scheme.
+    ///
+    /// The `s` parameter sets have small signatures, the `f` parameter sets sign faster.
+    SlhDsaSha2_128s,
+    /// `SLH-DSA-SHA2-128f` (FIPS 205).
+    SlhDsaSha2_128f,
+    /// `SLH-DSA-SHA2-192s` (FIPS 205).
+    SlhDsaSha2_192s,
+    /// `SLH-DSA-SHA2-192f` (FIPS 205).
+    SlhDsaSha2_192f,
+    /// `SLH-DSA-SHA2-256s` (FIPS 205).
+    SlhDsaSha2_256s,
+    /// `SLH-DSA-SHA2-256f` (FIPS 205).
+    SlhDsaSha2_256f,
+    /// `SLH-DSA-SHAKE-128s` (FIPS 205).
+    SlhDsaShake128s,
+    /// `SLH-DSA-SHAKE-128f` (FIPS 205).
+    SlhDsaShake128f,
+    /// `SLH-DSA-SHAKE-192s` (FIPS 205).
+    SlhDsaShake192s,
+    /// `SLH-DSA-SHAKE-192f` (FIPS 205).
+    SlhDsaShake192f,
+    /// `SLH-DSA-SHAKE-256s` (FIPS 205).
+    SlhDsaShake256s,
+    /// `SLH-DSA-SHAKE-256f` (FIPS 205).
+    SlhDsaShake256f,
+    /// Hybrid key exchange combining `X25519` with `ML-KEM-768`, as the `X25519MLKEM768` TLS group.
+    ///
+    /// Only usable with [`DHExchange`](crate::common::DHExchange).
+    X25519MlKem768,
+    /// Composite signature of `ML-DSA-44` and `Ed25519` (`id-MLDSA44-Ed25519-SHA512`).
+    ///
+    /// Composite signatures follow the IETF composite ML-DSA draft and are only valid if both
+    /// component signatures verify.
+    MlDsa44Ed25519,
+    /// Composite signature of `ML-DSA-65` and `Ed25519` (`id-MLDSA65-Ed25519-SHA512`).
+    MlDsa65Ed25519,
+    /// Composite signature of `ML-DSA-44` and `ECDSA` on `P-256` (`id-MLDSA44-ECDSA-P256-SHA256`).
+    MlDsa44EcdsaP256,
+    /// Composite signature of `ML-DSA-65` and `ECDSA` on `P-256` (`id-MLDSA65-ECDSA-P256-SHA512`).
+    MlDsa65EcdsaP256,
+}
+
+impl AsymmetricKeySpec {
+    /// Returns `true` if the spec is an `RSA` key size.
+    pub(crate) fn is_rsa(&self) -> bool {
+        matches!(
+            self,
+            Self::RSA1024 | Self::RSA2048 | Self::RSA3072 | Self::RSA4096 | Self::RSA8192
+        )
+    }
+
+    /// Returns the length of the scalars `r` and `s` of ECDSA signatures, or `None` if the spec
+    /// is not an ECDSA curve.
+    pub(crate) fn ecdsa_scalar_len(&self) -> Option<usize> {
+        match self {
+            Self::P256 | Self::Secp256k1 | Self::BrainpoolP256r1 | Self::Frp256v1 => Some(32),
+            Self::P384 | Self::BrainpoolP384r1 => Some(48),
+            Self::BrainpoolP512r1 => Some(64),
+            Self::P521 => Some(66),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the spec is a key encapsulation mechanism.
+    pub(crate) fn is_kem(&self) -> bool {
+        matches!(self, Self::MlKem512 | Self::MlKem768 | Self::MlKem1024)
+    }
+
+    /// Returns `true` if the spec is an `ML-DSA` parameter set.
+    pub(crate) fn is_ml_dsa(&self) -> bool {
+        matches!(self, Self::MlDsa44 | Self::MlDsa65 | Self::MlDsa87)
+    }
+
+    /// Returns `true` if the spec is a composite of `ML-DSA` and a classical signature scheme.
+    pub(crate) fn is_composite(&self) -> bool {
+        matches!(
+            self,
+            Self::MlDsa44Ed25519
+                | Self::MlDsa65Ed25519
+                | Self::MlDsa44EcdsaP256
+                | Self::MlDsa65EcdsaP256
+        )
+    }
+
+    /// Returns `true` if the spec is an `SLH-DSA` parameter set.
+    pub(crate) fn is_slh_dsa(&self) -> bool {
+        matches!(
+            self,
+            Self::SlhDsaSha2_128s
+                | Self::SlhDsaSha2_128f
+                | Self::SlhDsaSha2_192s
+                | Self::SlhDsaSha2_192f
+                | Self::SlhDsaSha2_256s
+                | Self::SlhDsaSha2_256f
+                | Self::SlhDsaShake128s
+                | Self::SlhDsaShake128f
+                | Self::SlhDsaShake192s
+                | Self::SlhDsaShake192f
+                | Self::SlhDsaShake256s
+                | Self::SlhDsaShake256f
+        )
+    }
+}
+
+/// Padding scheme of RSA signatures.
+///
+/// RSA encryption always uses OAEP with the hash of the key pair.
+/// flutter_rust_bridge:non_opaque
+#[repr(C)]
+#[derive(
+    Clone,
+    Debug,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Default,
+    EnumString,
+    IntoStaticStr,
+)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub enum RsaPadding {
+    /// `RSASSA-PKCS1-v1_5` (RFC 8017).
+    #[default]
+    Pkcs1v15,
+    /// `RSASSA-PSS` (RFC 8017) with MGF1 and a salt as long as the hash.
+    Pss,
+}
+
+/// Encoding of ECDSA signatures.
+#[repr(C)]
+#[derive(
+    Clone,
+    Debug,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Default,
+    EnumString,
+    IntoStaticStr,
+)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub enum SignatureEncoding {
+    /// ASN.1 DER `SEQUENCE` of the integers `r` and `s` (RFC 3279), used by X.509, Android and
+    /// Apple's `SecKey` API.
+    #[default]
+    Der,
+    /// Fixed size concatenation `r || s` of IEEE P1363, used by JOSE, COSE, WebAuthn and
+    /// CryptoKit.
+    P1363,
+}
+
+/// Encoding of elliptic curve points in public keys of the SEC 1 curves `P256` and `P384`.
+#[repr(C)]
+#[derive(
+    Clone,
+    Debug,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Default,
+    EnumString,
+    IntoStaticStr,
+)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub enum PointEncoding {
+    /// `0x04 || x || y`, 65 bytes for `P256`.
+    #[default]
+    Uncompressed,
+    /// `0x02` or `0x03`, depending on the parity of `y`, followed by `x`, 33 bytes for `P256`.
+    Compressed,
 }
 
 /// Represents the available cipher algorithms.
@@ -111,7 +303,6 @@ impl Cipher {
         }
     }
 
-    #[allow(dead_code)] // is used in tests
     pub(crate) fn iv_len(&self) -> usize {
         match self {
             Self::AesCbc128
@@ -122,4 +313,17 @@ impl Cipher {
             Self::XChaCha20Poly1305 => 24,
         }
     }
+
+    /// Returns the maximum number of encryptions with a single key.
+    ///
+    /// Random 96-bit nonces are limited to 2^32 encryptions, keeping the probability of a nonce
+    /// collision below 2^-32 as required by NIST SP 800-38D. Counter nonces and the random
+    /// 192-bit nonces of XChaCha20-Poly1305 are only limited by the 64-bit usage counter.
+    pub fn usage_limit(&self, counter_nonce: bool) -> u64 {
+        match self {
+            Self::XChaCha20Poly1305 => u64::MAX,
+            _ if counter_nonce => u64::MAX,
+            _ => 1 << 32,
+        }
+    }
 }
@@ -42,4 +42,5 @@ pub enum CryptoHash {
     Sha3_384,
     Sha3_512,
     Blake2b,
+    Blake2s,
 }
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::hashes::CryptoHash;
+
 /// Enum representing different secure key derivation functions
 ///
 /// For clients Argon2d is recommended as it offers excellent brute force resistance.
@@ -34,6 +36,10 @@ pub enum KDF {
     /// Partial brute force and partial side channel resistance.
     Argon2id(Argon2Options),
     Argon2i(Argon2Options),
+    /// Memory hard, but weaker than Argon2. Use for compatibility with existing scrypt hashes.
+    Scrypt(ScryptOptions),
+    /// Not memory hard. Use only if FIPS-140 compliance is required.
+    Pbkdf2(Pbkdf2Options),
 }
 
 /// Configuration for KDF with Argon2
@@ -64,6 +70,73 @@ impl Default for Argon2Options {
     }
 }
 
+/// Configuration for KDF with scrypt
+///
+/// When in doubt use the default.
+/// flutter_rust_bridge:non_opaque
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub struct ScryptOptions {
+    /// Binary logarithm of the CPU/memory cost `N`
+    pub log_n: u8,
+    /// Block size
+    pub r: u32,
+    /// Degree of parallelism
+    pub p: u32,
+}
+
+impl Default for ScryptOptions {
+    /// Defaults for servers.
+    /// Taken from Password Storage Cheat Sheet (12.03.2025)
+    /// <https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html>
+    fn default() -> Self {
+        ScryptOptions {
+            log_n: 17,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// Configuration for KDF with PBKDF2
+///
+/// When in doubt use the default.
+/// flutter_rust_bridge:non_opaque
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub struct Pbkdf2Options {
+    /// Number of iterations
+    pub iterations: u32,
+    /// Hash function used with HMAC as pseudo random function
+    pub hash: CryptoHash,
+}
+
+impl Default for Pbkdf2Options {
+    /// Defaults for servers.
+    /// Taken from Password Storage Cheat Sheet (12.03.2025)
+    /// <https://cheatsheetseries.owasp.org/cheatsheets/Password_Storage_Cheat_Sheet.html>
+    fn default() -> Self {
+        Pbkdf2Options {
+            iterations: 600_000,
+            hash: CryptoHash::Sha2_256,
+        }
+    }
+}
+
+/// Result of verifying a password against a stored PHC string.
+///
+/// flutter_rust_bridge:non_opaque
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub struct PasswordVerification {
+    /// `true` if the password matches the stored hash.
+    pub valid: bool,
+    /// `true` if the stored hash was not created with the requested [KDF] and parameters.
+    ///
+    /// If the password is valid, the password should be hashed again and the stored hash replaced.
+    pub needs_rehash: bool,
+}
+
 impl Default for KDF {
     /// Defaults for servers.
     /// Taken from Password Storage Cheat Sheet (12.03.2025)
@@ -0,0 +1,154 @@
+//! Conversion between the DER and IEEE P1363 encodings of ECDSA signatures.
+//!
+//! Key pairs sign in the [SignatureEncoding](super::algorithms::encryption::SignatureEncoding)
+//! of their spec. Signatures received in the other
+//! encoding are converted with [der_to_p1363] and [p1363_to_der] before verifying them.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{common::crypto::ecdsa, prelude::*};
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::P256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     signature_encoding: SignatureEncoding::P1363,
+//!     ephemeral: true,
+//!     ..Default::default()
+//! };
+//! let key_pair = provider.create_key_pair(spec).unwrap();
+//! let signature = key_pair.sign_data(b"data").unwrap();
+//! assert_eq!(signature.len(), 64);
+//!
+//! let der = ecdsa::p1363_to_der(&signature, AsymmetricKeySpec::P256).unwrap();
+//! assert_eq!(ecdsa::der_to_p1363(&der, AsymmetricKeySpec::P256).unwrap(), signature);
+//! ```
+
+use anyhow::anyhow;
+use pkcs8::der::{
+    asn1::{SequenceOf, UintRef},
+    Decode, Encode, Reader, SliceReader,
+};
+
+use super::algorithms::encryption::AsymmetricKeySpec;
+#[cfg(any(feature = "android", feature = "apple-secure-enclave"))]
+use super::algorithms::encryption::SignatureEncoding;
+#[cfg(any(feature = "android", feature = "apple-secure-enclave"))]
+use crate::common::config::KeyPairSpec;
+use crate::common::error::CalError;
+
+fn invalid_signature(error: impl Into<anyhow::Error>) -> CalError {
+    CalError::bad_parameter(
+        "Invalid ECDSA signature".to_owned(),
+        true,
+        Some(error.into()),
+    )
+}
+
+fn scalar_len(spec: AsymmetricKeySpec) -> Result<usize, CalError> {
+    spec.ecdsa_scalar_len()
+        .ok_or_else(|| CalError::unsupported_algorithm(format!("ECDSA signatures with {spec:?}")))
+}
+
+/// Converts a DER encoded ECDSA signature of a key pair with `spec` to the fixed size `r || s`
+/// encoding of IEEE P1363.
+pub fn der_to_p1363(signature: &[u8], spec: AsymmetricKeySpec) -> Result<Vec<u8>, CalError> {
+    let scalar_len = scalar_len(spec)?;
+    let mut reader = SliceReader::new(signature).map_err(invalid_signature)?;
+    let (r, s) = reader
+        .sequence(|reader| Ok((UintRef::decode(reader)?, UintRef::decode(reader)?)))
+        .and_then(|integers| reader.finish(integers))
+        .map_err(invalid_signature)?;
+
+    let mut raw = vec![0; 2 * scalar_len];
+    for (integer, output) in [r, s].iter().zip(raw.chunks_mut(scalar_len)) {
+        let bytes = integer.as_bytes();
+        if bytes.len() > scalar_len {
+            return Err(invalid_signature(anyhow!(
+                "Integer longer than {scalar_len} bytes"
+            )));
+        }
+        output[scalar_len - bytes.len()..].copy_from_slice(bytes);
+    }
+    Ok(raw)
+}
+
+/// Converts a fixed size `r || s` ECDSA signature of a key pair with `spec` to DER.
+pub fn p1363_to_der(signature: &[u8], spec: AsymmetricKeySpec) -> Result<Vec<u8>, CalError> {
+    let scalar_len = scalar_len(spec)?;
+    if signature.len() != 2 * scalar_len {
+        return Err(invalid_signature(anyhow!(
+            "Expected {} bytes, got {}",
+            2 * scalar_len,
+            signature.len()
+        )));
+    }
+
+    let mut sequence = SequenceOf::<UintRef<'_>, 2>::new();
+    for integer in signature.chunks(scalar_len) {
+        sequence
+            .add(UintRef::new(integer).map_err(invalid_signature)?)
+            .map_err(invalid_signature)?;
+    }
+    sequence.to_der().map_err(invalid_signature)
+}
+
+/// Converts a DER signature of a provider to the encoding of `spec`.
+///
+/// For providers whose platform API only signs in DER.
+#[cfg(any(feature = "android", feature = "apple-secure-enclave"))]
+pub(crate) fn encode_signature(spec: KeyPairSpec, signature: Vec<u8>) -> Result<Vec<u8>, CalError> {
+    match spec.signature_encoding {
+        SignatureEncoding::P1363 if spec.asym_spec.ecdsa_scalar_len().is_some() => {
+            der_to_p1363(&signature, spec.asym_spec)
+        }
+        _ => Ok(signature),
+    }
+}
+
+/// Converts a signature in the encoding of `spec` to DER, the counterpart of [encode_signature].
+///
+/// Returns `None` if the signature is malformed, which fails its verification.
+#[cfg(any(feature = "android", feature = "apple-secure-enclave"))]
+pub(crate) fn decode_signature(spec: KeyPairSpec, signature: &[u8]) -> Option<Vec<u8>> {
+    match spec.signature_encoding {
+        SignatureEncoding::P1363 if spec.asym_spec.ecdsa_scalar_len().is_some() => {
+            p1363_to_der(signature, spec.asym_spec).ok()
+        }
+        _ => Some(signature.to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signature_conversion() {
+        // r has a leading zero byte and s the high bit set.
+        let mut raw = vec![0x01; 64];
+        raw[0] = 0x00;
+        raw[32] = 0x80;
+
+        let der = p1363_to_der(&raw, AsymmetricKeySpec::P256).unwrap();
+        assert_eq!(&der[..4], [0x30, 0x44, 0x02, 0x1f]);
+        assert_eq!(&der[35..38], [0x02, 0x21, 0x00]);
+        assert_eq!(der_to_p1363(&der, AsymmetricKeySpec::P256).unwrap(), raw);
+
+        assert!(p1363_to_der(&raw[1..], AsymmetricKeySpec::P256).is_err());
+        assert!(der_to_p1363(&der[..der.len() - 1], AsymmetricKeySpec::P256).is_err());
+        assert!(der_to_p1363(&der, AsymmetricKeySpec::Curve25519).is_err());
+        let p384 = p1363_to_der(&[0x01; 96], AsymmetricKeySpec::P384).unwrap();
+        assert!(der_to_p1363(&p384, AsymmetricKeySpec::P256).is_err());
+
+        // P-521 scalars are 66 bytes long.
+        let der = p1363_to_der(&[0x01; 132], AsymmetricKeySpec::P521).unwrap();
+        assert_eq!(
+            der_to_p1363(&der, AsymmetricKeySpec::P521).unwrap(),
+            [0x01; 132]
+        );
+    }
+}
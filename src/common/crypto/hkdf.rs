@@ -0,0 +1,96 @@
+//! HMAC (RFC 2104) and HKDF (RFC 5869) built on [Provider::hash], for protocols implemented
+//! independently of a specific provider.
+
+use zeroize::Zeroizing;
+
+use crate::common::{crypto::algorithms::hashes::CryptoHash, error::CalError, Provider};
+
+/// Output and block length of `hash` in bytes.
+pub(crate) fn hash_lengths(hash: CryptoHash) -> Result<(usize, usize), CalError> {
+    match hash {
+        CryptoHash::Sha2_256 | CryptoHash::Blake2s => Ok((32, 64)),
+        CryptoHash::Sha2_384 => Ok((48, 128)),
+        CryptoHash::Sha2_512 | CryptoHash::Blake2b => Ok((64, 128)),
+        CryptoHash::Sha3_256 => Ok((32, 136)),
+        CryptoHash::Sha3_384 => Ok((48, 104)),
+        CryptoHash::Sha3_512 => Ok((64, 72)),
+        _ => Err(CalError::unsupported_algorithm(format!(
+            "{hash:?} is not supported for HMAC"
+        ))),
+    }
+}
+
+pub(crate) fn hmac(
+    provider: &Provider,
+    hash: CryptoHash,
+    key: &[u8],
+    data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let (_, block_len) = hash_lengths(hash)?;
+
+    let mut key = Zeroizing::new(if key.len() > block_len {
+        provider.hash(key, hash)?
+    } else {
+        key.to_vec()
+    });
+    key.resize(block_len, 0);
+
+    let mut inner = Zeroizing::new(key.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+    inner.extend_from_slice(data);
+    let inner_hash = Zeroizing::new(provider.hash(&inner, hash)?);
+
+    let mut outer = Zeroizing::new(key.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+    outer.extend_from_slice(&inner_hash);
+    Ok(Zeroizing::new(provider.hash(&outer, hash)?))
+}
+
+/// `HKDF-Extract`, an empty `salt` is replaced by zeros of the hash length.
+pub(crate) fn extract(
+    provider: &Provider,
+    hash: CryptoHash,
+    salt: &[u8],
+    input_key_material: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    if salt.is_empty() {
+        let (hash_len, _) = hash_lengths(hash)?;
+        hmac(provider, hash, &vec![0; hash_len], input_key_material)
+    } else {
+        hmac(provider, hash, salt, input_key_material)
+    }
+}
+
+/// `HKDF-Expand`
+pub(crate) fn expand(
+    provider: &Provider,
+    hash: CryptoHash,
+    pseudo_random_key: &[u8],
+    info: &[u8],
+    len: usize,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let (hash_len, _) = hash_lengths(hash)?;
+    if len > 255 * hash_len {
+        return Err(CalError::bad_parameter(
+            format!("HKDF output length {len} is too large"),
+            true,
+            None,
+        ));
+    }
+
+    let mut output = Zeroizing::new(Vec::with_capacity(len + hash_len));
+    let mut block = Zeroizing::new(Vec::new());
+    for counter in 1..=len.div_ceil(hash_len) {
+        let mut input = Zeroizing::new(block.to_vec());
+        input.extend_from_slice(info);
+        input.push(counter as u8);
+        block = hmac(provider, hash, pseudo_random_key, &input)?;
+        output.extend_from_slice(&block);
+    }
+    output.truncate(len);
+
+    Ok(output)
+}
+
+/// Compares MACs in constant time with respect to their content.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -1,5 +1,8 @@
 /// Structs and enumerations representing cryptographic algorithms.
 pub mod algorithms;
+/// Conversion between ECDSA signature encodings.
+pub mod ecdsa;
+pub(crate) mod hkdf;
 pub mod pkcs;
 
 #[repr(C)]
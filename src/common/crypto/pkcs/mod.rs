@@ -1 +1,2 @@
+pub mod pem;
 pub mod standards;
@@ -0,0 +1,285 @@
+//! PEM armor of DER encoded keys, certificates and certificate requests (RFC 7468).
+
+use std::borrow::Cow;
+
+use anyhow::anyhow;
+use pem_rfc7468::LineEnding;
+use strum::{EnumString, IntoStaticStr};
+use zeroize::Zeroizing;
+
+use super::standards::KeyFormat;
+use crate::common::error::CalError;
+
+const BEGIN: &str = "-----BEGIN ";
+const END: &str = "-----END ";
+const BOUNDARY_END: &str = "-----";
+
+/// Type labels of the supported PEM blocks.
+///
+/// # Examples
+///
+/// ```rust
+/// use crypto_layer::common::crypto::pkcs::pem::PemLabel;
+///
+/// assert_eq!(PemLabel::EcPrivateKey.as_str(), "EC PRIVATE KEY");
+/// assert_eq!("PUBLIC KEY".parse::<PemLabel>().unwrap(), PemLabel::PublicKey);
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumString, IntoStaticStr)]
+pub enum PemLabel {
+    /// PKCS#8 `PrivateKeyInfo` or `OneAsymmetricKey`.
+    #[strum(serialize = "PRIVATE KEY")]
+    PrivateKey,
+    /// X.509 `SubjectPublicKeyInfo`.
+    #[strum(serialize = "PUBLIC KEY")]
+    PublicKey,
+    /// SEC 1 `ECPrivateKey`.
+    #[strum(serialize = "EC PRIVATE KEY")]
+    EcPrivateKey,
+    /// PKCS#8 `EncryptedPrivateKeyInfo`.
+    #[strum(serialize = "ENCRYPTED PRIVATE KEY")]
+    EncryptedPrivateKey,
+    /// X.509 certificate.
+    #[strum(serialize = "CERTIFICATE")]
+    Certificate,
+    /// PKCS#10 certificate signing request.
+    #[strum(serialize = "CERTIFICATE REQUEST")]
+    CertificateRequest,
+}
+
+impl PemLabel {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// The DER format of unencrypted keys with this label.
+    fn key_format(self) -> Option<KeyFormat> {
+        match self {
+            PemLabel::PrivateKey => Some(KeyFormat::PKCS8),
+            PemLabel::PublicKey => Some(KeyFormat::SPKI),
+            PemLabel::EcPrivateKey => Some(KeyFormat::SEC1),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded PEM block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PemBlock {
+    pub label: PemLabel,
+    pub der: Vec<u8>,
+}
+
+fn invalid_pem(message: String, error: Option<pem_rfc7468::Error>) -> CalError {
+    CalError::bad_parameter(message, true, error.map(|e| anyhow!(e)))
+}
+
+/// Encodes DER data as PEM block with LF line endings.
+pub fn encode(label: PemLabel, der: &[u8]) -> String {
+    pem_rfc7468::encode_string(label.as_str(), LineEnding::LF, der)
+        .expect("PemLabel only contains valid labels")
+}
+
+/// Encodes multiple DER documents as consecutive PEM blocks, e.g. a certificate chain.
+pub fn encode_all<'a>(blocks: impl IntoIterator<Item = (PemLabel, &'a [u8])>) -> String {
+    blocks
+        .into_iter()
+        .map(|(label, der)| encode(label, der))
+        .collect()
+}
+
+fn decode_block(block: &str) -> Result<PemBlock, CalError> {
+    let (label, der) = pem_rfc7468::decode_vec(block.as_bytes())
+        .map_err(|e| invalid_pem("Invalid PEM block".to_owned(), Some(e)))?;
+    let label = label
+        .parse::<PemLabel>()
+        .map_err(|_| invalid_pem(format!("Unsupported PEM label: {label}"), None))?;
+
+    Ok(PemBlock { label, der })
+}
+
+/// Decodes all PEM blocks in `pem`.
+///
+/// Text between the blocks is ignored, as allowed by RFC 7468. Fails if there are no blocks,
+/// a block is malformed or has an unsupported label.
+pub fn decode_all(pem: &str) -> Result<Vec<PemBlock>, CalError> {
+    let mut blocks = Vec::new();
+    let mut rest = pem;
+
+    while let Some(start) = rest.find(BEGIN) {
+        let block = &rest[start..];
+        let label = block[BEGIN.len()..]
+            .split_once(BOUNDARY_END)
+            .map(|(label, _)| label)
+            .ok_or_else(|| invalid_pem("Unterminated PEM boundary".to_owned(), None))?;
+        let end_boundary = format!("{END}{label}{BOUNDARY_END}");
+        let end = block
+            .find(&end_boundary)
+            .ok_or_else(|| invalid_pem(format!("Missing end of PEM block {label}"), None))?
+            + end_boundary.len();
+
+        blocks.push(decode_block(&block[..end])?);
+        rest = &block[end..];
+    }
+
+    if blocks.is_empty() {
+        return Err(invalid_pem("No PEM block found".to_owned(), None));
+    }
+
+    Ok(blocks)
+}
+
+/// Decodes a single PEM block.
+pub fn decode(pem: &str) -> Result<PemBlock, CalError> {
+    let mut blocks = decode_all(pem)?;
+    if blocks.len() != 1 {
+        return Err(invalid_pem(
+            format!("Expected a single PEM block, found {}", blocks.len()),
+            None,
+        ));
+    }
+
+    Ok(blocks.remove(0))
+}
+
+/// Decodes a single PEM block and checks its label.
+pub fn decode_with_label(pem: &str, label: PemLabel) -> Result<Vec<u8>, CalError> {
+    let block = decode(pem)?;
+    if block.label != label {
+        return Err(invalid_pem(
+            format!(
+                "Expected PEM block {}, found {}",
+                label.as_str(),
+                block.label.as_str()
+            ),
+            None,
+        ));
+    }
+
+    Ok(block.der)
+}
+
+/// Calls `import` with the DER encoding and format of a [KeyFormat::PEM] key, or with `data`
+/// as is for other formats.
+pub(crate) fn import_key<T>(
+    data: &[u8],
+    format: KeyFormat,
+    import: impl FnOnce(&[u8], KeyFormat) -> Result<T, CalError>,
+) -> Result<T, CalError> {
+    if format != KeyFormat::PEM {
+        return import(data, format);
+    }
+
+    let pem = std::str::from_utf8(data).map_err(|e| {
+        CalError::bad_parameter("PEM is not UTF-8".to_owned(), true, Some(anyhow!(e)))
+    })?;
+    let block = decode(pem)?;
+    let der = Zeroizing::new(block.der);
+    let format = match block.label.key_format() {
+        Some(format) => format,
+        None if block.label == PemLabel::EncryptedPrivateKey => {
+            return Err(invalid_pem(
+                "Encrypted private keys need to be imported with their password".to_owned(),
+                None,
+            ))
+        }
+        None => {
+            return Err(invalid_pem(
+                format!("PEM block {} is not a supported key", block.label.as_str()),
+                None,
+            ))
+        }
+    };
+
+    import(&der, format)
+}
+
+/// Returns `data` as DER, decoding it first if it is PEM armored with `label`.
+pub(crate) fn der_or_pem(data: &[u8], label: PemLabel) -> Result<Cow<'_, [u8]>, CalError> {
+    if !data.trim_ascii_start().starts_with(BEGIN.as_bytes()) {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    let pem = std::str::from_utf8(data).map_err(|e| {
+        CalError::bad_parameter("PEM is not UTF-8".to_owned(), true, Some(anyhow!(e)))
+    })?;
+    decode_with_label(pem, label).map(Cow::Owned)
+}
+
+/// Calls `export` with the DER format for `label` and armors the result for [KeyFormat::PEM],
+/// otherwise calls `export` with `format`.
+pub(crate) fn export_key(
+    format: KeyFormat,
+    label: PemLabel,
+    export: impl FnOnce(KeyFormat) -> Result<Vec<u8>, CalError>,
+) -> Result<Vec<u8>, CalError> {
+    let Some(der_format) = label.key_format().filter(|_| format == KeyFormat::PEM) else {
+        return export(format);
+    };
+
+    let der = Zeroizing::new(export(der_format)?);
+    Ok(encode(label, &der).into_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 8410, section 10.1.
+    const ED25519_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEAGb9ECWmEzf6FQbrBZ9w7lshQhqowtrbLDFw4rXAxZuE=
+-----END PUBLIC KEY-----
+";
+
+    #[test]
+    fn test_round_trip() {
+        let block = decode(ED25519_PUBLIC_KEY).unwrap();
+        assert_eq!(block.label, PemLabel::PublicKey);
+        assert_eq!(block.der.len(), 44);
+        assert_eq!(encode(block.label, &block.der), ED25519_PUBLIC_KEY);
+    }
+
+    #[test]
+    fn test_multiple_blocks() {
+        let der = [0x30, 0x03, 0x02, 0x01, 0x01];
+        let pem = format!(
+            "Leaf certificate\n{}\nIntermediate\r\n{}",
+            encode(PemLabel::Certificate, &der),
+            encode(PemLabel::CertificateRequest, &der).replace('\n', "\r\n"),
+        );
+
+        let blocks = decode_all(&pem).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].label, PemLabel::Certificate);
+        assert_eq!(blocks[1].label, PemLabel::CertificateRequest);
+        assert!(blocks.iter().all(|block| block.der == der));
+        assert!(decode(&pem).is_err());
+        assert_eq!(
+            encode_all(
+                blocks
+                    .iter()
+                    .map(|block| (block.label, block.der.as_slice()))
+            ),
+            format!(
+                "{}{}",
+                encode(PemLabel::Certificate, &der),
+                encode(PemLabel::CertificateRequest, &der)
+            )
+        );
+    }
+
+    #[test]
+    fn test_label_validation() {
+        assert!(decode_with_label(ED25519_PUBLIC_KEY, PemLabel::PrivateKey).is_err());
+        assert!(decode_with_label(ED25519_PUBLIC_KEY, PemLabel::PublicKey).is_ok());
+
+        let unknown = ED25519_PUBLIC_KEY.replace("PUBLIC KEY", "RSA PUBLIC KEY");
+        assert!(decode(&unknown).is_err());
+
+        let mismatched = ED25519_PUBLIC_KEY.replacen("PUBLIC KEY", "PRIVATE KEY", 1);
+        assert!(decode(&mismatched).is_err());
+
+        assert!(decode("no pem here").is_err());
+        assert!(decode(&ED25519_PUBLIC_KEY.replace("MCow", "MC*w")).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /// Represents the various Public Key Infrastructure (PKI) standards.
 ///
 /// This enum provides a C-compatible representation of different PKI standards,
@@ -27,7 +29,10 @@
 /// The `#[repr(C)]` attribute ensures C compatibility, making these enums suitable for
 /// interfacing with C-based systems. They encompass a broad range of PKI applications,
 /// from encryption and digital signatures to certificate management.
+/// flutter_rust_bridge:non_opaque
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
 pub enum PkiStandards {
     /// PKCS (Public-Key Cryptography Standards) related operations.
     ///
@@ -37,6 +42,8 @@ pub enum PkiStandards {
     X509(X509Type),
     /// PEM (Privacy-Enhanced Mail) format for storing and sending cryptographic keys and certificates.
     Pem,
+    /// SEC 1 `ECPrivateKey` structure for elliptic curve private keys (RFC 5915).
+    Sec1,
 }
 
 /// Enumerates the PKCS (Public-Key Cryptography Standards) types.
@@ -57,7 +64,10 @@ pub enum PkiStandards {
 /// # Note
 ///
 /// `#[repr(C)]` attribute for C compatibility.
+/// flutter_rust_bridge:non_opaque
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
 pub enum PkcsType {
     /// RSA Cryptography Standard.
     Pkcs1,
@@ -67,7 +77,7 @@ pub enum PkcsType {
     Pkcs5,
     /// Cryptographic Message Syntax Standard.
     Pkcs7,
-    /// Private-Key Information Syntax Standard.
+    /// Private-Key Information Syntax Standard, version 1 (RFC 5208).
     Pkcs8,
     /// Selected Attribute Types.
     Pkcs9,
@@ -77,6 +87,8 @@ pub enum PkcsType {
     Pkcs11,
     /// Personal Information Exchange Syntax Standard.
     Pkcs12,
+    /// Asymmetric Key Packages, PKCS#8 version 2 (RFC 5958), which also carries the public key.
+    Pkcs8V2,
 }
 
 /// Enumerates the types within the X.509 standard.
@@ -98,7 +110,10 @@ pub enum PkcsType {
 ///
 /// Uses `#[repr(C)]` for compatibility with C language standards, facilitating use
 /// in systems where interoperability with C is required.
+/// flutter_rust_bridge:non_opaque
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
 pub enum X509Type {
     /// Represents an X.509 certificate.
     Certificate,
@@ -106,6 +121,54 @@ pub enum X509Type {
     CertificateRevocationList,
     /// Represents an X.509 Certificate Signing Request (CSR).
     CertificateSigningRequest,
+    /// Represents the `SubjectPublicKeyInfo` structure of X.509 for public keys (RFC 5280).
+    SubjectPublicKeyInfo,
+}
+
+/// Encoding of imported and exported asymmetric keys.
+///
+/// [KeyFormat::Raw] is the bare key material of the algorithm:
+/// - elliptic curve private keys are the big-endian scalar, public keys the SEC 1 point in the
+///   [PointEncoding](crate::common::crypto::algorithms::encryption::PointEncoding) of the spec,
+/// - `Curve25519` private keys are the 32 byte Ed25519 seed (or X25519 scalar), public keys 32 bytes,
+/// - `RSA` keys are the PKCS#1 `RSAPrivateKey` and `RSAPublicKey` structures,
+/// - other algorithms use the encoding of their specification.
+///
+/// The supported standards are PKCS#8 v1 and v2 and SEC 1 for private keys and
+/// `SubjectPublicKeyInfo` for public keys, all DER encoded. [KeyFormat::PEM] exports private
+/// keys as PKCS#8 and public keys as `SubjectPublicKeyInfo`, on import the format is taken from
+/// the PEM label.
+///
+/// # Examples
+///
+/// ```rust
+/// use crypto_layer::common::crypto::pkcs::standards::{KeyFormat, PkcsType, PkiStandards};
+///
+/// let format = KeyFormat::Pki(PkiStandards::Pkcs(PkcsType::Pkcs8));
+/// assert_eq!(format, KeyFormat::PKCS8);
+/// ```
+/// flutter_rust_bridge:non_opaque
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-interface", derive(ts_rs::TS), ts(export))]
+pub enum KeyFormat {
+    /// The bare key material.
+    Raw,
+    /// DER encoding defined by a PKI standard.
+    Pki(PkiStandards),
+}
+
+impl KeyFormat {
+    /// PKCS#8 v1 `PrivateKeyInfo`.
+    pub const PKCS8: Self = Self::Pki(PkiStandards::Pkcs(PkcsType::Pkcs8));
+    /// PKCS#8 v2 `OneAsymmetricKey`, including the public key.
+    pub const PKCS8_V2: Self = Self::Pki(PkiStandards::Pkcs(PkcsType::Pkcs8V2));
+    /// X.509 `SubjectPublicKeyInfo`.
+    pub const SPKI: Self = Self::Pki(PkiStandards::X509(X509Type::SubjectPublicKeyInfo));
+    /// SEC 1 `ECPrivateKey`.
+    pub const SEC1: Self = Self::Pki(PkiStandards::Sec1);
+    /// PEM armor (RFC 7468) of the DER formats, see [pem](super::pem).
+    pub const PEM: Self = Self::Pki(PkiStandards::Pem);
 }
 
 /// Represents the collection of Object Identifiers (OIDs) used in various cryptographic standards.
@@ -152,6 +215,9 @@ pub enum ObjectIdentifiers {
 /// # Encryption Algorithms
 /// - `Aes128Cbc`, `Aes256Gcm`, etc.
 ///
+/// # Password-Based Cryptography
+/// - `Pbes2`, `Pbkdf2`, `IdScrypt`, `HmacWithSha*`
+///
 /// # Key Agreement
 /// - `DhPublicNumber`, `EcdhStandardCurves`
 ///
@@ -199,6 +265,14 @@ pub enum OidType {
     EcdsaWithSha256,
     EcdsaWithSha384,
     EcdsaWithSha512,
+    IdEd25519,
+    IdX25519,
+    Prime256v1,
+    Secp384r1,
+    Secp521r1,
+    Secp256k1,
+    IdEd448,
+    IdX448,
 
     // Encryption Algorithms
     Aes128Cbc,
@@ -207,6 +281,14 @@ pub enum OidType {
     Aes128Gcm,
     Aes192Gcm,
     Aes256Gcm,
+    ChaCha20Poly1305,
+
+    // Password-Based Cryptography
+    Pbes2,
+    Pbkdf2,
+    IdScrypt,
+    HmacWithSha256,
+    HmacWithSha512,
 
     // Key Agreement
     DhPublicNumber,
@@ -262,12 +344,26 @@ impl OidType {
             OidType::EcdsaWithSha256 => "1.2.840.10045.4.3.2",
             OidType::EcdsaWithSha384 => "1.2.840.10045.4.3.3",
             OidType::EcdsaWithSha512 => "1.2.840.10045.4.3.4",
+            OidType::IdEd25519 => "1.3.101.112",
+            OidType::IdX25519 => "1.3.101.110",
+            OidType::Prime256v1 => "1.2.840.10045.3.1.7",
+            OidType::Secp384r1 => "1.3.132.0.34",
+            OidType::Secp521r1 => "1.3.132.0.35",
+            OidType::Secp256k1 => "1.3.132.0.10",
+            OidType::IdEd448 => "1.3.101.113",
+            OidType::IdX448 => "1.3.101.111",
             OidType::Aes128Cbc => "2.16.840.1.101.3.4.1.2",
             OidType::Aes192Cbc => "2.16.840.1.101.3.4.1.22",
             OidType::Aes256Cbc => "2.16.840.1.101.3.4.1.42",
             OidType::Aes128Gcm => "2.16.840.1.101.3.4.1.6",
             OidType::Aes192Gcm => "2.16.840.1.101.3.4.1.26",
             OidType::Aes256Gcm => "2.16.840.1.101.3.4.1.46",
+            OidType::ChaCha20Poly1305 => "1.2.840.113549.1.9.16.3.18",
+            OidType::Pbes2 => "1.2.840.113549.1.5.13",
+            OidType::Pbkdf2 => "1.2.840.113549.1.5.12",
+            OidType::IdScrypt => "1.3.6.1.4.1.11591.4.11",
+            OidType::HmacWithSha256 => "1.2.840.113549.2.9",
+            OidType::HmacWithSha512 => "1.2.840.113549.2.11",
             OidType::DhPublicNumber => "1.2.840.10046.2.1",
             OidType::EcdhStandardCurves => "1.3.132.0",
             OidType::EcdhSpecifiedCurves => "1.2.840.10045.3.1",
@@ -297,4 +393,10 @@ impl OidType {
             OidType::Pkcs9SigningTime => "1.2.840.113549.1.9.5",
         }
     }
+
+    /// The OID for DER encoding.
+    #[cfg(feature = "software")]
+    pub(crate) fn oid(self) -> pkcs8::ObjectIdentifier {
+        pkcs8::ObjectIdentifier::new(self.as_str()).expect("OidType only contains valid OIDs")
+    }
 }
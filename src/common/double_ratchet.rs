@@ -0,0 +1,656 @@
+//! [Double Ratchet](https://signal.org/docs/specifications/doubleratchet/) sessions with header encryption.
+//!
+//! The Diffie-Hellman ratchet uses `Curve25519` key pairs of the [Provider], hashing and AEAD are
+//! delegated to the provider as well. A session is usually set up with a shared secret and the
+//! responder's signed prekey from [X3DH](crate::common::x3dh).
+//!
+//! Message keys of skipped messages are kept up to [DoubleRatchetConfig::max_skip] per chain and
+//! [DoubleRatchetConfig::max_skipped_keys] in total, the oldest ones are dropped first.
+//! Sessions can be persisted with [DoubleRatchet::save], which stores the state through
+//! [Provider::store_secret_data].
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{common::double_ratchet::{DoubleRatchet, DoubleRatchetConfig}, prelude::*};
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeySpec {
+//!     cipher: Cipher::AesGcm256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     non_exportable: false,
+//!     ..Default::default()
+//! };
+//! let config = DoubleRatchetConfig::new(b"MyProtocol", spec);
+//! let shared_secret = provider.get_random(32);
+//!
+//! let bob_key_pair = provider
+//!     .create_key_pair(KeyPairSpec {
+//!         asym_spec: AsymmetricKeySpec::Curve25519,
+//!         cipher: None,
+//!         signing_hash: CryptoHash::Sha2_256,
+//!         ephemeral: true,
+//!         non_exportable: false,
+//!         ..Default::default()
+//!     })
+//!     .unwrap();
+//! # #[allow(deprecated)]
+//! let bob_public_key = bob_key_pair.start_dh_exchange().unwrap().get_public_key().unwrap();
+//!
+//! let mut alice = DoubleRatchet::initiate(&mut provider, config.clone(), &shared_secret, &bob_public_key).unwrap();
+//! let mut bob = DoubleRatchet::respond(&mut provider, config, &shared_secret, &bob_key_pair).unwrap();
+//!
+//! let message = alice.encrypt(&mut provider, b"Hello Bob", b"ad").unwrap();
+//! assert_eq!(bob.decrypt(&mut provider, &message, b"ad").unwrap(), b"Hello Bob");
+//! let message = bob.encrypt(&mut provider, b"Hello Alice", b"ad").unwrap();
+//! assert_eq!(alice.decrypt(&mut provider, &message, b"ad").unwrap(), b"Hello Alice");
+//! ```
+
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::common::{
+    config::{KeyPairSpec, KeySpec, KeyUsage},
+    crypto::{algorithms::encryption::AsymmetricKeySpec, hkdf},
+    error::CalError,
+    KeyHandle, KeyPairHandle, Provider,
+};
+
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+/// `dh || pn || n`
+const HEADER_LEN: usize = KEY_LEN + 4 + 4;
+
+/// Configuration of a [DoubleRatchet] session, which is persisted with the session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Zeroize)]
+pub struct DoubleRatchetConfig {
+    /// Application specific info for the KDFs.
+    pub info: Vec<u8>,
+    /// `cipher` is used for messages and headers, `signing_hash` for the KDFs.
+    pub key_spec: KeySpec,
+    /// Maximum number of message keys skipped in a single chain.
+    pub max_skip: u32,
+    /// Maximum number of stored message keys of skipped messages.
+    pub max_skipped_keys: usize,
+}
+
+impl DoubleRatchetConfig {
+    /// Creates a configuration allowing 1000 skipped messages per chain and 2000 in total.
+    pub fn new(info: &[u8], key_spec: KeySpec) -> Self {
+        Self {
+            info: info.to_vec(),
+            key_spec,
+            max_skip: 1000,
+            max_skipped_keys: 2000,
+        }
+    }
+}
+
+/// Ratchet key pair in the format of the provider, as it has to be persisted with the session.
+#[derive(Clone, Serialize, Deserialize, Zeroize)]
+struct RatchetKeyPair {
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+    /// X25519 public key sent in the headers.
+    dh_public_key: Vec<u8>,
+}
+
+fn ratchet_key_pair_spec() -> KeyPairSpec {
+    KeyPairSpec {
+        asym_spec: AsymmetricKeySpec::Curve25519,
+        cipher: None,
+        signing_hash: Default::default(),
+        rsa_padding: Default::default(),
+        signature_encoding: Default::default(),
+        point_encoding: Default::default(),
+        ephemeral: true,
+        non_exportable: false,
+        not_before: None,
+        not_after: None,
+        process_not_after: None,
+        key_usage: KeyUsage::ALL,
+    }
+}
+
+impl RatchetKeyPair {
+    #[allow(deprecated)]
+    fn from_key_pair(key_pair: &KeyPairHandle) -> Result<Self, CalError> {
+        Ok(Self {
+            private_key: key_pair.extract_key()?,
+            public_key: key_pair.get_public_key()?,
+            dh_public_key: key_pair.start_dh_exchange()?.get_public_key()?,
+        })
+    }
+
+    fn generate(provider: &mut Provider) -> Result<Self, CalError> {
+        Self::from_key_pair(&provider.create_key_pair(ratchet_key_pair_spec())?)
+    }
+
+    #[allow(deprecated)]
+    fn dh(
+        &self,
+        provider: &mut Provider,
+        public_key: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, CalError> {
+        let key_pair = provider.import_key_pair(
+            ratchet_key_pair_spec(),
+            &self.public_key,
+            &self.private_key,
+        )?;
+        Ok(Zeroizing::new(
+            key_pair
+                .start_dh_exchange()?
+                .compute_shared_secret(public_key)?,
+        ))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Zeroize)]
+struct SkippedMessageKey {
+    header_key: Vec<u8>,
+    message_number: u32,
+    message_key: Vec<u8>,
+}
+
+struct Header {
+    dh_public_key: Vec<u8>,
+    previous_chain_length: u32,
+    message_number: u32,
+}
+
+impl Header {
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.dh_public_key.as_slice(),
+            &self.previous_chain_length.to_be_bytes(),
+            &self.message_number.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, CalError> {
+        if bytes.len() != HEADER_LEN {
+            return Err(invalid_message());
+        }
+        let (dh_public_key, counters) = bytes.split_at(KEY_LEN);
+        let (previous_chain_length, message_number) = counters.split_at(4);
+
+        Ok(Self {
+            dh_public_key: dh_public_key.to_vec(),
+            previous_chain_length: u32::from_be_bytes(
+                previous_chain_length
+                    .try_into()
+                    .expect("Length already checked"),
+            ),
+            message_number: u32::from_be_bytes(
+                message_number.try_into().expect("Length already checked"),
+            ),
+        })
+    }
+}
+
+fn invalid_message() -> CalError {
+    CalError::failed_operation(
+        "Failed to decrypt Double Ratchet message".to_owned(),
+        true,
+        None,
+    )
+}
+
+/// State of a Double Ratchet session.
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct DoubleRatchet {
+    config: DoubleRatchetConfig,
+    sending_key_pair: RatchetKeyPair,
+    receiving_public_key: Option<Vec<u8>>,
+    root_key: Vec<u8>,
+    sending_chain_key: Option<Vec<u8>>,
+    receiving_chain_key: Option<Vec<u8>>,
+    sending_header_key: Option<Vec<u8>>,
+    receiving_header_key: Option<Vec<u8>>,
+    next_sending_header_key: Vec<u8>,
+    next_receiving_header_key: Vec<u8>,
+    sending_message_number: u32,
+    receiving_message_number: u32,
+    previous_chain_length: u32,
+    skipped_message_keys: Vec<SkippedMessageKey>,
+}
+
+impl DoubleRatchet {
+    /// Derives the root key and the initial header keys from the shared secret.
+    fn initial_keys(
+        provider: &Provider,
+        config: &DoubleRatchetConfig,
+        shared_secret: &[u8],
+    ) -> Result<[Vec<u8>; 3], CalError> {
+        let hash = config.key_spec.signing_hash;
+        let pseudo_random_key = hkdf::extract(provider, hash, &[], shared_secret)?;
+        let output = hkdf::expand(
+            provider,
+            hash,
+            &pseudo_random_key,
+            &[config.info.as_slice(), b"DoubleRatchetInit"].concat(),
+            3 * KEY_LEN,
+        )?;
+
+        Ok([
+            output[..KEY_LEN].to_vec(),
+            output[KEY_LEN..2 * KEY_LEN].to_vec(),
+            output[2 * KEY_LEN..].to_vec(),
+        ])
+    }
+
+    /// Starts a session as the party sending the first message.
+    ///
+    /// `remote_public_key` is the X25519 public key of the responder's ratchet key pair,
+    /// as returned by [DHExchange::get_public_key](crate::common::DHExchange::get_public_key).
+    pub fn initiate(
+        provider: &mut Provider,
+        config: DoubleRatchetConfig,
+        shared_secret: &[u8],
+        remote_public_key: &[u8],
+    ) -> Result<Self, CalError> {
+        let [root_key, header_key, next_header_key] =
+            Self::initial_keys(provider, &config, shared_secret)?;
+
+        let sending_key_pair = RatchetKeyPair::generate(provider)?;
+        let dh_output = sending_key_pair.dh(provider, remote_public_key)?;
+        let mut session = Self {
+            config,
+            sending_key_pair,
+            receiving_public_key: Some(remote_public_key.to_vec()),
+            root_key,
+            sending_chain_key: None,
+            receiving_chain_key: None,
+            sending_header_key: Some(header_key),
+            receiving_header_key: None,
+            next_sending_header_key: Vec::new(),
+            next_receiving_header_key: next_header_key,
+            sending_message_number: 0,
+            receiving_message_number: 0,
+            previous_chain_length: 0,
+            skipped_message_keys: Vec::new(),
+        };
+
+        let [root_key, chain_key, next_header_key] = session.kdf_root_key(provider, &dh_output)?;
+        session.root_key = root_key;
+        session.sending_chain_key = Some(chain_key);
+        session.next_sending_header_key = next_header_key;
+
+        Ok(session)
+    }
+
+    /// Starts a session as the party receiving the first message.
+    ///
+    /// `key_pair` is an exportable `Curve25519` key pair, usually the signed prekey used in X3DH.
+    pub fn respond(
+        provider: &mut Provider,
+        config: DoubleRatchetConfig,
+        shared_secret: &[u8],
+        key_pair: &KeyPairHandle,
+    ) -> Result<Self, CalError> {
+        if key_pair.spec().asym_spec != AsymmetricKeySpec::Curve25519 {
+            return Err(CalError::bad_parameter(
+                format!(
+                    "The Double Ratchet requires a Curve25519 key pair, got {:?}",
+                    key_pair.spec().asym_spec
+                ),
+                true,
+                None,
+            ));
+        }
+
+        let [root_key, header_key, next_header_key] =
+            Self::initial_keys(provider, &config, shared_secret)?;
+
+        Ok(Self {
+            config,
+            sending_key_pair: RatchetKeyPair::from_key_pair(key_pair)?,
+            receiving_public_key: None,
+            root_key,
+            sending_chain_key: None,
+            receiving_chain_key: None,
+            sending_header_key: None,
+            receiving_header_key: None,
+            next_sending_header_key: next_header_key,
+            next_receiving_header_key: header_key,
+            sending_message_number: 0,
+            receiving_message_number: 0,
+            previous_chain_length: 0,
+            skipped_message_keys: Vec::new(),
+        })
+    }
+
+    pub fn config(&self) -> &DoubleRatchetConfig {
+        &self.config
+    }
+
+    /// Number of currently stored message keys of skipped messages.
+    pub fn skipped_message_keys(&self) -> usize {
+        self.skipped_message_keys.len()
+    }
+
+    /// `KDF_RK_HE(rk, dh_out)` returning the new root key, chain key and next header key.
+    fn kdf_root_key(
+        &self,
+        provider: &Provider,
+        dh_output: &[u8],
+    ) -> Result<[Vec<u8>; 3], CalError> {
+        let hash = self.config.key_spec.signing_hash;
+        let pseudo_random_key = hkdf::extract(provider, hash, &self.root_key, dh_output)?;
+        let output = hkdf::expand(
+            provider,
+            hash,
+            &pseudo_random_key,
+            &[self.config.info.as_slice(), b"DoubleRatchetRoot"].concat(),
+            3 * KEY_LEN,
+        )?;
+
+        Ok([
+            output[..KEY_LEN].to_vec(),
+            output[KEY_LEN..2 * KEY_LEN].to_vec(),
+            output[2 * KEY_LEN..].to_vec(),
+        ])
+    }
+
+    /// `KDF_CK(ck)` returning the next chain key and the message key.
+    fn kdf_chain_key(
+        &self,
+        provider: &Provider,
+        chain_key: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+        let hash = self.config.key_spec.signing_hash;
+        let message_key = hkdf::hmac(provider, hash, chain_key, &[0x01])?;
+        let chain_key = hkdf::hmac(provider, hash, chain_key, &[0x02])?;
+        Ok((chain_key[..KEY_LEN].to_vec(), message_key.to_vec()))
+    }
+
+    fn import_key(&self, provider: &mut Provider, key: &[u8]) -> Result<KeyHandle, CalError> {
+        provider.import_key(
+            KeySpec {
+                ephemeral: true,
+                ..self.config.key_spec
+            },
+            &key[..self.config.key_spec.cipher.len()],
+        )
+    }
+
+    /// Derives the AEAD key and nonce from a message key, which is used only once.
+    fn message_key_and_iv(
+        &self,
+        provider: &mut Provider,
+        message_key: &[u8],
+    ) -> Result<(KeyHandle, Vec<u8>), CalError> {
+        let hash = self.config.key_spec.signing_hash;
+        let cipher = self.config.key_spec.cipher;
+        let pseudo_random_key = hkdf::extract(provider, hash, &[], message_key)?;
+        let output = hkdf::expand(
+            provider,
+            hash,
+            &pseudo_random_key,
+            &[self.config.info.as_slice(), b"DoubleRatchetMessage"].concat(),
+            cipher.len() + cipher.iv_len(),
+        )?;
+
+        let key = self.import_key(provider, &output[..cipher.len()])?;
+        Ok((key, output[cipher.len()..].to_vec()))
+    }
+
+    fn encrypt_header(
+        &self,
+        provider: &mut Provider,
+        header_key: &[u8],
+        header: &Header,
+    ) -> Result<Vec<u8>, CalError> {
+        let iv = provider.get_random(self.config.key_spec.cipher.iv_len());
+        let key = self.import_key(provider, header_key)?;
+        let encrypted_header = key.encrypt_with_aad(&header.to_bytes(), &iv, &[])?;
+        Ok([iv, encrypted_header].concat())
+    }
+
+    fn decrypt_header(
+        &self,
+        provider: &mut Provider,
+        header_key: &[u8],
+        encrypted_header: &[u8],
+    ) -> Option<Header> {
+        let (iv, encrypted_header) =
+            encrypted_header.split_at(self.config.key_spec.cipher.iv_len());
+        let key = self.import_key(provider, header_key).ok()?;
+        let header = key.decrypt_with_aad(encrypted_header, iv, &[]).ok()?;
+        Header::from_bytes(&header).ok()
+    }
+
+    fn encrypted_header_len(&self) -> usize {
+        self.config.key_spec.cipher.iv_len() + HEADER_LEN + TAG_LEN
+    }
+
+    /// Encrypts `plaintext`, authenticating `associated_data` which is not part of the message.
+    ///
+    /// The responder can only encrypt after decrypting the first message of the initiator.
+    pub fn encrypt(
+        &mut self,
+        provider: &mut Provider,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let (Some(chain_key), Some(header_key)) = (
+            self.sending_chain_key.as_ref(),
+            self.sending_header_key.as_ref(),
+        ) else {
+            return Err(CalError::failed_operation(
+                "The Double Ratchet session cannot send before receiving the first message"
+                    .to_owned(),
+                true,
+                None,
+            ));
+        };
+
+        let (chain_key, message_key) = self.kdf_chain_key(provider, chain_key)?;
+        let header = Header {
+            dh_public_key: self.sending_key_pair.dh_public_key.clone(),
+            previous_chain_length: self.previous_chain_length,
+            message_number: self.sending_message_number,
+        };
+        let encrypted_header = self.encrypt_header(provider, header_key, &header)?;
+
+        let (key, iv) = self.message_key_and_iv(provider, &message_key)?;
+        let ciphertext = key.encrypt_with_aad(
+            plaintext,
+            &iv,
+            &[associated_data, &encrypted_header].concat(),
+        )?;
+
+        self.sending_chain_key = Some(chain_key);
+        self.sending_message_number =
+            self.sending_message_number.checked_add(1).ok_or_else(|| {
+                CalError::failed_operation(
+                    "Double Ratchet message number overflow".to_owned(),
+                    true,
+                    None,
+                )
+            })?;
+
+        Ok([encrypted_header, ciphertext].concat())
+    }
+
+    /// Decrypts a message, which may arrive out of order.
+    ///
+    /// The session is only updated if the message could be decrypted.
+    pub fn decrypt(
+        &mut self,
+        provider: &mut Provider,
+        message: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        if message.len() < self.encrypted_header_len() + TAG_LEN {
+            return Err(invalid_message());
+        }
+
+        let mut session = self.clone();
+        let plaintext = session.decrypt_inner(provider, message, associated_data)?;
+        *self = session;
+
+        Ok(plaintext)
+    }
+
+    fn decrypt_inner(
+        &mut self,
+        provider: &mut Provider,
+        message: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let (encrypted_header, ciphertext) = message.split_at(self.encrypted_header_len());
+        let associated_data = [associated_data, encrypted_header].concat();
+
+        if let Some(plaintext) =
+            self.try_skipped_message_keys(provider, encrypted_header, ciphertext, &associated_data)?
+        {
+            return Ok(plaintext);
+        }
+
+        let current_header = self
+            .receiving_header_key
+            .as_ref()
+            .and_then(|header_key| self.decrypt_header(provider, header_key, encrypted_header));
+        let header = match current_header {
+            Some(header) => header,
+            None => {
+                let header = self
+                    .decrypt_header(provider, &self.next_receiving_header_key, encrypted_header)
+                    .ok_or_else(invalid_message)?;
+                self.skip_message_keys(provider, header.previous_chain_length)?;
+                self.dh_ratchet(provider, &header)?;
+                header
+            }
+        };
+
+        self.skip_message_keys(provider, header.message_number)?;
+        let chain_key = self
+            .receiving_chain_key
+            .as_ref()
+            .ok_or_else(invalid_message)?;
+        let (chain_key, message_key) = self.kdf_chain_key(provider, chain_key)?;
+        self.receiving_chain_key = Some(chain_key);
+        self.receiving_message_number += 1;
+
+        let (key, iv) = self.message_key_and_iv(provider, &message_key)?;
+        key.decrypt_with_aad(ciphertext, &iv, &associated_data)
+            .map_err(|_| invalid_message())
+    }
+
+    fn try_skipped_message_keys(
+        &mut self,
+        provider: &mut Provider,
+        encrypted_header: &[u8],
+        ciphertext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Option<Vec<u8>>, CalError> {
+        let position = self.skipped_message_keys.iter().position(|skipped| {
+            self.decrypt_header(provider, &skipped.header_key, encrypted_header)
+                .is_some_and(|header| header.message_number == skipped.message_number)
+        });
+        let Some(position) = position else {
+            return Ok(None);
+        };
+
+        let skipped = self.skipped_message_keys.remove(position);
+        let (key, iv) = self.message_key_and_iv(provider, &skipped.message_key)?;
+        key.decrypt_with_aad(ciphertext, &iv, associated_data)
+            .map(Some)
+            .map_err(|_| invalid_message())
+    }
+
+    fn skip_message_keys(&mut self, provider: &mut Provider, until: u32) -> Result<(), CalError> {
+        if until.saturating_sub(self.receiving_message_number) > self.config.max_skip {
+            return Err(CalError::failed_operation(
+                format!(
+                    "Too many skipped Double Ratchet messages, at most {} are allowed",
+                    self.config.max_skip
+                ),
+                true,
+                None,
+            ));
+        }
+
+        let (Some(mut chain_key), Some(header_key)) = (
+            self.receiving_chain_key.clone(),
+            self.receiving_header_key.clone(),
+        ) else {
+            return Ok(());
+        };
+
+        while self.receiving_message_number < until {
+            let (next_chain_key, message_key) = self.kdf_chain_key(provider, &chain_key)?;
+            chain_key = next_chain_key;
+            self.skipped_message_keys.push(SkippedMessageKey {
+                header_key: header_key.clone(),
+                message_number: self.receiving_message_number,
+                message_key,
+            });
+            self.receiving_message_number += 1;
+        }
+        self.receiving_chain_key = Some(chain_key);
+
+        // Drop the oldest keys.
+        if self.skipped_message_keys.len() > self.config.max_skipped_keys {
+            let excess = self.skipped_message_keys.len() - self.config.max_skipped_keys;
+            self.skipped_message_keys.drain(..excess);
+        }
+
+        Ok(())
+    }
+
+    fn dh_ratchet(&mut self, provider: &mut Provider, header: &Header) -> Result<(), CalError> {
+        self.previous_chain_length = self.sending_message_number;
+        self.sending_message_number = 0;
+        self.receiving_message_number = 0;
+        self.sending_header_key = Some(self.next_sending_header_key.clone());
+        self.receiving_header_key = Some(self.next_receiving_header_key.clone());
+        self.receiving_public_key = Some(header.dh_public_key.clone());
+
+        let dh_output = self.sending_key_pair.dh(provider, &header.dh_public_key)?;
+        let [root_key, chain_key, next_header_key] = self.kdf_root_key(provider, &dh_output)?;
+        self.root_key = root_key;
+        self.receiving_chain_key = Some(chain_key);
+        self.next_receiving_header_key = next_header_key;
+
+        self.sending_key_pair = RatchetKeyPair::generate(provider)?;
+        let dh_output = self.sending_key_pair.dh(provider, &header.dh_public_key)?;
+        let [root_key, chain_key, next_header_key] = self.kdf_root_key(provider, &dh_output)?;
+        self.root_key = root_key;
+        self.sending_chain_key = Some(chain_key);
+        self.next_sending_header_key = next_header_key;
+
+        Ok(())
+    }
+
+    /// Persists the session under `id` with [Provider::store_secret_data].
+    ///
+    /// The session has to be saved again after each [DoubleRatchet::encrypt] and [DoubleRatchet::decrypt].
+    pub fn save(&self, provider: &mut Provider, id: &str) -> Result<(), CalError> {
+        let state = Zeroizing::new(rmp_serde::to_vec_named(self).map_err(|e| {
+            CalError::failed_operation(
+                "Failed to serialize Double Ratchet session".to_owned(),
+                false,
+                Some(anyhow::anyhow!(e)),
+            )
+        })?);
+        provider.store_secret_data(id.to_owned(), &state)
+    }
+
+    /// Loads a session persisted with [DoubleRatchet::save].
+    pub fn load(provider: &Provider, id: &str) -> Result<Self, CalError> {
+        let state = Zeroizing::new(provider.load_secret_data(id.to_owned())?);
+        rmp_serde::from_slice(&state).map_err(|e| {
+            CalError::failed_operation(
+                "Failed to deserialize Double Ratchet session".to_owned(),
+                true,
+                Some(anyhow::anyhow!(e)),
+            )
+        })
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt;
 use anyhow::anyhow;
 use thiserror;
 
+use crate::common::config::KeyUsage;
 use crate::storage::{StorageManagerError, StorageManagerInitializationError};
 
 // Feel free to add more items to error.
@@ -81,6 +82,22 @@ pub enum CalErrorKind {
     #[error("Ephemeral Key Error")]
     EphemeralKeyError,
 
+    /// The key reached the safety limit of its cipher and has to be rotated.
+    #[error("Usage limit of key {key_id} reached, the key has to be rotated.")]
+    KeyUsageLimitReached { key_id: String },
+
+    /// The key is not allowed to be used for the operation.
+    #[error("Key {key_id} is not allowed to {usage}.")]
+    KeyUsageNotAllowed { key_id: String, usage: String },
+
+    /// The validity period of the key has not started yet.
+    #[error("Key {key_id} is not valid yet.")]
+    KeyNotYetValid { key_id: String },
+
+    /// The validity period of the key ended for the requested operation.
+    #[error("Key {key_id} expired.")]
+    KeyExpired { key_id: String },
+
     /// Errors that do not fall into the above classes.
     #[error("Other Error")]
     Other,
@@ -199,6 +216,43 @@ impl CalError {
         }
     }
 
+    pub(crate) fn key_usage_limit_reached(key_id: impl Into<String>) -> Self {
+        Self {
+            error_kind: CalErrorKind::KeyUsageLimitReached {
+                key_id: key_id.into(),
+            },
+            source: anyhow!("Key Usage Limit Error"),
+        }
+    }
+
+    pub(crate) fn key_usage_not_allowed(key_id: impl Into<String>, usage: KeyUsage) -> Self {
+        Self {
+            error_kind: CalErrorKind::KeyUsageNotAllowed {
+                key_id: key_id.into(),
+                usage: usage.to_string(),
+            },
+            source: anyhow!("Key Usage Error"),
+        }
+    }
+
+    pub(crate) fn key_not_yet_valid(key_id: impl Into<String>) -> Self {
+        Self {
+            error_kind: CalErrorKind::KeyNotYetValid {
+                key_id: key_id.into(),
+            },
+            source: anyhow!("Key Validity Error"),
+        }
+    }
+
+    pub(crate) fn key_expired(key_id: impl Into<String>) -> Self {
+        Self {
+            error_kind: CalErrorKind::KeyExpired {
+                key_id: key_id.into(),
+            },
+            source: anyhow!("Key Validity Error"),
+        }
+    }
+
     pub fn error_kind(&self) -> CalErrorKind {
         self.error_kind.clone()
     }
@@ -0,0 +1,1031 @@
+//! JSON Web Encryption (RFC 7516) in compact and JSON serialization.
+//!
+//! The content encryption key (CEK) is agreed with ECDH-ES on `P-256` or X25519 key pairs,
+//! encrypted with RSA-OAEP-256 or AES key wrap, or is the [KeyHandle] itself (`dir`). Content is
+//! encrypted with `A256GCM` or `A128CBC-HS256`, using the CEK as ephemeral [KeyHandle]. `dir`
+//! with `A128CBC-HS256` extracts the key, as it is split into an HMAC and an AES key.
+//!
+//! As for JWS, a JWE is only decrypted if its key management algorithm is in the allow-list
+//! passed to the decryption and matches the key. Compression (`zip`) and critical header
+//! parameters are not supported. `Curve25519` key pairs agree on X25519, Ed25519 keys as created
+//! by [Provider::create_key_pair] are converted to X25519 keys on both sides.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{
+//!     common::jose::jwe::{self, Algorithm, Encryption, JweHeader},
+//!     prelude::*,
+//! };
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::P256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     ..Default::default()
+//! };
+//! let key_pair = provider.create_key_pair(spec).unwrap();
+//!
+//! let header = JweHeader::new(Algorithm::EcdhEsA256KW, Encryption::A256GCM);
+//! let jwe = jwe::encrypt_compact(&mut provider, &key_pair, &header, b"secret").unwrap();
+//!
+//! let decrypted =
+//!     jwe::decrypt_compact(&mut provider, &jwe, &key_pair, &[Algorithm::EcdhEsA256KW]).unwrap();
+//! assert_eq!(decrypted.plaintext, b"secret");
+//! assert!(jwe::decrypt_compact(&mut provider, &jwe, &key_pair, &[Algorithm::EcdhEs]).is_err());
+//! ```
+
+use anyhow::anyhow;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use strum::{EnumString, IntoStaticStr};
+use zeroize::Zeroizing;
+
+use super::{
+    base64url, base64url_decode, base64url_encode,
+    jwk::{self, Curve, Jwk},
+};
+use crate::common::{
+    config::{KeyPairSpec, KeySpec, KeyUsage},
+    crypto::{
+        algorithms::{
+            encryption::{AsymmetricKeySpec, Cipher},
+            hashes::CryptoHash,
+        },
+        hkdf::constant_time_eq,
+    },
+    error::CalError,
+    KeyHandle, KeyPairHandle, Provider,
+};
+
+/// Supported key management algorithms (RFC 7518, section 4).
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, IntoStaticStr,
+)]
+pub enum Algorithm {
+    /// ECDH-ES key agreement of the CEK.
+    #[serde(rename = "ECDH-ES")]
+    #[strum(serialize = "ECDH-ES")]
+    EcdhEs,
+    /// ECDH-ES key agreement of a key wrapping the CEK with `A256KW`.
+    #[serde(rename = "ECDH-ES+A256KW")]
+    #[strum(serialize = "ECDH-ES+A256KW")]
+    EcdhEsA256KW,
+    /// `RSAES-OAEP` with SHA-256.
+    #[serde(rename = "RSA-OAEP-256")]
+    #[strum(serialize = "RSA-OAEP-256")]
+    RsaOaep256,
+    /// AES key wrap with a 256 bit key.
+    A256KW,
+    /// The key is the CEK.
+    #[serde(rename = "dir")]
+    #[strum(serialize = "dir")]
+    Dir,
+}
+
+impl Algorithm {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Algorithms using the key management key or the agreed key as CEK.
+    fn is_direct(self) -> bool {
+        matches!(self, Self::EcdhEs | Self::Dir)
+    }
+
+    /// Fails unless `key` is a key of this algorithm.
+    fn check_key(self, key: JweKey<'_>) -> Result<(), CalError> {
+        let matches = match (self, key) {
+            (Self::EcdhEs | Self::EcdhEsA256KW, JweKey::KeyPair(key_pair)) => {
+                ecdh_curve(key_pair.spec().asym_spec).is_ok()
+            }
+            (Self::RsaOaep256, JweKey::KeyPair(key_pair)) => {
+                let spec = key_pair.spec();
+                spec.asym_spec.is_rsa() && spec.signing_hash == CryptoHash::Sha2_256
+            }
+            (Self::A256KW, JweKey::Key(key)) => {
+                matches!(key.spec().cipher, Cipher::AesGcm256 | Cipher::AesCbc256)
+            }
+            (Self::Dir, JweKey::Key(_)) => true,
+            _ => false,
+        };
+        if !matches {
+            return Err(CalError::bad_parameter(
+                format!("Key does not match the JWE algorithm {}", self.as_str()),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Supported content encryption algorithms (RFC 7518, section 5).
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, IntoStaticStr,
+)]
+pub enum Encryption {
+    /// AES-256-GCM.
+    A256GCM,
+    /// AES-128-CBC with HMAC-SHA-256 truncated to 128 bits.
+    #[serde(rename = "A128CBC-HS256")]
+    #[strum(serialize = "A128CBC-HS256")]
+    A128CbcHs256,
+}
+
+impl Encryption {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Length of the CEK, which is the same for both algorithms.
+    fn key_len(self) -> usize {
+        32
+    }
+
+    fn iv_len(self) -> usize {
+        match self {
+            Self::A256GCM => 12,
+            Self::A128CbcHs256 => 16,
+        }
+    }
+}
+
+const TAG_LEN: usize = 16;
+
+/// Key encrypting or decrypting a JWE.
+#[derive(Clone, Copy, Debug)]
+pub enum JweKey<'a> {
+    /// Key pair of the ECDH-ES and RSA algorithms.
+    KeyPair(&'a KeyPairHandle),
+    /// Key of `A256KW` and `dir`.
+    Key(&'a KeyHandle),
+}
+
+impl<'a> From<&'a KeyPairHandle> for JweKey<'a> {
+    fn from(key_pair: &'a KeyPairHandle) -> Self {
+        Self::KeyPair(key_pair)
+    }
+}
+
+impl<'a> From<&'a KeyHandle> for JweKey<'a> {
+    fn from(key: &'a KeyHandle) -> Self {
+        Self::Key(key)
+    }
+}
+
+/// Header of a JWE.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JweHeader {
+    pub alg: Algorithm,
+    pub enc: Encryption,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+
+    /// Media type of the JWE.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+
+    /// Media type of the plaintext, e.g. `JWT` for nested JWTs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cty: Option<String>,
+
+    /// Ephemeral public key of the ECDH-ES algorithms, set on encryption.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epk: Option<Jwk>,
+
+    /// Information about the producer for the key agreement of the ECDH-ES algorithms.
+    #[serde(default, with = "base64url", skip_serializing_if = "Option::is_none")]
+    pub apu: Option<Vec<u8>>,
+
+    /// Information about the recipient for the key agreement of the ECDH-ES algorithms.
+    #[serde(default, with = "base64url", skip_serializing_if = "Option::is_none")]
+    pub apv: Option<Vec<u8>>,
+
+    /// Extensions which need to be understood, no extensions are supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+
+    /// Other header parameters.
+    #[serde(flatten)]
+    pub additional: Map<String, Value>,
+}
+
+impl JweHeader {
+    pub fn new(alg: Algorithm, enc: Encryption) -> Self {
+        Self {
+            alg,
+            enc,
+            kid: None,
+            typ: None,
+            cty: None,
+            epk: None,
+            apu: None,
+            apv: None,
+            crit: None,
+            additional: Map::new(),
+        }
+    }
+}
+
+/// Header and plaintext of a decrypted JWE.
+///
+/// The header of the JSON serialization joins the protected, shared and per-recipient header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decrypted {
+    pub header: JweHeader,
+    pub plaintext: Vec<u8>,
+}
+
+/// Members of the general and flattened JSON serialization.
+#[derive(Serialize, Deserialize)]
+struct JsonJwe {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protected: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unprotected: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recipients: Vec<JsonRecipient>,
+    #[serde(flatten)]
+    flattened: JsonRecipient,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aad: Option<String>,
+    iv: String,
+    ciphertext: String,
+    tag: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct JsonRecipient {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted_key: Option<String>,
+}
+
+impl JsonRecipient {
+    fn is_empty(&self) -> bool {
+        self.header.is_none() && self.encrypted_key.is_none()
+    }
+}
+
+/// Header parameters of a single recipient in the general JSON serialization.
+const RECIPIENT_PARAMETERS: [&str; 5] = ["alg", "kid", "epk", "apu", "apv"];
+
+fn invalid(message: impl std::fmt::Display) -> CalError {
+    CalError::bad_parameter(format!("Invalid JWE: {message}"), true, None)
+}
+
+fn decryption_failed() -> CalError {
+    CalError::failed_operation("Failed to decrypt JWE".to_owned(), true, None)
+}
+
+fn check_header(header: &JweHeader) -> Result<(), CalError> {
+    if header.crit.is_some() {
+        return Err(invalid("critical header parameters are not supported"));
+    }
+    if header.additional.contains_key("zip") {
+        return Err(invalid("compression is not supported"));
+    }
+    Ok(())
+}
+
+fn header_map(header: &JweHeader) -> Result<Map<String, Value>, CalError> {
+    check_header(header)?;
+    match serde_json::to_value(header) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => unreachable!("Headers are serialized as objects"),
+        Err(e) => Err(CalError::failed_operation(
+            "Failed to encode JWE header".to_owned(),
+            true,
+            Some(anyhow!(e)),
+        )),
+    }
+}
+
+fn encode_map(map: &Map<String, Value>) -> String {
+    base64url_encode(&serde_json::to_vec(map).expect("JSON maps are serializable"))
+}
+
+fn decode_map(encoded: &str) -> Result<Map<String, Value>, CalError> {
+    let json = base64url_decode(encoded, "JWE header")?;
+    serde_json::from_slice(&json).map_err(|e| {
+        CalError::bad_parameter("Invalid JWE header".to_owned(), true, Some(anyhow!(e)))
+    })
+}
+
+fn parse_header(map: Map<String, Value>) -> Result<JweHeader, CalError> {
+    let header: JweHeader = serde_json::from_value(Value::Object(map)).map_err(|e| {
+        CalError::bad_parameter("Invalid JWE header".to_owned(), true, Some(anyhow!(e)))
+    })?;
+    check_header(&header)?;
+    Ok(header)
+}
+
+fn check_allowed(header: &JweHeader, allowed: &[Algorithm]) -> Result<(), CalError> {
+    if !allowed.contains(&header.alg) {
+        return Err(invalid(format!(
+            "algorithm {} is not allowed",
+            header.alg.as_str()
+        )));
+    }
+    Ok(())
+}
+
+/// Curve of the ephemeral keys for an ECDH-ES recipient with `asym_spec`.
+fn ecdh_curve(asym_spec: AsymmetricKeySpec) -> Result<Curve, CalError> {
+    match asym_spec {
+        AsymmetricKeySpec::P256 => Ok(Curve::P256),
+        AsymmetricKeySpec::Curve25519 => Ok(Curve::X25519),
+        spec => Err(CalError::unsupported_algorithm(format!(
+            "ECDH-ES with {spec:?} keys"
+        ))),
+    }
+}
+
+/// Concat KDF with SHA-256 (NIST SP 800-56A, as profiled in RFC 7518, section 4.6.2).
+fn concat_kdf(
+    provider: &Provider,
+    shared_secret: &[u8],
+    algorithm_id: &str,
+    apu: &[u8],
+    apv: &[u8],
+    key_len: usize,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let length_prefixed =
+        |data: &[u8]| [(data.len() as u32).to_be_bytes().as_slice(), data].concat();
+    let other_info = [
+        length_prefixed(algorithm_id.as_bytes()),
+        length_prefixed(apu),
+        length_prefixed(apv),
+        ((key_len * 8) as u32).to_be_bytes().to_vec(),
+    ]
+    .concat();
+
+    let mut key = Zeroizing::new(Vec::with_capacity(key_len.next_multiple_of(32)));
+    for counter in 1..=key_len.div_ceil(32) as u32 {
+        let input = Zeroizing::new(
+            [
+                counter.to_be_bytes().as_slice(),
+                shared_secret,
+                other_info.as_slice(),
+            ]
+            .concat(),
+        );
+        key.extend(provider.hash(&input, CryptoHash::Sha2_256)?);
+    }
+    key.truncate(key_len);
+    Ok(key)
+}
+
+/// Derives the CEK of `ECDH-ES` or the key encryption key of `ECDH-ES+A256KW`.
+fn agreed_key(
+    provider: &Provider,
+    header: &JweHeader,
+    shared_secret: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    // X25519 yields zero for low order points.
+    if shared_secret.iter().all(|&byte| byte == 0) {
+        return Err(invalid("the ephemeral public key is a low order point"));
+    }
+    let (algorithm_id, key_len) = match header.alg {
+        Algorithm::EcdhEs => (header.enc.as_str(), header.enc.key_len()),
+        alg => (alg.as_str(), 32),
+    };
+    concat_kdf(
+        provider,
+        shared_secret,
+        algorithm_id,
+        header.apu.as_deref().unwrap_or_default(),
+        header.apv.as_deref().unwrap_or_default(),
+        key_len,
+    )
+}
+
+/// Public key of the recipient `key_pair` on the curve of [ecdh_curve].
+///
+/// Ed25519 public keys are mapped to the birational X25519 public key (RFC 7748, section 4.1),
+/// which is the public key of the X25519 key the recipient converts its Ed25519 key to.
+fn recipient_public_key(key_pair: &KeyPairHandle) -> Result<Vec<u8>, CalError> {
+    let public_key = key_pair.get_public_key()?;
+    if key_pair.spec().asym_spec != AsymmetricKeySpec::Curve25519
+        || jwk::export_public_key(key_pair)?.crv != Some(Curve::Ed25519)
+    {
+        return Ok(public_key);
+    }
+
+    let point = CompressedEdwardsY::from_slice(&public_key)
+        .ok()
+        .and_then(|point| point.decompress())
+        .ok_or_else(|| {
+            CalError::bad_parameter("Invalid Ed25519 public key".to_owned(), true, None)
+        })?;
+    Ok(point.to_montgomery().to_bytes().to_vec())
+}
+
+/// Agrees on a key with `key_pair` using a new ephemeral key, which is set as `epk`.
+fn agree_sender(
+    provider: &mut Provider,
+    key_pair: &KeyPairHandle,
+    header: &mut JweHeader,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let asym_spec = key_pair.spec().asym_spec;
+    let crv = ecdh_curve(asym_spec)?;
+    let exchange = provider.start_ephemeral_dh_exchange(KeyPairSpec {
+        asym_spec,
+        ephemeral: true,
+        ..Default::default()
+    })?;
+    header.epk = Some(Jwk::from_raw_public_key(crv, &exchange.get_public_key()?)?);
+    let shared_secret =
+        Zeroizing::new(exchange.compute_shared_secret(&recipient_public_key(key_pair)?)?);
+    agreed_key(provider, header, &shared_secret)
+}
+
+/// Agrees on a key with the `epk` of `header` using the private key of `key_pair`.
+///
+/// ECDH-ES is an exchange with the static key of the recipient by design.
+#[allow(deprecated)]
+fn agree_recipient(
+    provider: &Provider,
+    key_pair: &KeyPairHandle,
+    header: &JweHeader,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let epk = header
+        .epk
+        .as_ref()
+        .ok_or_else(|| invalid("missing header parameter \"epk\""))?;
+    if epk.crv != Some(ecdh_curve(key_pair.spec().asym_spec)?) {
+        return Err(invalid("the curve of \"epk\" does not match the key"));
+    }
+    let shared_secret = Zeroizing::new(
+        key_pair
+            .start_dh_exchange()?
+            .compute_shared_secret(&epk.raw_public_key()?)?,
+    );
+    agreed_key(provider, header, &shared_secret)
+}
+
+fn import_key_encryption_key(provider: &mut Provider, key: &[u8]) -> Result<KeyHandle, CalError> {
+    provider.import_key(
+        KeySpec {
+            cipher: Cipher::AesGcm256,
+            ephemeral: true,
+            key_usage: KeyUsage::WRAP,
+            ..Default::default()
+        },
+        key,
+    )
+}
+
+/// Content encryption key.
+enum Cek<'a> {
+    Raw(Zeroizing<Vec<u8>>),
+    /// Key of `dir`.
+    Key(&'a KeyHandle),
+}
+
+/// CEK of the direct algorithms for the sender, sets `epk` for `ECDH-ES`.
+fn direct_cek<'a>(
+    provider: &mut Provider,
+    key: JweKey<'a>,
+    header: &mut JweHeader,
+) -> Result<Cek<'a>, CalError> {
+    header.alg.check_key(key)?;
+    match key {
+        JweKey::Key(key) => Ok(Cek::Key(key)),
+        JweKey::KeyPair(key_pair) => Ok(Cek::Raw(agree_sender(provider, key_pair, header)?)),
+    }
+}
+
+/// Encrypts `cek` for the recipient `key`, sets `epk` for `ECDH-ES+A256KW`.
+fn encrypt_cek(
+    provider: &mut Provider,
+    key: JweKey<'_>,
+    header: &mut JweHeader,
+    cek: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    header.alg.check_key(key)?;
+    match (header.alg, key) {
+        (Algorithm::RsaOaep256, JweKey::KeyPair(key_pair)) => key_pair.encrypt_data(cek),
+        (Algorithm::A256KW, JweKey::Key(key)) => key.wrap_key(cek),
+        (Algorithm::EcdhEsA256KW, JweKey::KeyPair(key_pair)) => {
+            let key_encryption_key = agree_sender(provider, key_pair, header)?;
+            import_key_encryption_key(provider, &key_encryption_key)?.wrap_key(cek)
+        }
+        (alg, _) => Err(invalid(format!(
+            "{} does not encrypt the content encryption key",
+            alg.as_str()
+        ))),
+    }
+}
+
+/// Recovers the CEK from `encrypted_key` with the recipient `key`.
+fn decrypt_cek<'a>(
+    provider: &mut Provider,
+    key: JweKey<'a>,
+    header: &JweHeader,
+    encrypted_key: &[u8],
+) -> Result<Cek<'a>, CalError> {
+    header.alg.check_key(key)?;
+    if header.alg.is_direct() && !encrypted_key.is_empty() {
+        return Err(invalid(format!(
+            "{} needs an empty encrypted key",
+            header.alg.as_str()
+        )));
+    }
+
+    let cek = match (header.alg, key) {
+        (Algorithm::Dir, JweKey::Key(key)) => return Ok(Cek::Key(key)),
+        (Algorithm::EcdhEs, JweKey::KeyPair(key_pair)) => {
+            agree_recipient(provider, key_pair, header)?
+        }
+        (Algorithm::EcdhEsA256KW, JweKey::KeyPair(key_pair)) => {
+            let key_encryption_key = agree_recipient(provider, key_pair, header)?;
+            Zeroizing::new(
+                import_key_encryption_key(provider, &key_encryption_key)?
+                    .unwrap_key(encrypted_key)?,
+            )
+        }
+        (Algorithm::A256KW, JweKey::Key(key)) => Zeroizing::new(key.unwrap_key(encrypted_key)?),
+        (Algorithm::RsaOaep256, JweKey::KeyPair(key_pair)) => {
+            // A random CEK lets RSA decryption failures surface as authentication failures of
+            // the content (RFC 7516, section 11.5).
+            let key_len = header.enc.key_len();
+            Zeroizing::new(
+                key_pair
+                    .decrypt_data(encrypted_key)
+                    .ok()
+                    .filter(|cek| cek.len() == key_len)
+                    .unwrap_or_else(|| provider.get_random(key_len)),
+            )
+        }
+        _ => unreachable!("check_key only accepts matching keys"),
+    };
+    Ok(Cek::Raw(cek))
+}
+
+fn content_key(provider: &mut Provider, cipher: Cipher, key: &[u8]) -> Result<KeyHandle, CalError> {
+    provider.import_key(
+        KeySpec {
+            cipher,
+            signing_hash: CryptoHash::Sha2_256,
+            ephemeral: true,
+            key_usage: KeyUsage::ENCRYPT | KeyUsage::DECRYPT | KeyUsage::SIGN,
+            ..Default::default()
+        },
+        key,
+    )
+}
+
+/// HMAC and AES key of `A128CBC-HS256`.
+fn cbc_hmac_keys(
+    provider: &mut Provider,
+    cek: &Cek<'_>,
+) -> Result<(KeyHandle, KeyHandle), CalError> {
+    let cek = match cek {
+        Cek::Raw(cek) => cek.clone(),
+        Cek::Key(key) => Zeroizing::new(key.extract_key()?),
+    };
+    if cek.len() != Encryption::A128CbcHs256.key_len() {
+        return Err(invalid(format!(
+            "A128CBC-HS256 needs a 32 byte key, got {} bytes",
+            cek.len()
+        )));
+    }
+    let (mac_key, enc_key) = cek.split_at(16);
+    Ok((
+        content_key(provider, Cipher::AesCbc128, mac_key)?,
+        content_key(provider, Cipher::AesCbc128, enc_key)?,
+    ))
+}
+
+/// Tag of `A128CBC-HS256` (RFC 7518, section 5.2.2.1).
+fn cbc_hmac_tag(
+    mac_key: &KeyHandle,
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let aad_len = (aad.len() as u64 * 8).to_be_bytes();
+    let mut tag = mac_key.hmac(&[aad, iv, ciphertext, &aad_len].concat())?;
+    tag.truncate(TAG_LEN);
+    Ok(tag)
+}
+
+/// Returns the `A256GCM` key, `imported` holds a key imported from a raw CEK.
+fn gcm_key<'a>(
+    provider: &mut Provider,
+    cek: &Cek<'a>,
+    imported: &'a mut Option<KeyHandle>,
+) -> Result<&'a KeyHandle, CalError> {
+    match cek {
+        Cek::Key(key) if key.spec().cipher == Cipher::AesGcm256 => Ok(key),
+        Cek::Key(_) => Err(invalid("dir with A256GCM needs an AesGcm256 key")),
+        Cek::Raw(cek) => Ok(imported.insert(content_key(provider, Cipher::AesGcm256, cek)?)),
+    }
+}
+
+/// IV, cipher text and tag of the encrypted content.
+type EncryptedContent = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+/// Encrypts `plaintext` and returns the iv, cipher text and tag.
+fn encrypt_content(
+    provider: &mut Provider,
+    enc: Encryption,
+    cek: &Cek<'_>,
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<EncryptedContent, CalError> {
+    let iv = provider.get_random(enc.iv_len());
+    match enc {
+        Encryption::A256GCM => {
+            let mut imported = None;
+            let key = gcm_key(provider, cek, &mut imported)?;
+            let mut ciphertext = key.encrypt_with_aad(plaintext, &iv, aad)?;
+            let tag = ciphertext.split_off(ciphertext.len() - TAG_LEN);
+            Ok((iv, ciphertext, tag))
+        }
+        Encryption::A128CbcHs256 => {
+            let (mac_key, enc_key) = cbc_hmac_keys(provider, cek)?;
+            let ciphertext = enc_key.encrypt_cbc(plaintext, &iv)?;
+            let tag = cbc_hmac_tag(&mac_key, aad, &iv, &ciphertext)?;
+            Ok((iv, ciphertext, tag))
+        }
+    }
+}
+
+fn decrypt_content(
+    provider: &mut Provider,
+    enc: Encryption,
+    cek: &Cek<'_>,
+    iv: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    if iv.len() != enc.iv_len() || tag.len() != TAG_LEN {
+        return Err(invalid("invalid length of the iv or tag"));
+    }
+    match enc {
+        Encryption::A256GCM => {
+            let mut imported = None;
+            let key = gcm_key(provider, cek, &mut imported)?;
+            key.decrypt_with_aad(&[ciphertext, tag].concat(), iv, aad)
+                .map_err(|_| decryption_failed())
+        }
+        Encryption::A128CbcHs256 => {
+            let (mac_key, enc_key) = cbc_hmac_keys(provider, cek)?;
+            if !constant_time_eq(&cbc_hmac_tag(&mac_key, aad, iv, ciphertext)?, tag) {
+                return Err(decryption_failed());
+            }
+            enc_key
+                .decrypt_cbc(ciphertext, iv)
+                .map_err(|_| decryption_failed())
+        }
+    }
+}
+
+/// CEK and encrypted key of a single recipient.
+fn sender_cek<'a>(
+    provider: &mut Provider,
+    key: JweKey<'a>,
+    header: &mut JweHeader,
+) -> Result<(Cek<'a>, Vec<u8>), CalError> {
+    if header.alg.is_direct() {
+        return Ok((direct_cek(provider, key, header)?, Vec::new()));
+    }
+    let cek = Zeroizing::new(provider.get_random(header.enc.key_len()));
+    let encrypted_key = encrypt_cek(provider, key, header, &cek)?;
+    Ok((Cek::Raw(cek), encrypted_key))
+}
+
+/// Encrypts `plaintext` for `key` with the algorithms of `header` in compact serialization.
+///
+/// `epk` is set by the ECDH-ES algorithms.
+pub fn encrypt_compact<'a>(
+    provider: &mut Provider,
+    key: impl Into<JweKey<'a>>,
+    header: &JweHeader,
+    plaintext: &[u8],
+) -> Result<String, CalError> {
+    let mut header = header.clone();
+    check_header(&header)?;
+    let (cek, encrypted_key) = sender_cek(provider, key.into(), &mut header)?;
+    let protected = encode_map(&header_map(&header)?);
+    let (iv, ciphertext, tag) =
+        encrypt_content(provider, header.enc, &cek, plaintext, protected.as_bytes())?;
+
+    Ok([
+        protected,
+        base64url_encode(&encrypted_key),
+        base64url_encode(&iv),
+        base64url_encode(&ciphertext),
+        base64url_encode(&tag),
+    ]
+    .join("."))
+}
+
+/// Decrypts a JWE in compact serialization, if its algorithm is in `allowed`.
+pub fn decrypt_compact<'a>(
+    provider: &mut Provider,
+    jwe: &str,
+    key: impl Into<JweKey<'a>>,
+    allowed: &[Algorithm],
+) -> Result<Decrypted, CalError> {
+    let parts: Vec<&str> = jwe.split('.').collect();
+    let [protected, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+        return Err(invalid("compact serialization needs five parts"));
+    };
+    let header = parse_header(decode_map(protected)?)?;
+    check_allowed(&header, allowed)?;
+
+    let cek = decrypt_cek(
+        provider,
+        key.into(),
+        &header,
+        &base64url_decode(encrypted_key, "JWE encrypted key")?,
+    )?;
+    let plaintext = decrypt_content(
+        provider,
+        header.enc,
+        &cek,
+        &base64url_decode(iv, "JWE iv")?,
+        &base64url_decode(ciphertext, "JWE cipher text")?,
+        &base64url_decode(tag, "JWE tag")?,
+        protected.as_bytes(),
+    )?;
+    Ok(Decrypted { header, plaintext })
+}
+
+/// Additional authenticated data of the JSON serialization.
+fn json_aad(protected: &str, aad: Option<&str>) -> String {
+    match aad {
+        Some(aad) => format!("{protected}.{aad}"),
+        None => protected.to_owned(),
+    }
+}
+
+fn to_json(jwe: &JsonJwe) -> Result<String, CalError> {
+    serde_json::to_string(jwe).map_err(|e| {
+        CalError::failed_operation("Failed to encode JWE".to_owned(), true, Some(anyhow!(e)))
+    })
+}
+
+/// Encrypts `plaintext` in flattened JSON serialization, authenticating the optional `aad`.
+///
+/// The whole header is protected.
+pub fn encrypt_flattened<'a>(
+    provider: &mut Provider,
+    key: impl Into<JweKey<'a>>,
+    header: &JweHeader,
+    plaintext: &[u8],
+    aad: Option<&[u8]>,
+) -> Result<String, CalError> {
+    let mut header = header.clone();
+    check_header(&header)?;
+    let (cek, encrypted_key) = sender_cek(provider, key.into(), &mut header)?;
+    let protected = encode_map(&header_map(&header)?);
+    let aad = aad.map(base64url_encode);
+    let (iv, ciphertext, tag) = encrypt_content(
+        provider,
+        header.enc,
+        &cek,
+        plaintext,
+        json_aad(&protected, aad.as_deref()).as_bytes(),
+    )?;
+
+    to_json(&JsonJwe {
+        protected: Some(protected),
+        unprotected: None,
+        recipients: Vec::new(),
+        flattened: JsonRecipient {
+            header: None,
+            encrypted_key: (!encrypted_key.is_empty()).then(|| base64url_encode(&encrypted_key)),
+        },
+        aad,
+        iv: base64url_encode(&iv),
+        ciphertext: base64url_encode(&ciphertext),
+        tag: base64url_encode(&tag),
+    })
+}
+
+/// Encrypts `plaintext` once for every recipient in general JSON serialization.
+///
+/// `alg`, `kid`, `epk`, `apu` and `apv` are per-recipient header parameters, all other
+/// parameters have to be equal for all recipients and are protected. `ECDH-ES` and `dir` only
+/// support a single recipient.
+pub fn encrypt_json(
+    provider: &mut Provider,
+    recipients: &[(JweKey<'_>, JweHeader)],
+    plaintext: &[u8],
+    aad: Option<&[u8]>,
+) -> Result<String, CalError> {
+    let Some((first_key, first_header)) = recipients.first() else {
+        return Err(invalid("no recipients"));
+    };
+    let shared = |header: &JweHeader| {
+        header_map(header).map(|mut map| {
+            map.retain(|name, _| !RECIPIENT_PARAMETERS.contains(&name.as_str()));
+            map
+        })
+    };
+    let protected = shared(first_header)?;
+    for (_, header) in recipients {
+        if shared(header)? != protected {
+            return Err(invalid(
+                "recipients need the same header parameters apart from alg, kid, epk, apu and apv",
+            ));
+        }
+        if header.alg.is_direct() && recipients.len() > 1 {
+            return Err(invalid(format!(
+                "{} only supports a single recipient",
+                header.alg.as_str()
+            )));
+        }
+    }
+
+    let mut first_header = first_header.clone();
+    let (cek, first_encrypted_key) = sender_cek(provider, *first_key, &mut first_header)?;
+    let mut encrypted = vec![(first_header, first_encrypted_key)];
+    for (key, header) in &recipients[1..] {
+        let Cek::Raw(cek) = &cek else {
+            unreachable!("Only direct algorithms use a key as CEK");
+        };
+        let mut header = header.clone();
+        let encrypted_key = encrypt_cek(provider, *key, &mut header, cek)?;
+        encrypted.push((header, encrypted_key));
+    }
+
+    let recipients = encrypted
+        .iter()
+        .map(|(header, encrypted_key)| {
+            let mut map = header_map(header)?;
+            map.retain(|name, _| RECIPIENT_PARAMETERS.contains(&name.as_str()));
+            Ok(JsonRecipient {
+                header: Some(map),
+                encrypted_key: (!encrypted_key.is_empty()).then(|| base64url_encode(encrypted_key)),
+            })
+        })
+        .collect::<Result<_, CalError>>()?;
+
+    let protected = encode_map(&protected);
+    let aad = aad.map(base64url_encode);
+    let (iv, ciphertext, tag) = encrypt_content(
+        provider,
+        encrypted[0].0.enc,
+        &cek,
+        plaintext,
+        json_aad(&protected, aad.as_deref()).as_bytes(),
+    )?;
+
+    to_json(&JsonJwe {
+        protected: Some(protected),
+        unprotected: None,
+        recipients,
+        flattened: JsonRecipient::default(),
+        aad,
+        iv: base64url_encode(&iv),
+        ciphertext: base64url_encode(&ciphertext),
+        tag: base64url_encode(&tag),
+    })
+}
+
+/// Joins the header parameters of the protected, shared and per-recipient header.
+fn join_headers<'a>(
+    headers: impl IntoIterator<Item = &'a Map<String, Value>>,
+) -> Result<JweHeader, CalError> {
+    let mut joined = Map::new();
+    for header in headers {
+        for (name, value) in header {
+            if joined.insert(name.clone(), value.clone()).is_some() {
+                return Err(invalid(format!("duplicate header parameter \"{name}\"")));
+            }
+        }
+    }
+    parse_header(joined)
+}
+
+/// Decrypts a JWE in general or flattened JSON serialization and returns the plaintext of the
+/// first recipient with an algorithm in `allowed` matching `key`.
+pub fn decrypt_json<'a>(
+    provider: &mut Provider,
+    json: &str,
+    key: impl Into<JweKey<'a>>,
+    allowed: &[Algorithm],
+) -> Result<Decrypted, CalError> {
+    let key = key.into();
+    let jwe: JsonJwe = serde_json::from_str(json).map_err(|e| {
+        CalError::bad_parameter(
+            "Invalid JWE JSON serialization".to_owned(),
+            true,
+            Some(anyhow!(e)),
+        )
+    })?;
+    let recipients = match (jwe.recipients.is_empty(), jwe.flattened.is_empty()) {
+        (true, _) => vec![jwe.flattened],
+        (false, true) => jwe.recipients,
+        (false, false) => {
+            return Err(invalid(
+                "expected either \"recipients\" or \"header\" and \"encrypted_key\"",
+            ))
+        }
+    };
+
+    let protected = jwe.protected.as_deref().unwrap_or_default();
+    let protected_header = match protected {
+        "" => Map::new(),
+        protected => decode_map(protected)?,
+    };
+    let unprotected = jwe.unprotected.unwrap_or_default();
+    let aad = json_aad(protected, jwe.aad.as_deref());
+    let iv = base64url_decode(&jwe.iv, "JWE iv")?;
+    let ciphertext = base64url_decode(&jwe.ciphertext, "JWE cipher text")?;
+    let tag = base64url_decode(&jwe.tag, "JWE tag")?;
+
+    let mut last_error = invalid("no recipient with an allowed algorithm");
+    for recipient in &recipients {
+        let empty = Map::new();
+        let header = join_headers([
+            &protected_header,
+            &unprotected,
+            recipient.header.as_ref().unwrap_or(&empty),
+        ])?;
+        if !allowed.contains(&header.alg) || header.alg.check_key(key).is_err() {
+            continue;
+        }
+
+        let encrypted_key = match recipient.encrypted_key.as_deref() {
+            Some(encrypted_key) => base64url_decode(encrypted_key, "JWE encrypted key")?,
+            None => Vec::new(),
+        };
+        let plaintext = decrypt_cek(provider, key, &header, &encrypted_key).and_then(|cek| {
+            decrypt_content(
+                provider,
+                header.enc,
+                &cek,
+                &iv,
+                &ciphertext,
+                &tag,
+                aad.as_bytes(),
+            )
+        });
+        match plaintext {
+            Ok(plaintext) => return Ok(Decrypted { header, plaintext }),
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{factory::create_provider_from_name, jose::jwk};
+    use crate::prelude::ProviderImplConfig;
+
+    // RFC 7518, appendix C.
+    const ALICE_EPHEMERAL_KEY: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+        "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps"
+    }"#;
+    const BOB_KEY: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+        "y": "e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+        "d": "VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw"
+    }"#;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_concat_kdf() {
+        let mut provider = create_provider_from_name(
+            "SoftwareProvider",
+            ProviderImplConfig {
+                additional_config: vec![],
+            },
+        )
+        .unwrap();
+        let bob = jwk::import_key_pair(&mut provider, &Jwk::from_json(BOB_KEY).unwrap()).unwrap();
+
+        let mut header = JweHeader::new(Algorithm::EcdhEs, Encryption::A256GCM);
+        header.epk = Some(Jwk::from_json(ALICE_EPHEMERAL_KEY).unwrap());
+        let shared_secret = bob
+            .start_dh_exchange()
+            .unwrap()
+            .compute_shared_secret(&header.epk.as_ref().unwrap().raw_public_key().unwrap())
+            .unwrap();
+
+        // The example derives a 128 bit key for `A128GCM`.
+        let key = concat_kdf(&provider, &shared_secret, "A128GCM", b"Alice", b"Bob", 16).unwrap();
+        assert_eq!(base64url_encode(&key), "VqqN6vgjbSBcIijNcacQGg");
+    }
+}
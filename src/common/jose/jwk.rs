@@ -0,0 +1,1053 @@
+//! JSON Web Keys (RFC 7517) and JWK thumbprints (RFC 7638).
+//!
+//! Keys are converted through X.509 `SubjectPublicKeyInfo` and PKCS#8, so JWKs work with every
+//! provider supporting [KeyFormat::SPKI] and [KeyFormat::PKCS8] for the algorithm of the key.
+//! The [KeyPairSpec] or [KeySpec] of an imported JWK is inferred from its members.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{
+//!     common::jose::jwk::{self, Curve, Jwk},
+//!     prelude::*,
+//! };
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::P256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     ..Default::default()
+//! };
+//! let key_pair = provider.create_key_pair(spec).unwrap();
+//!
+//! let mut jwk = jwk::export_public_key(&key_pair).unwrap();
+//! assert_eq!(jwk.crv, Some(Curve::P256));
+//! let thumbprint = jwk.thumbprint(&provider, CryptoHash::Sha2_256).unwrap();
+//! jwk.kid = Some(jwk::base64url(&thumbprint));
+//!
+//! let jwk = Jwk::from_json(&jwk.to_json()).unwrap();
+//! let public_key = jwk::import_key_pair(&mut provider, &jwk).unwrap();
+//! let signature = key_pair.sign_data(b"data").unwrap();
+//! assert!(public_key.verify_signature(b"data", &signature).unwrap());
+//! ```
+
+use anyhow::anyhow;
+use pkcs1::{RsaPrivateKey, RsaPublicKey, UintRef};
+use pkcs8::{
+    der::{
+        asn1::{AnyRef, BitStringRef, Null, OctetStringRef},
+        Decode, Encode,
+    },
+    spki::{AlgorithmIdentifierRef, SubjectPublicKeyInfoRef},
+    ObjectIdentifier, PrivateKeyInfo,
+};
+use sec1::{EcParameters, EcPrivateKey};
+use serde::{Deserialize, Deserializer, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use super::base64url as base64url_serde;
+use crate::common::{
+    config::{KeyPairSpec, KeySpec, KeyUsage},
+    crypto::{
+        algorithms::{
+            encryption::{AsymmetricKeySpec, Cipher, RsaPadding},
+            hashes::CryptoHash,
+        },
+        pkcs::standards::{KeyFormat, OidType},
+    },
+    error::CalError,
+    KeyHandle, KeyPairHandle, Provider,
+};
+
+/// Key type (`kty`) of a JWK.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyType {
+    /// Elliptic curve key (RFC 7518).
+    #[serde(rename = "EC")]
+    Ec,
+    /// Octet key pair, i.e. Edwards or Montgomery curve key (RFC 8037).
+    #[serde(rename = "OKP")]
+    Okp,
+    #[serde(rename = "RSA")]
+    Rsa,
+    /// Symmetric key.
+    #[serde(rename = "oct")]
+    Oct,
+}
+
+/// Curve (`crv`) of an `EC` or `OKP` JWK.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Curve {
+    #[serde(rename = "P-256")]
+    P256,
+    #[serde(rename = "P-384")]
+    P384,
+    #[serde(rename = "P-521")]
+    P521,
+    #[serde(rename = "secp256k1")]
+    Secp256k1,
+    Ed25519,
+    X25519,
+    Ed448,
+    X448,
+}
+
+impl Curve {
+    fn key_type(self) -> KeyType {
+        match self {
+            Curve::P256 | Curve::P384 | Curve::P521 | Curve::Secp256k1 => KeyType::Ec,
+            Curve::Ed25519 | Curve::X25519 | Curve::Ed448 | Curve::X448 => KeyType::Okp,
+        }
+    }
+
+    fn asym_spec(self) -> AsymmetricKeySpec {
+        match self {
+            Curve::P256 => AsymmetricKeySpec::P256,
+            Curve::P384 => AsymmetricKeySpec::P384,
+            Curve::P521 => AsymmetricKeySpec::P521,
+            Curve::Secp256k1 => AsymmetricKeySpec::Secp256k1,
+            Curve::Ed25519 | Curve::X25519 => AsymmetricKeySpec::Curve25519,
+            Curve::Ed448 | Curve::X448 => AsymmetricKeySpec::Curve448,
+        }
+    }
+
+    /// Length of coordinates and private keys.
+    fn key_len(self) -> usize {
+        match self {
+            Curve::P256 | Curve::Secp256k1 | Curve::Ed25519 | Curve::X25519 => 32,
+            Curve::P384 => 48,
+            Curve::P521 => 66,
+            Curve::Ed448 => 57,
+            Curve::X448 => 56,
+        }
+    }
+
+    /// Hash used for signatures if the JWK has no `alg`.
+    fn default_hash(self) -> CryptoHash {
+        match self {
+            Curve::P256 | Curve::Secp256k1 | Curve::X25519 => CryptoHash::Sha2_256,
+            Curve::P384 => CryptoHash::Sha2_384,
+            Curve::P521 | Curve::Ed25519 | Curve::Ed448 | Curve::X448 => CryptoHash::Sha2_512,
+        }
+    }
+
+    /// Algorithm of the `SubjectPublicKeyInfo` and the named curve of EC keys.
+    fn oids(self) -> (OidType, Option<OidType>) {
+        match self {
+            Curve::P256 => (OidType::EcPublicKey, Some(OidType::Prime256v1)),
+            Curve::P384 => (OidType::EcPublicKey, Some(OidType::Secp384r1)),
+            Curve::P521 => (OidType::EcPublicKey, Some(OidType::Secp521r1)),
+            Curve::Secp256k1 => (OidType::EcPublicKey, Some(OidType::Secp256k1)),
+            Curve::Ed25519 => (OidType::IdEd25519, None),
+            Curve::X25519 => (OidType::IdX25519, None),
+            Curve::Ed448 => (OidType::IdEd448, None),
+            Curve::X448 => (OidType::IdX448, None),
+        }
+    }
+
+    fn from_oids(algorithm: ObjectIdentifier, curve: Option<ObjectIdentifier>) -> Option<Self> {
+        [
+            Curve::P256,
+            Curve::P384,
+            Curve::P521,
+            Curve::Secp256k1,
+            Curve::Ed25519,
+            Curve::X25519,
+            Curve::Ed448,
+            Curve::X448,
+        ]
+        .into_iter()
+        .find(|crv| {
+            let (crv_algorithm, crv_curve) = crv.oids();
+            crv_algorithm.oid() == algorithm && crv_curve.map(OidType::oid) == curve
+        })
+    }
+}
+
+/// Intended use (`use`) of a public key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PublicKeyUse {
+    #[serde(rename = "sig")]
+    Signature,
+    #[serde(rename = "enc")]
+    Encryption,
+}
+
+/// Operation (`key_ops`) a JWK may be used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyOperation {
+    Sign,
+    Verify,
+    Encrypt,
+    Decrypt,
+    WrapKey,
+    UnwrapKey,
+    DeriveKey,
+    DeriveBits,
+}
+
+impl KeyOperation {
+    fn key_usage(self) -> KeyUsage {
+        match self {
+            KeyOperation::Sign => KeyUsage::SIGN,
+            KeyOperation::Verify => KeyUsage::VERIFY,
+            KeyOperation::Encrypt => KeyUsage::ENCRYPT,
+            KeyOperation::Decrypt => KeyUsage::DECRYPT,
+            KeyOperation::WrapKey | KeyOperation::UnwrapKey => KeyUsage::WRAP,
+            KeyOperation::DeriveKey | KeyOperation::DeriveBits => {
+                KeyUsage::DERIVE | KeyUsage::AGREE
+            }
+        }
+    }
+
+    /// Operations covering `usage`, or `None` if all operations are allowed.
+    fn from_key_usage(usage: KeyUsage) -> Option<Vec<Self>> {
+        if usage == KeyUsage::ALL {
+            return None;
+        }
+
+        let operations = [
+            (KeyUsage::SIGN, &[KeyOperation::Sign][..]),
+            (KeyUsage::VERIFY, &[KeyOperation::Verify]),
+            (KeyUsage::ENCRYPT, &[KeyOperation::Encrypt]),
+            (KeyUsage::DECRYPT, &[KeyOperation::Decrypt]),
+            (
+                KeyUsage::WRAP,
+                &[KeyOperation::WrapKey, KeyOperation::UnwrapKey],
+            ),
+            (
+                KeyUsage::DERIVE | KeyUsage::AGREE,
+                &[KeyOperation::DeriveKey, KeyOperation::DeriveBits],
+            ),
+        ];
+        Some(
+            operations
+                .into_iter()
+                .filter(|(operation_usage, _)| usage.0 & operation_usage.0 != 0)
+                .flat_map(|(_, operations)| operations.iter().copied())
+                .collect(),
+        )
+    }
+}
+
+/// A JSON Web Key.
+///
+/// Key material is stored decoded, it is base64url encoded in JSON. Private members are zeroized
+/// on drop. Members not listed here are ignored.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct Jwk {
+    #[zeroize(skip)]
+    pub kty: KeyType,
+    #[serde(rename = "use", default, skip_serializing_if = "Option::is_none")]
+    #[zeroize(skip)]
+    pub key_use: Option<PublicKeyUse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[zeroize(skip)]
+    pub key_ops: Option<Vec<KeyOperation>>,
+    /// Algorithm the key is intended for, e.g. `ES256`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    /// Key id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[zeroize(skip)]
+    pub crv: Option<Curve>,
+    /// x-coordinate of `EC` keys or public key of `OKP` keys.
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub x: Option<Vec<u8>>,
+    /// y-coordinate of `EC` keys.
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub y: Option<Vec<u8>>,
+    /// RSA modulus.
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub n: Option<Vec<u8>>,
+    /// RSA public exponent.
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub e: Option<Vec<u8>>,
+    /// Private key of `EC` and `OKP` keys or private exponent of `RSA` keys.
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub d: Option<Vec<u8>>,
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub p: Option<Vec<u8>>,
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub q: Option<Vec<u8>>,
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub dp: Option<Vec<u8>>,
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub dq: Option<Vec<u8>>,
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub qi: Option<Vec<u8>>,
+    /// Symmetric key of `oct` keys.
+    #[serde(
+        default,
+        with = "base64url_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub k: Option<Vec<u8>>,
+}
+
+/// A JWK Set.
+///
+/// When parsing, keys with unsupported or missing members are ignored as recommended by
+/// RFC 7517, section 5.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+impl<'de> Deserialize<'de> for JwkSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawJwkSet {
+            keys: Vec<serde_json::Value>,
+        }
+
+        let keys = RawJwkSet::deserialize(deserializer)?
+            .keys
+            .into_iter()
+            .filter_map(|key| serde_json::from_value::<Jwk>(key).ok())
+            .filter(|key| key.check().is_ok())
+            .collect();
+        Ok(JwkSet { keys })
+    }
+}
+
+impl JwkSet {
+    pub fn from_json(json: &str) -> Result<Self, CalError> {
+        serde_json::from_str(json).map_err(|e| invalid_json("JWK Set", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("JWK Sets are always serializable")
+    }
+
+    /// Returns the key with the key id `kid`.
+    pub fn find(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|key| key.kid.as_deref() == Some(kid))
+    }
+}
+
+/// Encodes `data` as base64url without padding, e.g. to use a thumbprint as key id.
+pub fn base64url(data: &[u8]) -> String {
+    super::base64url_encode(data)
+}
+
+fn invalid(message: impl std::fmt::Display) -> CalError {
+    CalError::bad_parameter(format!("Invalid JWK: {message}"), true, None)
+}
+
+fn invalid_json(what: &str, error: serde_json::Error) -> CalError {
+    CalError::bad_parameter(format!("Invalid {what}"), true, Some(anyhow!(error)))
+}
+
+fn invalid_der(what: &str, error: impl Into<anyhow::Error>) -> CalError {
+    CalError::bad_parameter(
+        format!("Provider exported an invalid {what}"),
+        false,
+        Some(error.into()),
+    )
+}
+
+fn encoding_failed(error: impl Into<anyhow::Error>) -> CalError {
+    CalError::failed_operation("Failed to encode key".to_owned(), true, Some(error.into()))
+}
+
+fn member<'a>(value: &'a Option<Vec<u8>>, name: &str) -> Result<&'a [u8], CalError> {
+    value
+        .as_deref()
+        .ok_or_else(|| invalid(format!("missing member \"{name}\"")))
+}
+
+/// Hash of the JWS algorithms with a fixed hash.
+fn algorithm_hash(alg: &str) -> Option<CryptoHash> {
+    match alg {
+        "ES256" | "ES256K" | "RS256" | "PS256" | "HS256" => Some(CryptoHash::Sha2_256),
+        "ES384" | "RS384" | "PS384" | "HS384" => Some(CryptoHash::Sha2_384),
+        "ES512" | "RS512" | "PS512" | "HS512" | "EdDSA" => Some(CryptoHash::Sha2_512),
+        _ => None,
+    }
+}
+
+/// RSA key sizes supported by [AsymmetricKeySpec].
+fn rsa_spec(modulus: &[u8]) -> Result<AsymmetricKeySpec, CalError> {
+    let bits = modulus.iter().skip_while(|&&byte| byte == 0).count() * 8;
+    match bits {
+        1024 => Ok(AsymmetricKeySpec::RSA1024),
+        2048 => Ok(AsymmetricKeySpec::RSA2048),
+        3072 => Ok(AsymmetricKeySpec::RSA3072),
+        4096 => Ok(AsymmetricKeySpec::RSA4096),
+        8192 => Ok(AsymmetricKeySpec::RSA8192),
+        bits => Err(CalError::unsupported_algorithm(format!(
+            "RSA keys with {bits} bit modulus"
+        ))),
+    }
+}
+
+impl Jwk {
+    pub(crate) fn new(kty: KeyType) -> Self {
+        Jwk {
+            kty,
+            key_use: None,
+            key_ops: None,
+            alg: None,
+            kid: None,
+            crv: None,
+            x: None,
+            y: None,
+            n: None,
+            e: None,
+            d: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            k: None,
+        }
+    }
+
+    /// Parses a single JWK and checks that it has the members required by its key type.
+    pub fn from_json(json: &str) -> Result<Self, CalError> {
+        let jwk: Jwk = serde_json::from_str(json).map_err(|e| invalid_json("JWK", e))?;
+        jwk.check()?;
+        Ok(jwk)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("JWKs are always serializable")
+    }
+
+    /// Returns `true` if the JWK contains a private or symmetric key.
+    pub fn is_private(&self) -> bool {
+        self.d.is_some() || self.k.is_some()
+    }
+
+    /// Returns the JWK without private members.
+    ///
+    /// Fails for symmetric keys.
+    pub fn to_public(&self) -> Result<Jwk, CalError> {
+        if self.kty == KeyType::Oct {
+            return Err(invalid("symmetric keys have no public key"));
+        }
+
+        let mut public = self.clone();
+        for member in [
+            &mut public.d,
+            &mut public.p,
+            &mut public.q,
+            &mut public.dp,
+            &mut public.dq,
+            &mut public.qi,
+        ] {
+            member.zeroize();
+            *member = None;
+        }
+        Ok(public)
+    }
+
+    /// Checks the members required by the key type and their lengths.
+    pub(crate) fn check(&self) -> Result<(), CalError> {
+        match self.kty {
+            KeyType::Ec | KeyType::Okp => {
+                let crv = self.curve()?;
+                let x = member(&self.x, "x")?;
+                let y = self.y.as_deref();
+                let lengths_valid = x.len() == crv.key_len()
+                    && self.d.as_ref().is_none_or(|d| d.len() == crv.key_len())
+                    && match self.kty {
+                        KeyType::Ec => y.is_some_and(|y| y.len() == crv.key_len()),
+                        _ => y.is_none(),
+                    };
+                if !lengths_valid {
+                    return Err(invalid(format!("invalid key length for curve {crv:?}")));
+                }
+            }
+            KeyType::Rsa => {
+                rsa_spec(member(&self.n, "n")?)?;
+                member(&self.e, "e")?;
+                if self.d.is_some() {
+                    for (value, name) in [
+                        (&self.p, "p"),
+                        (&self.q, "q"),
+                        (&self.dp, "dp"),
+                        (&self.dq, "dq"),
+                        (&self.qi, "qi"),
+                    ] {
+                        member(value, name).map_err(|_| {
+                            CalError::unsupported_algorithm(format!(
+                                "RSA private keys without member \"{name}\""
+                            ))
+                        })?;
+                    }
+                }
+            }
+            KeyType::Oct => {
+                if member(&self.k, "k")?.is_empty() {
+                    return Err(invalid("empty symmetric key"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn curve(&self) -> Result<Curve, CalError> {
+        let crv = self.crv.ok_or_else(|| invalid("missing member \"crv\""))?;
+        if crv.key_type() != self.kty {
+            return Err(invalid(format!(
+                "curve {crv:?} does not belong to key type {:?}",
+                self.kty
+            )));
+        }
+        Ok(crv)
+    }
+
+    /// Public `EC` or `OKP` JWK of a raw public key, i.e. an uncompressed SEC1 point or the
+    /// public key of an Edwards or Montgomery curve.
+    pub(crate) fn from_raw_public_key(crv: Curve, public_key: &[u8]) -> Result<Self, CalError> {
+        let mut jwk = Jwk::new(crv.key_type());
+        jwk.crv = Some(crv);
+        match crv.key_type() {
+            KeyType::Ec => {
+                let coordinates = public_key
+                    .strip_prefix(&[0x04])
+                    .filter(|coordinates| coordinates.len() == 2 * crv.key_len())
+                    .ok_or_else(|| invalid(format!("expected an uncompressed {crv:?} point")))?;
+                let (x, y) = coordinates.split_at(crv.key_len());
+                jwk.x = Some(x.to_vec());
+                jwk.y = Some(y.to_vec());
+            }
+            _ => jwk.x = Some(public_key.to_vec()),
+        }
+        jwk.check()?;
+        Ok(jwk)
+    }
+
+    /// Raw public key of an `EC` or `OKP` JWK, see [Jwk::from_raw_public_key].
+    pub(crate) fn raw_public_key(&self) -> Result<Vec<u8>, CalError> {
+        self.check()?;
+        match self.kty {
+            KeyType::Ec => Ok([&[0x04][..], member(&self.x, "x")?, member(&self.y, "y")?].concat()),
+            KeyType::Okp => Ok(member(&self.x, "x")?.to_vec()),
+            _ => Err(invalid("only EC and OKP keys have a raw public key")),
+        }
+    }
+
+    /// Operations allowed by `key_ops` and `use`.
+    fn key_usage(&self) -> KeyUsage {
+        let operations = self.key_ops.as_ref().map_or(KeyUsage::ALL, |operations| {
+            operations.iter().fold(KeyUsage::NONE, |usage, operation| {
+                usage | operation.key_usage()
+            })
+        });
+        let key_use = match self.key_use {
+            Some(PublicKeyUse::Signature) => KeyUsage::SIGN | KeyUsage::VERIFY,
+            Some(PublicKeyUse::Encryption) => {
+                KeyUsage::ENCRYPT
+                    | KeyUsage::DECRYPT
+                    | KeyUsage::WRAP
+                    | KeyUsage::DERIVE
+                    | KeyUsage::AGREE
+            }
+            None => KeyUsage::ALL,
+        };
+        operations & key_use
+    }
+
+    /// Infers the spec of an `EC`, `OKP` or `RSA` JWK.
+    ///
+    /// The hash and RSA padding are taken from `alg` if possible, otherwise it is the usual hash
+    /// of the curve or SHA-256 for RSA. The key usage is restricted by `key_ops` and `use`. The spec is
+    /// ephemeral.
+    pub fn key_pair_spec(&self) -> Result<KeyPairSpec, CalError> {
+        self.check()?;
+        let (asym_spec, default_hash) = match self.kty {
+            KeyType::Ec | KeyType::Okp => {
+                let crv = self.curve()?;
+                (crv.asym_spec(), crv.default_hash())
+            }
+            KeyType::Rsa => (rsa_spec(member(&self.n, "n")?)?, CryptoHash::Sha2_256),
+            KeyType::Oct => return Err(invalid("symmetric keys have no key pair spec")),
+        };
+
+        Ok(KeyPairSpec {
+            asym_spec,
+            signing_hash: self
+                .alg
+                .as_deref()
+                .and_then(algorithm_hash)
+                .unwrap_or(default_hash),
+            rsa_padding: match self.alg.as_deref() {
+                Some(alg) if alg.starts_with("PS") => RsaPadding::Pss,
+                _ => RsaPadding::Pkcs1v15,
+            },
+            ephemeral: true,
+            key_usage: self.key_usage(),
+            ..Default::default()
+        })
+    }
+
+    /// Infers the spec of an `oct` JWK.
+    ///
+    /// `A128GCM`, `A256GCM`, `A128KW` and `A256KW` select the cipher, `HS256`, `HS384` and
+    /// `HS512` the HMAC hash. Otherwise the cipher is chosen by the key length and the hash is
+    /// SHA-256. Keys which fit no cipher can only be used for HMACs. The key usage is further
+    /// restricted by `key_ops` and `use`. The spec is ephemeral.
+    pub fn key_spec(&self) -> Result<KeySpec, CalError> {
+        if self.kty != KeyType::Oct {
+            return Err(invalid("only symmetric keys have a key spec"));
+        }
+        self.check()?;
+        let key_len = member(&self.k, "k")?.len();
+
+        let (cipher, signing_hash) = match self.alg.as_deref() {
+            Some("A128GCM" | "A128KW") => (Some(Cipher::AesGcm128), CryptoHash::Sha2_256),
+            Some("A256GCM" | "A256KW") => (Some(Cipher::AesGcm256), CryptoHash::Sha2_256),
+            Some(alg @ ("HS256" | "HS384" | "HS512")) => (
+                None,
+                algorithm_hash(alg).expect("HMAC algorithms have a hash"),
+            ),
+            _ => (None, CryptoHash::Sha2_256),
+        };
+        if cipher.is_some_and(|cipher| cipher.len() != key_len) {
+            return Err(invalid(format!(
+                "{key_len} byte key for algorithm {}",
+                self.alg.as_deref().unwrap_or_default()
+            )));
+        }
+
+        let length_cipher = [Cipher::AesGcm128, Cipher::AesGcm256]
+            .into_iter()
+            .find(|cipher| cipher.len() == key_len);
+        let (cipher, key_usage) = match cipher.or(length_cipher) {
+            Some(cipher) => (cipher, self.key_usage()),
+            // The cipher of the spec is never used, as HMACs take keys of any length.
+            None => (
+                Cipher::AesGcm256,
+                self.key_usage() & (KeyUsage::SIGN | KeyUsage::VERIFY),
+            ),
+        };
+
+        Ok(KeySpec {
+            cipher,
+            signing_hash,
+            ephemeral: true,
+            key_usage,
+            ..Default::default()
+        })
+    }
+
+    /// Computes the JWK thumbprint (RFC 7638) of the public key, or of the key for `oct` keys.
+    pub fn thumbprint(&self, provider: &Provider, hash: CryptoHash) -> Result<Vec<u8>, CalError> {
+        self.check()?;
+        // Required members in lexicographic order, all values are free of JSON escapes.
+        let encode = |value: &Option<Vec<u8>>| value.as_deref().map(base64url).unwrap_or_default();
+        let canonical = Zeroizing::new(match self.kty {
+            KeyType::Ec => format!(
+                r#"{{"crv":{},"kty":"EC","x":"{}","y":"{}"}}"#,
+                serde_json::to_string(&self.curve()?).expect("Curves are serializable"),
+                encode(&self.x),
+                encode(&self.y)
+            ),
+            KeyType::Okp => format!(
+                r#"{{"crv":{},"kty":"OKP","x":"{}"}}"#,
+                serde_json::to_string(&self.curve()?).expect("Curves are serializable"),
+                encode(&self.x)
+            ),
+            KeyType::Rsa => format!(
+                r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+                encode(&self.e),
+                encode(&self.n)
+            ),
+            KeyType::Oct => {
+                let k = Zeroizing::new(encode(&self.k));
+                format!(r#"{{"k":"{}","kty":"oct"}}"#, k.as_str())
+            }
+        });
+
+        provider.hash(canonical.as_bytes(), hash)
+    }
+
+    /// DER encoded `SubjectPublicKeyInfo` of an `EC`, `OKP` or `RSA` JWK.
+    fn spki(&self) -> Result<Vec<u8>, CalError> {
+        let (algorithm, parameters, public_key) = match self.kty {
+            KeyType::Ec | KeyType::Okp => {
+                let crv = self.curve()?;
+                let (algorithm, curve) = crv.oids();
+                (
+                    algorithm.oid(),
+                    curve.map(OidType::oid),
+                    self.raw_public_key()?,
+                )
+            }
+            KeyType::Rsa => {
+                let public_key = RsaPublicKey {
+                    modulus: UintRef::new(member(&self.n, "n")?).map_err(encoding_failed)?,
+                    public_exponent: UintRef::new(member(&self.e, "e")?)
+                        .map_err(encoding_failed)?,
+                }
+                .to_der()
+                .map_err(encoding_failed)?;
+                return SubjectPublicKeyInfoRef {
+                    algorithm: AlgorithmIdentifierRef {
+                        oid: OidType::RsaEncryption.oid(),
+                        parameters: Some(AnyRef::from(Null)),
+                    },
+                    subject_public_key: BitStringRef::from_bytes(&public_key)
+                        .map_err(encoding_failed)?,
+                }
+                .to_der()
+                .map_err(encoding_failed);
+            }
+            KeyType::Oct => return Err(invalid("symmetric keys have no public key")),
+        };
+
+        SubjectPublicKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: algorithm,
+                parameters: parameters.as_ref().map(AnyRef::from),
+            },
+            subject_public_key: BitStringRef::from_bytes(&public_key).map_err(encoding_failed)?,
+        }
+        .to_der()
+        .map_err(encoding_failed)
+    }
+
+    /// DER encoded PKCS#8 document of a private `EC`, `OKP` or `RSA` JWK.
+    fn pkcs8(&self) -> Result<Zeroizing<Vec<u8>>, CalError> {
+        let d = member(&self.d, "d")?;
+        let (algorithm, parameters, private_key, public_key) = match self.kty {
+            KeyType::Ec => {
+                let crv = self.curve()?;
+                let (algorithm, curve) = crv.oids();
+                let curve = curve.map(OidType::oid);
+                let public_key =
+                    [&[0x04][..], member(&self.x, "x")?, member(&self.y, "y")?].concat();
+                let private_key = EcPrivateKey {
+                    private_key: d,
+                    parameters: curve.map(EcParameters::NamedCurve),
+                    public_key: Some(&public_key),
+                }
+                .to_der()
+                .map_err(encoding_failed)?;
+                (algorithm.oid(), curve, private_key, None)
+            }
+            KeyType::Okp => {
+                let (algorithm, _) = self.curve()?.oids();
+                let private_key = OctetStringRef::new(d)
+                    .and_then(|d| d.to_der())
+                    .map_err(encoding_failed)?;
+                // The public key is checked against the private key on import.
+                (algorithm.oid(), None, private_key, self.x.as_deref())
+            }
+            KeyType::Rsa => {
+                fn uint<'a>(
+                    value: &'a Option<Vec<u8>>,
+                    name: &str,
+                ) -> Result<UintRef<'a>, CalError> {
+                    member(value, name)
+                        .and_then(|value| UintRef::new(value).map_err(encoding_failed))
+                }
+                let private_key = RsaPrivateKey {
+                    modulus: uint(&self.n, "n")?,
+                    public_exponent: uint(&self.e, "e")?,
+                    private_exponent: uint(&self.d, "d")?,
+                    prime1: uint(&self.p, "p")?,
+                    prime2: uint(&self.q, "q")?,
+                    exponent1: uint(&self.dp, "dp")?,
+                    exponent2: uint(&self.dq, "dq")?,
+                    coefficient: uint(&self.qi, "qi")?,
+                    other_prime_infos: None,
+                }
+                .to_der()
+                .map_err(encoding_failed)?;
+                let info = PrivateKeyInfo {
+                    algorithm: AlgorithmIdentifierRef {
+                        oid: OidType::RsaEncryption.oid(),
+                        parameters: Some(AnyRef::from(Null)),
+                    },
+                    private_key: &Zeroizing::new(private_key),
+                    public_key: None,
+                };
+                return info.to_der().map(Zeroizing::new).map_err(encoding_failed);
+            }
+            KeyType::Oct => return Err(invalid("symmetric keys are not key pairs")),
+        };
+        let private_key = Zeroizing::new(private_key);
+
+        PrivateKeyInfo {
+            algorithm: AlgorithmIdentifierRef {
+                oid: algorithm,
+                parameters: parameters.as_ref().map(AnyRef::from),
+            },
+            private_key: &private_key,
+            public_key,
+        }
+        .to_der()
+        .map(Zeroizing::new)
+        .map_err(encoding_failed)
+    }
+}
+
+/// Fills the public members of `jwk` from a DER encoded `SubjectPublicKeyInfo`.
+fn public_key_jwk(spki: &[u8]) -> Result<Jwk, CalError> {
+    let info = SubjectPublicKeyInfoRef::try_from(spki)
+        .map_err(|e| invalid_der("SubjectPublicKeyInfo", e))?;
+    let public_key = info
+        .subject_public_key
+        .as_bytes()
+        .ok_or_else(|| invalid_der("SubjectPublicKeyInfo", anyhow!("Unaligned public key")))?;
+
+    if info.algorithm.oid == OidType::RsaEncryption.oid() {
+        let public_key =
+            RsaPublicKey::from_der(public_key).map_err(|e| invalid_der("RSA public key", e))?;
+        let mut jwk = Jwk::new(KeyType::Rsa);
+        jwk.n = Some(public_key.modulus.as_bytes().to_vec());
+        jwk.e = Some(public_key.public_exponent.as_bytes().to_vec());
+        return Ok(jwk);
+    }
+
+    let curve = info
+        .algorithm
+        .parameters
+        .map(|parameters| parameters.decode_as::<ObjectIdentifier>())
+        .transpose()
+        .map_err(|e| invalid_der("SubjectPublicKeyInfo", e))?;
+    let crv = Curve::from_oids(info.algorithm.oid, curve).ok_or_else(|| {
+        CalError::unsupported_algorithm(format!(
+            "JWKs for keys with algorithm {}",
+            info.algorithm.oid
+        ))
+    })?;
+
+    Jwk::from_raw_public_key(crv, public_key)
+        .map_err(|e| invalid_der("SubjectPublicKeyInfo", anyhow!(e)))
+}
+
+/// Exports the public key of `key_pair` as JWK.
+///
+/// `key_ops` is only set for private JWKs.
+pub fn export_public_key(key_pair: &KeyPairHandle) -> Result<Jwk, CalError> {
+    public_key_jwk(&key_pair.export_public_key(KeyFormat::SPKI)?)
+}
+
+/// Exports the private key of `key_pair` as JWK.
+///
+/// Fails like [KeyPairHandle::extract_key] for non exportable keys.
+pub fn export_key_pair(key_pair: &KeyPairHandle) -> Result<Jwk, CalError> {
+    let mut jwk = export_public_key(key_pair)?;
+    let pkcs8 = Zeroizing::new(key_pair.export_private_key(KeyFormat::PKCS8)?);
+    let info =
+        PrivateKeyInfo::try_from(pkcs8.as_slice()).map_err(|e| invalid_der("PKCS#8 key", e))?;
+
+    match jwk.kty {
+        KeyType::Ec => {
+            let private_key = EcPrivateKey::try_from(info.private_key)
+                .map_err(|e| invalid_der("PKCS#8 key", e))?;
+            jwk.d = Some(private_key.private_key.to_vec());
+        }
+        KeyType::Okp => {
+            let private_key = OctetStringRef::from_der(info.private_key)
+                .map_err(|e| invalid_der("PKCS#8 key", e))?;
+            jwk.d = Some(private_key.as_bytes().to_vec());
+        }
+        KeyType::Rsa => {
+            let private_key = RsaPrivateKey::try_from(info.private_key)
+                .map_err(|e| invalid_der("PKCS#8 key", anyhow!(e)))?;
+            jwk.d = Some(private_key.private_exponent.as_bytes().to_vec());
+            jwk.p = Some(private_key.prime1.as_bytes().to_vec());
+            jwk.q = Some(private_key.prime2.as_bytes().to_vec());
+            jwk.dp = Some(private_key.exponent1.as_bytes().to_vec());
+            jwk.dq = Some(private_key.exponent2.as_bytes().to_vec());
+            jwk.qi = Some(private_key.coefficient.as_bytes().to_vec());
+        }
+        KeyType::Oct => unreachable!("public_key_jwk only returns asymmetric keys"),
+    }
+    jwk.key_ops = KeyOperation::from_key_usage(key_pair.spec().key_usage);
+    jwk.check()?;
+    Ok(jwk)
+}
+
+/// Exports a symmetric key as `oct` JWK.
+///
+/// Fails like [KeyHandle::extract_key] for non exportable keys.
+pub fn export_key(key: &KeyHandle) -> Result<Jwk, CalError> {
+    let mut jwk = Jwk::new(KeyType::Oct);
+    jwk.k = Some(key.extract_key()?);
+    jwk.key_ops = KeyOperation::from_key_usage(key.spec().key_usage);
+    Ok(jwk)
+}
+
+/// Imports an `EC`, `OKP` or `RSA` JWK with the spec inferred by [Jwk::key_pair_spec].
+///
+/// Public JWKs are imported as public keys.
+pub fn import_key_pair(provider: &mut Provider, jwk: &Jwk) -> Result<KeyPairHandle, CalError> {
+    import_key_pair_with_spec(provider, jwk, jwk.key_pair_spec()?)
+}
+
+/// Imports an `EC`, `OKP` or `RSA` JWK with `spec`, e.g. a persistent variant of
+/// [Jwk::key_pair_spec].
+///
+/// Fails if the algorithm of `spec` does not match the JWK.
+pub fn import_key_pair_with_spec(
+    provider: &mut Provider,
+    jwk: &Jwk,
+    spec: KeyPairSpec,
+) -> Result<KeyPairHandle, CalError> {
+    let inferred = jwk.key_pair_spec()?;
+    if spec.asym_spec != inferred.asym_spec {
+        return Err(invalid(format!(
+            "{:?} key for a {:?} spec",
+            inferred.asym_spec, spec.asym_spec
+        )));
+    }
+
+    if jwk.d.is_some() {
+        provider.import_key_pair_with_format(spec, &jwk.pkcs8()?, KeyFormat::PKCS8)
+    } else {
+        provider.import_public_key_with_format(spec, &jwk.spki()?, KeyFormat::SPKI)
+    }
+}
+
+/// Imports an `oct` JWK with the spec inferred by [Jwk::key_spec].
+pub fn import_key(provider: &mut Provider, jwk: &Jwk) -> Result<KeyHandle, CalError> {
+    import_key_with_spec(provider, jwk, jwk.key_spec()?)
+}
+
+/// Imports an `oct` JWK with `spec`.
+pub fn import_key_with_spec(
+    provider: &mut Provider,
+    jwk: &Jwk,
+    spec: KeySpec,
+) -> Result<KeyHandle, CalError> {
+    jwk.key_spec()?;
+    provider.import_key(spec, member(&jwk.k, "k")?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 7638, section 3.1.
+    const RFC7638_KEY: &str = r#"{
+        "kty": "RSA",
+        "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+        "e": "AQAB",
+        "alg": "RS256",
+        "kid": "2011-04-29"
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let jwk = Jwk::from_json(RFC7638_KEY).unwrap();
+        assert_eq!(jwk.kty, KeyType::Rsa);
+        assert_eq!(jwk.e.as_deref(), Some(&[1, 0, 1][..]));
+        assert!(!jwk.is_private());
+
+        let spec = jwk.key_pair_spec().unwrap();
+        assert_eq!(spec.asym_spec, AsymmetricKeySpec::RSA2048);
+        assert_eq!(spec.signing_hash, CryptoHash::Sha2_256);
+        assert_eq!(Jwk::from_json(&jwk.to_json()).unwrap(), jwk);
+    }
+
+    #[test]
+    fn test_key_usage() {
+        let mut jwk = Jwk::from_json(RFC7638_KEY).unwrap();
+        assert_eq!(jwk.key_usage(), KeyUsage::ALL);
+
+        jwk.key_use = Some(PublicKeyUse::Signature);
+        assert_eq!(jwk.key_usage(), KeyUsage::SIGN | KeyUsage::VERIFY);
+
+        jwk.key_ops = Some(vec![KeyOperation::Verify, KeyOperation::Encrypt]);
+        assert_eq!(jwk.key_usage(), KeyUsage::VERIFY);
+
+        assert_eq!(
+            KeyOperation::from_key_usage(KeyUsage::SIGN | KeyUsage::AGREE),
+            Some(vec![
+                KeyOperation::Sign,
+                KeyOperation::DeriveKey,
+                KeyOperation::DeriveBits
+            ])
+        );
+        assert_eq!(KeyOperation::from_key_usage(KeyUsage::ALL), None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        // Wrong curve for the key type.
+        assert!(Jwk::from_json(
+            r#"{"kty":"OKP","crv":"P-256","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        )
+        .is_err());
+        // Missing y-coordinate.
+        assert!(Jwk::from_json(
+            r#"{"kty":"EC","crv":"P-256","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        )
+        .is_err());
+        // Padding is not allowed.
+        assert!(Jwk::from_json(r#"{"kty":"oct","k":"AAAAAAAAAAAAAAAAAAAAAA=="}"#).is_err());
+        assert!(Jwk::from_json(r#"{"kty":"oct","k":"AAAAAAAAAAAAAAAAAAAAAA"}"#).is_ok());
+        assert!(
+            Jwk::from_json(r#"{"kty":"oct","k":"AAAAAAAAAAAAAAAAAAAAAA","alg":"A256GCM"}"#)
+                .unwrap()
+                .key_spec()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_jwk_set() {
+        let set = JwkSet::from_json(&format!(
+            r#"{{"keys":[{{"kty":"unknown"}},{RFC7638_KEY},{{"kty":"EC","crv":"P-256"}}]}}"#
+        ))
+        .unwrap();
+        assert_eq!(set.keys.len(), 1);
+        assert!(set.find("2011-04-29").is_some());
+        assert!(set.find("2011-04-30").is_none());
+        assert_eq!(JwkSet::from_json(&set.to_json()).unwrap(), set);
+    }
+}
@@ -0,0 +1,573 @@
+//! JSON Web Signatures (RFC 7515) in compact and JSON serialization.
+//!
+//! JWS are signed with a [KeyPairHandle] or, for the HMAC algorithms, a [KeyHandle]. A JWS is
+//! only accepted if its algorithm is in the allow-list passed to the verification and matches
+//! the spec of the key, which rules out algorithm confusion like verifying an `HS256` JWS with a
+//! public key as HMAC secret. The `none` algorithm and critical header parameters are never
+//! accepted.
+//!
+//! ECDSA signatures of key pairs with [SignatureEncoding::Der] are converted to the fixed size
+//! `r || s` encoding of JOSE.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{
+//!     common::jose::jws::{self, Algorithm, JwsHeader},
+//!     prelude::*,
+//! };
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::P256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     ..Default::default()
+//! };
+//! let key_pair = provider.create_key_pair(spec).unwrap();
+//!
+//! let mut header = JwsHeader::new(Algorithm::ES256);
+//! header.typ = Some("at+jwt".to_owned());
+//! let token = jws::sign_compact(&key_pair, &header, br#"{"sub":"alice"}"#).unwrap();
+//!
+//! let verified = jws::verify_compact(&token, &key_pair, &[Algorithm::ES256]).unwrap();
+//! assert_eq!(verified.payload, br#"{"sub":"alice"}"#);
+//! assert!(jws::verify_compact(&token, &key_pair, &[Algorithm::EdDSA]).is_err());
+//! ```
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use strum::{EnumString, IntoStaticStr};
+
+use super::{
+    base64url_decode, base64url_encode,
+    jwk::{self, JwkSet},
+};
+use crate::common::{
+    config::{KeyPairSpec, KeySpec},
+    crypto::{
+        algorithms::{
+            encryption::{AsymmetricKeySpec, RsaPadding, SignatureEncoding},
+            hashes::CryptoHash,
+        },
+        ecdsa,
+    },
+    error::CalError,
+    KeyHandle, KeyPairHandle, Provider,
+};
+
+/// Supported JWS algorithms (RFC 7518, RFC 8037).
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, IntoStaticStr,
+)]
+pub enum Algorithm {
+    /// ECDSA with `P-256` and SHA-256.
+    ES256,
+    /// ECDSA with `P-384` and SHA-384.
+    ES384,
+    /// Ed25519.
+    EdDSA,
+    /// HMAC with SHA-256.
+    HS256,
+    /// HMAC with SHA-512.
+    HS512,
+    /// `RSASSA-PKCS1-v1_5` with SHA-256.
+    RS256,
+    /// `RSASSA-PSS` with SHA-256.
+    PS256,
+}
+
+impl Algorithm {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+
+    fn hash(self) -> CryptoHash {
+        match self {
+            Self::ES384 => CryptoHash::Sha2_384,
+            Self::HS512 | Self::EdDSA => CryptoHash::Sha2_512,
+            _ => CryptoHash::Sha2_256,
+        }
+    }
+
+    fn is_ecdsa(self) -> bool {
+        matches!(self, Self::ES256 | Self::ES384)
+    }
+
+    fn is_hmac(self) -> bool {
+        matches!(self, Self::HS256 | Self::HS512)
+    }
+
+    fn key_pair_matches(self, spec: KeyPairSpec) -> bool {
+        match self {
+            Self::ES256 => {
+                spec.asym_spec == AsymmetricKeySpec::P256 && spec.signing_hash == self.hash()
+            }
+            Self::ES384 => {
+                spec.asym_spec == AsymmetricKeySpec::P384 && spec.signing_hash == self.hash()
+            }
+            Self::EdDSA => spec.asym_spec == AsymmetricKeySpec::Curve25519,
+            Self::RS256 | Self::PS256 => {
+                spec.asym_spec.is_rsa()
+                    && spec.signing_hash == self.hash()
+                    && spec.rsa_padding == self.rsa_padding()
+            }
+            Self::HS256 | Self::HS512 => false,
+        }
+    }
+
+    fn rsa_padding(self) -> RsaPadding {
+        match self {
+            Self::PS256 => RsaPadding::Pss,
+            _ => RsaPadding::Pkcs1v15,
+        }
+    }
+
+    /// Fails unless `key` is a key of this algorithm.
+    fn check_key(self, key: JwsKey<'_>) -> Result<(), CalError> {
+        let matches = match key {
+            JwsKey::KeyPair(key_pair) => self.key_pair_matches(key_pair.spec()),
+            JwsKey::Key(key) => self.is_hmac() && key.spec().signing_hash == self.hash(),
+        };
+        if !matches {
+            return Err(CalError::bad_parameter(
+                format!("Key does not match the JWS algorithm {}", self.as_str()),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn sign(self, key: JwsKey<'_>, input: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.check_key(key)?;
+        match key {
+            JwsKey::KeyPair(key_pair) => {
+                let signature = key_pair.sign_data(input)?;
+                let spec = key_pair.spec();
+                match spec.signature_encoding {
+                    SignatureEncoding::Der if self.is_ecdsa() => {
+                        ecdsa::der_to_p1363(&signature, spec.asym_spec)
+                    }
+                    _ => Ok(signature),
+                }
+            }
+            JwsKey::Key(key) => key.hmac(input),
+        }
+    }
+
+    pub(crate) fn verify(
+        self,
+        key: JwsKey<'_>,
+        input: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, CalError> {
+        self.check_key(key)?;
+        match key {
+            JwsKey::KeyPair(key_pair) => {
+                let spec = key_pair.spec();
+                match spec.signature_encoding {
+                    SignatureEncoding::Der if self.is_ecdsa() => {
+                        match ecdsa::p1363_to_der(signature, spec.asym_spec) {
+                            Ok(signature) => key_pair.verify_signature(input, &signature),
+                            Err(_) => Ok(false),
+                        }
+                    }
+                    _ => key_pair.verify_signature(input, signature),
+                }
+            }
+            JwsKey::Key(key) => key.verify_hmac(input, signature),
+        }
+    }
+}
+
+/// Key signing or verifying a JWS.
+#[derive(Clone, Copy, Debug)]
+pub enum JwsKey<'a> {
+    KeyPair(&'a KeyPairHandle),
+    /// HMAC key.
+    Key(&'a KeyHandle),
+}
+
+impl<'a> From<&'a KeyPairHandle> for JwsKey<'a> {
+    fn from(key_pair: &'a KeyPairHandle) -> Self {
+        Self::KeyPair(key_pair)
+    }
+}
+
+impl<'a> From<&'a KeyHandle> for JwsKey<'a> {
+    fn from(key: &'a KeyHandle) -> Self {
+        Self::Key(key)
+    }
+}
+
+/// Protected header of a JWS.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JwsHeader {
+    pub alg: Algorithm,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+
+    /// Media type of the JWS, e.g. `JWT`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+
+    /// Media type of the payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cty: Option<String>,
+
+    /// Extensions which need to be understood, no extensions are supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+
+    /// Other header parameters.
+    #[serde(flatten)]
+    pub additional: Map<String, Value>,
+}
+
+impl JwsHeader {
+    pub fn new(alg: Algorithm) -> Self {
+        Self {
+            alg,
+            kid: None,
+            typ: None,
+            cty: None,
+            crit: None,
+            additional: Map::new(),
+        }
+    }
+}
+
+/// Protected header and payload of a verified JWS.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verified {
+    pub header: JwsHeader,
+    pub payload: Vec<u8>,
+}
+
+/// Members of the general and flattened JSON serialization.
+#[derive(Serialize, Deserialize)]
+struct JsonJws {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    signatures: Vec<JsonSignature>,
+    #[serde(flatten)]
+    flattened: Option<JsonSignature>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonSignature {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protected: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<Map<String, Value>>,
+    signature: String,
+}
+
+fn invalid(message: impl std::fmt::Display) -> CalError {
+    CalError::bad_parameter(format!("Invalid JWS: {message}"), true, None)
+}
+
+fn encode_header(header: &JwsHeader) -> Result<String, CalError> {
+    if header.crit.is_some() {
+        return Err(invalid("critical header parameters are not supported"));
+    }
+    serde_json::to_vec(header)
+        .map(|header| base64url_encode(&header))
+        .map_err(|e| {
+            CalError::failed_operation(
+                "Failed to encode JWS header".to_owned(),
+                true,
+                Some(anyhow!(e)),
+            )
+        })
+}
+
+fn decode_header(protected: &str) -> Result<JwsHeader, CalError> {
+    let header = base64url_decode(protected, "JWS header")?;
+    let header: JwsHeader = serde_json::from_slice(&header).map_err(|e| {
+        CalError::bad_parameter("Invalid JWS header".to_owned(), true, Some(anyhow!(e)))
+    })?;
+    if header.crit.is_some() {
+        return Err(invalid("critical header parameters are not supported"));
+    }
+    Ok(header)
+}
+
+/// Signs `protected.payload` and returns the base64url encoded signature.
+fn sign_encoded(
+    key: JwsKey<'_>,
+    alg: Algorithm,
+    protected: &str,
+    payload: &str,
+) -> Result<String, CalError> {
+    let signature = alg.sign(key, format!("{protected}.{payload}").as_bytes())?;
+    Ok(base64url_encode(&signature))
+}
+
+/// Verifies base64url encoded parts and returns the protected header.
+fn verify_encoded(
+    protected: &str,
+    payload: &str,
+    signature: &str,
+    key: JwsKey<'_>,
+    allowed: &[Algorithm],
+) -> Result<JwsHeader, CalError> {
+    let header = decode_header(protected)?;
+    if !allowed.contains(&header.alg) {
+        return Err(invalid(format!(
+            "algorithm {} is not allowed",
+            header.alg.as_str()
+        )));
+    }
+
+    let signature = base64url_decode(signature, "JWS signature")?;
+    if !header
+        .alg
+        .verify(key, format!("{protected}.{payload}").as_bytes(), &signature)?
+    {
+        return Err(CalError::failed_operation(
+            "Invalid JWS signature".to_owned(),
+            true,
+            None,
+        ));
+    }
+    Ok(header)
+}
+
+fn split_compact(jws: &str) -> Result<(&str, &str, &str), CalError> {
+    let mut parts = jws.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(protected), Some(payload), Some(signature), None) => {
+            Ok((protected, payload, signature))
+        }
+        _ => Err(invalid("compact serialization needs three parts")),
+    }
+}
+
+/// Signs `payload` with the algorithm of `header` in compact serialization.
+pub fn sign_compact<'a>(
+    key: impl Into<JwsKey<'a>>,
+    header: &JwsHeader,
+    payload: &[u8],
+) -> Result<String, CalError> {
+    let protected = encode_header(header)?;
+    let payload = base64url_encode(payload);
+    let signature = sign_encoded(key.into(), header.alg, &protected, &payload)?;
+    Ok(format!("{protected}.{payload}.{signature}"))
+}
+
+/// Verifies a JWS in compact serialization, if its algorithm is in `allowed`.
+pub fn verify_compact<'a>(
+    jws: &str,
+    key: impl Into<JwsKey<'a>>,
+    allowed: &[Algorithm],
+) -> Result<Verified, CalError> {
+    let (protected, payload, signature) = split_compact(jws)?;
+    if payload.is_empty() {
+        return Err(invalid("payload is detached"));
+    }
+    let header = verify_encoded(protected, payload, signature, key.into(), allowed)?;
+    Ok(Verified {
+        header,
+        payload: base64url_decode(payload, "JWS payload")?,
+    })
+}
+
+/// Signs `payload` in compact serialization without the payload (RFC 7515, appendix F).
+pub fn sign_detached<'a>(
+    key: impl Into<JwsKey<'a>>,
+    header: &JwsHeader,
+    payload: &[u8],
+) -> Result<String, CalError> {
+    let protected = encode_header(header)?;
+    let signature = sign_encoded(
+        key.into(),
+        header.alg,
+        &protected,
+        &base64url_encode(payload),
+    )?;
+    Ok(format!("{protected}..{signature}"))
+}
+
+/// Verifies a JWS in compact serialization with a detached `payload` and returns its header.
+pub fn verify_detached<'a>(
+    jws: &str,
+    payload: &[u8],
+    key: impl Into<JwsKey<'a>>,
+    allowed: &[Algorithm],
+) -> Result<JwsHeader, CalError> {
+    let (protected, attached, signature) = split_compact(jws)?;
+    if !attached.is_empty() {
+        return Err(invalid("payload is not detached"));
+    }
+    verify_encoded(
+        protected,
+        &base64url_encode(payload),
+        signature,
+        key.into(),
+        allowed,
+    )
+}
+
+fn to_json(jws: &JsonJws) -> Result<String, CalError> {
+    serde_json::to_string(jws).map_err(|e| {
+        CalError::failed_operation("Failed to encode JWS".to_owned(), true, Some(anyhow!(e)))
+    })
+}
+
+/// Signs `payload` once per key in general JSON serialization.
+///
+/// All header parameters are protected.
+pub fn sign_json(signers: &[(JwsKey<'_>, JwsHeader)], payload: &[u8]) -> Result<String, CalError> {
+    if signers.is_empty() {
+        return Err(invalid("no signers"));
+    }
+
+    let payload = base64url_encode(payload);
+    let signatures = signers
+        .iter()
+        .map(|(key, header)| {
+            let protected = encode_header(header)?;
+            let signature = sign_encoded(*key, header.alg, &protected, &payload)?;
+            Ok(JsonSignature {
+                protected: Some(protected),
+                header: None,
+                signature,
+            })
+        })
+        .collect::<Result<_, CalError>>()?;
+
+    to_json(&JsonJws {
+        payload: Some(payload),
+        signatures,
+        flattened: None,
+    })
+}
+
+/// Signs `payload` in flattened JSON serialization.
+pub fn sign_flattened<'a>(
+    key: impl Into<JwsKey<'a>>,
+    header: &JwsHeader,
+    payload: &[u8],
+) -> Result<String, CalError> {
+    let protected = encode_header(header)?;
+    let payload = base64url_encode(payload);
+    let signature = sign_encoded(key.into(), header.alg, &protected, &payload)?;
+
+    to_json(&JsonJws {
+        payload: Some(payload),
+        signatures: Vec::new(),
+        flattened: Some(JsonSignature {
+            protected: Some(protected),
+            header: None,
+            signature,
+        }),
+    })
+}
+
+/// Verifies a JWS in general or flattened JSON serialization.
+///
+/// Succeeds if any signature with an algorithm in `allowed` matching `key` is valid. The
+/// algorithm needs to be in the protected header, unprotected headers are ignored.
+pub fn verify_json<'a>(
+    json: &str,
+    key: impl Into<JwsKey<'a>>,
+    allowed: &[Algorithm],
+) -> Result<Verified, CalError> {
+    let key = key.into();
+    let jws: JsonJws = serde_json::from_str(json).map_err(|e| {
+        CalError::bad_parameter(
+            "Invalid JWS JSON serialization".to_owned(),
+            true,
+            Some(anyhow!(e)),
+        )
+    })?;
+    let payload = jws.payload.ok_or_else(|| invalid("payload is detached"))?;
+    let signatures = match (jws.flattened, jws.signatures.is_empty()) {
+        (Some(signature), true) => vec![signature],
+        (None, false) => jws.signatures,
+        _ => return Err(invalid("expected either \"signatures\" or \"signature\"")),
+    };
+
+    let mut last_error = invalid("no signature with an allowed algorithm");
+    for signature in &signatures {
+        let Some(protected) = signature.protected.as_deref() else {
+            continue;
+        };
+        match decode_header(protected) {
+            Ok(header) if allowed.contains(&header.alg) && header.alg.check_key(key).is_ok() => {}
+            _ => continue,
+        }
+
+        match verify_encoded(protected, &payload, &signature.signature, key, allowed) {
+            Ok(header) => {
+                return Ok(Verified {
+                    header,
+                    payload: base64url_decode(&payload, "JWS payload")?,
+                })
+            }
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+/// Verifies a JWS in compact serialization with the key of `jwks` named by its `kid`.
+///
+/// The key is imported ephemerally. A JWK with an `alg` member is only used for that algorithm.
+pub fn verify_compact_with_jwks(
+    provider: &mut Provider,
+    jws: &str,
+    jwks: &JwkSet,
+    allowed: &[Algorithm],
+) -> Result<Verified, CalError> {
+    let (protected, _, _) = split_compact(jws)?;
+    let header = decode_header(protected)?;
+    if !allowed.contains(&header.alg) {
+        return Err(invalid(format!(
+            "algorithm {} is not allowed",
+            header.alg.as_str()
+        )));
+    }
+
+    let kid = header
+        .kid
+        .as_deref()
+        .ok_or_else(|| invalid("header has no \"kid\""))?;
+    let jwk = jwks
+        .find(kid)
+        .ok_or_else(|| invalid(format!("no JWK with key id {kid}")))?;
+    if jwk
+        .alg
+        .as_deref()
+        .is_some_and(|jwk_alg| jwk_alg != header.alg.as_str())
+    {
+        return Err(invalid(format!(
+            "JWK {kid} is restricted to algorithm {}",
+            jwk.alg.as_deref().unwrap_or_default()
+        )));
+    }
+
+    if header.alg.is_hmac() {
+        let spec = KeySpec {
+            signing_hash: header.alg.hash(),
+            ..jwk.key_spec()?
+        };
+        let key = jwk::import_key_with_spec(provider, jwk, spec)?;
+        return verify_compact(jws, &key, allowed);
+    }
+
+    let spec = KeyPairSpec {
+        signing_hash: header.alg.hash(),
+        rsa_padding: header.alg.rsa_padding(),
+        ..jwk.key_pair_spec()?
+    };
+    let key_pair = jwk::import_key_pair_with_spec(provider, jwk, spec)?;
+    verify_compact(jws, &key_pair, allowed)
+}
@@ -0,0 +1,57 @@
+//! JSON Object Signing and Encryption (JOSE) on top of a [Provider](super::Provider).
+
+use anyhow::anyhow;
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+
+use super::error::CalError;
+
+/// JSON Web Encryption (RFC 7516).
+pub mod jwe;
+/// JSON Web Keys (RFC 7517) and JWK thumbprints (RFC 7638).
+pub mod jwk;
+/// JSON Web Signatures (RFC 7515).
+pub mod jws;
+
+/// Encodes `data` as base64url without padding.
+pub(crate) fn base64url_encode(data: &[u8]) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(data)
+}
+
+/// Decodes base64url without padding, `what` names the decoded value in errors.
+pub(crate) fn base64url_decode(data: &str, what: &str) -> Result<Vec<u8>, CalError> {
+    BASE64_URL_SAFE_NO_PAD.decode(data).map_err(|e| {
+        CalError::bad_parameter(
+            format!("{what} is not base64url encoded"),
+            true,
+            Some(anyhow!(e)),
+        )
+    })
+}
+
+/// Serde adapter for optional base64url encoded members.
+pub(crate) mod base64url {
+    use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_str(&BASE64_URL_SAFE_NO_PAD.encode(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                BASE64_URL_SAFE_NO_PAD
+                    .decode(value)
+                    .map_err(D::Error::custom)
+            })
+            .transpose()
+    }
+}
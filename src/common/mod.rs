@@ -1,6 +1,7 @@
 use crate::common::traits::key_handle::DHKeyExchangeImpl;
-use crate::prelude::{CryptoHash, KDF};
-use config::{KeyPairSpec, KeySpec, ProviderConfig, Spec};
+use crate::prelude::{Cipher, CryptoHash, KeyFormat, PasswordVerification, KDF};
+use config::{unix_time, EncryptionUsage, KeyPairSpec, KeySpec, KeyUse, ProviderConfig, Spec};
+use crypto::pkcs::pem::{self, PemLabel};
 use error::CalError;
 use traits::key_handle::DHKeyExchangeImplEnum;
 use traits::key_handle::{
@@ -10,13 +11,34 @@ use traits::module_provider::{ProviderImpl, ProviderImplEnum};
 
 /// Configuration for providers, key and key pairs.
 pub mod config;
+/// CBOR Object Signing and Encryption (COSE).
+#[cfg(feature = "software")]
+pub mod cose;
 /// Cryptographic algorithms or standards.
 pub mod crypto;
+/// Double Ratchet sessions for end-to-end encrypted messaging.
+pub mod double_ratchet;
 /// Error representations.
 pub mod error;
 /// Functions used for creating providers.
 pub mod factory;
+/// JSON Web Keys, signatures and encryption.
+#[cfg(feature = "software")]
+pub mod jose;
+/// Noise Protocol Framework handshakes.
+#[cfg(feature = "software")]
+pub mod noise;
+/// Password-authenticated key exchange for pairing devices.
+#[cfg(feature = "software")]
+pub mod pake;
+/// Secure channels with nonce counters and replay protection.
+pub mod secure_channel;
+/// Shamir secret sharing of keys.
+pub mod shamir;
 pub(crate) mod traits;
+/// Extended Triple Diffie-Hellman (X3DH) key agreement.
+#[cfg(feature = "software")]
+pub mod x3dh;
 
 // Do not delete this struct, it is a workaround for a bug in the code generation
 #[doc(hidden)]
@@ -34,8 +56,9 @@ pub struct Provider {
 impl Provider {
     /// Creates a new symmetric key.
     pub fn create_key(&mut self, spec: KeySpec) -> Result<KeyHandle, CalError> {
-        self.implementation
-            .create_key(spec)
+        spec.validity()
+            .check_period()
+            .and_then(|_| self.implementation.create_key(spec))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to create key"))
     }
 
@@ -48,15 +71,17 @@ impl Provider {
 
     /// Imports a symmetric key from raw data.
     pub fn import_key(&mut self, spec: KeySpec, data: &[u8]) -> Result<KeyHandle, CalError> {
-        self.implementation
-            .import_key(spec, data)
+        spec.validity()
+            .check_period()
+            .and_then(|_| self.implementation.import_key(spec, data))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to import key"))
     }
 
     /// Creates a new asymmetric key pair.
     pub fn create_key_pair(&mut self, spec: KeyPairSpec) -> Result<KeyPairHandle, CalError> {
-        self.implementation
-            .create_key_pair(spec)
+        spec.validity()
+            .check_period()
+            .and_then(|_| self.implementation.create_key_pair(spec))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to create key pair"))
     }
 
@@ -74,22 +99,109 @@ impl Provider {
         public_key: &[u8],
         private_key: &[u8],
     ) -> Result<KeyPairHandle, CalError> {
-        self.implementation
-            .import_key_pair(spec, public_key, private_key)
+        spec.validity()
+            .check_period()
+            .and_then(|_| {
+                self.implementation
+                    .import_key_pair(spec, public_key, private_key)
+            })
             .inspect_err(|error| tracing::error!(error = %error, "Failed to import key pair"))
     }
 
     /// Imports a public key only.
+    ///
+    /// Elliptic curve points are rejected unless they are in the prime order subgroup of the
+    /// curve. `P256` and `P384` points may be compressed.
     pub fn import_public_key(
         &mut self,
         spec: KeyPairSpec,
         public_key: &[u8],
     ) -> Result<KeyPairHandle, CalError> {
-        self.implementation
-            .import_public_key(spec, public_key)
+        spec.validity()
+            .check_period()
+            .and_then(|_| self.implementation.import_public_key(spec, public_key))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to import public key"))
+    }
+
+    /// Imports an asymmetric key pair from a private key encoded in `format`.
+    ///
+    /// The public key is taken from the encoding if present, otherwise it is derived from the
+    /// private key.
+    pub fn import_key_pair_with_format(
+        &mut self,
+        spec: KeyPairSpec,
+        private_key: &[u8],
+        format: KeyFormat,
+    ) -> Result<KeyPairHandle, CalError> {
+        spec.validity()
+            .check_period()
+            .and_then(|_| {
+                pem::import_key(private_key, format, |private_key, format| {
+                    self.implementation
+                        .import_key_pair_with_format(spec, private_key, format)
+                })
+            })
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to import key pair"))
+    }
+
+    /// Imports a public key encoded in `format`.
+    pub fn import_public_key_with_format(
+        &mut self,
+        spec: KeyPairSpec,
+        public_key: &[u8],
+        format: KeyFormat,
+    ) -> Result<KeyPairHandle, CalError> {
+        spec.validity()
+            .check_period()
+            .and_then(|_| {
+                pem::import_key(public_key, format, |public_key, format| {
+                    self.implementation
+                        .import_public_key_with_format(spec, public_key, format)
+                })
+            })
             .inspect_err(|error| tracing::error!(error = %error, "Failed to import public key"))
     }
 
+    /// Imports a symmetric key from a PKCS#8 `EncryptedPrivateKeyInfo` protected by `password`.
+    ///
+    /// `data` is either DER or PEM with the label `ENCRYPTED PRIVATE KEY`.
+    pub fn import_encrypted_key(
+        &mut self,
+        spec: KeySpec,
+        data: &[u8],
+        password: &str,
+    ) -> Result<KeyHandle, CalError> {
+        spec.validity()
+            .check_period()
+            .and_then(|_| pem::der_or_pem(data, PemLabel::EncryptedPrivateKey))
+            .and_then(|data| {
+                self.implementation
+                    .import_encrypted_key(spec, &data, password)
+            })
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to import encrypted key"))
+    }
+
+    /// Imports a key pair from a PKCS#8 `EncryptedPrivateKeyInfo` protected by `password`.
+    ///
+    /// `data` is either DER or PEM with the label `ENCRYPTED PRIVATE KEY`.
+    pub fn import_encrypted_key_pair(
+        &mut self,
+        spec: KeyPairSpec,
+        data: &[u8],
+        password: &str,
+    ) -> Result<KeyPairHandle, CalError> {
+        spec.validity()
+            .check_period()
+            .and_then(|_| pem::der_or_pem(data, PemLabel::EncryptedPrivateKey))
+            .and_then(|data| {
+                self.implementation
+                    .import_encrypted_key_pair(spec, &data, password)
+            })
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to import encrypted key pair"),
+            )
+    }
+
     /// Generates a key pair suited for a Diffie-Hellman Key Exchange.
     pub fn start_ephemeral_dh_exchange(
         &mut self,
@@ -115,12 +227,26 @@ impl Provider {
     }
 
     /// Returns all keys stored in this provider.
+    ///
+    /// The spec of every key includes its validity period, so keys about to expire are reported
+    /// with their `not_after`. [Provider::get_expiring_keys] returns only those keys.
     pub fn get_all_keys(&self) -> Result<Vec<(String, Spec)>, CalError> {
         self.implementation
             .get_all_keys()
             .inspect_err(|error| tracing::error!(error = %error, "Failed to get all keys"))
     }
 
+    /// Returns the stored keys which can no longer be used to protect data in `within` seconds,
+    /// including keys which already expired.
+    pub fn get_expiring_keys(&self, within: u64) -> Result<Vec<(String, Spec)>, CalError> {
+        let now = unix_time();
+        Ok(self
+            .get_all_keys()?
+            .into_iter()
+            .filter(|(_, spec)| spec.validity().expires_within(now, within))
+            .collect())
+    }
+
     /// Returns the name of this provider.
     #[must_use]
     pub fn provider_name(&self) -> String {
@@ -148,6 +274,34 @@ impl Provider {
             )
     }
 
+    /// Hashes a password for storage with a random salt.
+    ///
+    /// The result is a [PHC string](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md),
+    /// which contains the algorithm, parameters, salt and hash.
+    ///
+    /// Use [Provider::derive_key_from_password] instead, if you need a key.
+    pub fn hash_password(&self, password: &str, kdf: KDF) -> Result<String, CalError> {
+        self.implementation
+            .hash_password(password, kdf)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to hash password"))
+    }
+
+    /// Verifies a password against a PHC string created by [Provider::hash_password].
+    ///
+    /// The algorithm and parameters used for verification are taken from `password_hash`.
+    /// `kdf` is the currently desired configuration. If `password_hash` was created with another configuration,
+    /// [PasswordVerification::needs_rehash] is set.
+    pub fn verify_password(
+        &self,
+        password: &str,
+        password_hash: &str,
+        kdf: KDF,
+    ) -> Result<PasswordVerification, CalError> {
+        self.implementation
+            .verify_password(password, password_hash, kdf)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to verify password"))
+    }
+
     #[deprecated(
         note = "This function was deprecated in favor of the [KeyHandle::derive_key] method."
     )]
@@ -174,6 +328,57 @@ impl Provider {
             .hash(input, hash)
             .inspect_err(|error| tracing::error!(error = %error, "Failed to hash input"))
     }
+
+    /// Persists secret data under `id`, overwriting existing data.
+    ///
+    /// The data is encrypted and signed like key metadata, but kept separate from keys,
+    /// so it is neither returned by [Provider::get_all_keys] nor loadable as key.
+    pub fn store_secret_data(&mut self, id: String, data: &[u8]) -> Result<(), CalError> {
+        self.implementation
+            .store_secret_data(id, data)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to store secret data"))
+    }
+
+    /// Loads secret data stored with [Provider::store_secret_data].
+    pub fn load_secret_data(&self, id: String) -> Result<Vec<u8>, CalError> {
+        self.implementation
+            .load_secret_data(id)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to load secret data"))
+    }
+
+    /// Deletes secret data stored with [Provider::store_secret_data].
+    pub fn delete_secret_data(&mut self, id: String) -> Result<(), CalError> {
+        self.implementation
+            .delete_secret_data(id)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to delete secret data"))
+    }
+
+    /// Replaces the material of a stored symmetric key with a new version under the same id.
+    ///
+    /// [KeyHandle::encrypt_versioned] uses the newest version, while [KeyHandle::decrypt_versioned]
+    /// uses the version named in the cipher text. Older versions are kept until they are retired
+    /// with [Provider::retire_key_version].
+    pub fn rotate_key(&mut self, key_id: String) -> Result<KeyHandle, CalError> {
+        self.implementation
+            .rotate_key(key_id)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to rotate key"))
+    }
+
+    /// Deletes an older version of a rotated key, cipher texts of this version can no longer be decrypted.
+    pub fn retire_key_version(&mut self, key_id: String, version: u32) -> Result<(), CalError> {
+        self.implementation
+            .retire_key_version(key_id, version)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to retire key version"))
+    }
+
+    /// Returns the available versions of a stored key, oldest first.
+    ///
+    /// The last version is the one used for encryption.
+    pub fn key_versions(&self, key_id: String) -> Result<Vec<u32>, CalError> {
+        self.implementation
+            .key_versions(key_id)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to get key versions"))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -187,33 +392,39 @@ pub struct KeyPairHandle {
 impl KeyPairHandle {
     /// Encrypts the given data using the cryptographic key.
     pub fn encrypt_data(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.implementation.encrypt_data(data).inspect_err(
-            |error| tracing::error!(error = %error, "Failed to encrypt data with key pair"),
-        )
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_data(data))
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to encrypt data with key pair"),
+            )
     }
 
     /// Decrypts the given encrypted data using the cryptographic key.
     pub fn decrypt_data(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.implementation.decrypt_data(data).inspect_err(
-            |error| tracing::error!(error = %error, "Failed to decrypt data with key pair"),
-        )
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.decrypt_data(data))
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to decrypt data with key pair"),
+            )
     }
 
     /// Signs the given data using the cryptographic key.
     pub fn sign_data(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.implementation
-            .sign_data(data)
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.sign_data(data))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to sign data"))
     }
 
     /// Verifies the signature of the given data using the cryptographic key.
     pub fn verify_signature(&self, data: &[u8], signature: &[u8]) -> Result<bool, CalError> {
-        self.implementation
-            .verify_signature(data, signature)
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.verify_signature(data, signature))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to verify signature"))
     }
 
     /// Returns the raw public key as binary.
+    ///
+    /// Points of SEC 1 curves are encoded as set by [KeyPairSpec::point_encoding].
     pub fn get_public_key(&self) -> Result<Vec<u8>, CalError> {
         self.implementation
             .get_public_key()
@@ -230,10 +441,68 @@ impl KeyPairHandle {
             .inspect_err(|error| tracing::error!(error = %error, "Failed to extract private key"))
     }
 
+    /// Returns the private key encoded in `format`.
+    ///
+    /// Fails like [extract_key](KeyPairHandle::extract_key) for non exportable keys.
+    pub fn export_private_key(&self, format: KeyFormat) -> Result<Vec<u8>, CalError> {
+        pem::export_key(format, PemLabel::PrivateKey, |format| {
+            self.implementation.export_private_key(format)
+        })
+        .inspect_err(|error| tracing::error!(error = %error, "Failed to export private key"))
+    }
+
+    /// Returns the public key encoded in `format`.
+    pub fn export_public_key(&self, format: KeyFormat) -> Result<Vec<u8>, CalError> {
+        pem::export_key(format, PemLabel::PublicKey, |format| {
+            self.implementation.export_public_key(format)
+        })
+        .inspect_err(|error| tracing::error!(error = %error, "Failed to export public key"))
+    }
+
+    /// Returns the private key as DER encoded PKCS#8 `EncryptedPrivateKeyInfo` protected by
+    /// `password`.
+    ///
+    /// The key encryption key is derived from `password` with `kdf`, which has to be
+    /// [KDF::Pbkdf2] or [KDF::Scrypt]. `cipher` has to be [Cipher::AesCbc256], which is the most
+    /// compatible, or [Cipher::AesGcm256]. Non exportable keys are refused. Use
+    /// [pem::encode] with [PemLabel::EncryptedPrivateKey] for PEM.
+    pub fn export_encrypted_private_key(
+        &self,
+        password: &str,
+        kdf: KDF,
+        cipher: Cipher,
+    ) -> Result<Vec<u8>, CalError> {
+        self.implementation
+            .export_encrypted_private_key(password, kdf, cipher)
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to export encrypted private key"),
+            )
+    }
+
     #[deprecated(note = "Non ephemeral dh exchanges are possibly insecure.")]
     #[allow(dead_code)]
     pub fn start_dh_exchange(&self) -> Result<DHExchange, CalError> {
-        self.implementation.start_dh_exchange()
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.start_dh_exchange())
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to start DH exchange"))
+    }
+
+    /// Encapsulates a fresh shared secret to `public_key` with a key encapsulation mechanism.
+    ///
+    /// Returns the ciphertext, which needs to be sent to the owner of `public_key`,
+    /// and the shared secret as [KeyHandle].
+    /// The spec of the key handle is derived from the cipher of this key pair's [KeyPairSpec].
+    pub fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, KeyHandle), CalError> {
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encapsulate(public_key))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to encapsulate"))
+    }
+
+    /// Decapsulates the shared secret from a ciphertext created with [KeyPairHandle::encapsulate].
+    pub fn decapsulate(&self, ciphertext: &[u8]) -> Result<KeyHandle, CalError> {
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.decapsulate(ciphertext))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to decapsulate"))
     }
 
     /// Returns the id of the key pair, which can be used with `load_key_pair`.
@@ -255,6 +524,13 @@ impl KeyPairHandle {
     pub fn spec(&self) -> KeyPairSpec {
         self.implementation.spec()
     }
+
+    /// Checks the validity period of the key for `key_use`.
+    fn check_validity(&self, key_use: KeyUse) -> Result<(), CalError> {
+        self.spec().validity().check(key_use, unix_time(), || {
+            self.implementation.id().unwrap_or_default()
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -275,11 +551,27 @@ impl KeyHandle {
             .inspect_err(|error| tracing::error!(error = %error, "Failed to extract key"))
     }
 
+    /// Returns the key as DER encoded PKCS#8 `EncryptedPrivateKeyInfo` protected by `password`.
+    ///
+    /// The key is wrapped in a `PrivateKeyInfo` with the OID of its cipher, see
+    /// [KeyPairHandle::export_encrypted_private_key] for the parameters.
+    pub fn export_encrypted_key(
+        &self,
+        password: &str,
+        kdf: KDF,
+        cipher: Cipher,
+    ) -> Result<Vec<u8>, CalError> {
+        self.implementation
+            .export_encrypted_key(password, kdf, cipher)
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to export encrypted key"))
+    }
+
     #[deprecated(
         note = "Deprecated in favor of the more specific `encrypt` and `encrypt_with_iv` methods."
     )]
     pub fn encrypt_data(&self, data: &[u8], iv: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
-        self.implementation.encrypt_data(data, iv)
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_data(data, iv))
     }
 
     /// Encrypt data.
@@ -288,8 +580,8 @@ impl KeyHandle {
     ///
     /// The resulting output is a pair of cipher text and generated iv: `(cipher_text, iv)`
     pub fn encrypt(&self, data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
-        self.implementation
-            .encrypt(data)
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt(data))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to encrypt data"))
     }
 
@@ -297,40 +589,152 @@ impl KeyHandle {
     ///
     /// Some providers panic, if the iv is not the correct length.
     pub fn encrypt_with_iv(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.implementation
-            .encrypt_with_iv(data, iv)
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_with_iv(data, iv))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to encrypt data with IV"))
     }
 
+    /// Encrypt data with a nonce derived from the usage counter of the key.
+    ///
+    /// Unlike random nonces, counter nonces cannot collide, which allows far more encryptions
+    /// with a single key, see [Cipher::usage_limit](crate::prelude::Cipher::usage_limit).
+    /// The counter is persisted with the key, so this is only supported for stored keys.
+    ///
+    /// The resulting output is a pair of cipher text and used iv: `(cipher_text, iv)`
+    pub fn encrypt_with_counter_nonce(&self, data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_with_counter_nonce(data))
+            .inspect_err(|error| {
+                tracing::error!(error = %error, "Failed to encrypt data with counter nonce")
+            })
+    }
+
+    /// Returns the number of encryptions done with the key and the limits of its cipher.
+    ///
+    /// Once a limit is reached, encryption fails with
+    /// [CalErrorKind::KeyUsageLimitReached](crate::prelude::CalErrorKind::KeyUsageLimitReached)
+    /// and the key has to be rotated with [Provider::rotate_key].
+    pub fn encryption_usage(&self) -> Result<EncryptionUsage, CalError> {
+        self.implementation
+            .encryption_usage()
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to get encryption usage"))
+    }
+
+    /// Encrypts data with the newest version of a rotated key.
+    ///
+    /// The output is `version || iv || cipher_text`, with the version as big-endian `u32`.
+    /// Keys which were never rotated are in version 1.
+    pub fn encrypt_versioned(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_versioned(data))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to encrypt data"))
+    }
+
+    /// Decrypts the output of [KeyHandle::encrypt_versioned] with the key version it names.
+    pub fn decrypt_versioned(&self, encrypted_data: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.decrypt_versioned(encrypted_data))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to decrypt data"))
+    }
+
     /// Decrypts the given encrypted data using the cryptographic key.
     pub fn decrypt_data(&self, encrypted_data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.implementation
-            .decrypt_data(encrypted_data, iv)
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.decrypt_data(encrypted_data, iv))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to decrypt data"))
     }
 
+    /// Encrypts data with an AEAD cipher, authenticating `aad` alongside it.
+    ///
+    /// Unlike [`encrypt_with_iv`](KeyHandle::encrypt_with_iv), the output is the plain
+    /// cipher text followed by the tag, as specified for the cipher. The iv is required and must
+    /// never be reused with the same key.
+    pub fn encrypt_with_aad(
+        &self,
+        data: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_with_aad(data, iv, aad))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to encrypt data with AAD"))
+    }
+
+    /// Decrypts data encrypted with [`encrypt_with_aad`](KeyHandle::encrypt_with_aad).
+    pub fn decrypt_with_aad(
+        &self,
+        encrypted_data: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| {
+                self.implementation
+                    .decrypt_with_aad(encrypted_data, iv, aad)
+            })
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to decrypt data with AAD"))
+    }
+
     /// Calculates HMAC of the given data.
     pub fn hmac(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.implementation
-            .hmac(data)
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.hmac(data))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to calculate HMAC"))
     }
 
     /// Verifies data with the given signature.
     pub fn verify_hmac(&self, data: &[u8], hmac: &[u8]) -> Result<bool, CalError> {
-        self.implementation
-            .verify_hmac(data, hmac)
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.verify_hmac(data, hmac))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to verify HMAC"))
     }
 
+    /// Encrypts data with AES-CBC, the caller has to authenticate the cipher text.
+    pub(crate) fn encrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.encrypt_cbc(data, iv))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to encrypt data with CBC"))
+    }
+
+    /// Decrypts data encrypted with [`encrypt_cbc`](KeyHandle::encrypt_cbc), only call it after
+    /// the cipher text was authenticated.
+    pub(crate) fn decrypt_cbc(
+        &self,
+        encrypted_data: &[u8],
+        iv: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.decrypt_cbc(encrypted_data, iv))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to decrypt data with CBC"))
+    }
+
+    /// Wraps `key` with AES key wrap (RFC 3394), using this key as key encryption key.
+    ///
+    /// Needs [KeyUsage::WRAP](config::KeyUsage::WRAP). `key` has to be a multiple of 8 and at
+    /// least 16 bytes long, the wrapped key is 8 bytes longer.
+    pub fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.wrap_key(key))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to wrap key"))
+    }
+
+    /// Unwraps a key wrapped with [`wrap_key`](KeyHandle::wrap_key), failing if it was modified.
+    pub fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.check_validity(KeyUse::Process)
+            .and_then(|_| self.implementation.unwrap_key(wrapped_key))
+            .inspect_err(|error| tracing::error!(error = %error, "Failed to unwrap key"))
+    }
+
     /// Derives an ephemeral key from this key as base with the same spec as the base key.
     ///
+    /// Only the key usage differs, a derived key may encrypt, decrypt, sign and verify data.
+    ///
     /// A derived key is exportable if the base key (self) is exportable.
     ///
     /// This operation is deterministic, meaning the same nonce and key are always going to result in the same [KeyHandle].
     pub fn derive_key(&self, nonce: &[u8]) -> Result<KeyHandle, CalError> {
-        self.implementation
-            .derive_key(nonce)
+        self.check_validity(KeyUse::Protect)
+            .and_then(|_| self.implementation.derive_key(nonce))
             .inspect_err(|error| tracing::error!(error = %error, "Failed to derive key"))
     }
 
@@ -353,6 +757,13 @@ impl KeyHandle {
     pub fn spec(&self) -> KeySpec {
         self.implementation.spec()
     }
+
+    /// Checks the validity period of the key for `key_use`.
+    fn check_validity(&self, key_use: KeyUse) -> Result<(), CalError> {
+        self.spec().validity().check(key_use, unix_time(), || {
+            self.implementation.id().unwrap_or_default()
+        })
+    }
 }
 
 #[allow(dead_code)]
@@ -372,12 +783,57 @@ impl DHExchange {
     }
 
     /// Get the public key of the internal key pair to use for the other party.
+    ///
+    /// With [`AsymmetricKeySpec::X25519MlKem768`](crate::prelude::AsymmetricKeySpec::X25519MlKem768)
+    /// the server has to call this after deriving its session keys, as its public key then
+    /// carries the ML-KEM ciphertext for the client.
     pub fn get_public_key(&self) -> Result<Vec<u8>, CalError> {
         self.implementation.get_public_key().inspect_err(
             |error| tracing::error!(error = %error, "Failed to get DH exchange public key"),
         )
     }
 
+    /// Computes the raw shared secret with the public key of the other party.
+    ///
+    /// The result is the plain output of the key agreement and must not be used as key directly.
+    /// This is meant for protocols with their own key schedule, like [Noise](crate::common::noise),
+    /// otherwise prefer the `derive_*` methods.
+    pub fn compute_shared_secret(&self, peer_public_key: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.implementation
+            .compute_shared_secret(peer_public_key)
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to compute DH shared secret"),
+            )
+    }
+
+    /// Returns the key confirmation MAC of this party, to be sent to the other party.
+    ///
+    /// Has to be called after deriving session keys. The MAC is bound to both public keys, the
+    /// derived session keys, the role of this party and the optional `transcript`, which both
+    /// parties have to agree on.
+    pub fn key_confirmation(&self, transcript: Option<&[u8]>) -> Result<Vec<u8>, CalError> {
+        self.implementation
+            .key_confirmation(transcript)
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to compute key confirmation"),
+            )
+    }
+
+    /// Verifies the key confirmation MAC of the other party.
+    ///
+    /// Returns `true` if the other party derived the same session keys with the same `transcript`.
+    pub fn verify_key_confirmation(
+        &self,
+        peer_confirmation: &[u8],
+        transcript: Option<&[u8]>,
+    ) -> Result<bool, CalError> {
+        self.implementation
+            .verify_key_confirmation(peer_confirmation, transcript)
+            .inspect_err(
+                |error| tracing::error!(error = %error, "Failed to verify key confirmation"),
+            )
+    }
+
     /// Derive client session keys (rx, tx) - client is the templator in your code.
     pub fn derive_client_session_keys(
         &mut self,
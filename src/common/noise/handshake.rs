@@ -0,0 +1,432 @@
+use zeroize::Zeroizing;
+
+use super::{
+    check_message_len,
+    symmetric::{SymmetricState, TAG_LEN},
+    NoiseParams, NoiseTransport, Token,
+};
+use crate::common::{
+    config::{KeyPairSpec, KeyUsage},
+    error::CalError,
+    DHExchange, KeyPairHandle, Provider,
+};
+
+/// Configures and starts a Noise handshake.
+pub struct NoiseBuilder {
+    params: NoiseParams,
+    prologue: Vec<u8>,
+    local_static_key: Option<KeyPairHandle>,
+    remote_static_key: Option<Vec<u8>>,
+    ephemeral_key: Option<DHExchange>,
+}
+
+impl NoiseBuilder {
+    pub fn new(params: NoiseParams) -> Self {
+        Self {
+            params,
+            prologue: Vec::new(),
+            local_static_key: None,
+            remote_static_key: None,
+            ephemeral_key: None,
+        }
+    }
+
+    /// Sets the prologue, which both parties have to agree on.
+    pub fn prologue(mut self, prologue: &[u8]) -> Self {
+        self.prologue = prologue.to_vec();
+        self
+    }
+
+    /// Sets the static key pair of this party.
+    ///
+    /// The key pair has to support [KeyPairHandle::start_dh_exchange].
+    pub fn local_static_key(mut self, key_pair: KeyPairHandle) -> Self {
+        self.local_static_key = Some(key_pair);
+        self
+    }
+
+    /// Sets the static public key of the other party, required by the initiator of `NK` and `IK`.
+    ///
+    /// This is the DH public key, as returned by [DHExchange::get_public_key] of the other party's
+    /// [KeyPairHandle::start_dh_exchange], which differs from [KeyPairHandle::get_public_key] for
+    /// `Curve25519` key pairs holding Ed25519 keys.
+    pub fn remote_static_key(mut self, public_key: &[u8]) -> Self {
+        self.remote_static_key = Some(public_key.to_vec());
+        self
+    }
+
+    /// Uses a fixed ephemeral key instead of generating one.
+    ///
+    /// This is meant for test vectors only, reusing ephemeral keys breaks the security of Noise.
+    pub fn fixed_ephemeral_key(mut self, ephemeral_key: DHExchange) -> Self {
+        self.ephemeral_key = Some(ephemeral_key);
+        self
+    }
+
+    /// Starts the handshake as initiator, who sends the first message.
+    pub fn build_initiator(self, provider: &mut Provider) -> Result<NoiseHandshake<'_>, CalError> {
+        NoiseHandshake::new(self, provider, true)
+    }
+
+    /// Starts the handshake as responder.
+    pub fn build_responder(self, provider: &mut Provider) -> Result<NoiseHandshake<'_>, CalError> {
+        NoiseHandshake::new(self, provider, false)
+    }
+}
+
+struct LocalKey {
+    exchange: DHExchange,
+    public_key: Vec<u8>,
+}
+
+impl LocalKey {
+    fn new(exchange: DHExchange) -> Result<Self, CalError> {
+        let public_key = exchange.get_public_key()?;
+        Ok(Self {
+            exchange,
+            public_key,
+        })
+    }
+}
+
+fn missing(name: &str) -> CalError {
+    CalError::bad_parameter(
+        format!("The Noise handshake requires the {name}"),
+        true,
+        None,
+    )
+}
+
+/// A Noise handshake in progress.
+///
+/// Messages have to be written and read alternately as defined by the pattern,
+/// afterwards [NoiseHandshake::into_transport_mode] returns the transport state.
+pub struct NoiseHandshake<'a> {
+    symmetric: SymmetricState<'a>,
+    params: NoiseParams,
+    initiator: bool,
+    local_static: Option<LocalKey>,
+    local_ephemeral: Option<LocalKey>,
+    fixed_ephemeral: Option<DHExchange>,
+    remote_static: Option<Vec<u8>>,
+    remote_ephemeral: Option<Vec<u8>>,
+    message_index: usize,
+}
+
+impl<'a> NoiseHandshake<'a> {
+    fn new(
+        builder: NoiseBuilder,
+        provider: &'a mut Provider,
+        initiator: bool,
+    ) -> Result<Self, CalError> {
+        let params = builder.params;
+        params.validate()?;
+
+        let (initiator_pre, responder_pre) = params.pattern.pre_messages();
+        let (local_pre, remote_pre) = if initiator {
+            (initiator_pre, responder_pre)
+        } else {
+            (responder_pre, initiator_pre)
+        };
+
+        // Tokens of the messages this party sends.
+        let local_tokens = params
+            .pattern
+            .messages()
+            .iter()
+            .skip(if initiator { 0 } else { 1 })
+            .step_by(2)
+            .flat_map(|tokens| tokens.iter());
+        let needs_local_static =
+            local_pre.contains(&Token::S) || local_tokens.clone().any(|t| *t == Token::S);
+
+        let local_static = match builder.local_static_key {
+            Some(key_pair) => {
+                if key_pair.spec().asym_spec != params.dh {
+                    return Err(CalError::bad_parameter(
+                        format!(
+                            "The static key pair uses {:?}, but the protocol uses {:?}",
+                            key_pair.spec().asym_spec,
+                            params.dh
+                        ),
+                        true,
+                        None,
+                    ));
+                }
+                // Static DH is inherent to the patterns using static keys.
+                #[allow(deprecated)]
+                let exchange = key_pair.start_dh_exchange()?;
+                Some(LocalKey::new(exchange)?)
+            }
+            None if needs_local_static => return Err(missing("local static key")),
+            None => None,
+        };
+
+        if remote_pre.contains(&Token::S) && builder.remote_static_key.is_none() {
+            return Err(missing("remote static key"));
+        }
+        if let Some(remote_static) = builder.remote_static_key.as_ref() {
+            if remote_static.len() != params.dh_len() {
+                return Err(CalError::bad_parameter(
+                    format!(
+                        "Invalid remote static key length: expected {} bytes, got {}",
+                        params.dh_len(),
+                        remote_static.len()
+                    ),
+                    true,
+                    None,
+                ));
+            }
+        }
+
+        let mut symmetric = SymmetricState::new(provider, params)?;
+        symmetric.mix_hash(&builder.prologue)?;
+
+        // Only static keys are used in the pre-messages of the supported patterns.
+        for (pre_message, is_local) in [(initiator_pre, initiator), (responder_pre, !initiator)] {
+            if pre_message.contains(&Token::S) {
+                let public_key = if is_local {
+                    &local_static
+                        .as_ref()
+                        .ok_or_else(|| missing("local static key"))?
+                        .public_key
+                } else {
+                    builder
+                        .remote_static_key
+                        .as_ref()
+                        .ok_or_else(|| missing("remote static key"))?
+                };
+                symmetric.mix_hash(public_key)?;
+            }
+        }
+
+        Ok(Self {
+            symmetric,
+            params,
+            initiator,
+            local_static,
+            local_ephemeral: None,
+            fixed_ephemeral: builder.ephemeral_key,
+            remote_static: builder.remote_static_key,
+            remote_ephemeral: None,
+            message_index: 0,
+        })
+    }
+
+    /// Returns `true` if all handshake messages have been processed.
+    pub fn is_handshake_finished(&self) -> bool {
+        self.message_index >= self.params.pattern.messages().len()
+    }
+
+    /// Returns `true` if this party has to write the next message.
+    pub fn is_my_turn(&self) -> bool {
+        !self.is_handshake_finished() && self.message_index.is_multiple_of(2) == self.initiator
+    }
+
+    /// Returns the static public key of the other party, if already known.
+    pub fn remote_static_key(&self) -> Option<&[u8]> {
+        self.remote_static.as_deref()
+    }
+
+    /// Returns the current handshake hash, which can be used for channel binding.
+    pub fn handshake_hash(&self) -> &[u8] {
+        self.symmetric.handshake_hash()
+    }
+
+    fn next_tokens(&self, writing: bool) -> Result<&'static [Token], CalError> {
+        if self.is_handshake_finished() {
+            return Err(CalError::failed_operation(
+                "The Noise handshake is already finished".to_owned(),
+                true,
+                None,
+            ));
+        }
+        if self.is_my_turn() != writing {
+            return Err(CalError::failed_operation(
+                format!(
+                    "It is not this party's turn to {} a message",
+                    if writing { "write" } else { "read" }
+                ),
+                true,
+                None,
+            ));
+        }
+
+        Ok(self.params.pattern.messages()[self.message_index])
+    }
+
+    fn dh(
+        &self,
+        local: Option<&LocalKey>,
+        remote: Option<&Vec<u8>>,
+    ) -> Result<Zeroizing<Vec<u8>>, CalError> {
+        let local = local.ok_or_else(|| missing("local key for the DH"))?;
+        let remote = remote.ok_or_else(|| missing("remote key for the DH"))?;
+        Ok(Zeroizing::new(
+            local.exchange.compute_shared_secret(remote)?,
+        ))
+    }
+
+    fn mix_dh(&mut self, token: Token) -> Result<(), CalError> {
+        // `es` and `se` name the initiator's key first.
+        let shared_secret = match (token, self.initiator) {
+            (Token::EE, _) => self.dh(
+                self.local_ephemeral.as_ref(),
+                self.remote_ephemeral.as_ref(),
+            ),
+            (Token::ES, true) | (Token::SE, false) => {
+                self.dh(self.local_ephemeral.as_ref(), self.remote_static.as_ref())
+            }
+            (Token::SE, true) | (Token::ES, false) => {
+                self.dh(self.local_static.as_ref(), self.remote_ephemeral.as_ref())
+            }
+            (Token::SS, _) => self.dh(self.local_static.as_ref(), self.remote_static.as_ref()),
+            _ => unreachable!("Only DH tokens are mixed"),
+        }?;
+
+        self.symmetric.mix_key(&shared_secret)
+    }
+
+    /// Length of the message written for `tokens` and a payload of `payload_len` bytes.
+    fn message_len(&self, tokens: &[Token], payload_len: usize) -> usize {
+        let dh_len = self.params.dh_len();
+        let mut has_key = self.symmetric.has_key();
+        let mut len = payload_len;
+        for token in tokens {
+            match token {
+                Token::E => len += dh_len,
+                Token::S => len += dh_len + if has_key { TAG_LEN } else { 0 },
+                _ => has_key = true,
+            }
+        }
+        len + if has_key { TAG_LEN } else { 0 }
+    }
+
+    /// Writes the next handshake message carrying `payload`.
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>, CalError> {
+        let tokens = self.next_tokens(true)?;
+        // Checked before any token is processed, as they change the handshake state.
+        check_message_len(self.message_len(tokens, payload.len()))?;
+        let mut message = Vec::new();
+
+        for token in tokens {
+            match token {
+                Token::E => {
+                    let exchange =
+                        match self.fixed_ephemeral.take() {
+                            Some(exchange) => exchange,
+                            None => self.symmetric.provider.start_ephemeral_dh_exchange(
+                                KeyPairSpec {
+                                    asym_spec: self.params.dh,
+                                    cipher: None,
+                                    signing_hash: self.params.hash,
+                                    rsa_padding: Default::default(),
+                                    signature_encoding: Default::default(),
+                                    point_encoding: Default::default(),
+                                    ephemeral: true,
+                                    non_exportable: false,
+                                    not_before: None,
+                                    not_after: None,
+                                    process_not_after: None,
+                                    key_usage: KeyUsage::ALL,
+                                },
+                            )?,
+                        };
+                    let ephemeral = LocalKey::new(exchange)?;
+                    message.extend_from_slice(&ephemeral.public_key);
+                    self.symmetric.mix_hash(&ephemeral.public_key)?;
+                    self.local_ephemeral = Some(ephemeral);
+                }
+                Token::S => {
+                    let public_key = self
+                        .local_static
+                        .as_ref()
+                        .ok_or_else(|| missing("local static key"))?
+                        .public_key
+                        .clone();
+                    message.extend(self.symmetric.encrypt_and_hash(&public_key)?);
+                }
+                token => self.mix_dh(*token)?,
+            }
+        }
+
+        message.extend(self.symmetric.encrypt_and_hash(payload)?);
+
+        self.message_index += 1;
+        Ok(message)
+    }
+
+    /// Reads the next handshake message and returns its payload.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, CalError> {
+        let tokens = self.next_tokens(false)?;
+        check_message_len(message.len())?;
+
+        let too_short = || {
+            CalError::bad_parameter(
+                "Noise handshake message is too short".to_owned(),
+                true,
+                None,
+            )
+        };
+        let dh_len = self.params.dh_len();
+        let mut remaining = message;
+
+        for token in tokens {
+            match token {
+                Token::E => {
+                    let (public_key, rest) =
+                        remaining.split_at_checked(dh_len).ok_or_else(too_short)?;
+                    self.symmetric.mix_hash(public_key)?;
+                    self.remote_ephemeral = Some(public_key.to_vec());
+                    remaining = rest;
+                }
+                Token::S => {
+                    let len = if self.symmetric.has_key() {
+                        dh_len + TAG_LEN
+                    } else {
+                        dh_len
+                    };
+                    let (encrypted, rest) =
+                        remaining.split_at_checked(len).ok_or_else(too_short)?;
+                    self.remote_static = Some(self.symmetric.decrypt_and_hash(encrypted)?);
+                    remaining = rest;
+                }
+                token => self.mix_dh(*token)?,
+            }
+        }
+
+        let payload = self.symmetric.decrypt_and_hash(remaining)?;
+        self.message_index += 1;
+        Ok(payload)
+    }
+
+    /// Finishes the handshake and returns the transport state.
+    pub fn into_transport_mode(self) -> Result<NoiseTransport, CalError> {
+        if !self.is_handshake_finished() {
+            return Err(CalError::failed_operation(
+                "The Noise handshake is not finished yet".to_owned(),
+                true,
+                None,
+            ));
+        }
+
+        let handshake_hash = self.symmetric.handshake_hash().to_vec();
+        let (initiator_to_responder, responder_to_initiator) = self.symmetric.split()?;
+
+        Ok(if self.initiator {
+            NoiseTransport::new(
+                responder_to_initiator,
+                initiator_to_responder,
+                handshake_hash,
+                self.remote_static,
+            )
+        } else {
+            NoiseTransport::new(
+                initiator_to_responder,
+                responder_to_initiator,
+                handshake_hash,
+                self.remote_static,
+            )
+        })
+    }
+}
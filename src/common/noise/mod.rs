@@ -0,0 +1,227 @@
+//! Handshakes of the [Noise Protocol Framework](https://noiseprotocol.org/noise.html) (revision 34).
+//!
+//! Diffie-Hellman, hashing and AEAD operations are delegated to a [Provider](crate::common::Provider),
+//! static keys are taken from [KeyPairHandle](crate::common::KeyPairHandle)s and a finished handshake
+//! results in a [NoiseTransport] holding the rx and tx [KeyHandle](crate::common::KeyHandle)s.
+//!
+//! Supported are the `NN`, `NK`, `XX` and `IK` patterns with `25519` or `P256`, `ChaChaPoly` or `AESGCM`
+//! and `SHA256`, `SHA512`, `BLAKE2s` or `BLAKE2b`.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{common::noise::{NoiseBuilder, NoiseParams}, prelude::*};
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut initiator_provider = create_provider_from_name("SoftwareProvider", impl_config.clone()).unwrap();
+//! let mut responder_provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let params: NoiseParams = "Noise_NN_25519_ChaChaPoly_SHA256".parse().unwrap();
+//! let mut initiator = NoiseBuilder::new(params).build_initiator(&mut initiator_provider).unwrap();
+//! let mut responder = NoiseBuilder::new(params).build_responder(&mut responder_provider).unwrap();
+//!
+//! let message = initiator.write_message(b"").unwrap();
+//! responder.read_message(&message).unwrap();
+//! let message = responder.write_message(b"").unwrap();
+//! initiator.read_message(&message).unwrap();
+//!
+//! let mut initiator = initiator.into_transport_mode().unwrap();
+//! let mut responder = responder.into_transport_mode().unwrap();
+//!
+//! let message = initiator.write_message(b"Hello").unwrap();
+//! assert_eq!(responder.read_message(&message).unwrap(), b"Hello");
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use crate::common::{
+    crypto::algorithms::{encryption::AsymmetricKeySpec, hashes::CryptoHash},
+    error::CalError,
+};
+use crate::prelude::Cipher;
+
+mod handshake;
+mod symmetric;
+mod transport;
+
+pub use handshake::{NoiseBuilder, NoiseHandshake};
+pub use transport::NoiseTransport;
+
+/// Maximum length of a Noise message.
+pub const MAX_MESSAGE_LEN: usize = 65535;
+
+fn check_message_len(len: usize) -> Result<(), CalError> {
+    if len > MAX_MESSAGE_LEN {
+        return Err(CalError::bad_parameter(
+            "Noise message exceeds the maximum length".to_owned(),
+            true,
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// Supported handshake patterns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoisePattern {
+    /// No static keys.
+    NN,
+    /// The responder's static key is known to the initiator.
+    NK,
+    /// Both static keys are transmitted during the handshake.
+    XX,
+    /// The responder's static key is known, the initiator's is transmitted in the first message.
+    IK,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Token {
+    E,
+    S,
+    EE,
+    ES,
+    SE,
+    SS,
+}
+
+impl NoisePattern {
+    /// Pre-messages of initiator and responder.
+    pub(crate) fn pre_messages(&self) -> (&'static [Token], &'static [Token]) {
+        match self {
+            Self::NN | Self::XX => (&[], &[]),
+            Self::NK | Self::IK => (&[], &[Token::S]),
+        }
+    }
+
+    /// Handshake messages, alternating between initiator and responder.
+    pub(crate) fn messages(&self) -> &'static [&'static [Token]] {
+        use Token::*;
+
+        match self {
+            Self::NN => &[&[E], &[E, EE]],
+            Self::NK => &[&[E, ES], &[E, EE]],
+            Self::XX => &[&[E], &[E, EE, S, ES], &[S, SE]],
+            Self::IK => &[&[E, ES, S, SS], &[E, EE, SE]],
+        }
+    }
+}
+
+/// Parameters of a Noise protocol, parsable from protocol names like `Noise_XX_25519_ChaChaPoly_SHA256`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NoiseParams {
+    pub pattern: NoisePattern,
+    /// [AsymmetricKeySpec::Curve25519] or [AsymmetricKeySpec::P256].
+    pub dh: AsymmetricKeySpec,
+    /// [Cipher::ChaCha20Poly1305] or [Cipher::AesGcm256].
+    pub cipher: Cipher,
+    /// [CryptoHash::Sha2_256], [CryptoHash::Sha2_512], [CryptoHash::Blake2s] or [CryptoHash::Blake2b].
+    pub hash: CryptoHash,
+}
+
+impl NoiseParams {
+    pub(crate) fn validate(&self) -> Result<(), CalError> {
+        if !matches!(
+            self.dh,
+            AsymmetricKeySpec::Curve25519 | AsymmetricKeySpec::P256
+        ) {
+            return Err(unsupported(&format!("{:?}", self.dh)));
+        }
+        if !matches!(self.cipher, Cipher::ChaCha20Poly1305 | Cipher::AesGcm256) {
+            return Err(unsupported(&format!("{:?}", self.cipher)));
+        }
+        if !matches!(
+            self.hash,
+            CryptoHash::Sha2_256 | CryptoHash::Sha2_512 | CryptoHash::Blake2s | CryptoHash::Blake2b
+        ) {
+            return Err(unsupported(&format!("{:?}", self.hash)));
+        }
+        Ok(())
+    }
+
+    /// Length of public keys of the DH function.
+    pub(crate) fn dh_len(&self) -> usize {
+        match self.dh {
+            AsymmetricKeySpec::P256 => 65,
+            _ => 32,
+        }
+    }
+
+    /// Output length of the hash function.
+    pub(crate) fn hash_len(&self) -> usize {
+        match self.hash {
+            CryptoHash::Sha2_256 | CryptoHash::Blake2s => 32,
+            _ => 64,
+        }
+    }
+}
+
+fn unsupported(part: &str) -> CalError {
+    CalError::unsupported_algorithm(format!("Unsupported Noise protocol part: '{part}'"))
+}
+
+impl FromStr for NoiseParams {
+    type Err = CalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('_').collect();
+        let [protocol, pattern, dh, cipher, hash] = parts.as_slice() else {
+            return Err(CalError::bad_parameter(
+                format!("Invalid Noise protocol name: '{s}'"),
+                true,
+                None,
+            ));
+        };
+
+        if *protocol != "Noise" {
+            return Err(unsupported(protocol));
+        }
+
+        Ok(Self {
+            pattern: match *pattern {
+                "NN" => NoisePattern::NN,
+                "NK" => NoisePattern::NK,
+                "XX" => NoisePattern::XX,
+                "IK" => NoisePattern::IK,
+                other => return Err(unsupported(other)),
+            },
+            dh: match *dh {
+                "25519" => AsymmetricKeySpec::Curve25519,
+                "P256" => AsymmetricKeySpec::P256,
+                other => return Err(unsupported(other)),
+            },
+            cipher: match *cipher {
+                "ChaChaPoly" => Cipher::ChaCha20Poly1305,
+                "AESGCM" => Cipher::AesGcm256,
+                other => return Err(unsupported(other)),
+            },
+            hash: match *hash {
+                "SHA256" => CryptoHash::Sha2_256,
+                "SHA512" => CryptoHash::Sha2_512,
+                "BLAKE2s" => CryptoHash::Blake2s,
+                "BLAKE2b" => CryptoHash::Blake2b,
+                other => return Err(unsupported(other)),
+            },
+        })
+    }
+}
+
+impl fmt::Display for NoiseParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dh = match self.dh {
+            AsymmetricKeySpec::P256 => "P256",
+            _ => "25519",
+        };
+        let cipher = match self.cipher {
+            Cipher::AesGcm256 => "AESGCM",
+            _ => "ChaChaPoly",
+        };
+        let hash = match self.hash {
+            CryptoHash::Sha2_256 => "SHA256",
+            CryptoHash::Sha2_512 => "SHA512",
+            CryptoHash::Blake2s => "BLAKE2s",
+            _ => "BLAKE2b",
+        };
+
+        write!(f, "Noise_{:?}_{dh}_{cipher}_{hash}", self.pattern)
+    }
+}
@@ -0,0 +1,213 @@
+use zeroize::Zeroizing;
+
+use super::NoiseParams;
+use crate::common::{
+    config::{KeySpec, KeyUsage},
+    crypto::hkdf,
+    error::CalError,
+    KeyHandle, Provider,
+};
+use crate::prelude::Cipher;
+
+type SecretBytes = Zeroizing<Vec<u8>>;
+
+/// Length of the tag appended by the AEAD ciphers.
+pub(crate) const TAG_LEN: usize = 16;
+
+/// Imports `key` as an ephemeral [KeyHandle] for the cipher of `params`.
+pub(crate) fn import_cipher_key(
+    provider: &mut Provider,
+    params: &NoiseParams,
+    key: &[u8],
+) -> Result<KeyHandle, CalError> {
+    let spec = KeySpec {
+        cipher: params.cipher,
+        signing_hash: params.hash,
+        ephemeral: true,
+        non_exportable: false,
+        not_before: None,
+        not_after: None,
+        process_not_after: None,
+        key_usage: KeyUsage::ALL,
+    };
+
+    provider.import_key(spec, &key[..params.cipher.len()])
+}
+
+/// `CipherState` of the Noise specification.
+pub(crate) struct CipherState {
+    key: KeyHandle,
+    cipher: Cipher,
+    nonce: u64,
+}
+
+impl CipherState {
+    pub(crate) fn new(key: KeyHandle, cipher: Cipher) -> Self {
+        Self {
+            key,
+            cipher,
+            nonce: 0,
+        }
+    }
+
+    pub(crate) fn key(&self) -> &KeyHandle {
+        &self.key
+    }
+
+    pub(crate) fn into_key(self) -> KeyHandle {
+        self.key
+    }
+
+    /// 32 bits of zeros followed by the nonce, little-endian for ChaChaPoly and big-endian for AESGCM.
+    fn iv(&self) -> Result<[u8; 12], CalError> {
+        // 2^64-1 is reserved by the specification.
+        if self.nonce == u64::MAX {
+            return Err(CalError::failed_operation(
+                "Noise nonce exhausted".to_owned(),
+                true,
+                None,
+            ));
+        }
+
+        let mut iv = [0u8; 12];
+        match self.cipher {
+            Cipher::AesGcm256 => iv[4..].copy_from_slice(&self.nonce.to_be_bytes()),
+            _ => iv[4..].copy_from_slice(&self.nonce.to_le_bytes()),
+        }
+
+        Ok(iv)
+    }
+
+    pub(crate) fn encrypt_with_ad(
+        &mut self,
+        ad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let ciphertext = self.key.encrypt_with_aad(plaintext, &self.iv()?, ad)?;
+        self.nonce += 1;
+        Ok(ciphertext)
+    }
+
+    /// The nonce is only incremented if the decryption succeeds.
+    pub(crate) fn decrypt_with_ad(
+        &mut self,
+        ad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let plaintext = self.key.decrypt_with_aad(ciphertext, &self.iv()?, ad)?;
+        self.nonce += 1;
+        Ok(plaintext)
+    }
+}
+
+/// `SymmetricState` of the Noise specification.
+pub(crate) struct SymmetricState<'a> {
+    pub(crate) provider: &'a mut Provider,
+    params: NoiseParams,
+    chaining_key: Zeroizing<Vec<u8>>,
+    handshake_hash: Vec<u8>,
+    cipher_state: Option<CipherState>,
+}
+
+impl<'a> SymmetricState<'a> {
+    pub(crate) fn new(provider: &'a mut Provider, params: NoiseParams) -> Result<Self, CalError> {
+        let protocol_name = params.to_string();
+
+        let handshake_hash = if protocol_name.len() <= params.hash_len() {
+            let mut padded = protocol_name.into_bytes();
+            padded.resize(params.hash_len(), 0);
+            padded
+        } else {
+            provider.hash(protocol_name.as_bytes(), params.hash)?
+        };
+
+        Ok(Self {
+            provider,
+            params,
+            chaining_key: Zeroizing::new(handshake_hash.clone()),
+            handshake_hash,
+            cipher_state: None,
+        })
+    }
+
+    pub(crate) fn handshake_hash(&self) -> &[u8] {
+        &self.handshake_hash
+    }
+
+    pub(crate) fn has_key(&self) -> bool {
+        self.cipher_state.is_some()
+    }
+
+    /// `HKDF(chaining_key, input_key_material, 2)`
+    fn hkdf(&self, input_key_material: &[u8]) -> Result<(SecretBytes, SecretBytes), CalError> {
+        let temp_key = hkdf::extract(
+            self.provider,
+            self.params.hash,
+            &self.chaining_key,
+            input_key_material,
+        )?;
+        let output = hkdf::expand(
+            self.provider,
+            self.params.hash,
+            &temp_key,
+            &[],
+            2 * self.params.hash_len(),
+        )?;
+        let (output1, output2) = output.split_at(self.params.hash_len());
+        Ok((
+            Zeroizing::new(output1.to_vec()),
+            Zeroizing::new(output2.to_vec()),
+        ))
+    }
+
+    pub(crate) fn mix_key(&mut self, input_key_material: &[u8]) -> Result<(), CalError> {
+        let (chaining_key, temp_key) = self.hkdf(input_key_material)?;
+        self.chaining_key = chaining_key;
+
+        let key = import_cipher_key(self.provider, &self.params, &temp_key)?;
+        self.cipher_state = Some(CipherState::new(key, self.params.cipher));
+        Ok(())
+    }
+
+    pub(crate) fn mix_hash(&mut self, data: &[u8]) -> Result<(), CalError> {
+        let mut input = std::mem::take(&mut self.handshake_hash);
+        input.extend_from_slice(data);
+        self.handshake_hash = self.provider.hash(&input, self.params.hash)?;
+        Ok(())
+    }
+
+    pub(crate) fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, CalError> {
+        let ciphertext = match self.cipher_state.as_mut() {
+            Some(cipher_state) => cipher_state.encrypt_with_ad(&self.handshake_hash, plaintext)?,
+            None => plaintext.to_vec(),
+        };
+        self.mix_hash(&ciphertext)?;
+        Ok(ciphertext)
+    }
+
+    pub(crate) fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, CalError> {
+        let plaintext = match self.cipher_state.as_mut() {
+            Some(cipher_state) => cipher_state.decrypt_with_ad(&self.handshake_hash, ciphertext)?,
+            None => ciphertext.to_vec(),
+        };
+        self.mix_hash(ciphertext)?;
+        Ok(plaintext)
+    }
+
+    /// Returns the cipher states for initiator to responder and responder to initiator.
+    pub(crate) fn split(self) -> Result<(CipherState, CipherState), CalError> {
+        let (key1, key2) = self.hkdf(&[])?;
+        let params = self.params;
+
+        let initiator_to_responder = CipherState::new(
+            import_cipher_key(self.provider, &params, &key1)?,
+            params.cipher,
+        );
+        let responder_to_initiator = CipherState::new(
+            import_cipher_key(self.provider, &params, &key2)?,
+            params.cipher,
+        );
+
+        Ok((initiator_to_responder, responder_to_initiator))
+    }
+}
@@ -0,0 +1,69 @@
+use super::{
+    check_message_len,
+    symmetric::{CipherState, TAG_LEN},
+};
+use crate::common::{error::CalError, KeyHandle};
+
+/// Transport state of a finished Noise handshake.
+pub struct NoiseTransport {
+    rx: CipherState,
+    tx: CipherState,
+    handshake_hash: Vec<u8>,
+    remote_static_key: Option<Vec<u8>>,
+}
+
+impl NoiseTransport {
+    pub(crate) fn new(
+        rx: CipherState,
+        tx: CipherState,
+        handshake_hash: Vec<u8>,
+        remote_static_key: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            rx,
+            tx,
+            handshake_hash,
+            remote_static_key,
+        }
+    }
+
+    /// Encrypts `payload` for the other party.
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>, CalError> {
+        // Checked before encrypting, so that the nonce is not used up by a rejected message.
+        check_message_len(payload.len() + TAG_LEN)?;
+        self.tx.encrypt_with_ad(&[], payload)
+    }
+
+    /// Decrypts a message of the other party.
+    ///
+    /// Messages have to be read in the order they were written.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, CalError> {
+        check_message_len(message.len())?;
+        self.rx.decrypt_with_ad(&[], message)
+    }
+
+    /// Returns the final handshake hash, which can be used for channel binding.
+    pub fn handshake_hash(&self) -> &[u8] {
+        &self.handshake_hash
+    }
+
+    /// Returns the static public key of the other party, if the pattern transmitted or required it.
+    pub fn remote_static_key(&self) -> Option<&[u8]> {
+        self.remote_static_key.as_deref()
+    }
+
+    /// Key used for decrypting received messages.
+    pub fn rx_key(&self) -> &KeyHandle {
+        self.rx.key()
+    }
+
+    /// Key used for encrypting sent messages.
+    pub fn tx_key(&self) -> &KeyHandle {
+        self.tx.key()
+    }
+
+    /// Returns the rx and tx keys, discarding the nonces.
+    pub fn into_key_handles(self) -> (KeyHandle, KeyHandle) {
+        (self.rx.into_key(), self.tx.into_key())
+    }
+}
@@ -0,0 +1,374 @@
+//! [CPace](https://datatracker.ietf.org/doc/draft-irtf-cfrg-cpace/) password-authenticated key
+//! exchange over Ristretto255 with SHA-512, for pairing devices with a short code.
+//!
+//! [PakeExchange] follows the shape of [DHExchange](crate::common::DHExchange): both parties
+//! start an exchange with the pairing code, send their public message, and derive keys. Explicit
+//! key confirmation adds one message per party, so that a wrong pairing code is detected before
+//! keys are derived. An exchange can be used for a single attempt only, a failed confirmation
+//! requires starting a new exchange.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{
+//!     common::pake::{PakeConfig, PakeExchange, PakeRole},
+//!     prelude::*,
+//! };
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeySpec {
+//!     cipher: Cipher::AesGcm256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     non_exportable: false,
+//!     ..Default::default()
+//! };
+//! let config = PakeConfig::new(b"device-a device-b", spec);
+//!
+//! let mut alice = PakeExchange::start(&provider, PakeRole::Initiator, b"123456", config.clone()).unwrap();
+//! let mut bob = PakeExchange::start(&provider, PakeRole::Responder, b"123456", config).unwrap();
+//!
+//! let alice_confirmation = alice.compute_confirmation(&provider, &bob.get_public_key()).unwrap();
+//! let bob_confirmation = bob.compute_confirmation(&provider, &alice.get_public_key()).unwrap();
+//!
+//! let (alice_rx, alice_tx) = alice.derive_key_handles(&mut provider, &bob_confirmation).unwrap();
+//! let (bob_rx, bob_tx) = bob.derive_key_handles(&mut provider, &alice_confirmation).unwrap();
+//!
+//! assert_eq!(alice_tx.extract_key().unwrap(), bob_rx.extract_key().unwrap());
+//! assert_eq!(alice_rx.extract_key().unwrap(), bob_tx.extract_key().unwrap());
+//! ```
+
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::IsIdentity,
+};
+use zeroize::Zeroizing;
+
+use crate::common::{
+    config::KeySpec,
+    crypto::{algorithms::hashes::CryptoHash, hkdf},
+    error::CalError,
+    KeyHandle, Provider,
+};
+
+const DSI: &[u8] = b"CPaceRistretto255";
+const HASH: CryptoHash = CryptoHash::Sha2_512;
+/// Input block size of SHA-512, the generator string is padded to it.
+const HASH_BLOCK_LEN: usize = 128;
+const POINT_LEN: usize = 32;
+
+/// Role of a party, the initiator is the party sending its public message first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PakeRole {
+    Initiator,
+    Responder,
+}
+
+impl PakeRole {
+    fn label(self) -> &'static [u8] {
+        match self {
+            PakeRole::Initiator => b"initiator",
+            PakeRole::Responder => b"responder",
+        }
+    }
+
+    fn peer(self) -> Self {
+        match self {
+            PakeRole::Initiator => PakeRole::Responder,
+            PakeRole::Responder => PakeRole::Initiator,
+        }
+    }
+}
+
+/// Parameters both parties have to agree on.
+#[derive(Clone, Debug)]
+pub struct PakeConfig {
+    /// Channel identifier, for example the identifiers of both devices.
+    pub channel_id: Vec<u8>,
+    /// Optional session id, which should be unique for each exchange if available.
+    pub session_id: Vec<u8>,
+    /// Associated data sent with the own public message, for example a device name.
+    ///
+    /// This may differ between the parties and is authenticated by the key confirmation.
+    pub associated_data: Vec<u8>,
+    /// Spec of the derived keys.
+    pub key_spec: KeySpec,
+}
+
+impl PakeConfig {
+    pub fn new(channel_id: &[u8], key_spec: KeySpec) -> Self {
+        Self {
+            channel_id: channel_id.to_vec(),
+            session_id: Vec::new(),
+            associated_data: Vec::new(),
+            key_spec,
+        }
+    }
+}
+
+enum State {
+    Started {
+        scalar: Zeroizing<Scalar>,
+    },
+    Confirming {
+        intermediate_session_key: Zeroizing<Vec<u8>>,
+        transcript: Vec<u8>,
+    },
+    Finished,
+}
+
+/// A CPace exchange of one party.
+pub struct PakeExchange {
+    role: PakeRole,
+    config: PakeConfig,
+    public_key: Vec<u8>,
+    peer_associated_data: Option<Vec<u8>>,
+    state: State,
+}
+
+/// Prepends the LEB128 encoded length.
+fn prepend_len(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len() + 2);
+    let mut len = data.len();
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            encoded.push(byte);
+            break;
+        }
+        encoded.push(byte | 0x80);
+    }
+    encoded.extend_from_slice(data);
+    encoded
+}
+
+fn lv_cat(parts: &[&[u8]]) -> Vec<u8> {
+    parts.iter().flat_map(|part| prepend_len(part)).collect()
+}
+
+fn bad_message(description: &str) -> CalError {
+    CalError::bad_parameter(format!("Invalid PAKE message: {description}"), true, None)
+}
+
+fn invalid_state(description: &str) -> CalError {
+    CalError::failed_operation(
+        format!("Invalid PAKE exchange state: {description}"),
+        true,
+        None,
+    )
+}
+
+impl PakeExchange {
+    /// Starts an exchange with the shared `password`, e.g. a pairing code.
+    pub fn start(
+        provider: &Provider,
+        role: PakeRole,
+        password: &[u8],
+        config: PakeConfig,
+    ) -> Result<Self, CalError> {
+        let zero_padding_len =
+            HASH_BLOCK_LEN.saturating_sub(1 + prepend_len(password).len() + prepend_len(DSI).len());
+        let generator_string = Zeroizing::new(lv_cat(&[
+            DSI,
+            password,
+            &vec![0; zero_padding_len],
+            &config.channel_id,
+            &config.session_id,
+        ]));
+        let generator_hash = Zeroizing::new(provider.hash(&generator_string, HASH)?);
+        let generator = RistrettoPoint::from_uniform_bytes(
+            generator_hash
+                .as_slice()
+                .try_into()
+                .map_err(|_| invalid_state("unexpected hash length"))?,
+        );
+
+        let random = Zeroizing::new(provider.get_random(64));
+        let scalar = Zeroizing::new(Scalar::from_bytes_mod_order_wide(
+            random
+                .as_slice()
+                .try_into()
+                .map_err(|_| invalid_state("failed to generate a random scalar"))?,
+        ));
+
+        let mut public_key = (generator * *scalar).compress().to_bytes().to_vec();
+        public_key.extend_from_slice(&config.associated_data);
+
+        Ok(Self {
+            role,
+            config,
+            public_key,
+            peer_associated_data: None,
+            state: State::Started { scalar },
+        })
+    }
+
+    /// Returns the public message to send to the other party.
+    pub fn get_public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    /// Returns the associated data of the other party, once its public message was processed.
+    ///
+    /// The data is only authenticated after a successful call to one of the `derive_*` methods.
+    pub fn peer_associated_data(&self) -> Option<&[u8]> {
+        self.peer_associated_data.as_deref()
+    }
+
+    /// Processes the public message of the other party and returns the own key confirmation,
+    /// which has to be sent to the other party.
+    pub fn compute_confirmation(
+        &mut self,
+        provider: &Provider,
+        peer_public_key: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let State::Started { scalar } = std::mem::replace(&mut self.state, State::Finished) else {
+            return Err(invalid_state("the public message was already processed"));
+        };
+
+        if peer_public_key.len() < POINT_LEN {
+            return Err(bad_message("the message is too short"));
+        }
+        let (peer_point, peer_associated_data) = peer_public_key.split_at(POINT_LEN);
+        let peer_point = CompressedRistretto::from_slice(peer_point)
+            .ok()
+            .and_then(|point| point.decompress())
+            .ok_or_else(|| bad_message("invalid point"))?;
+
+        let shared_point = peer_point * *scalar;
+        if shared_point.is_identity() {
+            return Err(bad_message("invalid point"));
+        }
+        let shared_secret = Zeroizing::new(shared_point.compress().to_bytes());
+
+        let (own_point, own_associated_data) = self.public_key.split_at(POINT_LEN);
+        let transcript = match self.role {
+            PakeRole::Initiator => [
+                lv_cat(&[own_point, own_associated_data]),
+                lv_cat(&[peer_point.compress().as_bytes(), peer_associated_data]),
+            ],
+            PakeRole::Responder => [
+                lv_cat(&[peer_point.compress().as_bytes(), peer_associated_data]),
+                lv_cat(&[own_point, own_associated_data]),
+            ],
+        }
+        .concat();
+
+        let mut input = Zeroizing::new(lv_cat(&[
+            &[DSI, b"_ISK"].concat(),
+            &self.config.session_id,
+            shared_secret.as_slice(),
+        ]));
+        input.extend_from_slice(&transcript);
+        let intermediate_session_key = Zeroizing::new(provider.hash(&input, HASH)?);
+
+        let confirmation =
+            Self::confirmation(provider, &intermediate_session_key, self.role, &transcript)?;
+
+        self.peer_associated_data = Some(peer_associated_data.to_vec());
+        self.state = State::Confirming {
+            intermediate_session_key,
+            transcript,
+        };
+        Ok(confirmation)
+    }
+
+    fn confirmation(
+        provider: &Provider,
+        intermediate_session_key: &[u8],
+        role: PakeRole,
+        transcript: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let key = hkdf::expand(
+            provider,
+            HASH,
+            intermediate_session_key,
+            &[b"CPace confirmation ", role.label()].concat(),
+            64,
+        )?;
+        Ok(hkdf::hmac(provider, HASH, &key, transcript)?.to_vec())
+    }
+
+    /// Verifies the key confirmation of the other party and returns the intermediate session key.
+    fn finish(
+        &mut self,
+        provider: &Provider,
+        peer_confirmation: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, CalError> {
+        let State::Confirming {
+            intermediate_session_key,
+            transcript,
+        } = std::mem::replace(&mut self.state, State::Finished)
+        else {
+            return Err(invalid_state(
+                "the key confirmation has to be computed before deriving keys",
+            ));
+        };
+
+        let expected = Self::confirmation(
+            provider,
+            &intermediate_session_key,
+            self.role.peer(),
+            &transcript,
+        )?;
+        if !hkdf::constant_time_eq(&expected, peer_confirmation) {
+            return Err(CalError::failed_operation(
+                "PAKE key confirmation failed, the passwords might differ".to_owned(),
+                true,
+                None,
+            ));
+        }
+
+        Ok(intermediate_session_key)
+    }
+
+    fn derive_key(
+        &self,
+        provider: &mut Provider,
+        intermediate_session_key: &[u8],
+        label: &[u8],
+    ) -> Result<KeyHandle, CalError> {
+        let key = hkdf::expand(
+            provider,
+            HASH,
+            intermediate_session_key,
+            &[b"CPace key ", label].concat(),
+            self.config.key_spec.cipher.len(),
+        )?;
+        provider.import_key(self.config.key_spec, &key)
+    }
+
+    /// Verifies the key confirmation of the other party and derives a single shared key.
+    pub fn derive_key_handle(
+        &mut self,
+        provider: &mut Provider,
+        peer_confirmation: &[u8],
+    ) -> Result<KeyHandle, CalError> {
+        let intermediate_session_key = self.finish(provider, peer_confirmation)?;
+        self.derive_key(provider, &intermediate_session_key, b"shared")
+    }
+
+    /// Verifies the key confirmation of the other party and derives session keys (rx, tx).
+    pub fn derive_key_handles(
+        &mut self,
+        provider: &mut Provider,
+        peer_confirmation: &[u8],
+    ) -> Result<(KeyHandle, KeyHandle), CalError> {
+        let intermediate_session_key = self.finish(provider, peer_confirmation)?;
+        let rx = self.derive_key(
+            provider,
+            &intermediate_session_key,
+            &[self.role.peer().label(), b" to ", self.role.label()].concat(),
+        )?;
+        let tx = self.derive_key(
+            provider,
+            &intermediate_session_key,
+            &[self.role.label(), b" to ", self.role.peer().label()].concat(),
+        )?;
+        Ok((rx, tx))
+    }
+}
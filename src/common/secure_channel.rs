@@ -0,0 +1,390 @@
+//! Secure channels with counter based nonces and replay protection on top of session keys.
+//!
+//! A [SecureChannel] is built from the rx and tx [KeyHandle]s derived by a
+//! [DHExchange](crate::common::DHExchange). Each direction has a monotonic message counter, from
+//! which the nonces are derived, so nonces are never reused. Received counters are tracked in a
+//! sliding window, rejecting replayed messages and messages older than the window.
+//!
+//! With [SecureChannelConfig::rekey_after] both directions switch to a new key after the given
+//! number of messages, the new key is derived from the previous one with HKDF.
+//! The state can be persisted with [SecureChannel::save], which stores it through
+//! [Provider::store_secret_data], or serialized with serde directly.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{
+//!     common::secure_channel::{SecureChannel, SecureChannelConfig},
+//!     prelude::*,
+//! };
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::P256,
+//!     cipher: Some(Cipher::AesGcm256),
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     non_exportable: false,
+//!     ..Default::default()
+//! };
+//! let mut client = provider.start_ephemeral_dh_exchange(spec).unwrap();
+//! let mut server = provider.start_ephemeral_dh_exchange(spec).unwrap();
+//! let (server_rx, server_tx) = server.derive_server_key_handles(&client.get_public_key().unwrap()).unwrap();
+//! let (client_rx, client_tx) = client.derive_client_key_handles(&server.get_public_key().unwrap()).unwrap();
+//!
+//! let config = SecureChannelConfig::new();
+//! let mut client = SecureChannel::new(&client_rx, &client_tx, config.clone()).unwrap();
+//! let mut server = SecureChannel::new(&server_rx, &server_tx, config).unwrap();
+//!
+//! let message = client.encrypt(&mut provider, b"Hello", b"").unwrap();
+//! assert_eq!(server.decrypt(&mut provider, &message, b"").unwrap(), b"Hello");
+//! assert!(server.decrypt(&mut provider, &message, b"").is_err());
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::common::{config::KeySpec, crypto::hkdf, error::CalError, KeyHandle, Provider};
+
+const COUNTER_LEN: usize = 8;
+/// Maximum size of the replay window, which is tracked in a `u64`.
+const MAX_REPLAY_WINDOW: u32 = 64;
+/// Maximum number of rekeys applied for a single received message.
+const MAX_REKEY_SKIP: u64 = 1024;
+
+/// Configuration of a [SecureChannel], which is persisted with the channel.
+///
+/// The configuration is checked again when deserializing it, so a tampered state cannot lead to
+/// an unbounded replay window or rekeying after zero messages.
+#[derive(Clone, Debug, PartialEq, Serialize, Zeroize)]
+pub struct SecureChannelConfig {
+    /// Number of most recent messages accepted out of order, at most 64.
+    pub replay_window: u32,
+    /// Number of messages per direction after which the key is replaced.
+    ///
+    /// Has to be at least [SecureChannelConfig::replay_window].
+    pub rekey_after: Option<u64>,
+}
+
+impl SecureChannelConfig {
+    /// Creates a configuration with a replay window of 64 messages and without rekeying.
+    pub fn new() -> Self {
+        Self {
+            replay_window: MAX_REPLAY_WINDOW,
+            rekey_after: None,
+        }
+    }
+
+    fn check(&self) -> Result<(), CalError> {
+        if self.replay_window == 0 || self.replay_window > MAX_REPLAY_WINDOW {
+            return Err(CalError::bad_parameter(
+                format!("The replay window has to be between 1 and {MAX_REPLAY_WINDOW} messages"),
+                true,
+                None,
+            ));
+        }
+        // As the replay window is not empty, this also rules out rekeying after zero messages.
+        if self
+            .rekey_after
+            .is_some_and(|rekey_after| rekey_after < self.replay_window as u64)
+        {
+            return Err(CalError::bad_parameter(
+                "Rekeying has to happen after at least as many messages as the replay window"
+                    .to_owned(),
+                true,
+                None,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SecureChannelConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'de> Deserialize<'de> for SecureChannelConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawSecureChannelConfig {
+            replay_window: u32,
+            rekey_after: Option<u64>,
+        }
+
+        let raw = RawSecureChannelConfig::deserialize(deserializer)?;
+        let config = SecureChannelConfig {
+            replay_window: raw.replay_window,
+            rekey_after: raw.rekey_after,
+        };
+        config.check().map_err(serde::de::Error::custom)?;
+        Ok(config)
+    }
+}
+
+/// State of a secure channel.
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct SecureChannel {
+    config: SecureChannelConfig,
+    key_spec: KeySpec,
+    sending_key: Vec<u8>,
+    sending_epoch: u64,
+    sending_counter: u64,
+    receiving_key: Vec<u8>,
+    previous_receiving_key: Option<Vec<u8>>,
+    receiving_epoch: u64,
+    /// Highest received counter.
+    highest_received: Option<u64>,
+    /// Bit `i` is set if `highest_received - i` was received.
+    received_window: u64,
+}
+
+fn invalid_message() -> CalError {
+    CalError::failed_operation(
+        "Failed to decrypt secure channel message".to_owned(),
+        true,
+        None,
+    )
+}
+
+impl SecureChannel {
+    /// Creates a channel from the rx and tx keys of a key exchange.
+    ///
+    /// The keys have to be exportable, as their material is kept in the channel state.
+    pub fn new(
+        rx: &KeyHandle,
+        tx: &KeyHandle,
+        config: SecureChannelConfig,
+    ) -> Result<Self, CalError> {
+        config.check()?;
+
+        let key_spec = tx.spec();
+        if rx.spec() != key_spec {
+            return Err(CalError::bad_parameter(
+                "The rx and tx keys need the same spec".to_owned(),
+                true,
+                None,
+            ));
+        }
+        if key_spec.cipher.iv_len() < COUNTER_LEN {
+            return Err(CalError::unsupported_algorithm(format!(
+                "The nonce of {:?} is too short for a secure channel",
+                key_spec.cipher
+            )));
+        }
+
+        Ok(Self {
+            config,
+            key_spec,
+            sending_key: tx.extract_key()?,
+            sending_epoch: 0,
+            sending_counter: 0,
+            receiving_key: rx.extract_key()?,
+            previous_receiving_key: None,
+            receiving_epoch: 0,
+            highest_received: None,
+            received_window: 0,
+        })
+    }
+
+    pub fn config(&self) -> &SecureChannelConfig {
+        &self.config
+    }
+
+    /// Number of messages sent so far.
+    pub fn sent_messages(&self) -> u64 {
+        self.sending_counter
+    }
+
+    fn epoch(&self, counter: u64) -> u64 {
+        self.config
+            .rekey_after
+            .map_or(0, |rekey_after| counter / rekey_after)
+    }
+
+    fn rekey(&self, provider: &Provider, key: &[u8]) -> Result<Vec<u8>, CalError> {
+        Ok(hkdf::expand(
+            provider,
+            self.key_spec.signing_hash,
+            key,
+            b"SecureChannelRekey",
+            self.key_spec.cipher.len(),
+        )?
+        .to_vec())
+    }
+
+    /// The counter in big-endian, left padded with zeros to the nonce length.
+    fn nonce(&self, counter: u64) -> Vec<u8> {
+        let mut nonce = vec![0; self.key_spec.cipher.iv_len() - COUNTER_LEN];
+        nonce.extend_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+
+    fn import_key(&self, provider: &mut Provider, key: &[u8]) -> Result<KeyHandle, CalError> {
+        provider.import_key(
+            KeySpec {
+                ephemeral: true,
+                ..self.key_spec
+            },
+            key,
+        )
+    }
+
+    /// Encrypts `plaintext`, authenticating `associated_data` which is not part of the message.
+    pub fn encrypt(
+        &mut self,
+        provider: &mut Provider,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        let counter = self.sending_counter;
+        let next_counter = counter.checked_add(1).ok_or_else(|| {
+            CalError::failed_operation(
+                "Secure channel message counter exhausted".to_owned(),
+                true,
+                None,
+            )
+        })?;
+
+        let epoch = self.epoch(counter);
+        if epoch != self.sending_epoch {
+            self.sending_key = self.rekey(provider, &self.sending_key)?;
+            self.sending_epoch = epoch;
+        }
+
+        let header = counter.to_be_bytes();
+        let key = self.import_key(provider, &self.sending_key)?;
+        let ciphertext = key.encrypt_with_aad(
+            plaintext,
+            &self.nonce(counter),
+            &[header.as_slice(), associated_data].concat(),
+        )?;
+        self.sending_counter = next_counter;
+
+        Ok([header.as_slice(), &ciphertext].concat())
+    }
+
+    /// Decrypts a message, which may arrive out of order within the replay window.
+    ///
+    /// Replayed messages and messages older than the window are rejected. The channel is only
+    /// updated if the message could be decrypted.
+    pub fn decrypt(
+        &mut self,
+        provider: &mut Provider,
+        message: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        if message.len() < COUNTER_LEN {
+            return Err(invalid_message());
+        }
+        let (header, ciphertext) = message.split_at(COUNTER_LEN);
+        let counter = u64::from_be_bytes(header.try_into().map_err(|_| invalid_message())?);
+
+        if let Some(highest_received) = self.highest_received {
+            if counter <= highest_received {
+                let age = highest_received - counter;
+                if age >= self.config.replay_window as u64 {
+                    return Err(CalError::failed_operation(
+                        format!("Secure channel message {counter} is outside of the replay window"),
+                        true,
+                        None,
+                    ));
+                }
+                if self.received_window & (1 << age) != 0 {
+                    return Err(CalError::failed_operation(
+                        format!("Secure channel message {counter} was replayed"),
+                        true,
+                        None,
+                    ));
+                }
+            }
+        }
+
+        let epoch = self.epoch(counter);
+        let mut receiving_key = Zeroizing::new(self.receiving_key.clone());
+        let mut previous_receiving_key = self.previous_receiving_key.clone().map(Zeroizing::new);
+        if epoch > self.receiving_epoch {
+            if epoch - self.receiving_epoch > MAX_REKEY_SKIP {
+                return Err(invalid_message());
+            }
+            for _ in self.receiving_epoch..epoch {
+                let next_key = Zeroizing::new(self.rekey(provider, &receiving_key)?);
+                previous_receiving_key = Some(std::mem::replace(&mut receiving_key, next_key));
+            }
+        }
+        let key = if epoch >= self.receiving_epoch {
+            receiving_key.to_vec()
+        } else if epoch + 1 == self.receiving_epoch {
+            previous_receiving_key
+                .as_ref()
+                .ok_or_else(invalid_message)?
+                .to_vec()
+        } else {
+            return Err(invalid_message());
+        };
+
+        let key = self.import_key(provider, &key)?;
+        let plaintext = key
+            .decrypt_with_aad(
+                ciphertext,
+                &self.nonce(counter),
+                &[header, associated_data].concat(),
+            )
+            .map_err(|_| invalid_message())?;
+
+        if epoch > self.receiving_epoch {
+            self.receiving_key = receiving_key.to_vec();
+            self.previous_receiving_key = previous_receiving_key.map(|key| key.to_vec());
+            self.receiving_epoch = epoch;
+        }
+        match self.highest_received {
+            Some(highest_received) if counter <= highest_received => {
+                self.received_window |= 1 << (highest_received - counter);
+            }
+            Some(highest_received) => {
+                let shift = counter - highest_received;
+                self.received_window = if shift >= 64 {
+                    1
+                } else {
+                    (self.received_window << shift) | 1
+                };
+                self.highest_received = Some(counter);
+            }
+            None => {
+                self.received_window = 1;
+                self.highest_received = Some(counter);
+            }
+        }
+
+        Ok(plaintext)
+    }
+
+    /// Persists the channel under `id` with [Provider::store_secret_data].
+    ///
+    /// The channel has to be saved again after each [SecureChannel::encrypt] and [SecureChannel::decrypt].
+    pub fn save(&self, provider: &mut Provider, id: &str) -> Result<(), CalError> {
+        let state = Zeroizing::new(rmp_serde::to_vec_named(self).map_err(|e| {
+            CalError::failed_operation(
+                "Failed to serialize secure channel".to_owned(),
+                false,
+                Some(anyhow::anyhow!(e)),
+            )
+        })?);
+        provider.store_secret_data(id.to_owned(), &state)
+    }
+
+    /// Loads a channel persisted with [SecureChannel::save].
+    pub fn load(provider: &Provider, id: &str) -> Result<Self, CalError> {
+        let state = Zeroizing::new(provider.load_secret_data(id.to_owned())?);
+        rmp_serde::from_slice(&state).map_err(|e| {
+            CalError::failed_operation(
+                "Failed to deserialize secure channel".to_owned(),
+                true,
+                Some(anyhow::anyhow!(e)),
+            )
+        })
+    }
+}
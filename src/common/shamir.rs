@@ -0,0 +1,340 @@
+//! Shamir secret sharing over GF(256) for backup and recovery of exportable keys.
+//!
+//! A key is split into `share_count` [KeyShare]s, any `threshold` of which recover the key.
+//! Each share carries a checksum detecting corrupted shares, and the shared secret is extended
+//! with a digest, so that combining shares of different splits or tampered shares fails instead
+//! of importing a wrong key.
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{common::shamir, prelude::*};
+//!
+//! let impl_config = ProviderImplConfig { additional_config: vec![] };
+//! let mut provider = create_provider_from_name("SoftwareProvider", impl_config).unwrap();
+//!
+//! let spec = KeyPairSpec {
+//!     asym_spec: AsymmetricKeySpec::Curve25519,
+//!     cipher: None,
+//!     signing_hash: CryptoHash::Sha2_512,
+//!     ephemeral: true,
+//!     non_exportable: false,
+//!     ..Default::default()
+//! };
+//! let key_pair = provider.create_key_pair(spec).unwrap();
+//!
+//! let shares = shamir::split_key_pair(&provider, &key_pair, 3, 5).unwrap();
+//! let recovered = shamir::combine_key_pair(&mut provider, &shares[1..4]).unwrap();
+//!
+//! assert_eq!(recovered.get_public_key().unwrap(), key_pair.get_public_key().unwrap());
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::common::{config::Spec, error::CalError, KeyHandle, KeyPairHandle, Provider};
+
+const DOMAIN: &[u8] = b"crypto-layer shamir v1";
+const SET_ID_LEN: usize = 16;
+const DIGEST_LEN: usize = 16;
+
+/// A share of a key created with [split_key] or [split_key_pair].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Zeroize)]
+pub struct KeyShare {
+    /// Random id shared by all shares of one split.
+    pub set_id: Vec<u8>,
+    /// Number of shares needed for recovery.
+    pub threshold: u8,
+    /// x-coordinate of the share, between 1 and 255.
+    pub index: u8,
+    /// Spec of the split key.
+    #[zeroize(skip)]
+    pub spec: Spec,
+    /// Public key of a split key pair.
+    pub public_key: Option<Vec<u8>>,
+    pub value: Vec<u8>,
+    /// SHA-256 over all other fields.
+    pub checksum: Vec<u8>,
+}
+
+fn serialized_spec(spec: &Spec) -> Result<Vec<u8>, CalError> {
+    serde_json::to_vec(spec).map_err(|e| {
+        CalError::failed_operation(
+            "Failed to serialize spec".to_owned(),
+            false,
+            Some(anyhow::anyhow!(e)),
+        )
+    })
+}
+
+impl KeyShare {
+    pub(crate) fn compute_checksum(&self) -> Result<Vec<u8>, CalError> {
+        let public_key = self.public_key.as_deref().unwrap_or_default();
+
+        Ok(Sha256::new()
+            .chain_update(DOMAIN)
+            .chain_update(&self.set_id)
+            .chain_update([self.threshold, self.index])
+            .chain_update(serialized_spec(&self.spec)?)
+            .chain_update((public_key.len() as u64).to_be_bytes())
+            .chain_update(public_key)
+            .chain_update(&self.value)
+            .finalize()
+            .to_vec())
+    }
+
+    /// Returns `true` if the checksum of the share is valid.
+    pub fn verify(&self) -> bool {
+        self.compute_checksum()
+            .is_ok_and(|checksum| checksum == self.checksum)
+    }
+}
+
+/// Multiplication in GF(2^8) with the AES polynomial, without secret dependent branches.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverse in GF(2^8) as `a^254`.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Digest appended to the secret, binding it to the metadata of the split.
+fn secret_digest(
+    set_id: &[u8],
+    spec: &Spec,
+    public_key: Option<&[u8]>,
+    secret: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let public_key = public_key.unwrap_or_default();
+    let digest = Sha256::new()
+        .chain_update(DOMAIN)
+        .chain_update(set_id)
+        .chain_update(serialized_spec(spec)?)
+        .chain_update((public_key.len() as u64).to_be_bytes())
+        .chain_update(public_key)
+        .chain_update(secret)
+        .finalize();
+
+    Ok(digest[..DIGEST_LEN].to_vec())
+}
+
+fn split(
+    provider: &Provider,
+    secret: &[u8],
+    spec: Spec,
+    public_key: Option<Vec<u8>>,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<KeyShare>, CalError> {
+    if threshold < 2 || threshold > share_count {
+        return Err(CalError::bad_parameter(
+            format!("Invalid threshold {threshold} for {share_count} shares, it has to be between 2 and the number of shares"),
+            true,
+            None,
+        ));
+    }
+
+    let set_id = provider.get_random(SET_ID_LEN);
+    let digest = secret_digest(&set_id, &spec, public_key.as_deref(), secret)?;
+    let secret = Zeroizing::new([secret, &digest].concat());
+
+    // Coefficients of the polynomials for all bytes, the constant terms are the secret.
+    let degree = threshold as usize - 1;
+    let coefficients = Zeroizing::new(provider.get_random(secret.len() * degree));
+    if coefficients.len() != secret.len() * degree {
+        return Err(CalError::failed_operation(
+            "Failed to generate random coefficients".to_owned(),
+            false,
+            None,
+        ));
+    }
+
+    (1..=share_count)
+        .map(|index| {
+            let value = (0..secret.len())
+                .map(|byte| {
+                    // Horner's method, from the highest coefficient down to the constant term.
+                    coefficients[byte * degree..(byte + 1) * degree]
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, &coefficient| gf_mul(acc, index) ^ coefficient)
+                })
+                .zip(secret.iter())
+                .map(|(higher_terms, &constant)| gf_mul(higher_terms, index) ^ constant)
+                .collect();
+
+            let mut share = KeyShare {
+                set_id: set_id.clone(),
+                threshold,
+                index,
+                spec,
+                public_key: public_key.clone(),
+                value,
+                checksum: Vec::new(),
+            };
+            share.checksum = share.compute_checksum()?;
+            Ok(share)
+        })
+        .collect()
+}
+
+fn invalid_shares(description: &str) -> CalError {
+    CalError::bad_parameter(format!("Invalid key shares: {description}"), true, None)
+}
+
+/// Validates the shares and recovers the secret.
+fn combine(shares: &[KeyShare]) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let Some(first) = shares.first() else {
+        return Err(invalid_shares("no shares given"));
+    };
+
+    for (i, share) in shares.iter().enumerate() {
+        if !share.verify() {
+            return Err(invalid_shares(&format!(
+                "share {} is corrupted",
+                share.index
+            )));
+        }
+        if share.set_id != first.set_id
+            || share.threshold != first.threshold
+            || share.spec != first.spec
+            || share.public_key != first.public_key
+            || share.value.len() != first.value.len()
+        {
+            return Err(invalid_shares("the shares belong to different keys"));
+        }
+        if share.index == 0 || shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(invalid_shares(
+                "the share indices have to be unique and non-zero",
+            ));
+        }
+    }
+    if shares.len() < first.threshold as usize {
+        return Err(invalid_shares(&format!(
+            "{} shares are needed, but only {} were given",
+            first.threshold,
+            shares.len()
+        )));
+    }
+    if first.value.len() <= DIGEST_LEN {
+        return Err(invalid_shares("the share values are too short"));
+    }
+
+    // Lagrange interpolation at x = 0, subtraction is addition in GF(2^8).
+    let basis: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+                })
+        })
+        .collect();
+
+    let mut secret = Zeroizing::new(vec![0u8; first.value.len()]);
+    for (share, &basis) in shares.iter().zip(basis.iter()) {
+        for (secret_byte, &value) in secret.iter_mut().zip(share.value.iter()) {
+            *secret_byte ^= gf_mul(value, basis);
+        }
+    }
+
+    let secret_len = secret.len() - DIGEST_LEN;
+    let digest = secret.split_off(secret_len);
+    let expected = secret_digest(
+        &first.set_id,
+        &first.spec,
+        first.public_key.as_deref(),
+        &secret,
+    )?;
+    if digest != expected {
+        return Err(CalError::failed_operation(
+            "The recovered key does not match its digest".to_owned(),
+            true,
+            None,
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// Splits an exportable key into `share_count` shares, `threshold` of which recover the key.
+pub fn split_key(
+    provider: &Provider,
+    key: &KeyHandle,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<KeyShare>, CalError> {
+    let secret = Zeroizing::new(key.extract_key()?);
+    split(
+        provider,
+        &secret,
+        Spec::KeySpec(key.spec()),
+        None,
+        threshold,
+        share_count,
+    )
+}
+
+/// Splits the private key of an exportable key pair into `share_count` shares,
+/// `threshold` of which recover the key pair.
+pub fn split_key_pair(
+    provider: &Provider,
+    key_pair: &KeyPairHandle,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<KeyShare>, CalError> {
+    let secret = Zeroizing::new(key_pair.extract_key()?);
+    split(
+        provider,
+        &secret,
+        Spec::KeyPairSpec(key_pair.spec()),
+        Some(key_pair.get_public_key()?),
+        threshold,
+        share_count,
+    )
+}
+
+/// Recovers a key from shares created with [split_key] and imports it with its original spec.
+pub fn combine_key(provider: &mut Provider, shares: &[KeyShare]) -> Result<KeyHandle, CalError> {
+    let secret = combine(shares)?;
+    match shares[0].spec {
+        Spec::KeySpec(spec) => provider.import_key(spec, &secret),
+        Spec::KeyPairSpec(_) => Err(invalid_shares("the shares belong to a key pair")),
+    }
+}
+
+/// Recovers a key pair from shares created with [split_key_pair] and imports it with its original spec.
+pub fn combine_key_pair(
+    provider: &mut Provider,
+    shares: &[KeyShare],
+) -> Result<KeyPairHandle, CalError> {
+    let secret = combine(shares)?;
+    match (shares[0].spec, shares[0].public_key.as_ref()) {
+        (Spec::KeyPairSpec(spec), Some(public_key)) => {
+            provider.import_key_pair(spec, public_key, &secret)
+        }
+        _ => Err(invalid_shares("the shares belong to a symmetric key")),
+    }
+}
@@ -19,7 +19,11 @@ compile_error!(
 );
 
 use crate::common::{
-    config::{KeyPairSpec, KeySpec},
+    config::{EncryptionUsage, KeyPairSpec, KeySpec},
+    crypto::{
+        algorithms::{encryption::Cipher, key_derivation::KDF},
+        pkcs::standards::KeyFormat,
+    },
     error::CalError,
     DHExchange, KeyHandle,
 };
@@ -64,6 +68,33 @@ pub(crate) trait KeyHandleImpl: Send + Sync {
         Ok(cipher_text)
     }
 
+    /// Encrypt data with a nonce derived from the persisted usage counter of the key.
+    ///
+    /// The resulting output is a pair of cipher text and used iv: `(cipher_text, iv)`
+    #[allow(unused_variables)]
+    fn encrypt_with_counter_nonce(&self, data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Returns the number of encryptions done with the key and the limits of its cipher.
+    fn encryption_usage(&self) -> Result<EncryptionUsage, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Encrypts data with the newest version of the key.
+    ///
+    /// The output is `version || iv || cipher_text`, with the version as big-endian `u32`.
+    #[allow(unused_variables)]
+    fn encrypt_versioned(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Decrypts the output of [KeyHandleImpl::encrypt_versioned] with the version it names.
+    #[allow(unused_variables)]
+    fn decrypt_versioned(&self, encrypted_data: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Decrypts the given encrypted data using the cryptographic key.
     ///
     /// # Arguments
@@ -74,12 +105,58 @@ pub(crate) trait KeyHandleImpl: Send + Sync {
     /// A `Result` containing the decrypted data as a `Vec<u8>` on success, or a `CalError` on failure.
     fn decrypt_data(&self, encrypted_data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError>;
 
+    /// Encrypts data with an AEAD cipher, authenticating `aad` alongside it.
+    ///
+    /// The iv is required and the output is the cipher text followed by the tag.
+    #[allow(unused_variables)]
+    fn encrypt_with_aad(&self, data: &[u8], iv: &[u8], aad: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Decrypts data encrypted with [`encrypt_with_aad`](KeyHandleImpl::encrypt_with_aad).
+    #[allow(unused_variables)]
+    fn decrypt_with_aad(
+        &self,
+        encrypted_data: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Calculates HMAC of the given data.
     fn hmac(&self, data: &[u8]) -> Result<Vec<u8>, CalError>;
 
     /// Verifies data with the given signature.
     fn verify_hmac(&self, data: &[u8], hmac: &[u8]) -> Result<bool, CalError>;
 
+    /// Encrypts data with AES-CBC and PKCS#7 padding.
+    ///
+    /// CBC is not authenticated, it is only used inside composite modes that authenticate the
+    /// cipher text.
+    #[allow(unused_variables)]
+    fn encrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Decrypts data encrypted with [`encrypt_cbc`](KeyHandleImpl::encrypt_cbc).
+    #[allow(unused_variables)]
+    fn decrypt_cbc(&self, encrypted_data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Wraps `key` with AES key wrap (RFC 3394), using this key as key encryption key.
+    #[allow(unused_variables)]
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Unwraps a key wrapped with [`wrap_key`](KeyHandleImpl::wrap_key).
+    #[allow(unused_variables)]
+    fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Derives an ephemeral key from this key as base with the same spec as the base key.
     ///
     /// A derived key is exportable if the base key (self) is exportable.
@@ -93,6 +170,20 @@ pub(crate) trait KeyHandleImpl: Send + Sync {
     /// with [CalErrorKind::NotImplemented](super::CalErrorKind::NotImplemented).
     fn extract_key(&self) -> Result<Vec<u8>, CalError>;
 
+    /// Returns the key as DER encoded PKCS#8 `EncryptedPrivateKeyInfo` protected by `password`.
+    ///
+    /// The key is encoded as `PrivateKeyInfo` with the OID of its cipher before encryption,
+    /// ciphers without an OID like `XChaCha20Poly1305` are not supported.
+    #[allow(unused_variables)]
+    fn export_encrypted_key(
+        &self,
+        password: &str,
+        kdf: KDF,
+        cipher: Cipher,
+    ) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Returns the id of the key, which can be used with `load_key`.
     fn id(&self) -> Result<String, CalError>;
 
@@ -161,12 +252,57 @@ pub(crate) trait KeyPairHandleImpl: Send + Sync {
     /// with [CalErrorKind::NotImplemented](super::CalErrorKind::NotImplemented).
     fn extract_key(&self) -> Result<Vec<u8>, CalError>;
 
+    /// Returns the private key encoded in `format`.
+    ///
+    /// Fails like [extract_key](KeyPairHandleImpl::extract_key) for non exportable keys.
+    #[allow(unused_variables)]
+    fn export_private_key(&self, format: KeyFormat) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Returns the public key encoded in `format`.
+    #[allow(unused_variables)]
+    fn export_public_key(&self, format: KeyFormat) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Returns the private key as DER encoded PKCS#8 `EncryptedPrivateKeyInfo` protected by
+    /// `password`.
+    #[allow(unused_variables)]
+    fn export_encrypted_private_key(
+        &self,
+        password: &str,
+        kdf: KDF,
+        cipher: Cipher,
+    ) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// [DEPRECATED]: Starts a [DHExchange].
     ///
     /// Some Providers might return [CalError]
     /// with [CalErrorKind::NotImplemented](super::CalErrorKind::NotImplemented).
     fn start_dh_exchange(&self) -> Result<DHExchange, CalError>;
 
+    /// Encapsulates a fresh shared secret to the given public key.
+    ///
+    /// The algorithm of this key pair is used. The shared secret is returned as [KeyHandle]
+    /// with the cipher of this key pair's spec.
+    ///
+    /// # Returns
+    /// A `Result` containing the ciphertext for the owner of `public_key` and the shared secret,
+    /// or a `CalError` on failure.
+    #[allow(unused_variables)]
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, KeyHandle), CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Decapsulates the shared secret from a ciphertext created with [encapsulate](KeyPairHandleImpl::encapsulate).
+    #[allow(unused_variables)]
+    fn decapsulate(&self, ciphertext: &[u8]) -> Result<KeyHandle, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Returns the id of the key pair, which can be used with `load_key_pair`.
     fn id(&self) -> Result<String, CalError>;
 
@@ -205,6 +341,28 @@ pub(crate) trait DHKeyExchangeImpl: Send + Sync {
     /// Get the public key of the internal key pair to use for the other party
     fn get_public_key(&self) -> Result<Vec<u8>, CalError>;
 
+    /// Computes the raw shared secret with the public key of the other party.
+    #[allow(unused_variables)]
+    fn compute_shared_secret(&self, peer_public_key: &[u8]) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Computes the key confirmation MAC of this party for the derived session keys.
+    #[allow(unused_variables)]
+    fn key_confirmation(&self, transcript: Option<&[u8]>) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Verifies the key confirmation MAC of the other party.
+    #[allow(unused_variables)]
+    fn verify_key_confirmation(
+        &self,
+        peer_confirmation: &[u8],
+        transcript: Option<&[u8]>,
+    ) -> Result<bool, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Derive client session keys (rx, tx) - client is the templator in your code
     fn derive_client_session_keys(
         &mut self,
@@ -9,7 +9,10 @@ use crate::provider::software::{SoftwareProvider, SoftwareProviderFactory};
 use crate::{
     common::{
         config::{KeyPairSpec, KeySpec, ProviderConfig, ProviderImplConfig, Spec},
-        crypto::algorithms::key_derivation::KDF,
+        crypto::{
+            algorithms::key_derivation::{PasswordVerification, KDF},
+            pkcs::standards::KeyFormat,
+        },
         error::CalError,
         DHExchange, KeyHandle, KeyPairHandle,
     },
@@ -115,6 +118,53 @@ pub(crate) trait ProviderImpl: Send + Sync {
         public_key: &[u8],
     ) -> Result<KeyPairHandle, CalError>;
 
+    /// Imports a key pair from a private key encoded in `format`.
+    ///
+    /// The public key is taken from the encoding if present, otherwise it is derived from the
+    /// private key.
+    #[allow(dead_code, unused_variables)]
+    fn import_key_pair_with_format(
+        &mut self,
+        spec: KeyPairSpec,
+        private_key: &[u8],
+        format: KeyFormat,
+    ) -> Result<KeyPairHandle, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Imports a public key encoded in `format`.
+    #[allow(dead_code, unused_variables)]
+    fn import_public_key_with_format(
+        &mut self,
+        spec: KeyPairSpec,
+        public_key: &[u8],
+        format: KeyFormat,
+    ) -> Result<KeyPairHandle, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Imports a symmetric key from a PKCS#8 `EncryptedPrivateKeyInfo` protected by `password`.
+    #[allow(dead_code, unused_variables)]
+    fn import_encrypted_key(
+        &mut self,
+        spec: KeySpec,
+        data: &[u8],
+        password: &str,
+    ) -> Result<KeyHandle, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Imports a key pair from a PKCS#8 `EncryptedPrivateKeyInfo` protected by `password`.
+    #[allow(dead_code, unused_variables)]
+    fn import_encrypted_key_pair(
+        &mut self,
+        spec: KeyPairSpec,
+        data: &[u8],
+        password: &str,
+    ) -> Result<KeyPairHandle, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Generates a key pair suited for a Diffie-Hellman Key Exchange
     ///
     /// # Arguments
@@ -158,6 +208,23 @@ pub(crate) trait ProviderImpl: Send + Sync {
         Err(CalError::not_implemented())
     }
 
+    /// Hashes a password with a random salt and returns the hash as PHC string.
+    #[allow(dead_code, unused_variables)]
+    fn hash_password(&self, password: &str, kdf: KDF) -> Result<String, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Verifies a password against a PHC string and checks if the hash was created with `kdf`.
+    #[allow(dead_code, unused_variables)]
+    fn verify_password(
+        &self,
+        password: &str,
+        password_hash: &str,
+        kdf: KDF,
+    ) -> Result<PasswordVerification, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Derives a high-entropy key from a low-entropy password and a unique salt
     #[allow(dead_code, unused_variables)]
     fn derive_key_from_base(
@@ -176,6 +243,44 @@ pub(crate) trait ProviderImpl: Send + Sync {
         Err(CalError::not_implemented())
     }
 
+    /// Persists secret data, which is kept separate from the keys of the provider.
+    #[allow(dead_code, unused_variables)]
+    fn store_secret_data(&mut self, id: String, data: &[u8]) -> Result<(), CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Loads secret data stored with [ProviderImpl::store_secret_data].
+    #[allow(dead_code, unused_variables)]
+    fn load_secret_data(&self, id: String) -> Result<Vec<u8>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Deletes secret data stored with [ProviderImpl::store_secret_data].
+    #[allow(dead_code, unused_variables)]
+    fn delete_secret_data(&mut self, id: String) -> Result<(), CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Replaces the material of a stored symmetric key with a new version under the same id.
+    ///
+    /// Older versions are kept for decryption until retired with [ProviderImpl::retire_key_version].
+    #[allow(dead_code, unused_variables)]
+    fn rotate_key(&mut self, key_id: String) -> Result<KeyHandle, CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Deletes an older version of a rotated key.
+    #[allow(dead_code, unused_variables)]
+    fn retire_key_version(&mut self, key_id: String, version: u32) -> Result<(), CalError> {
+        Err(CalError::not_implemented())
+    }
+
+    /// Returns the available versions of a stored key, oldest first.
+    #[allow(dead_code, unused_variables)]
+    fn key_versions(&self, key_id: String) -> Result<Vec<u32>, CalError> {
+        Err(CalError::not_implemented())
+    }
+
     /// Generates random bytes
     ///
     /// # Arguments
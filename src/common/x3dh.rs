@@ -0,0 +1,399 @@
+//! [X3DH](https://signal.org/docs/specifications/x3dh/) key agreement on top of a [Provider].
+//!
+//! Identity keys and prekeys are persisted `Curve25519` [KeyPairHandle]s. The identity key is an
+//! Ed25519 key, which signs the signed prekey and is converted to X25519 for the Diffie-Hellman
+//! computations, while the prekeys are used through [KeyPairHandle::start_dh_exchange].
+//!
+//! # Example
+//!
+//! ```
+//! use crypto_layer::{common::x3dh::X3DH, prelude::*};
+//!
+//! # let (alice_dir, bob_dir) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+//! let impl_config = |dir: &std::path::Path| ProviderImplConfig {
+//!     additional_config: vec![AdditionalConfig::FileStoreConfig {
+//!         db_dir: dir.to_string_lossy().to_string(),
+//!     }],
+//! };
+//! let mut alice = create_provider_from_name("SoftwareProvider", impl_config(alice_dir.path())).unwrap();
+//! let mut bob = create_provider_from_name("SoftwareProvider", impl_config(bob_dir.path())).unwrap();
+//!
+//! let spec = KeySpec {
+//!     cipher: Cipher::AesGcm256,
+//!     signing_hash: CryptoHash::Sha2_256,
+//!     ephemeral: true,
+//!     non_exportable: false,
+//!     ..Default::default()
+//! };
+//! let x3dh = X3DH::new(b"MyProtocol", spec);
+//!
+//! let bob_identity_key = x3dh.create_identity_key(&mut bob).unwrap();
+//! let signed_prekey = x3dh.create_signed_prekey(&mut bob).unwrap();
+//! let one_time_prekeys = x3dh.create_one_time_prekeys(&mut bob, 10).unwrap();
+//! let bundle = x3dh
+//!     .export_bundle(&bob_identity_key, &signed_prekey, one_time_prekeys.first())
+//!     .unwrap();
+//!
+//! let alice_identity_key = x3dh.create_identity_key(&mut alice).unwrap();
+//! let (alice_session, message) = x3dh.initiate(&mut alice, &alice_identity_key, &bundle).unwrap();
+//! let bob_session = x3dh.respond(&mut bob, &bob_identity_key, &message).unwrap();
+//!
+//! assert_eq!(alice_session.associated_data, bob_session.associated_data);
+//! ```
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::common::{
+    config::{KeyPairSpec, KeySpec, KeyUsage},
+    crypto::{
+        algorithms::{encryption::AsymmetricKeySpec, hashes::CryptoHash},
+        hkdf,
+    },
+    error::CalError,
+    KeyHandle, KeyPairHandle, Provider,
+};
+
+const KEY_LEN: usize = 32;
+
+/// Prefix of the secret data ids, under which the kind of each issued prekey is persisted.
+const PREKEY_ID_PREFIX: &str = "x3dh_prekey_";
+const SIGNED_PREKEY: &[u8] = b"signed";
+const ONE_TIME_PREKEY: &[u8] = b"one-time";
+
+/// Public part of a one-time prekey.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OneTimePreKey {
+    /// Id of the prekey in the responder's provider.
+    pub id: String,
+    /// X25519 public key.
+    pub public_key: Vec<u8>,
+}
+
+/// Prekey bundle published by the responder.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreKeyBundle {
+    /// Ed25519 public identity key.
+    pub identity_key: Vec<u8>,
+    /// Id of the signed prekey in the responder's provider.
+    pub signed_prekey_id: String,
+    /// X25519 public signed prekey.
+    pub signed_prekey: Vec<u8>,
+    /// Signature of `signed_prekey` by the identity key.
+    pub signed_prekey_signature: Vec<u8>,
+    pub one_time_prekey: Option<OneTimePreKey>,
+}
+
+/// Initial message sent from the initiator to the responder.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InitialMessage {
+    /// Ed25519 public identity key of the initiator.
+    pub identity_key: Vec<u8>,
+    /// X25519 public ephemeral key of the initiator.
+    pub ephemeral_key: Vec<u8>,
+    pub signed_prekey_id: String,
+    pub one_time_prekey_id: Option<String>,
+}
+
+/// Result of the key agreement.
+pub struct X3DHSession {
+    /// The shared key.
+    pub key: KeyHandle,
+    /// `identity_key_initiator || identity_key_responder`, to be used as associated data.
+    pub associated_data: Vec<u8>,
+}
+
+/// X3DH with an application specific `info` and a [KeySpec] for the shared key.
+///
+/// The `signing_hash` of the [KeySpec] is used for the KDF.
+#[derive(Clone, Debug)]
+pub struct X3DH {
+    info: Vec<u8>,
+    key_spec: KeySpec,
+}
+
+fn key_pair_spec(hash: CryptoHash) -> KeyPairSpec {
+    KeyPairSpec {
+        asym_spec: AsymmetricKeySpec::Curve25519,
+        cipher: None,
+        signing_hash: hash,
+        rsa_padding: Default::default(),
+        signature_encoding: Default::default(),
+        point_encoding: Default::default(),
+        ephemeral: false,
+        non_exportable: false,
+        not_before: None,
+        not_after: None,
+        process_not_after: None,
+        key_usage: KeyUsage::ALL,
+    }
+}
+
+fn check_identity_key(identity_key: &KeyPairHandle) -> Result<(), CalError> {
+    if identity_key.spec().asym_spec != AsymmetricKeySpec::Curve25519 {
+        return Err(CalError::bad_parameter(
+            format!(
+                "X3DH requires a Curve25519 identity key, got {:?}",
+                identity_key.spec().asym_spec
+            ),
+            true,
+            None,
+        ));
+    }
+    Ok(())
+}
+
+fn invalid_key(name: &str) -> CalError {
+    CalError::bad_parameter(format!("Invalid X3DH {name}"), true, None)
+}
+
+/// Converts an Ed25519 public key to its X25519 counterpart, rejecting small order points.
+fn identity_key_to_x25519(identity_key: &[u8]) -> Result<Vec<u8>, CalError> {
+    let point = CompressedEdwardsY::from_slice(identity_key)
+        .ok()
+        .and_then(|point| point.decompress())
+        .filter(|point| !point.is_small_order())
+        .ok_or_else(|| invalid_key("identity key"))?;
+
+    Ok(point.to_montgomery().to_bytes().to_vec())
+}
+
+/// Creates a persisted prekey and records it as issued prekey of `kind`.
+fn create_prekey(
+    provider: &mut Provider,
+    hash: CryptoHash,
+    kind: &[u8],
+) -> Result<KeyPairHandle, CalError> {
+    let key_pair = provider.create_key_pair(key_pair_spec(hash))?;
+    provider.store_secret_data(format!("{PREKEY_ID_PREFIX}{}", key_pair.id()?), kind)?;
+    Ok(key_pair)
+}
+
+/// Loads the prekey `id`, which has to be an issued prekey of `kind`.
+///
+/// The ids come from the unauthenticated initial message, so any other key pair of the
+/// provider, like the identity key, is rejected before it is loaded.
+fn load_prekey(provider: &mut Provider, id: &str, kind: &[u8]) -> Result<KeyPairHandle, CalError> {
+    let issued = provider
+        .load_secret_data(format!("{PREKEY_ID_PREFIX}{id}"))
+        .is_ok_and(|issued_kind| issued_kind == kind);
+    if !issued {
+        return Err(CalError::bad_parameter(
+            format!("Unknown X3DH prekey {id}"),
+            true,
+            None,
+        ));
+    }
+    provider.load_key_pair(id.to_owned())
+}
+
+/// Returns the X25519 public key used for Diffie-Hellman with `key_pair`.
+#[allow(deprecated)]
+fn dh_public_key(key_pair: &KeyPairHandle) -> Result<Vec<u8>, CalError> {
+    key_pair.start_dh_exchange()?.get_public_key()
+}
+
+/// Computes `DH(key_pair, public_key)`.
+#[allow(deprecated)]
+fn dh(key_pair: &KeyPairHandle, public_key: &[u8]) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    Ok(Zeroizing::new(
+        key_pair
+            .start_dh_exchange()?
+            .compute_shared_secret(public_key)?,
+    ))
+}
+
+impl X3DH {
+    pub fn new(info: &[u8], key_spec: KeySpec) -> Self {
+        Self {
+            info: info.to_vec(),
+            key_spec,
+        }
+    }
+
+    /// Creates a persisted identity key.
+    pub fn create_identity_key(&self, provider: &mut Provider) -> Result<KeyPairHandle, CalError> {
+        provider.create_key_pair(key_pair_spec(self.key_spec.signing_hash))
+    }
+
+    /// Creates a persisted signed prekey, which should be replaced periodically.
+    ///
+    /// Only prekeys created with this and [X3DH::create_one_time_prekeys] are accepted by
+    /// [X3DH::respond].
+    pub fn create_signed_prekey(&self, provider: &mut Provider) -> Result<KeyPairHandle, CalError> {
+        create_prekey(provider, self.key_spec.signing_hash, SIGNED_PREKEY)
+    }
+
+    /// Creates `count` persisted one-time prekeys.
+    pub fn create_one_time_prekeys(
+        &self,
+        provider: &mut Provider,
+        count: usize,
+    ) -> Result<Vec<KeyPairHandle>, CalError> {
+        (0..count)
+            .map(|_| create_prekey(provider, self.key_spec.signing_hash, ONE_TIME_PREKEY))
+            .collect()
+    }
+
+    /// Exports the bundle to be published, signing the signed prekey with the identity key.
+    pub fn export_bundle(
+        &self,
+        identity_key: &KeyPairHandle,
+        signed_prekey: &KeyPairHandle,
+        one_time_prekey: Option<&KeyPairHandle>,
+    ) -> Result<PreKeyBundle, CalError> {
+        check_identity_key(identity_key)?;
+
+        let signed_prekey_public = dh_public_key(signed_prekey)?;
+        let one_time_prekey = one_time_prekey
+            .map(|key_pair| {
+                Ok::<_, CalError>(OneTimePreKey {
+                    id: key_pair.id()?,
+                    public_key: dh_public_key(key_pair)?,
+                })
+            })
+            .transpose()?;
+
+        Ok(PreKeyBundle {
+            identity_key: identity_key.get_public_key()?,
+            signed_prekey_id: signed_prekey.id()?,
+            signed_prekey_signature: identity_key.sign_data(&signed_prekey_public)?,
+            signed_prekey: signed_prekey_public,
+            one_time_prekey,
+        })
+    }
+
+    /// `KDF(F || DH1 || DH2 || DH3 || DH4)` imported as [KeyHandle].
+    fn derive_key(
+        &self,
+        provider: &mut Provider,
+        shared_secrets: &[Zeroizing<Vec<u8>>],
+    ) -> Result<KeyHandle, CalError> {
+        let mut input_key_material = Zeroizing::new(vec![0xFF; KEY_LEN]);
+        for shared_secret in shared_secrets {
+            input_key_material.extend_from_slice(shared_secret);
+        }
+
+        let hash = self.key_spec.signing_hash;
+        let pseudo_random_key = hkdf::extract(provider, hash, &[], &input_key_material)?;
+        let key = hkdf::expand(
+            provider,
+            hash,
+            &pseudo_random_key,
+            &self.info,
+            self.key_spec.cipher.len(),
+        )?;
+
+        provider.import_key(self.key_spec, &key)
+    }
+
+    /// Verifies `bundle` and derives the shared key as initiator.
+    pub fn initiate(
+        &self,
+        provider: &mut Provider,
+        identity_key: &KeyPairHandle,
+        bundle: &PreKeyBundle,
+    ) -> Result<(X3DHSession, InitialMessage), CalError> {
+        check_identity_key(identity_key)?;
+
+        let remote_identity_key = provider.import_public_key(
+            KeyPairSpec {
+                ephemeral: true,
+                ..key_pair_spec(self.key_spec.signing_hash)
+            },
+            &bundle.identity_key,
+        )?;
+        if !remote_identity_key
+            .verify_signature(&bundle.signed_prekey, &bundle.signed_prekey_signature)?
+        {
+            return Err(CalError::failed_operation(
+                "Invalid signature of the signed prekey".to_owned(),
+                true,
+                None,
+            ));
+        }
+        let remote_identity_dh_key = identity_key_to_x25519(&bundle.identity_key)?;
+
+        let ephemeral_key = provider.start_ephemeral_dh_exchange(KeyPairSpec {
+            ephemeral: true,
+            ..key_pair_spec(self.key_spec.signing_hash)
+        })?;
+
+        let mut shared_secrets = vec![
+            dh(identity_key, &bundle.signed_prekey)?,
+            Zeroizing::new(ephemeral_key.compute_shared_secret(&remote_identity_dh_key)?),
+            Zeroizing::new(ephemeral_key.compute_shared_secret(&bundle.signed_prekey)?),
+        ];
+        if let Some(one_time_prekey) = bundle.one_time_prekey.as_ref() {
+            shared_secrets.push(Zeroizing::new(
+                ephemeral_key.compute_shared_secret(&one_time_prekey.public_key)?,
+            ));
+        }
+
+        let key = self.derive_key(provider, &shared_secrets)?;
+        let local_identity_key = identity_key.get_public_key()?;
+
+        Ok((
+            X3DHSession {
+                key,
+                associated_data: [local_identity_key.as_slice(), &bundle.identity_key].concat(),
+            },
+            InitialMessage {
+                identity_key: local_identity_key,
+                ephemeral_key: ephemeral_key.get_public_key()?,
+                signed_prekey_id: bundle.signed_prekey_id.clone(),
+                one_time_prekey_id: bundle
+                    .one_time_prekey
+                    .as_ref()
+                    .map(|one_time_prekey| one_time_prekey.id.clone()),
+            },
+        ))
+    }
+
+    /// Derives the shared key as responder.
+    ///
+    /// The referenced prekeys have to be issued by this party. The one-time prekey is loaded from
+    /// the provider and deleted afterwards, so that a replayed initial message fails.
+    pub fn respond(
+        &self,
+        provider: &mut Provider,
+        identity_key: &KeyPairHandle,
+        message: &InitialMessage,
+    ) -> Result<X3DHSession, CalError> {
+        check_identity_key(identity_key)?;
+
+        let remote_identity_dh_key = identity_key_to_x25519(&message.identity_key)?;
+        let signed_prekey = load_prekey(provider, &message.signed_prekey_id, SIGNED_PREKEY)?;
+        let one_time_prekey = message
+            .one_time_prekey_id
+            .as_ref()
+            .map(|id| load_prekey(provider, id, ONE_TIME_PREKEY))
+            .transpose()?;
+
+        let mut shared_secrets = vec![
+            dh(&signed_prekey, &remote_identity_dh_key)?,
+            dh(identity_key, &message.ephemeral_key)?,
+            dh(&signed_prekey, &message.ephemeral_key)?,
+        ];
+        if let Some(one_time_prekey) = one_time_prekey.as_ref() {
+            shared_secrets.push(dh(one_time_prekey, &message.ephemeral_key)?);
+        }
+
+        let key = self.derive_key(provider, &shared_secrets)?;
+        if let Some(one_time_prekey) = one_time_prekey {
+            // Forget the prekey first, so that it is not accepted again if deleting it fails.
+            provider.delete_secret_data(format!("{PREKEY_ID_PREFIX}{}", one_time_prekey.id()?))?;
+            one_time_prekey.delete()?;
+        }
+
+        Ok(X3DHSession {
+            key,
+            associated_data: [
+                message.identity_key.as_slice(),
+                &identity_key.get_public_key()?,
+            ]
+            .concat(),
+        })
+    }
+}
@@ -1,12 +1,14 @@
 pub use crate::common::{
     config::{
-        AdditionalConfig, KeyPairSpec, KeySpec, ProviderConfig, ProviderImplConfig, SecurityLevel,
+        AdditionalConfig, EncryptionUsage, KeyPairSpec, KeySpec, KeyUsage, ProviderConfig,
+        ProviderImplConfig, SecurityLevel,
     },
     crypto::algorithms::{
-        encryption::{AsymmetricKeySpec, Cipher},
+        encryption::{AsymmetricKeySpec, Cipher, PointEncoding, RsaPadding, SignatureEncoding},
         hashes::CryptoHash,
-        key_derivation::{Argon2Options, KDF},
+        key_derivation::{Argon2Options, PasswordVerification, Pbkdf2Options, ScryptOptions, KDF},
     },
+    crypto::pkcs::standards::KeyFormat,
     error::{CalError, CalErrorKind},
     factory::{
         create_provider, create_provider_from_name, get_all_providers, get_provider_capabilities,
@@ -10,7 +10,7 @@ use crate::{
         traits::{key_handle::DHKeyExchangeImpl, module_provider::ProviderImpl},
         KeyHandle,
     },
-    prelude::{CalError, KeyPairSpec, KeySpec},
+    prelude::{CalError, KeyPairSpec, KeySpec, KeyUsage},
     provider::android::{
         provider::AndroidProvider,
         utils::get_exchange_algorithm,
@@ -141,6 +141,10 @@ impl DHKeyExchangeImpl for AndroidDHExchange {
             signing_hash: self.spec.signing_hash,
             ephemeral: self.spec.ephemeral,
             non_exportable: self.spec.non_exportable,
+            not_before: None,
+            not_after: self.spec.not_after,
+            process_not_after: self.spec.process_not_after,
+            key_usage: KeyUsage::DATA,
         };
 
         let rx_handle = self.provider.import_key(spec, &rx_key)?;
@@ -163,6 +167,10 @@ impl DHKeyExchangeImpl for AndroidDHExchange {
             signing_hash: self.spec.signing_hash,
             ephemeral: self.spec.ephemeral,
             non_exportable: self.spec.non_exportable,
+            not_before: None,
+            not_after: self.spec.not_after,
+            process_not_after: self.spec.process_not_after,
+            key_usage: KeyUsage::DATA,
         };
 
         let rx_handle = self.provider.import_key(spec, &rx_key)?;
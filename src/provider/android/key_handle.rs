@@ -6,6 +6,7 @@ use crate::provider::software::key_handle::id_from_buffer;
 use crate::{
     common::{
         config::{KeyPairSpec, KeySpec},
+        crypto::ecdsa,
         error::{CalError, ToCalError},
         traits::key_handle::{KeyHandleImpl, KeyPairHandleImpl},
         DHExchange, KeyHandle,
@@ -287,12 +288,16 @@ impl KeyPairHandleImpl for AndroidKeyPairHandle {
 
         let output = s.sign(&env).err_internal()?;
 
-        Ok(output)
+        ecdsa::encode_signature(self.spec, output)
     }
 
     fn verify_signature(&self, data: &[u8], signature: &[u8]) -> Result<bool, CalError> {
         trace!("verifying");
 
+        let Some(signature) = ecdsa::decode_signature(self.spec, signature) else {
+            return Ok(false);
+        };
+
         let vm = context::android_context()?.vm();
         let vm = unsafe { JavaVM::from_raw(vm.cast()) }.err_internal()?;
         let env = vm.attach_current_thread().err_internal()?;
@@ -313,7 +318,7 @@ impl KeyPairHandleImpl for AndroidKeyPairHandle {
         let data_bytes = data.to_vec().into_boxed_slice();
         s.update(&env, data_bytes).err_internal()?;
 
-        let signature_boxed = signature.to_vec().into_boxed_slice();
+        let signature_boxed = signature.into_boxed_slice();
         let output = s.verify(&env, signature_boxed).err_internal()?;
 
         Ok(output)
@@ -11,8 +11,8 @@ use crate::{
         dh_exchange::AndroidDHExchange,
         key_handle::{AndroidKeyHandle, AndroidKeyPairHandle},
         utils::{
-            get_asym_key_size, get_cipher_name, get_cipher_padding, get_key_size, get_mode_name,
-            is_rsa, Padding,
+            check_point_encoding, get_asym_key_size, get_cipher_name, get_cipher_padding,
+            get_key_size, get_mode_name, get_purposes, is_rsa, Padding,
         },
         wrapper::{self, context},
         ANDROID_KEYSTORE,
@@ -145,9 +145,12 @@ impl ProviderImpl for AndroidProvider {
         let env = vm.attach_current_thread().err_internal()?;
 
         // build up key specs
-        let mut kps_builder =
-            wrapper::key_generation::builder::Builder::new(&env, key_id.to_owned(), 1 | 2 | 4 | 8)
-                .err_internal()?;
+        let mut kps_builder = wrapper::key_generation::builder::Builder::new(
+            &env,
+            key_id.to_owned(),
+            get_purposes(spec.key_usage, 1 | 2 | 4 | 8)?,
+        )
+        .err_internal()?;
 
         kps_builder = kps_builder
             .set_block_modes(&env, vec![get_mode_name(spec.cipher)?])
@@ -161,6 +164,13 @@ impl ProviderImpl for AndroidProvider {
 
         kps_builder = kps_builder
             .set_is_strongbox_backed(&env, self.used_factory.secure_element)
+            .err_internal()?
+            .set_key_validity(
+                &env,
+                spec.not_before,
+                spec.not_after,
+                spec.process_not_after,
+            )
             .err_internal()?;
 
         let kps = kps_builder.build(&env).err_internal()?;
@@ -205,6 +215,7 @@ impl ProviderImpl for AndroidProvider {
         if self.storage_manager.is_none() && !spec.ephemeral {
             return Err(CalError::ephemeral_key_required());
         }
+        check_point_encoding(spec.point_encoding)?;
 
         let key_id = nanoid!(10);
         info!("generating key pair: {}", key_id);
@@ -214,9 +225,12 @@ impl ProviderImpl for AndroidProvider {
         let env = vm.attach_current_thread().err_internal()?;
 
         // build up key specs
-        let mut kps_builder =
-            wrapper::key_generation::builder::Builder::new(&env, key_id.to_owned(), 1 | 2 | 4 | 8)
-                .err_internal()?;
+        let mut kps_builder = wrapper::key_generation::builder::Builder::new(
+            &env,
+            key_id.to_owned(),
+            get_purposes(spec.key_usage, 1 | 2 | 4 | 8)?,
+        )
+        .err_internal()?;
 
         match is_rsa(spec.asym_spec) {
             true => {
@@ -238,6 +252,13 @@ impl ProviderImpl for AndroidProvider {
         };
         kps_builder = kps_builder
             .set_is_strongbox_backed(&env, self.used_factory.secure_element)
+            .err_internal()?
+            .set_key_validity(
+                &env,
+                spec.not_before,
+                spec.not_after,
+                spec.process_not_after,
+            )
             .err_internal()?;
 
         let kps = kps_builder.build(&env).err_internal()?;
@@ -365,19 +386,28 @@ impl ProviderImpl for AndroidProvider {
         .err_internal()?;
         key_store.load(&env, None).err_internal()?;
 
-        let protections =
-            wrapper::key_generation::protections_builder::ProtectionsBuilder::new(&env, 3)
-                .err_internal()?
-                .set_block_modes(&env, vec![get_mode_name(spec.cipher)?])
-                .err_internal()?
-                .set_encryption_paddings(&env, vec![get_cipher_padding(spec.cipher)?.into()])
-                .err_internal()?
-                .set_randomized_encryption_required(&env, false)
-                .err_internal()?
-                .set_is_strongbox_backed(&env, self.used_factory.secure_element)
-                .err_internal()?
-                .build(&env)
-                .err_internal()?;
+        let protections = wrapper::key_generation::protections_builder::ProtectionsBuilder::new(
+            &env,
+            get_purposes(spec.key_usage, 1 | 2)?,
+        )
+        .err_internal()?
+        .set_block_modes(&env, vec![get_mode_name(spec.cipher)?])
+        .err_internal()?
+        .set_encryption_paddings(&env, vec![get_cipher_padding(spec.cipher)?.into()])
+        .err_internal()?
+        .set_randomized_encryption_required(&env, false)
+        .err_internal()?
+        .set_is_strongbox_backed(&env, self.used_factory.secure_element)
+        .err_internal()?
+        .set_key_validity(
+            &env,
+            spec.not_before,
+            spec.not_after,
+            spec.process_not_after,
+        )
+        .err_internal()?
+        .build(&env)
+        .err_internal()?;
 
         key_store
             .set_entry(
@@ -440,6 +470,7 @@ impl ProviderImpl for AndroidProvider {
         if self.storage_manager.is_none() && !spec.ephemeral {
             return Err(CalError::ephemeral_key_required());
         }
+        check_point_encoding(spec.point_encoding)?;
 
         let key_id = nanoid!(10);
         info!("generating key pair for dh exchange: {}", key_id);
@@ -449,9 +480,12 @@ impl ProviderImpl for AndroidProvider {
         let env = vm.attach_current_thread().err_internal()?;
 
         // build up key specs
-        let mut kps_builder =
-            wrapper::key_generation::builder::Builder::new(&env, key_id.to_owned(), 64)
-                .err_internal()?;
+        let mut kps_builder = wrapper::key_generation::builder::Builder::new(
+            &env,
+            key_id.to_owned(),
+            get_purposes(spec.key_usage, 64)?,
+        )
+        .err_internal()?;
 
         match is_rsa(spec.asym_spec) {
             true => {
@@ -473,6 +507,13 @@ impl ProviderImpl for AndroidProvider {
         };
         kps_builder = kps_builder
             .set_is_strongbox_backed(&env, self.used_factory.secure_element)
+            .err_internal()?
+            .set_key_validity(
+                &env,
+                spec.not_before,
+                spec.not_after,
+                spec.process_not_after,
+            )
             .err_internal()?;
 
         let kps = kps_builder.build(&env).err_internal()?;
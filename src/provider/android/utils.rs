@@ -1,7 +1,7 @@
 use crate::common::{
-    config::KeyPairSpec,
+    config::{KeyPairSpec, KeyUsage},
     crypto::algorithms::{
-        encryption::{AsymmetricKeySpec, Cipher},
+        encryption::{AsymmetricKeySpec, Cipher, PointEncoding},
         hashes::CryptoHash,
     },
     error::CalError,
@@ -36,7 +36,7 @@ impl From<CryptoHash> for String {
             | CryptoHash::Sha3_224
             | CryptoHash::Sha3_384
             | CryptoHash::Sha3_512 => "SHA3".to_string(),
-            CryptoHash::Blake2b => todo!(),
+            CryptoHash::Blake2b | CryptoHash::Blake2s => todo!(),
         }
     }
 }
@@ -159,6 +159,29 @@ impl From<AsymmetricKeySpec> for String {
             | AsymmetricKeySpec::P521
             | AsymmetricKeySpec::Secp256k1
             | AsymmetricKeySpec::Frp256v1 => "EC".to_string(),
+            AsymmetricKeySpec::MlKem512
+            | AsymmetricKeySpec::MlKem768
+            | AsymmetricKeySpec::MlKem1024 => "ML-KEM".to_string(),
+            AsymmetricKeySpec::MlDsa44
+            | AsymmetricKeySpec::MlDsa65
+            | AsymmetricKeySpec::MlDsa87 => "ML-DSA".to_string(),
+            AsymmetricKeySpec::SlhDsaSha2_128s
+            | AsymmetricKeySpec::SlhDsaSha2_128f
+            | AsymmetricKeySpec::SlhDsaSha2_192s
+            | AsymmetricKeySpec::SlhDsaSha2_192f
+            | AsymmetricKeySpec::SlhDsaSha2_256s
+            | AsymmetricKeySpec::SlhDsaSha2_256f
+            | AsymmetricKeySpec::SlhDsaShake128s
+            | AsymmetricKeySpec::SlhDsaShake128f
+            | AsymmetricKeySpec::SlhDsaShake192s
+            | AsymmetricKeySpec::SlhDsaShake192f
+            | AsymmetricKeySpec::SlhDsaShake256s
+            | AsymmetricKeySpec::SlhDsaShake256f => "SLH-DSA".to_string(),
+            AsymmetricKeySpec::X25519MlKem768 => "X25519MLKEM768".to_string(),
+            AsymmetricKeySpec::MlDsa44Ed25519
+            | AsymmetricKeySpec::MlDsa65Ed25519
+            | AsymmetricKeySpec::MlDsa44EcdsaP256
+            | AsymmetricKeySpec::MlDsa65EcdsaP256 => "COMPOSITE".to_string(),
         }
     }
 }
@@ -181,6 +204,29 @@ pub fn get_exchange_algorithm(spec: KeyPairSpec) -> String {
         | AsymmetricKeySpec::P521
         | AsymmetricKeySpec::Secp256k1
         | AsymmetricKeySpec::Frp256v1 => "ECDH".to_string(),
+        AsymmetricKeySpec::MlKem512
+        | AsymmetricKeySpec::MlKem768
+        | AsymmetricKeySpec::MlKem1024 => "ML-KEM".to_string(),
+        AsymmetricKeySpec::MlDsa44 | AsymmetricKeySpec::MlDsa65 | AsymmetricKeySpec::MlDsa87 => {
+            "ML-DSA".to_string()
+        }
+        AsymmetricKeySpec::SlhDsaSha2_128s
+        | AsymmetricKeySpec::SlhDsaSha2_128f
+        | AsymmetricKeySpec::SlhDsaSha2_192s
+        | AsymmetricKeySpec::SlhDsaSha2_192f
+        | AsymmetricKeySpec::SlhDsaSha2_256s
+        | AsymmetricKeySpec::SlhDsaSha2_256f
+        | AsymmetricKeySpec::SlhDsaShake128s
+        | AsymmetricKeySpec::SlhDsaShake128f
+        | AsymmetricKeySpec::SlhDsaShake192s
+        | AsymmetricKeySpec::SlhDsaShake192f
+        | AsymmetricKeySpec::SlhDsaShake256s
+        | AsymmetricKeySpec::SlhDsaShake256f => "SLH-DSA".to_string(),
+        AsymmetricKeySpec::X25519MlKem768 => "X25519MLKEM768".to_string(),
+        AsymmetricKeySpec::MlDsa44Ed25519
+        | AsymmetricKeySpec::MlDsa65Ed25519
+        | AsymmetricKeySpec::MlDsa44EcdsaP256
+        | AsymmetricKeySpec::MlDsa65EcdsaP256 => "COMPOSITE".to_string(),
     }
 }
 
@@ -202,8 +248,82 @@ pub fn get_signature_algorithm(spec: KeyPairSpec) -> Result<String, CalError> {
         | AsymmetricKeySpec::P521
         | AsymmetricKeySpec::Secp256k1
         | AsymmetricKeySpec::Frp256v1 => "ECDSA".to_string(),
+        AsymmetricKeySpec::MlKem512
+        | AsymmetricKeySpec::MlKem768
+        | AsymmetricKeySpec::MlKem1024
+        | AsymmetricKeySpec::X25519MlKem768 => {
+            return Err(CalError::unsupported_algorithm(format!(
+                "{:?} can not be used for signatures",
+                spec.asym_spec
+            )))
+        }
+        AsymmetricKeySpec::MlDsa44
+        | AsymmetricKeySpec::MlDsa65
+        | AsymmetricKeySpec::MlDsa87
+        | AsymmetricKeySpec::SlhDsaSha2_128s
+        | AsymmetricKeySpec::SlhDsaSha2_128f
+        | AsymmetricKeySpec::SlhDsaSha2_192s
+        | AsymmetricKeySpec::SlhDsaSha2_192f
+        | AsymmetricKeySpec::SlhDsaSha2_256s
+        | AsymmetricKeySpec::SlhDsaSha2_256f
+        | AsymmetricKeySpec::SlhDsaShake128s
+        | AsymmetricKeySpec::SlhDsaShake128f
+        | AsymmetricKeySpec::SlhDsaShake192s
+        | AsymmetricKeySpec::SlhDsaShake192f
+        | AsymmetricKeySpec::SlhDsaShake256s
+        | AsymmetricKeySpec::SlhDsaShake256f
+        | AsymmetricKeySpec::MlDsa44Ed25519
+        | AsymmetricKeySpec::MlDsa65Ed25519
+        | AsymmetricKeySpec::MlDsa44EcdsaP256
+        | AsymmetricKeySpec::MlDsa65EcdsaP256 => {
+            return Err(CalError::unsupported_algorithm(format!(
+                "{:?} is not supported by the Android KeyStore",
+                spec.asym_spec
+            )))
+        }
     };
     let part2 = get_hash_name(spec.signing_hash)?;
 
     Ok(format!("{part2}with{part1}"))
 }
+
+/// `KeyProperties.PURPOSE_*` values of Android, `PURPOSE_AGREE_KEY` is available since API level 31.
+const ANDROID_PURPOSES: [(KeyUsage, i32); 6] = [
+    (KeyUsage::ENCRYPT, 1),
+    (KeyUsage::DECRYPT, 2),
+    (KeyUsage::SIGN, 4),
+    (KeyUsage::VERIFY, 8),
+    (KeyUsage::WRAP, 32),
+    (KeyUsage::AGREE, 64),
+];
+
+/// Maps key usages to Android key purposes, limited to the `supported` purposes of the key type.
+///
+/// [KeyUsage::DERIVE] has no Android equivalent.
+pub(crate) fn get_purposes(usage: KeyUsage, supported: i32) -> Result<i32, CalError> {
+    let purposes = ANDROID_PURPOSES
+        .iter()
+        .filter(|(key_usage, _)| usage.contains(*key_usage))
+        .fold(0, |purposes, (_, purpose)| purposes | purpose)
+        & supported;
+
+    if purposes == 0 {
+        return Err(CalError::bad_parameter(
+            format!("None of the key usages {usage} are supported by Android for this key"),
+            true,
+            None,
+        ));
+    }
+    Ok(purposes)
+}
+
+/// The Android KeyStore returns public keys as X.509 `SubjectPublicKeyInfo` with uncompressed
+/// points, other encodings are refused instead of being ignored.
+pub(crate) fn check_point_encoding(encoding: PointEncoding) -> Result<(), CalError> {
+    match encoding {
+        PointEncoding::Uncompressed => Ok(()),
+        encoding => Err(CalError::unsupported_algorithm(format!(
+            "{encoding:?} points are not supported by the Android KeyStore"
+        ))),
+    }
+}
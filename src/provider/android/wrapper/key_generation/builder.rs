@@ -252,6 +252,47 @@ impl<'env: 'borrow, 'borrow> Builder<'env, 'borrow> {
         Ok(self)
     }
 
+    /// Sets the validity period of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The `Builder` instance.
+    /// * `env` - The JNI environment.
+    /// * `start` - Unix time in seconds before which the key cannot be used.
+    /// * `origination_end` - Unix time in seconds after which the key cannot encrypt or sign.
+    /// * `consumption_end` - Unix time in seconds after which the key cannot decrypt or verify.
+    ///
+    /// # Returns
+    ///
+    /// A `JniResult` containing the updated `Builder` instance.
+    pub(crate) fn set_key_validity(
+        mut self,
+        env: &'borrow JNIEnv<'env>,
+        start: Option<u64>,
+        origination_end: Option<u64>,
+        consumption_end: Option<u64>,
+    ) -> JniResult<Self> {
+        for (method, time) in [
+            ("setKeyValidityStart", start),
+            ("setKeyValidityForOriginationEnd", origination_end),
+            ("setKeyValidityForConsumptionEnd", consumption_end),
+        ] {
+            let Some(time) = time else {
+                continue;
+            };
+            let millis = i64::try_from(time.saturating_mul(1000)).unwrap_or(i64::MAX);
+            let date = env.new_object("java/util/Date", "(J)V", &[JValue::Long(millis)])?;
+            let result = env.call_method(
+                self.raw.as_obj(),
+                method,
+                "(Ljava/util/Date;)Landroid/security/keystore/KeyGenParameterSpec$Builder;",
+                &[JValue::Object(date)],
+            )?;
+            self.raw = AutoLocal::new(env, result.l()?);
+        }
+        Ok(self)
+    }
+
     pub(crate) fn set_randomized_encryption_required(
         mut self,
         env: &'borrow JNIEnv<'env>,
@@ -17,7 +17,7 @@ use tracing::instrument;
 use crate::common::config::KeyPairSpec;
 use crate::common::error::ToCalError;
 use crate::common::{
-    crypto::algorithms::hashes::CryptoHash,
+    crypto::{algorithms::hashes::CryptoHash, ecdsa},
     error::{CalError, KeyType},
     traits::key_handle::KeyPairHandleImpl,
     DHExchange,
@@ -98,21 +98,26 @@ pub(crate) struct AppleSecureEnclaveKeyPair {
 impl KeyPairHandleImpl for AppleSecureEnclaveKeyPair {
     #[instrument(level = "trace", skip(data))]
     fn sign_data(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
-        self.private_key
+        let signature = self
+            .private_key
             .create_signature(
                 signature_algorithm_from_crypto_hash(self.spec.signing_hash)?,
                 data,
             )
-            .err_internal()
+            .err_internal()?;
+        ecdsa::encode_signature(self.spec, signature)
     }
 
     #[instrument(level = "trace", skip(data, signature))]
     fn verify_signature(&self, data: &[u8], signature: &[u8]) -> Result<bool, CalError> {
+        let Some(signature) = ecdsa::decode_signature(self.spec, signature) else {
+            return Ok(false);
+        };
         self.public_key
             .verify_signature(
                 signature_algorithm_from_crypto_hash(self.spec.signing_hash)?,
                 data,
-                signature,
+                &signature,
             )
             .err_internal()
     }
@@ -14,7 +14,10 @@ use tracing::instrument;
 use crate::{
     common::{
         config::{KeyPairSpec, KeySpec, ProviderConfig, ProviderImplConfig, SecurityLevel, Spec},
-        crypto::algorithms::{encryption::AsymmetricKeySpec, hashes::CryptoHash},
+        crypto::algorithms::{
+            encryption::{AsymmetricKeySpec, PointEncoding},
+            hashes::CryptoHash,
+        },
         error::CalError,
         traits::module_provider::{ProviderFactory, ProviderImpl, ProviderImplEnum},
         DHExchange, KeyHandle, KeyPairHandle,
@@ -72,6 +75,17 @@ fn check_key_pair_spec_for_compatibility(key_spec: &KeyPairSpec) -> Result<(), C
         }
     }
 
+    if key_spec.point_encoding != PointEncoding::Uncompressed {
+        return Err(CalError::bad_parameter(
+            format!(
+                "Point encoding not supported: {:#?}",
+                &key_spec.point_encoding
+            ),
+            true,
+            None,
+        ));
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,191 @@
+//! AES modes not offered by `ring`: CBC with PKCS#7 padding and AES key wrap (RFC 3394).
+//!
+//! CBC is not authenticated, callers have to authenticate the cipher text before decrypting it
+//! to rule out padding oracles.
+
+use ::aes::{
+    cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
+    Aes128, Aes256, Block,
+};
+use zeroize::Zeroizing;
+
+use crate::common::{crypto::hkdf::constant_time_eq, error::CalError};
+
+pub(super) const BLOCK_LEN: usize = 16;
+/// Length of the semiblocks of the key wrap and of its integrity check value.
+const SEMIBLOCK_LEN: usize = 8;
+const WRAP_IV: [u8; SEMIBLOCK_LEN] = [0xa6; SEMIBLOCK_LEN];
+
+// Only lives for a single operation, boxing the key schedule would not save anything.
+#[allow(clippy::large_enum_variant)]
+enum Aes {
+    Aes128(Aes128),
+    Aes256(Aes256),
+}
+
+impl Aes {
+    fn new(key: &[u8]) -> Result<Self, CalError> {
+        match key.len() {
+            16 => Ok(Self::Aes128(Aes128::new(GenericArray::from_slice(key)))),
+            32 => Ok(Self::Aes256(Aes256::new(GenericArray::from_slice(key)))),
+            len => Err(CalError::bad_parameter(
+                format!("Invalid AES key length: {len} bytes"),
+                true,
+                None,
+            )),
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut Block) {
+        match self {
+            Self::Aes128(cipher) => cipher.encrypt_block(block),
+            Self::Aes256(cipher) => cipher.encrypt_block(block),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut Block) {
+        match self {
+            Self::Aes128(cipher) => cipher.decrypt_block(block),
+            Self::Aes256(cipher) => cipher.decrypt_block(block),
+        }
+    }
+}
+
+fn decryption_failed() -> CalError {
+    CalError::failed_operation("Decryption failed".to_owned(), true, None)
+}
+
+/// Encrypts `data` with AES-128-CBC or AES-256-CBC, depending on the length of `key`.
+pub(super) fn cbc_encrypt(
+    key: &[u8],
+    iv: &[u8; BLOCK_LEN],
+    data: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let cipher = Aes::new(key)?;
+    // PKCS#7 padding, a full block is added if the data is already aligned.
+    let padding = BLOCK_LEN - data.len() % BLOCK_LEN;
+    let mut buffer = Zeroizing::new(data.to_vec());
+    buffer.extend(std::iter::repeat_n(padding as u8, padding));
+
+    let mut previous = *iv;
+    let mut output = Vec::with_capacity(buffer.len());
+    for chunk in buffer.chunks(BLOCK_LEN) {
+        let mut block = Block::clone_from_slice(chunk);
+        block.iter_mut().zip(previous).for_each(|(b, p)| *b ^= p);
+        cipher.encrypt_block(&mut block);
+        previous.copy_from_slice(&block);
+        output.extend_from_slice(&block);
+    }
+    Ok(output)
+}
+
+/// Decrypts the output of [cbc_encrypt] and removes the padding.
+pub(super) fn cbc_decrypt(
+    key: &[u8],
+    iv: &[u8],
+    data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let cipher = Aes::new(key)?;
+    if iv.len() != BLOCK_LEN || data.is_empty() || !data.len().is_multiple_of(BLOCK_LEN) {
+        return Err(decryption_failed());
+    }
+
+    let mut previous = iv;
+    let mut output = Zeroizing::new(Vec::with_capacity(data.len()));
+    for chunk in data.chunks(BLOCK_LEN) {
+        let mut block = Block::clone_from_slice(chunk);
+        cipher.decrypt_block(&mut block);
+        block.iter_mut().zip(previous).for_each(|(b, p)| *b ^= p);
+        output.extend_from_slice(&block);
+        previous = chunk;
+    }
+
+    let padding = *output.last().expect("Data is not empty") as usize;
+    if padding == 0
+        || padding > BLOCK_LEN
+        || !output[output.len() - padding..]
+            .iter()
+            .all(|&b| b as usize == padding)
+    {
+        return Err(decryption_failed());
+    }
+    let len = output.len() - padding;
+    output.truncate(len);
+    Ok(output)
+}
+
+/// Wraps `key` with the key encryption key `kek` (RFC 3394, section 2.2.1).
+///
+/// The wrapped key is 8 bytes longer than `key`, which has to be a multiple of 8 bytes and at
+/// least 16 bytes long.
+pub(super) fn wrap(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, CalError> {
+    let cipher = Aes::new(kek)?;
+    if key.len() < 2 * SEMIBLOCK_LEN || !key.len().is_multiple_of(SEMIBLOCK_LEN) {
+        return Err(CalError::bad_parameter(
+            format!(
+                "Keys to wrap need to be a multiple of 8 and at least 16 bytes long, got {} bytes",
+                key.len()
+            ),
+            true,
+            None,
+        ));
+    }
+
+    let mut registers = Zeroizing::new(key.to_vec());
+    let n = registers.len() / SEMIBLOCK_LEN;
+    let mut a = WRAP_IV;
+    let mut block = Zeroizing::new(Block::default());
+    for j in 0..6 {
+        for (i, register) in registers.chunks_mut(SEMIBLOCK_LEN).enumerate() {
+            block[..SEMIBLOCK_LEN].copy_from_slice(&a);
+            block[SEMIBLOCK_LEN..].copy_from_slice(register);
+            cipher.encrypt_block(&mut block);
+
+            let t = (n * j + i + 1) as u64;
+            a.copy_from_slice(&block[..SEMIBLOCK_LEN]);
+            a.iter_mut().zip(t.to_be_bytes()).for_each(|(a, t)| *a ^= t);
+            register.copy_from_slice(&block[SEMIBLOCK_LEN..]);
+        }
+    }
+
+    Ok([a.as_slice(), &registers].concat())
+}
+
+/// Unwraps a key wrapped with [wrap] and checks its integrity (RFC 3394, section 2.2.2).
+pub(super) fn unwrap(kek: &[u8], wrapped_key: &[u8]) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let cipher = Aes::new(kek)?;
+    if wrapped_key.len() < 3 * SEMIBLOCK_LEN || !wrapped_key.len().is_multiple_of(SEMIBLOCK_LEN) {
+        return Err(CalError::bad_parameter(
+            format!("Invalid wrapped key length: {} bytes", wrapped_key.len()),
+            true,
+            None,
+        ));
+    }
+
+    let (iv, registers) = wrapped_key.split_at(SEMIBLOCK_LEN);
+    let mut a: [u8; SEMIBLOCK_LEN] = iv.try_into().expect("Length already checked");
+    let mut registers = Zeroizing::new(registers.to_vec());
+    let n = registers.len() / SEMIBLOCK_LEN;
+    let mut block = Zeroizing::new(Block::default());
+    for j in (0..6).rev() {
+        for (i, register) in registers.chunks_mut(SEMIBLOCK_LEN).enumerate().rev() {
+            let t = (n * j + i + 1) as u64;
+            a.iter_mut().zip(t.to_be_bytes()).for_each(|(a, t)| *a ^= t);
+            block[..SEMIBLOCK_LEN].copy_from_slice(&a);
+            block[SEMIBLOCK_LEN..].copy_from_slice(register);
+            cipher.decrypt_block(&mut block);
+
+            a.copy_from_slice(&block[..SEMIBLOCK_LEN]);
+            register.copy_from_slice(&block[SEMIBLOCK_LEN..]);
+        }
+    }
+
+    if !constant_time_eq(&a, &WRAP_IV) {
+        return Err(CalError::failed_operation(
+            "Failed to unwrap key, the integrity check failed".to_owned(),
+            true,
+            None,
+        ));
+    }
+    Ok(registers)
+}
@@ -0,0 +1,230 @@
+//! Composite ML-DSA signatures as specified in `draft-ietf-lamps-pq-composite-sigs`.
+//!
+//! Keys and signatures are the concatenation of their ML-DSA and traditional components:
+//! the private key is `mldsa_seed || trad_sk`, the public key `mldsa_pk || trad_pk` and the
+//! signature `mldsa_sig || trad_sig`. Both components sign the same message representative and
+//! a signature is only valid if both of them verify.
+
+use p256::{
+    ecdsa::{
+        signature::{Signer, Verifier},
+        DerSignature, SigningKey as P256SigningKey, VerifyingKey as P256VerifyingKey,
+    },
+    elliptic_curve::rand_core::OsRng,
+    SecretKey as P256SecretKey,
+};
+use sha2::{Digest, Sha256, Sha512};
+
+use super::mldsa;
+use crate::common::{crypto::algorithms::encryption::AsymmetricKeySpec, error::CalError};
+
+const PREFIX: &[u8] = b"CompositeAlgorithmSignatures2025";
+const ML_DSA_SEED_LEN: usize = 32;
+
+enum Traditional {
+    Ed25519,
+    EcdsaP256,
+}
+
+enum PreHash {
+    Sha256,
+    Sha512,
+}
+
+struct Composite {
+    ml_dsa: AsymmetricKeySpec,
+    ml_dsa_public_key_len: usize,
+    ml_dsa_signature_len: usize,
+    traditional: Traditional,
+    pre_hash: PreHash,
+    label: &'static [u8],
+}
+
+impl Composite {
+    fn new(spec: AsymmetricKeySpec) -> Result<Self, CalError> {
+        let (ml_dsa, traditional, pre_hash, label): (_, _, _, &[u8]) = match spec {
+            AsymmetricKeySpec::MlDsa44Ed25519 => (
+                AsymmetricKeySpec::MlDsa44,
+                Traditional::Ed25519,
+                PreHash::Sha512,
+                b"COMPSIG-MLDSA44-Ed25519-SHA512",
+            ),
+            AsymmetricKeySpec::MlDsa65Ed25519 => (
+                AsymmetricKeySpec::MlDsa65,
+                Traditional::Ed25519,
+                PreHash::Sha512,
+                b"COMPSIG-MLDSA65-Ed25519-SHA512",
+            ),
+            AsymmetricKeySpec::MlDsa44EcdsaP256 => (
+                AsymmetricKeySpec::MlDsa44,
+                Traditional::EcdsaP256,
+                PreHash::Sha256,
+                b"COMPSIG-MLDSA44-ECDSA-P256-SHA256",
+            ),
+            AsymmetricKeySpec::MlDsa65EcdsaP256 => (
+                AsymmetricKeySpec::MlDsa65,
+                Traditional::EcdsaP256,
+                PreHash::Sha512,
+                b"COMPSIG-MLDSA65-ECDSA-P256-SHA512",
+            ),
+            _ => {
+                return Err(CalError::unsupported_algorithm(format!(
+                    "{:?} is not a composite signature algorithm",
+                    spec
+                )))
+            }
+        };
+
+        let (ml_dsa_public_key_len, ml_dsa_signature_len) = match ml_dsa {
+            AsymmetricKeySpec::MlDsa44 => (1312, 2420),
+            _ => (1952, 3309),
+        };
+
+        Ok(Self {
+            ml_dsa,
+            ml_dsa_public_key_len,
+            ml_dsa_signature_len,
+            traditional,
+            pre_hash,
+            label,
+        })
+    }
+
+    /// `M' = Prefix || Label || len(ctx) || ctx || PH(M)` with an empty context.
+    fn message_representative(&self, data: &[u8]) -> Vec<u8> {
+        let digest = match self.pre_hash {
+            PreHash::Sha256 => Sha256::digest(data).to_vec(),
+            PreHash::Sha512 => Sha512::digest(data).to_vec(),
+        };
+
+        [PREFIX, self.label, &[0], &digest].concat()
+    }
+}
+
+fn invalid_key(name: &str) -> CalError {
+    CalError::bad_parameter(format!("Invalid composite {name}"), true, None)
+}
+
+/// Generates a composite key pair and returns `(private_key, public_key)`.
+///
+/// The traditional private key is a 32 byte seed for `Ed25519` and a DER encoded
+/// `ECPrivateKey` for `ECDSA`, its public key is the raw or uncompressed SEC1 point.
+pub(super) fn generate_key_pair(spec: AsymmetricKeySpec) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+    let composite = Composite::new(spec)?;
+    let (ml_dsa_private_key, ml_dsa_public_key) = mldsa::generate_key_pair(composite.ml_dsa)?;
+
+    let (traditional_private_key, traditional_public_key) = match composite.traditional {
+        Traditional::Ed25519 => {
+            let key_pair = ed25519_compact::KeyPair::from_seed(ed25519_compact::Seed::generate());
+            (key_pair.sk.seed().to_vec(), key_pair.pk.to_vec())
+        }
+        Traditional::EcdsaP256 => {
+            let secret_key = P256SecretKey::random(&mut OsRng);
+            let private_key = secret_key.to_sec1_der().map_err(|e| {
+                CalError::failed_operation(
+                    "Failed to encode P-256 private key".to_owned(),
+                    false,
+                    Some(anyhow::anyhow!(e)),
+                )
+            })?;
+            let public_key = P256VerifyingKey::from(secret_key.public_key())
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec();
+            (private_key.to_vec(), public_key)
+        }
+    };
+
+    Ok((
+        [ml_dsa_private_key, traditional_private_key].concat(),
+        [ml_dsa_public_key, traditional_public_key].concat(),
+    ))
+}
+
+pub(super) fn sign_data(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let composite = Composite::new(spec)?;
+    if private_key.len() <= ML_DSA_SEED_LEN {
+        return Err(invalid_key("private key"));
+    }
+    let (ml_dsa_private_key, traditional_private_key) = private_key.split_at(ML_DSA_SEED_LEN);
+    let message = composite.message_representative(data);
+
+    let ml_dsa_signature = mldsa::sign_data(
+        composite.ml_dsa,
+        ml_dsa_private_key,
+        &message,
+        composite.label,
+    )?;
+
+    let traditional_signature = match composite.traditional {
+        Traditional::Ed25519 => {
+            let seed = ed25519_compact::Seed::from_slice(traditional_private_key)
+                .map_err(|_| invalid_key("Ed25519 private key"))?;
+            ed25519_compact::KeyPair::from_seed(seed)
+                .sk
+                .sign(&message, None)
+                .to_vec()
+        }
+        Traditional::EcdsaP256 => {
+            let secret_key = P256SecretKey::from_sec1_der(traditional_private_key)
+                .map_err(|_| invalid_key("P-256 private key"))?;
+            let signature: DerSignature = P256SigningKey::from(secret_key).sign(&message);
+            signature.as_bytes().to_vec()
+        }
+    };
+
+    Ok([ml_dsa_signature, traditional_signature].concat())
+}
+
+/// Returns `true` only if both component signatures are valid.
+///
+/// Malformed signatures result in `Ok(false)`, malformed public keys in an error.
+pub(super) fn verify_signature(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, CalError> {
+    let composite = Composite::new(spec)?;
+    if public_key.len() <= composite.ml_dsa_public_key_len {
+        return Err(invalid_key("public key"));
+    }
+    if signature.len() <= composite.ml_dsa_signature_len {
+        return Ok(false);
+    }
+
+    let (ml_dsa_public_key, traditional_public_key) =
+        public_key.split_at(composite.ml_dsa_public_key_len);
+    let (ml_dsa_signature, traditional_signature) =
+        signature.split_at(composite.ml_dsa_signature_len);
+    let message = composite.message_representative(data);
+
+    let ml_dsa_valid = mldsa::verify_signature(
+        composite.ml_dsa,
+        ml_dsa_public_key,
+        &message,
+        ml_dsa_signature,
+        composite.label,
+    )?;
+
+    let traditional_valid = match composite.traditional {
+        Traditional::Ed25519 => {
+            let public_key = ed25519_compact::PublicKey::from_slice(traditional_public_key)
+                .map_err(|_| invalid_key("Ed25519 public key"))?;
+            ed25519_compact::Signature::from_slice(traditional_signature)
+                .is_ok_and(|signature| public_key.verify(&message, &signature).is_ok())
+        }
+        Traditional::EcdsaP256 => {
+            let public_key = P256VerifyingKey::from_sec1_bytes(traditional_public_key)
+                .map_err(|_| invalid_key("P-256 public key"))?;
+            DerSignature::try_from(traditional_signature)
+                .is_ok_and(|signature| public_key.verify(&message, &signature).is_ok())
+        }
+    };
+
+    Ok(ml_dsa_valid && traditional_valid)
+}
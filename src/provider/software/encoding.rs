@@ -0,0 +1,577 @@
+//! Standard encodings of asymmetric keys.
+//!
+//! Internally `P256` and `P384` private keys are PKCS#8 v1 documents as used by ring, `Curve25519`
+//! private keys are either 64 byte Ed25519 secret keys or 32 byte X25519 scalars and `RSA` private
+//! keys are PKCS#8 documents as well. Public keys are stored raw, which is PKCS#1 `RSAPublicKey`
+//! for `RSA` and the uncompressed SEC 1 point for `P256` and `P384`.
+
+use anyhow::anyhow;
+use curve25519_dalek::{edwards::CompressedEdwardsY, montgomery::MontgomeryPoint};
+use p256::{
+    elliptic_curve::sec1::ToEncodedPoint, PublicKey as P256PublicKey, SecretKey as P256SecretKey,
+};
+use p384::PublicKey as P384PublicKey;
+use pkcs8::{
+    der::{
+        asn1::{AnyRef, BitStringRef, OctetStringRef},
+        Decode, Encode,
+    },
+    spki::{AlgorithmIdentifierRef, SubjectPublicKeyInfoRef},
+    ObjectIdentifier, PrivateKeyInfo,
+};
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair},
+};
+use sec1::{EcParameters, EcPrivateKey};
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use super::{mlkem, rsa};
+use crate::common::{
+    crypto::{
+        algorithms::encryption::{AsymmetricKeySpec, Cipher, PointEncoding},
+        pkcs::standards::{KeyFormat, OidType},
+    },
+    error::CalError,
+};
+
+/// Length of Ed25519 seeds and X25519 scalars.
+const CURVE25519_KEY_LEN: usize = 32;
+
+fn invalid(what: &str, error: impl Into<anyhow::Error>) -> CalError {
+    CalError::bad_parameter(format!("Invalid {what}"), true, Some(error.into()))
+}
+
+fn encoding_failed(error: impl Into<anyhow::Error>) -> CalError {
+    CalError::failed_operation("Failed to encode key".to_owned(), true, Some(error.into()))
+}
+
+fn unsupported_format(spec: AsymmetricKeySpec, format: KeyFormat) -> CalError {
+    CalError::unsupported_algorithm(format!("{format:?} is not supported for {spec:?} keys"))
+}
+
+/// Named curve and scalar length of the elliptic curves, which are stored as PKCS#8.
+fn ec_curve(spec: AsymmetricKeySpec) -> Option<(ObjectIdentifier, usize)> {
+    match spec {
+        AsymmetricKeySpec::P256 => Some((OidType::Prime256v1.oid(), 32)),
+        AsymmetricKeySpec::P384 => Some((OidType::Secp384r1.oid(), 48)),
+        _ => None,
+    }
+}
+
+/// Checks the named curve of SEC 1 or algorithm parameters, which may be omitted.
+fn check_curve(
+    parameters: Option<ObjectIdentifier>,
+    curve: ObjectIdentifier,
+) -> Result<(), CalError> {
+    match parameters {
+        Some(parameters) if parameters != curve => Err(CalError::bad_parameter(
+            format!("Key is for curve {parameters}, expected {curve}"),
+            true,
+            None,
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Accepts the internal PKCS#8 document or a raw scalar.
+fn ec_scalar(private_key: &[u8], scalar_len: usize) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    if private_key.len() == scalar_len {
+        return Ok(Zeroizing::new(private_key.to_vec()));
+    }
+
+    let info = PrivateKeyInfo::try_from(private_key).map_err(|e| invalid("private key", e))?;
+    let ec_key = EcPrivateKey::try_from(info.private_key).map_err(|e| invalid("private key", e))?;
+    Ok(Zeroizing::new(ec_key.private_key.to_vec()))
+}
+
+fn ec_private_key_der(
+    curve: Option<ObjectIdentifier>,
+    scalar: &[u8],
+    public_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    EcPrivateKey {
+        private_key: scalar,
+        parameters: curve.map(EcParameters::NamedCurve),
+        public_key: Some(public_key),
+    }
+    .to_der()
+    .map(Zeroizing::new)
+    .map_err(encoding_failed)
+}
+
+/// PKCS#8 v1, or v2 if `public_key` is given.
+fn private_key_info_der(
+    algorithm: AlgorithmIdentifierRef<'_>,
+    private_key: &[u8],
+    public_key: Option<&[u8]>,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    PrivateKeyInfo {
+        algorithm,
+        private_key,
+        public_key,
+    }
+    .to_der()
+    .map(Zeroizing::new)
+    .map_err(encoding_failed)
+}
+
+fn ec_pkcs8_der(
+    curve: ObjectIdentifier,
+    scalar: &[u8],
+    public_key: &[u8],
+    include_public_key: bool,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let ec_key = ec_private_key_der(None, scalar, public_key)?;
+    let algorithm = AlgorithmIdentifierRef {
+        oid: OidType::EcPublicKey.oid(),
+        parameters: Some(AnyRef::from(&curve)),
+    };
+    private_key_info_der(algorithm, &ec_key, include_public_key.then_some(public_key))
+}
+
+/// Ed25519 secret keys are 64 bytes, the first half being the seed.
+fn curve25519_key(private_key: &[u8]) -> (ObjectIdentifier, &[u8]) {
+    if private_key.len() == ed25519_compact::SecretKey::BYTES {
+        (OidType::IdEd25519.oid(), &private_key[..CURVE25519_KEY_LEN])
+    } else {
+        (OidType::IdX25519.oid(), private_key)
+    }
+}
+
+pub(crate) fn export_private_key(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+    public_key: &[u8],
+    format: KeyFormat,
+) -> Result<Vec<u8>, CalError> {
+    if spec.is_rsa() {
+        let encoded = match format {
+            KeyFormat::Raw => rsa::pkcs1_private_key(spec, private_key)?,
+            KeyFormat::PKCS8 => rsa::encode_key_pair(&rsa::private_key(spec, private_key)?)?.0,
+            _ => return Err(unsupported_format(spec, format)),
+        };
+        return Ok(encoded.to_vec());
+    }
+
+    if let Some((curve, scalar_len)) = ec_curve(spec) {
+        let scalar = ec_scalar(private_key, scalar_len)?;
+        let encoded = match format {
+            KeyFormat::Raw => scalar,
+            KeyFormat::SEC1 => ec_private_key_der(Some(curve), &scalar, public_key)?,
+            KeyFormat::PKCS8 => ec_pkcs8_der(curve, &scalar, public_key, false)?,
+            KeyFormat::PKCS8_V2 => ec_pkcs8_der(curve, &scalar, public_key, true)?,
+            _ => return Err(unsupported_format(spec, format)),
+        };
+        return Ok(encoded.to_vec());
+    }
+
+    match (spec, format) {
+        (AsymmetricKeySpec::Curve25519, KeyFormat::Raw) => {
+            Ok(curve25519_key(private_key).1.to_vec())
+        }
+        (AsymmetricKeySpec::Curve25519, KeyFormat::PKCS8 | KeyFormat::PKCS8_V2) => {
+            let (algorithm, key) = curve25519_key(private_key);
+            let key = Zeroizing::new(
+                OctetStringRef::new(key)
+                    .and_then(|key| key.to_der())
+                    .map_err(encoding_failed)?,
+            );
+            let algorithm = AlgorithmIdentifierRef {
+                oid: algorithm,
+                parameters: None,
+            };
+            let public_key = (format == KeyFormat::PKCS8_V2).then_some(public_key);
+            Ok(private_key_info_der(algorithm, &key, public_key)?.to_vec())
+        }
+        (_, KeyFormat::Raw) => Ok(private_key.to_vec()),
+        _ => Err(unsupported_format(spec, format)),
+    }
+}
+
+/// `private_key` tells Ed25519 and X25519 keys apart, public keys without it are Ed25519 keys.
+pub(crate) fn export_public_key(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+    private_key: Option<&[u8]>,
+    format: KeyFormat,
+) -> Result<Vec<u8>, CalError> {
+    let algorithm = match (spec, format) {
+        (_, KeyFormat::Raw) => return Ok(public_key.to_vec()),
+        (spec, KeyFormat::SPKI) if spec.is_rsa() => (OidType::RsaEncryption.oid(), None),
+        (AsymmetricKeySpec::Curve25519, KeyFormat::SPKI) => (
+            private_key
+                .map(|private_key| curve25519_key(private_key).0)
+                .unwrap_or_else(|| OidType::IdEd25519.oid()),
+            None,
+        ),
+        (spec, KeyFormat::SPKI) => match ec_curve(spec) {
+            Some((curve, _)) => (OidType::EcPublicKey.oid(), Some(curve)),
+            None => return Err(unsupported_format(spec, format)),
+        },
+        _ => return Err(unsupported_format(spec, format)),
+    };
+
+    SubjectPublicKeyInfoRef {
+        algorithm: AlgorithmIdentifierRef {
+            oid: algorithm.0,
+            // RFC 3279 requires explicit NULL parameters for RSA.
+            parameters: match algorithm.1.as_ref() {
+                Some(curve) => Some(AnyRef::from(curve)),
+                None if spec.is_rsa() => Some(AnyRef::NULL),
+                None => None,
+            },
+        },
+        subject_public_key: BitStringRef::from_bytes(public_key).map_err(encoding_failed)?,
+    }
+    .to_der()
+    .map_err(encoding_failed)
+}
+
+/// Returns the private key in the internal representation and the raw public key.
+pub(crate) fn import_private_key(
+    spec: AsymmetricKeySpec,
+    data: &[u8],
+    format: KeyFormat,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), CalError> {
+    if spec.is_rsa() {
+        return match format {
+            KeyFormat::Raw | KeyFormat::PKCS8 | KeyFormat::PKCS8_V2 => {
+                rsa::encode_key_pair(&rsa::private_key(spec, data)?)
+            }
+            _ => Err(unsupported_format(spec, format)),
+        };
+    }
+
+    if let Some((curve, scalar_len)) = ec_curve(spec) {
+        // PKCS#8 v2 keys may carry the public key twice.
+        let (scalar, public_keys) = match format {
+            KeyFormat::Raw => (data, vec![]),
+            KeyFormat::SEC1 => {
+                let ec_key = EcPrivateKey::try_from(data).map_err(|e| invalid("SEC 1 key", e))?;
+                check_curve(ec_key.parameters.and_then(|p| p.named_curve()), curve)?;
+                (ec_key.private_key, ec_key.public_key.into_iter().collect())
+            }
+            KeyFormat::PKCS8 | KeyFormat::PKCS8_V2 => {
+                let info = PrivateKeyInfo::try_from(data).map_err(|e| invalid("PKCS#8 key", e))?;
+                let parameters = info
+                    .algorithm
+                    .assert_algorithm_oid(OidType::EcPublicKey.oid())
+                    .and_then(|_| info.algorithm.parameters_oid())
+                    .map_err(|e| invalid("PKCS#8 algorithm", e))?;
+                check_curve(Some(parameters), curve)?;
+                let ec_key = EcPrivateKey::try_from(info.private_key)
+                    .map_err(|e| invalid("PKCS#8 key", e))?;
+                check_curve(ec_key.parameters.and_then(|p| p.named_curve()), curve)?;
+                let public_keys = [ec_key.public_key, info.public_key];
+                (
+                    ec_key.private_key,
+                    public_keys.into_iter().flatten().collect(),
+                )
+            }
+            _ => return Err(unsupported_format(spec, format)),
+        };
+
+        if scalar.len() != scalar_len {
+            return Err(CalError::bad_parameter(
+                format!(
+                    "Invalid {spec:?} private key length: {} bytes",
+                    scalar.len()
+                ),
+                true,
+                None,
+            ));
+        }
+
+        let public_key = match (public_keys.first(), spec) {
+            (Some(public_key), _) => public_key.to_vec(),
+            (None, AsymmetricKeySpec::P256) => P256SecretKey::from_slice(scalar)
+                .map_err(|e| invalid("P-256 private key", anyhow!(e)))?
+                .public_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+            (None, spec) => {
+                return Err(CalError::bad_parameter(
+                    format!("{spec:?} private keys need to include the public key"),
+                    true,
+                    None,
+                ))
+            }
+        };
+
+        let private_key = ec_pkcs8_der(curve, scalar, &public_key, false)?;
+        // Checks the scalar and that it matches the public key.
+        let key_pair = EcdsaKeyPair::from_pkcs8(spec.into(), &private_key, &SystemRandom::new())
+            .map_err(|e| invalid("private key", anyhow!(e)))?;
+        let public_key = key_pair.public_key().as_ref().to_vec();
+        for included in public_keys {
+            if validate_public_key(spec, included, false)? != public_key {
+                return Err(CalError::bad_parameter(
+                    "Public key does not match the private key".to_owned(),
+                    true,
+                    None,
+                ));
+            }
+        }
+        return Ok((private_key, public_key));
+    }
+
+    if spec != AsymmetricKeySpec::Curve25519 {
+        return Err(unsupported_format(spec, format));
+    }
+
+    let (algorithm, key, public_key) = match format {
+        KeyFormat::Raw => (OidType::IdEd25519.oid(), data, None),
+        KeyFormat::PKCS8 | KeyFormat::PKCS8_V2 => {
+            let info = PrivateKeyInfo::try_from(data).map_err(|e| invalid("PKCS#8 key", e))?;
+            let key =
+                OctetStringRef::from_der(info.private_key).map_err(|e| invalid("PKCS#8 key", e))?;
+            (info.algorithm.oid, key.as_bytes(), info.public_key)
+        }
+        _ => return Err(unsupported_format(spec, format)),
+    };
+
+    let key: [u8; CURVE25519_KEY_LEN] = key.try_into().map_err(|_| {
+        CalError::bad_parameter(
+            format!("Invalid Curve25519 private key length: {} bytes", key.len()),
+            true,
+            None,
+        )
+    })?;
+
+    let (private_key, derived_public_key) = if algorithm == OidType::IdEd25519.oid() {
+        let key_pair = ed25519_compact::KeyPair::from_seed(ed25519_compact::Seed::new(key));
+        (key_pair.sk.to_vec(), key_pair.pk.to_vec())
+    } else if algorithm == OidType::IdX25519.oid() {
+        let secret = StaticSecret::from(key);
+        (
+            key.to_vec(),
+            X25519PublicKey::from(&secret).as_bytes().to_vec(),
+        )
+    } else {
+        return Err(CalError::bad_parameter(
+            format!("Unexpected algorithm {algorithm} for Curve25519 key"),
+            true,
+            None,
+        ));
+    };
+
+    if public_key.is_some_and(|public_key| public_key != derived_public_key) {
+        return Err(CalError::bad_parameter(
+            "Public key does not match the private key".to_owned(),
+            true,
+            None,
+        ));
+    }
+
+    Ok((Zeroizing::new(private_key), derived_public_key))
+}
+
+/// Symmetric keys are wrapped in a PKCS#8 `PrivateKeyInfo` with the OID of their cipher.
+///
+/// XChaCha20-Poly1305 has no OID of its own and would be imported as ChaCha20-Poly1305 key.
+fn cipher_oid(cipher: Cipher) -> Result<ObjectIdentifier, CalError> {
+    let oid = match cipher {
+        Cipher::AesGcm128 => OidType::Aes128Gcm,
+        Cipher::AesGcm256 => OidType::Aes256Gcm,
+        Cipher::AesCbc128 => OidType::Aes128Cbc,
+        Cipher::AesCbc256 => OidType::Aes256Cbc,
+        Cipher::ChaCha20Poly1305 => OidType::ChaCha20Poly1305,
+        Cipher::XChaCha20Poly1305 => {
+            return Err(CalError::unsupported_algorithm(
+                "PKCS#8 encoding of XChaCha20Poly1305 keys".to_owned(),
+            ))
+        }
+    };
+    Ok(oid.oid())
+}
+
+pub(crate) fn export_symmetric_key(
+    cipher: Cipher,
+    key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let key = Zeroizing::new(
+        OctetStringRef::new(key)
+            .and_then(|key| key.to_der())
+            .map_err(encoding_failed)?,
+    );
+    let algorithm = AlgorithmIdentifierRef {
+        oid: cipher_oid(cipher)?,
+        parameters: None,
+    };
+    private_key_info_der(algorithm, &key, None)
+}
+
+pub(crate) fn import_symmetric_key(
+    cipher: Cipher,
+    data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let info = PrivateKeyInfo::try_from(data).map_err(|e| invalid("PKCS#8 key", e))?;
+    if info.algorithm.oid != cipher_oid(cipher)? {
+        return Err(CalError::bad_parameter(
+            format!(
+                "Unexpected algorithm {} for {cipher:?} key",
+                info.algorithm.oid
+            ),
+            true,
+            None,
+        ));
+    }
+
+    let key = OctetStringRef::from_der(info.private_key).map_err(|e| invalid("PKCS#8 key", e))?;
+    if key.as_bytes().len() != cipher.len() {
+        return Err(CalError::bad_parameter(
+            format!(
+                "Invalid {cipher:?} key length: {} bytes",
+                key.as_bytes().len()
+            ),
+            true,
+            None,
+        ));
+    }
+
+    Ok(Zeroizing::new(key.as_bytes().to_vec()))
+}
+
+/// Returns the raw public key.
+pub(crate) fn import_public_key(
+    spec: AsymmetricKeySpec,
+    data: &[u8],
+    format: KeyFormat,
+) -> Result<Vec<u8>, CalError> {
+    match format {
+        KeyFormat::Raw if spec.is_rsa() => {
+            rsa::public_key(spec, data)?;
+            return Ok(data.to_vec());
+        }
+        KeyFormat::Raw => return validate_public_key(spec, data, false),
+        KeyFormat::SPKI
+            if ec_curve(spec).is_some()
+                || spec.is_rsa()
+                || spec == AsymmetricKeySpec::Curve25519 => {}
+        _ => return Err(unsupported_format(spec, format)),
+    }
+
+    let info =
+        SubjectPublicKeyInfoRef::try_from(data).map_err(|e| invalid("SubjectPublicKeyInfo", e))?;
+    let algorithm = info.algorithm.oid;
+    if let Some((curve, _)) = ec_curve(spec) {
+        let parameters = info
+            .algorithm
+            .assert_algorithm_oid(OidType::EcPublicKey.oid())
+            .and_then(|_| info.algorithm.parameters_oid())
+            .map_err(|e| invalid("SubjectPublicKeyInfo algorithm", e))?;
+        check_curve(Some(parameters), curve)?;
+    } else if spec.is_rsa() {
+        info.algorithm
+            .assert_algorithm_oid(OidType::RsaEncryption.oid())
+            .map_err(|e| invalid("SubjectPublicKeyInfo algorithm", e))?;
+    } else if algorithm != OidType::IdEd25519.oid() && algorithm != OidType::IdX25519.oid() {
+        return Err(CalError::bad_parameter(
+            format!("Unexpected algorithm {algorithm} for {spec:?} key"),
+            true,
+            None,
+        ));
+    }
+
+    let public_key = info.subject_public_key.as_bytes().ok_or_else(|| {
+        CalError::bad_parameter(
+            "Public key is not a whole number of bytes".to_owned(),
+            true,
+            None,
+        )
+    })?;
+    if spec.is_rsa() {
+        rsa::public_key(spec, public_key)?;
+        return Ok(public_key.to_vec());
+    }
+
+    validate_public_key(spec, public_key, algorithm == OidType::IdX25519.oid())
+}
+
+/// Checks that `public_key` is a point of the prime order subgroup of the curve of `spec` and
+/// returns it in the internal encoding, which is the uncompressed SEC 1 point for `P256` and
+/// `P384`.
+///
+/// `Curve25519` public keys are X25519 keys if `x25519` is set and Ed25519 keys otherwise, only
+/// canonical encodings are accepted. Keys of other algorithms are returned unchanged.
+pub(crate) fn validate_public_key(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+    x25519: bool,
+) -> Result<Vec<u8>, CalError> {
+    match spec {
+        AsymmetricKeySpec::P256 => Ok(P256PublicKey::from_sec1_bytes(public_key)
+            .map_err(|e| invalid("P-256 public key", anyhow!(e)))?
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()),
+        AsymmetricKeySpec::P384 => Ok(P384PublicKey::from_sec1_bytes(public_key)
+            .map_err(|e| invalid("P-384 public key", anyhow!(e)))?
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()),
+        AsymmetricKeySpec::Curve25519 => {
+            let bytes: [u8; CURVE25519_KEY_LEN] = public_key.try_into().map_err(|_| {
+                CalError::bad_parameter(
+                    format!(
+                        "Invalid Curve25519 public key length: {} bytes",
+                        public_key.len()
+                    ),
+                    true,
+                    None,
+                )
+            })?;
+            let point = if x25519 {
+                MontgomeryPoint(bytes)
+                    .to_edwards(0)
+                    .filter(|point| point.to_montgomery().0 == bytes)
+            } else {
+                CompressedEdwardsY(bytes)
+                    .decompress()
+                    .filter(|point| point.compress().0 == bytes)
+            };
+            match point {
+                Some(point) if !point.is_small_order() && point.is_torsion_free() => {
+                    Ok(public_key.to_vec())
+                }
+                _ => Err(CalError::bad_parameter(
+                    format!(
+                        "Invalid {} public key",
+                        if x25519 { "X25519" } else { "Ed25519" }
+                    ),
+                    true,
+                    None,
+                )),
+            }
+        }
+        spec if spec.is_kem() => {
+            mlkem::check_public_key(spec, public_key)?;
+            Ok(public_key.to_vec())
+        }
+        _ => Ok(public_key.to_vec()),
+    }
+}
+
+/// Returns the internal `public_key` with its point in `encoding`.
+///
+/// Compressing a SEC 1 point only needs the parity of `y`, so this works for all curves.
+pub(crate) fn encode_public_key(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+    encoding: PointEncoding,
+) -> Vec<u8> {
+    match (ec_curve(spec), public_key, encoding) {
+        (Some((_, len)), [0x04, point @ ..], PointEncoding::Compressed)
+            if point.len() == 2 * len =>
+        {
+            let (x, y) = point.split_at(len);
+            let mut compressed = vec![0x02 | (y[len - 1] & 1)];
+            compressed.extend_from_slice(x);
+            compressed
+        }
+        _ => public_key.to_vec(),
+    }
+}
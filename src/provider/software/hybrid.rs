@@ -0,0 +1,126 @@
+//! `X25519MLKEM768` hybrid key exchange, laid out as in the TLS hybrid design
+//! (`draft-ietf-tls-ecdhe-mlkem`).
+//!
+//! The client share is `ek || x25519_pk`, the server share is `ct || x25519_pk` and the
+//! shared secret is `mlkem_ss || x25519_ss`. Callers feed the shared secret together with both
+//! shares into a KDF, so the session keys stay secure as long as either component is unbroken.
+
+use p256::elliptic_curve::rand_core::OsRng;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use super::mlkem;
+use crate::common::{crypto::algorithms::encryption::AsymmetricKeySpec, error::CalError};
+
+const X25519_LEN: usize = 32;
+const ENCAPSULATION_KEY_LEN: usize = 1184;
+const DECAPSULATION_KEY_LEN: usize = 2400;
+const CIPHERTEXT_LEN: usize = 1088;
+
+pub(super) const PRIVATE_KEY_LEN: usize = DECAPSULATION_KEY_LEN + X25519_LEN;
+pub(super) const CLIENT_SHARE_LEN: usize = ENCAPSULATION_KEY_LEN + X25519_LEN;
+pub(super) const SERVER_SHARE_LEN: usize = CIPHERTEXT_LEN + X25519_LEN;
+
+fn invalid_length(name: &str, expected: usize, actual: usize) -> CalError {
+    CalError::bad_parameter(
+        format!("Invalid X25519MLKEM768 {name} length: expected {expected} bytes, got {actual}"),
+        true,
+        None,
+    )
+}
+
+fn x25519(private_key: &[u8], peer_public_key: &[u8]) -> Result<Zeroizing<[u8; 32]>, CalError> {
+    let private_key: [u8; X25519_LEN] = private_key
+        .try_into()
+        .map_err(|_| invalid_length("X25519 private key", X25519_LEN, private_key.len()))?;
+    let peer_public_key: [u8; X25519_LEN] = peer_public_key
+        .try_into()
+        .map_err(|_| invalid_length("X25519 public key", X25519_LEN, peer_public_key.len()))?;
+
+    let shared_secret =
+        StaticSecret::from(private_key).diffie_hellman(&X25519PublicKey::from(peer_public_key));
+
+    // Low order points result in an all zero output, which must be rejected.
+    if !shared_secret.was_contributory() {
+        return Err(CalError::bad_parameter(
+            "X25519 public key of the peer is a low order point".to_owned(),
+            true,
+            None,
+        ));
+    }
+
+    Ok(Zeroizing::new(shared_secret.to_bytes()))
+}
+
+fn public_key(private_key: &[u8]) -> Result<X25519PublicKey, CalError> {
+    let private_key: [u8; X25519_LEN] = private_key
+        .try_into()
+        .map_err(|_| invalid_length("X25519 private key", X25519_LEN, private_key.len()))?;
+    Ok(X25519PublicKey::from(&StaticSecret::from(private_key)))
+}
+
+/// Generates a key pair and returns `(private_key, client_share)`.
+///
+/// The private key is the expanded ML-KEM-768 decapsulation key followed by the X25519 secret.
+pub(super) fn generate_key_pair() -> Result<(Vec<u8>, Vec<u8>), CalError> {
+    let (decapsulation_key, encapsulation_key) =
+        mlkem::generate_key_pair(AsymmetricKeySpec::MlKem768)?;
+    let private = StaticSecret::random_from_rng(OsRng);
+    let public = X25519PublicKey::from(&private);
+
+    Ok((
+        [decapsulation_key.as_slice(), private.as_bytes()].concat(),
+        [encapsulation_key.as_slice(), public.as_bytes()].concat(),
+    ))
+}
+
+/// Server side: encapsulates to the client share and returns `(server_share, shared_secret)`.
+pub(super) fn respond(
+    private_key: &[u8],
+    client_share: &[u8],
+) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), CalError> {
+    if client_share.len() != CLIENT_SHARE_LEN {
+        return Err(invalid_length(
+            "client share",
+            CLIENT_SHARE_LEN,
+            client_share.len(),
+        ));
+    }
+    let (encapsulation_key, peer_x25519) = client_share.split_at(ENCAPSULATION_KEY_LEN);
+
+    let (ciphertext, mlkem_secret) =
+        mlkem::encapsulate_to(AsymmetricKeySpec::MlKem768, encapsulation_key)?;
+    let x25519_private = &private_key[DECAPSULATION_KEY_LEN..];
+    let x25519_secret = x25519(x25519_private, peer_x25519)?;
+
+    let x25519_public = public_key(x25519_private)?;
+    let server_share = [ciphertext.as_slice(), x25519_public.as_bytes()].concat();
+    let shared_secret =
+        Zeroizing::new([mlkem_secret.as_slice(), x25519_secret.as_slice()].concat());
+
+    Ok((server_share, shared_secret))
+}
+
+/// Client side: decapsulates the server share and returns the shared secret.
+pub(super) fn finish(
+    private_key: &[u8],
+    server_share: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    if server_share.len() != SERVER_SHARE_LEN {
+        return Err(invalid_length(
+            "server share",
+            SERVER_SHARE_LEN,
+            server_share.len(),
+        ));
+    }
+    let (ciphertext, peer_x25519) = server_share.split_at(CIPHERTEXT_LEN);
+    let (decapsulation_key, x25519_private) = private_key.split_at(DECAPSULATION_KEY_LEN);
+
+    let mlkem_secret =
+        mlkem::decapsulate_with(AsymmetricKeySpec::MlKem768, decapsulation_key, ciphertext)?;
+    let x25519_secret = x25519(x25519_private, peer_x25519)?;
+
+    Ok(Zeroizing::new(
+        [mlkem_secret.as_slice(), x25519_secret.as_slice()].concat(),
+    ))
+}
@@ -1,32 +1,51 @@
 use crate::{
     common::{
-        config::{KeyPairSpec, KeySpec},
-        crypto::algorithms::encryption::AsymmetricKeySpec,
+        config::{EncryptionUsage, KeyPairSpec, KeySpec, KeyUsage},
+        crypto::{algorithms::encryption::AsymmetricKeySpec, pkcs::standards::KeyFormat},
         error::{CalError, KeyType},
         traits::key_handle::{KeyHandleImpl, KeyPairHandleImpl},
         DHExchange, KeyHandle,
     },
-    prelude::Cipher,
+    prelude::{Cipher, KDF},
     provider::software::util::ring_hmac_algorithm_from_signing_hash,
 };
 
+use super::{
+    aes, composite, encoding, mldsa, mlkem, pbes2,
+    provider::SoftwareDHExchange,
+    rsa, slhdsa,
+    usage::KeyUsageCounter,
+    versions::{KeyVersions, VersionStorage},
+};
+
 use anyhow::anyhow;
 use base64::Engine;
 use chacha20poly1305::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     XChaCha20Poly1305,
 };
-use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use nanoid::nanoid;
+use p256::{
+    elliptic_curve::rand_core::{OsRng, RngCore},
+    pkcs8::DecodePrivateKey,
+    SecretKey as P256SecretKey,
+};
 use ring::{
     aead::{Aad, Algorithm, LessSafeKey, Nonce, UnboundKey, MAX_TAG_LEN, NONCE_LEN},
+    hkdf,
     rand::{SecureRandom, SystemRandom},
     signature::{EcdsaKeyPair, Signature, UnparsedPublicKey},
 };
+use sha2::{Digest, Sha512};
 use tracing::{error, instrument, warn};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use super::StorageManager;
 
+/// Length of the version prefix of [KeyHandleImpl::encrypt_versioned].
+const VERSION_LEN: usize = 4;
+
 #[derive(Debug, Clone)]
 pub(crate) struct SoftwareKeyPairHandle {
     pub(crate) key_id: String,
@@ -43,6 +62,8 @@ pub(crate) struct SoftwareKeyHandle {
     #[zeroize(skip)]
     pub(crate) storage_manager: Option<StorageManager>,
     pub(crate) spec: KeySpec,
+    #[zeroize(skip)]
+    pub(crate) usage: KeyUsageCounter,
 }
 
 impl SoftwareKeyHandle {
@@ -57,8 +78,123 @@ impl SoftwareKeyHandle {
             key: key_data,
             storage_manager,
             spec,
+            usage: KeyUsageCounter::default(),
         })
     }
+
+    /// Counts an encryption, failing if the safety limit of the cipher is reached.
+    fn count_encryption(&self, counter_nonce: bool) -> Result<u64, CalError> {
+        // A handle loaded before the key was rotated must neither encrypt with a version that
+        // is no longer current nor be charged to the current version.
+        if let (Some(version), Some((versions, _))) = (self.usage.version(), self.stored_version()?)
+        {
+            if version != versions.current {
+                return Err(CalError::failed_operation(
+                    format!(
+                        "Key {} was rotated to version {}, load it again to encrypt",
+                        self.key_id, versions.current
+                    ),
+                    true,
+                    None,
+                ));
+            }
+        }
+        self.usage
+            .reserve(&self.key_id, self.spec.cipher.usage_limit(counter_nonce))
+    }
+
+    /// Key wrapping is only defined for AES keys.
+    fn require_aes_key(&self) -> Result<(), CalError> {
+        match self.spec.cipher {
+            Cipher::AesGcm128 | Cipher::AesGcm256 | Cipher::AesCbc128 | Cipher::AesCbc256 => Ok(()),
+            cipher => Err(CalError::unsupported_algorithm(format!(
+                "Key wrapping with {cipher:?} keys"
+            ))),
+        }
+    }
+
+    /// CBC is only offered to composite modes like `A128CBC-HS256`, so the key has to be
+    /// imported for it.
+    fn require_cbc_key(&self) -> Result<(), CalError> {
+        match self.spec.cipher {
+            Cipher::AesCbc128 | Cipher::AesCbc256 => Ok(()),
+            cipher => Err(CalError::unsupported_algorithm(format!(
+                "CBC encryption with {cipher:?} keys"
+            ))),
+        }
+    }
+
+    fn aead_key(&self) -> Result<LessSafeKey, CalError> {
+        let unbound_key = UnboundKey::new(self.spec.cipher.into(), &self.key).map_err(|err| {
+            CalError::failed_operation(
+                "Failed to create unbound AEAD key".to_owned(),
+                false,
+                Some(anyhow!(err)),
+            )
+        })?;
+        Ok(LessSafeKey::new(unbound_key))
+    }
+}
+
+impl SoftwareKeyHandle {
+    /// Version metadata and material of the newest version, if the key is stored.
+    fn stored_version(&self) -> Result<Option<(KeyVersions, Vec<u8>)>, CalError> {
+        let Some(storage_manager) = self
+            .storage_manager
+            .as_ref()
+            .filter(|_| !self.spec.ephemeral)
+        else {
+            return Ok(None);
+        };
+
+        match storage_manager.get(self.key_id.clone()) {
+            Ok(key_data) => {
+                let versions = KeyVersions::from_key_data(&key_data)?;
+                let key = key_data.secret_data.ok_or_else(|| {
+                    CalError::missing_key(self.key_id.clone(), KeyType::Symmetric)
+                })?;
+                Ok(Some((versions, key)))
+            }
+            // Derived keys share the storage manager, but are not stored.
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Handle of another version of this key.
+    fn version_handle(&self, key: Vec<u8>, usage: KeyUsageCounter) -> Self {
+        Self {
+            key_id: self.key_id.clone(),
+            key,
+            storage_manager: self.storage_manager.clone(),
+            spec: self.spec,
+            usage,
+        }
+    }
+}
+
+fn invalid_cbc_iv_length(actual: usize) -> CalError {
+    CalError::bad_parameter(
+        format!(
+            "Invalid IV length for CBC: expected {} bytes, got {actual}",
+            aes::BLOCK_LEN
+        ),
+        true,
+        None,
+    )
+}
+
+fn invalid_iv_length(cipher: Cipher, actual: usize) -> CalError {
+    CalError::bad_parameter(
+        format!(
+            "Invalid IV length for {:?}: expected {} bytes, got {}",
+            cipher,
+            cipher.iv_len(),
+            actual
+        ),
+        true,
+        None,
+    )
 }
 
 /// Hashes and encodes a buffer to a string.
@@ -78,9 +214,12 @@ pub(crate) fn id_from_buffer(id: &[u8], buff: &[u8]) -> String {
     base64::prelude::BASE64_STANDARD.encode(hash_vec)
 }
 
-impl KeyHandleImpl for SoftwareKeyHandle {
-    #[instrument(level = "trace")]
-    fn encrypt_data(&self, data: &[u8], iv: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+impl SoftwareKeyHandle {
+    fn encrypt_data_uncounted(
+        &self,
+        data: &[u8],
+        iv: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), CalError> {
         match self.spec.cipher {
             Cipher::AesGcm128 | Cipher::AesGcm256 => {
                 let (nonce, nonce_bytes) = if !iv.is_empty() {
@@ -179,9 +318,117 @@ impl KeyHandleImpl for SoftwareKeyHandle {
             )),
         }
     }
+}
+
+impl KeyHandleImpl for SoftwareKeyHandle {
+    #[instrument(level = "trace")]
+    fn encrypt_data(&self, data: &[u8], iv: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::ENCRYPT, &self.key_id)?;
+        self.count_encryption(false)?;
+        self.encrypt_data_uncounted(data, iv)
+    }
+
+    fn encrypt_with_counter_nonce(&self, data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::ENCRYPT, &self.key_id)?;
+        if !self.usage.is_persistent() {
+            return Err(CalError::bad_parameter(
+                "Counter nonces are only supported for stored keys, as the counter of ephemeral keys is not persisted".to_owned(),
+                true,
+                None,
+            ));
+        }
+
+        let counter = self.count_encryption(true)?;
+        let mut iv = vec![0; self.spec.cipher.iv_len() - 8];
+        iv.extend_from_slice(&counter.to_be_bytes());
+        self.encrypt_data_uncounted(data, &iv)
+    }
+
+    fn encryption_usage(&self) -> Result<EncryptionUsage, CalError> {
+        Ok(EncryptionUsage {
+            encryptions: self.usage.count()?,
+            random_nonce_limit: self.spec.cipher.usage_limit(false),
+            counter_nonce_limit: self.spec.cipher.usage_limit(true),
+        })
+    }
+
+    fn encrypt_versioned(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
+        let (version, (ciphertext, iv)) = match self.stored_version()? {
+            // This handle was loaded before the key was rotated, the newest version is counted
+            // by its own counter.
+            Some((versions, key)) if self.usage.version() != Some(versions.current) => {
+                let usage = self
+                    .storage_manager
+                    .as_ref()
+                    .map(|storage_manager| {
+                        KeyUsageCounter::persistent(
+                            storage_manager,
+                            &self.key_id,
+                            versions.current,
+                            self.spec,
+                        )
+                    })
+                    .unwrap_or_default();
+                let handle = self.version_handle(key, usage);
+                (versions.current, handle.encrypt_data(data, &[])?)
+            }
+            Some((versions, _)) => (versions.current, self.encrypt_data(data, &[])?),
+            None => (
+                KeyVersions::default().current,
+                self.encrypt_data(data, &[])?,
+            ),
+        };
+
+        Ok([version.to_be_bytes().as_slice(), &iv, &ciphertext].concat())
+    }
+
+    fn decrypt_versioned(&self, encrypted_data: &[u8]) -> Result<Vec<u8>, CalError> {
+        let iv_len = self.spec.cipher.iv_len();
+        if encrypted_data.len() < VERSION_LEN + iv_len {
+            return Err(CalError::bad_parameter(
+                "Versioned cipher text is too short".to_owned(),
+                true,
+                None,
+            ));
+        }
+        let (version, rest) = encrypted_data.split_at(VERSION_LEN);
+        let (iv, ciphertext) = rest.split_at(iv_len);
+        let version =
+            u32::from_be_bytes(version.try_into().map_err(|_| {
+                CalError::bad_parameter("Invalid key version".to_owned(), true, None)
+            })?);
+
+        let key = match self.stored_version()? {
+            Some((versions, key)) if versions.current == version => key,
+            Some((versions, _)) if versions.retained.contains(&version) => {
+                // `stored_version` only returns versions with a storage manager.
+                let storage_manager = self.storage_manager.as_ref().ok_or_else(|| {
+                    CalError::missing_key(self.key_id.clone(), KeyType::Symmetric)
+                })?;
+                VersionStorage::new(storage_manager).load(&self.key_id, version)?
+            }
+            None if version == KeyVersions::default().current => self.key.clone(),
+            _ => {
+                return Err(CalError::missing_key(
+                    format!("{} version {version}", self.key_id),
+                    KeyType::Symmetric,
+                ))
+            }
+        };
+
+        self.version_handle(key, KeyUsageCounter::default())
+            .decrypt_data(ciphertext, iv)
+    }
 
     #[instrument(level = "trace")]
     fn decrypt_data(&self, encrypted_data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::DECRYPT, &self.key_id)?;
         match self.spec.cipher {
             Cipher::AesGcm128 | Cipher::AesGcm256 => {
                 if encrypted_data.len() <= NONCE_LEN {
@@ -269,7 +516,105 @@ impl KeyHandleImpl for SoftwareKeyHandle {
         }
     }
 
+    fn encrypt_with_aad(&self, data: &[u8], iv: &[u8], aad: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::ENCRYPT, &self.key_id)?;
+        self.count_encryption(false)?;
+        match self.spec.cipher {
+            Cipher::AesGcm128 | Cipher::AesGcm256 | Cipher::ChaCha20Poly1305 => {
+                let nonce = Nonce::try_assume_unique_for_key(iv)
+                    .map_err(|_| invalid_iv_length(self.spec.cipher, iv.len()))?;
+
+                let mut in_out = data.to_vec();
+                self.aead_key()?
+                    .seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
+                    .map_err(|_| {
+                        CalError::failed_operation("Encryption failed".to_string(), true, None)
+                    })?;
+
+                Ok(in_out)
+            }
+            Cipher::XChaCha20Poly1305 => {
+                if iv.len() != self.spec.cipher.iv_len() {
+                    return Err(invalid_iv_length(self.spec.cipher, iv.len()));
+                }
+
+                let cipher = XChaCha20Poly1305::new_from_slice(&self.key).map_err(|e| {
+                    CalError::failed_operation("Invalid key length", true, Some(anyhow!(e)))
+                })?;
+                cipher
+                    .encrypt(iv.into(), Payload { msg: data, aad })
+                    .map_err(|e| {
+                        CalError::failed_operation("failed encrypting", false, Some(anyhow!(e)))
+                    })
+            }
+            _ => Err(CalError::unsupported_algorithm(format!(
+                "{:?} is not an AEAD cipher",
+                self.spec.cipher
+            ))),
+        }
+    }
+
+    fn decrypt_with_aad(
+        &self,
+        encrypted_data: &[u8],
+        iv: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::DECRYPT, &self.key_id)?;
+        match self.spec.cipher {
+            Cipher::AesGcm128 | Cipher::AesGcm256 | Cipher::ChaCha20Poly1305 => {
+                let nonce = Nonce::try_assume_unique_for_key(iv)
+                    .map_err(|_| invalid_iv_length(self.spec.cipher, iv.len()))?;
+
+                let mut in_out = encrypted_data.to_vec();
+                let plaintext_len = self
+                    .aead_key()?
+                    .open_in_place(nonce, Aad::from(aad), &mut in_out)
+                    .map_err(|err| {
+                        CalError::failed_operation(
+                            "Failed decryption with ring".to_owned(),
+                            false,
+                            Some(anyhow!(err)),
+                        )
+                    })?
+                    .len();
+                in_out.truncate(plaintext_len);
+
+                Ok(in_out)
+            }
+            Cipher::XChaCha20Poly1305 => {
+                if iv.len() != self.spec.cipher.iv_len() {
+                    return Err(invalid_iv_length(self.spec.cipher, iv.len()));
+                }
+
+                let cipher = XChaCha20Poly1305::new_from_slice(&self.key).map_err(|e| {
+                    CalError::failed_operation("Invalid key length", true, Some(anyhow!(e)))
+                })?;
+                cipher
+                    .decrypt(
+                        iv.into(),
+                        Payload {
+                            msg: encrypted_data,
+                            aad,
+                        },
+                    )
+                    .map_err(|e| {
+                        CalError::failed_operation("failed decrypting", false, Some(anyhow!(e)))
+                    })
+            }
+            _ => Err(CalError::unsupported_algorithm(format!(
+                "{:?} is not an AEAD cipher",
+                self.spec.cipher
+            ))),
+        }
+    }
+
     fn hmac(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec.key_usage.require(KeyUsage::SIGN, &self.key_id)?;
         let hmac_algorithm = ring_hmac_algorithm_from_signing_hash(self.spec.signing_hash)
             .ok_or_else(|| {
                 CalError::bad_parameter(
@@ -285,6 +630,9 @@ impl KeyHandleImpl for SoftwareKeyHandle {
     }
 
     fn verify_hmac(&self, data: &[u8], tag: &[u8]) -> Result<bool, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::VERIFY, &self.key_id)?;
         let hmac_algorithm = ring_hmac_algorithm_from_signing_hash(self.spec.signing_hash)
             .ok_or_else(|| {
                 CalError::bad_parameter(
@@ -304,12 +652,16 @@ impl KeyHandleImpl for SoftwareKeyHandle {
     }
 
     fn derive_key(&self, nonce: &[u8]) -> Result<KeyHandle, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::DERIVE, &self.key_id)?;
         // `digest` and `blake2` crate have both update functions that get each other int the way.
         use blake2::Blake2bVar;
         use digest::{Update, VariableOutput};
 
         let mut spec = self.spec.clone();
         spec.ephemeral = true;
+        spec.key_usage = KeyUsage::DATA;
         let key_length = spec.cipher.len();
 
         let mut hasher = Blake2bVar::new(key_length).map_err(|e| {
@@ -348,6 +700,38 @@ impl KeyHandleImpl for SoftwareKeyHandle {
         })
     }
 
+    fn encrypt_cbc(&self, data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::ENCRYPT, &self.key_id)?;
+        self.require_cbc_key()?;
+        let iv = iv.try_into().map_err(|_| invalid_cbc_iv_length(iv.len()))?;
+        aes::cbc_encrypt(&self.key, iv, data)
+    }
+
+    fn decrypt_cbc(&self, encrypted_data: &[u8], iv: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::DECRYPT, &self.key_id)?;
+        self.require_cbc_key()?;
+        if iv.len() != aes::BLOCK_LEN {
+            return Err(invalid_cbc_iv_length(iv.len()));
+        }
+        Ok(aes::cbc_decrypt(&self.key, iv, encrypted_data)?.to_vec())
+    }
+
+    fn wrap_key(&self, key: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec.key_usage.require(KeyUsage::WRAP, &self.key_id)?;
+        self.require_aes_key()?;
+        aes::wrap(&self.key, key)
+    }
+
+    fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec.key_usage.require(KeyUsage::WRAP, &self.key_id)?;
+        self.require_aes_key()?;
+        Ok(aes::unwrap(&self.key, wrapped_key)?.to_vec())
+    }
+
     fn extract_key(&self) -> Result<Vec<u8>, CalError> {
         if self.spec.non_exportable {
             Err(CalError::non_exportable())
@@ -356,12 +740,30 @@ impl KeyHandleImpl for SoftwareKeyHandle {
         }
     }
 
+    fn export_encrypted_key(
+        &self,
+        password: &str,
+        kdf: KDF,
+        cipher: Cipher,
+    ) -> Result<Vec<u8>, CalError> {
+        let key = Zeroizing::new(self.extract_key()?);
+        let private_key_info = encoding::export_symmetric_key(self.spec.cipher, &key)?;
+        pbes2::encrypt(&private_key_info, password, kdf, cipher)
+    }
+
     fn id(&self) -> Result<String, CalError> {
         Ok(self.key_id.clone())
     }
 
     #[doc = " Delete this key."]
     fn delete(self) -> Result<(), CalError> {
+        let versions = self.stored_version()?.map(|(versions, _)| versions);
+        if let (Some(versions), Some(s)) = (&versions, &self.storage_manager) {
+            let version_storage = VersionStorage::new(s);
+            for &version in &versions.retained {
+                version_storage.delete(&self.key_id, version)?;
+            }
+        }
         if let Some(s) = &self.storage_manager {
             s.delete(self.key_id.clone()).map_err(|err| {
                 // TODO: Better mapping to CalError.
@@ -376,7 +778,13 @@ impl KeyHandleImpl for SoftwareKeyHandle {
             })?
         }
 
-        Ok(())
+        // The counters go last, a key that outlives a failed delete must not reuse nonces.
+        match (versions, &self.storage_manager) {
+            (Some(versions), Some(s)) => versions.all().into_iter().try_for_each(|version| {
+                KeyUsageCounter::persistent(s, &self.key_id, version, self.spec).delete()
+            }),
+            _ => self.usage.delete(),
+        }
     }
 
     fn spec(&self) -> KeySpec {
@@ -384,8 +792,66 @@ impl KeyHandleImpl for SoftwareKeyHandle {
     }
 }
 
+/// HKDF info for keys derived from the shared secret of a key encapsulation.
+const KEM_KEY_INFO: &[u8] = b"crypto-layer KEM key";
+
+/// Output length of an HKDF expansion.
+struct KeyLength(usize);
+
+impl hkdf::KeyType for KeyLength {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+impl SoftwareKeyPairHandle {
+    /// Creates an ephemeral key handle from a shared secret resulting from a key encapsulation.
+    fn shared_secret_key_handle(&self, shared_secret: &[u8]) -> Result<KeyHandle, CalError> {
+        let cipher = self.spec.cipher.ok_or_else(|| {
+            CalError::bad_parameter(
+                "encapsulate and decapsulate need a KeyPairSpec supplied which cipher is not None.",
+                true,
+                None,
+            )
+        })?;
+
+        // The key is derived with HKDF-SHA256 instead of taking the raw shared secret, so both
+        // sides end up with the same key no matter the hash of their `KeyPairSpec`.
+        let mut key = Zeroizing::new(vec![0; cipher.len()]);
+        hkdf::Salt::new(hkdf::HKDF_SHA256, &[])
+            .extract(shared_secret)
+            .expand(&[KEM_KEY_INFO], KeyLength(cipher.len()))
+            .and_then(|okm| okm.fill(&mut key))
+            .map_err(|_| {
+                CalError::failed_operation(
+                    format!("Failed to derive a {cipher:?} key from the shared secret"),
+                    false,
+                    None,
+                )
+            })?;
+
+        let spec = KeySpec {
+            cipher,
+            signing_hash: self.spec.signing_hash,
+            ephemeral: true,
+            non_exportable: self.spec.non_exportable,
+            not_before: None,
+            not_after: self.spec.not_after,
+            process_not_after: self.spec.process_not_after,
+            key_usage: KeyUsage::DATA,
+        };
+
+        let handle = SoftwareKeyHandle::new(nanoid!(10), spec, key.to_vec(), None)?;
+
+        Ok(KeyHandle {
+            implementation: handle.into(),
+        })
+    }
+}
+
 impl KeyPairHandleImpl for SoftwareKeyPairHandle {
     fn sign_data(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec.key_usage.require(KeyUsage::SIGN, &self.key_id)?;
         let Some(signing_key) = self.signing_key.as_ref() else {
             return Err(CalError::failed_operation(
                 "No private key available for signing".to_string(),
@@ -407,14 +873,15 @@ impl KeyPairHandleImpl for SoftwareKeyPairHandle {
                 // Secure random generator for signing
                 let rng = SystemRandom::new();
 
-                let signing_key = EcdsaKeyPair::from_pkcs8(
-                    self.spec.asym_spec.into(),
-                    signing_key.as_slice(),
-                    &rng,
-                )
-                .map_err(|_| {
-                    CalError::failed_operation("Failed to use signing key".to_string(), true, None)
-                })?;
+                let signing_key =
+                    EcdsaKeyPair::from_pkcs8(self.spec.into(), signing_key.as_slice(), &rng)
+                        .map_err(|_| {
+                            CalError::failed_operation(
+                                "Failed to use signing key".to_string(),
+                                true,
+                                None,
+                            )
+                        })?;
 
                 // Sign the data
                 let signature: Signature = signing_key.sign(&rng, data).map_err(|_| {
@@ -423,11 +890,24 @@ impl KeyPairHandleImpl for SoftwareKeyPairHandle {
 
                 Ok(signature.as_ref().to_vec())
             }
+            spec if spec.is_ml_dsa() => mldsa::sign_data(spec, signing_key, data, &[]),
+            spec if spec.is_composite() => composite::sign_data(spec, signing_key, data),
+            spec if spec.is_slh_dsa() => slhdsa::sign_data(spec, signing_key, data),
+            spec if spec.is_rsa() => rsa::sign_data(
+                spec,
+                self.spec.signing_hash,
+                self.spec.rsa_padding,
+                signing_key,
+                data,
+            ),
             _ => todo!(),
         }
     }
 
     fn verify_signature(&self, data: &[u8], signature: &[u8]) -> Result<bool, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::VERIFY, &self.key_id)?;
         match self.spec.asym_spec {
             AsymmetricKeySpec::Curve25519 => {
                 ed25519_compact::PublicKey::from_slice(self.public_key.as_slice())
@@ -446,27 +926,69 @@ impl KeyPairHandleImpl for SoftwareKeyPairHandle {
             }
             AsymmetricKeySpec::P256 | AsymmetricKeySpec::P384 => {
                 // Create an UnparsedPublicKey using the algorithm and the public key bytes
-                Ok(
-                    UnparsedPublicKey::new(self.spec.asym_spec.into(), &self.public_key)
-                        .verify(data, signature)
-                        .inspect_err(|e| warn!("Failed to verify signature: {e:?}"))
-                        .is_ok(),
-                )
+                Ok(UnparsedPublicKey::new(self.spec.into(), &self.public_key)
+                    .verify(data, signature)
+                    .inspect_err(|e| warn!("Failed to verify signature: {e:?}"))
+                    .is_ok())
             }
+            spec if spec.is_ml_dsa() => {
+                mldsa::verify_signature(spec, &self.public_key, data, signature, &[])
+            }
+            spec if spec.is_composite() => {
+                composite::verify_signature(spec, &self.public_key, data, signature)
+            }
+            spec if spec.is_slh_dsa() => {
+                slhdsa::verify_signature(spec, &self.public_key, data, signature)
+            }
+            spec if spec.is_rsa() => rsa::verify_signature(
+                spec,
+                self.spec.signing_hash,
+                self.spec.rsa_padding,
+                &self.public_key,
+                data,
+                signature,
+            ),
             _ => todo!(),
         }
     }
 
-    fn encrypt_data(&self, _data: &[u8]) -> Result<Vec<u8>, CalError> {
-        Err(CalError::not_implemented())
+    /// Encrypts with RSA-OAEP, using the signing hash of the spec for OAEP and MGF1.
+    fn encrypt_data(&self, data: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::ENCRYPT, &self.key_id)?;
+        match self.spec.asym_spec {
+            spec if spec.is_rsa() => {
+                rsa::encrypt_data(spec, self.spec.signing_hash, &self.public_key, data)
+            }
+            _ => Err(CalError::not_implemented()),
+        }
     }
 
-    fn decrypt_data(&self, _encrypted_data: &[u8]) -> Result<Vec<u8>, CalError> {
-        Err(CalError::not_implemented())
+    fn decrypt_data(&self, encrypted_data: &[u8]) -> Result<Vec<u8>, CalError> {
+        self.spec
+            .key_usage
+            .require(KeyUsage::DECRYPT, &self.key_id)?;
+        let Some(private_key) = self.signing_key.as_ref() else {
+            return Err(CalError::missing_key(self.key_id.clone(), KeyType::Private));
+        };
+        match self.spec.asym_spec {
+            spec if spec.is_rsa() => {
+                Ok(
+                    rsa::decrypt_data(spec, self.spec.signing_hash, private_key, encrypted_data)?
+                        .to_vec(),
+                )
+            }
+            _ => Err(CalError::not_implemented()),
+        }
     }
 
     fn get_public_key(&self) -> Result<Vec<u8>, CalError> {
-        Ok(self.public_key.clone())
+        Ok(encoding::encode_public_key(
+            self.spec.asym_spec,
+            &self.public_key,
+            self.spec.point_encoding,
+        ))
     }
 
     fn extract_key(&self) -> Result<Vec<u8>, CalError> {
@@ -479,8 +1001,120 @@ impl KeyPairHandleImpl for SoftwareKeyPairHandle {
         }
     }
 
+    fn export_private_key(&self, format: KeyFormat) -> Result<Vec<u8>, CalError> {
+        let private_key = self.extract_key()?;
+        encoding::export_private_key(self.spec.asym_spec, &private_key, &self.public_key, format)
+    }
+
+    fn export_public_key(&self, format: KeyFormat) -> Result<Vec<u8>, CalError> {
+        if format == KeyFormat::Raw {
+            return self.get_public_key();
+        }
+        encoding::export_public_key(
+            self.spec.asym_spec,
+            &self.public_key,
+            self.signing_key.as_deref(),
+            format,
+        )
+    }
+
+    fn export_encrypted_private_key(
+        &self,
+        password: &str,
+        kdf: KDF,
+        cipher: Cipher,
+    ) -> Result<Vec<u8>, CalError> {
+        let private_key_info = Zeroizing::new(self.export_private_key(KeyFormat::PKCS8)?);
+        pbes2::encrypt(&private_key_info, password, kdf, cipher)
+    }
+
+    /// Starts a DH exchange with the static private key of this key pair.
+    ///
+    /// `Curve25519` key pairs accept either a raw 32 byte X25519 private key or an Ed25519
+    /// secret key, which is converted to X25519. The public key for the exchange has to be taken
+    /// from [DHExchange::get_public_key], as it differs from the Ed25519 public key.
     fn start_dh_exchange(&self) -> Result<DHExchange, CalError> {
-        todo!("DH exchange not supported")
+        self.spec.key_usage.require(KeyUsage::AGREE, &self.key_id)?;
+        let Some(private_key) = self.signing_key.as_ref() else {
+            return Err(CalError::missing_key(self.key_id.clone(), KeyType::Private));
+        };
+
+        let (private_key, public_key) = match self.spec.asym_spec {
+            AsymmetricKeySpec::Curve25519 => {
+                let private_key: [u8; 32] = match private_key.len() {
+                    32 => private_key
+                        .as_slice()
+                        .try_into()
+                        .expect("Length already checked"),
+                    64 => {
+                        // Same conversion as libsodium's `crypto_sign_ed25519_sk_to_curve25519`.
+                        let hash = Sha512::digest(&private_key[..32]);
+                        hash[..32].try_into().expect("Sha512 output is 64 bytes")
+                    }
+                    len => {
+                        return Err(CalError::bad_parameter(
+                            format!("Invalid Curve25519 private key length: {len} bytes"),
+                            true,
+                            None,
+                        ))
+                    }
+                };
+                let private = StaticSecret::from(private_key);
+                let public = X25519PublicKey::from(&private);
+                (private.to_bytes().to_vec(), public.as_bytes().to_vec())
+            }
+            AsymmetricKeySpec::P256 => {
+                let private = P256SecretKey::from_pkcs8_der(private_key)
+                    .or_else(|_| P256SecretKey::from_slice(private_key))
+                    .map_err(|e| {
+                        CalError::bad_parameter(
+                            "Invalid P-256 private key".to_owned(),
+                            true,
+                            Some(anyhow!(e)),
+                        )
+                    })?;
+                (
+                    private.to_bytes().to_vec(),
+                    private.public_key().to_sec1_bytes().to_vec(),
+                )
+            }
+            spec => {
+                return Err(CalError::unsupported_algorithm(format!(
+                    "{:?} does not support DH exchanges",
+                    spec
+                )))
+            }
+        };
+
+        let dh_exchange = SoftwareDHExchange::from_keypair_bytes(
+            self.key_id.clone(),
+            &private_key,
+            &public_key,
+            self.storage_manager.clone(),
+            self.spec,
+        )?;
+
+        Ok(DHExchange {
+            implementation: dh_exchange.into(),
+        })
+    }
+
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, KeyHandle), CalError> {
+        self.spec.key_usage.require(KeyUsage::AGREE, &self.key_id)?;
+        let (ciphertext, shared_secret) = mlkem::encapsulate_to(self.spec.asym_spec, public_key)?;
+
+        Ok((ciphertext, self.shared_secret_key_handle(&shared_secret)?))
+    }
+
+    fn decapsulate(&self, ciphertext: &[u8]) -> Result<KeyHandle, CalError> {
+        self.spec.key_usage.require(KeyUsage::AGREE, &self.key_id)?;
+        let Some(private_key) = self.signing_key.as_ref() else {
+            return Err(CalError::missing_key(self.key_id.clone(), KeyType::Private));
+        };
+
+        let shared_secret = mlkem::decapsulate_with(self.spec.asym_spec, private_key, ciphertext)?;
+
+        self.shared_secret_key_handle(&shared_secret)
     }
 
     fn id(&self) -> Result<String, CalError> {
@@ -0,0 +1,129 @@
+use ml_dsa::{
+    EncodedSignature, EncodedSigningKey, EncodedVerifyingKey, KeyGen, KeyPair, MlDsa44, MlDsa65,
+    MlDsa87, MlDsaParams, Signature, SigningKey, VerifyingKey, B32,
+};
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use zeroize::Zeroizing;
+
+use crate::common::{crypto::algorithms::encryption::AsymmetricKeySpec, error::CalError};
+
+/// Length of the seed `ξ` from which ML-DSA key pairs are derived.
+const SEED_LEN: usize = 32;
+
+fn invalid_length(name: &str, actual: usize) -> CalError {
+    CalError::bad_parameter(
+        format!("Invalid ML-DSA {name} length: {actual} bytes"),
+        true,
+        None,
+    )
+}
+
+fn generate<P: MlDsaParams + KeyGen<KeyPair = KeyPair<P>>>() -> (Vec<u8>, Vec<u8>) {
+    let mut seed = Zeroizing::new(B32::default());
+    OsRng.fill_bytes(seed.as_mut_slice());
+
+    let key_pair = P::key_gen_internal(&seed);
+    (seed.to_vec(), key_pair.verifying_key().encode().to_vec())
+}
+
+/// Accepts either the 32 byte seed or the expanded private key encoding of FIPS 204.
+fn signing_key<P: MlDsaParams + KeyGen<KeyPair = KeyPair<P>>>(
+    private_key: &[u8],
+) -> Result<SigningKey<P>, CalError> {
+    if private_key.len() == SEED_LEN {
+        let seed = B32::try_from(private_key).expect("Length already checked");
+        return Ok(P::key_gen_internal(&seed).signing_key().clone());
+    }
+
+    let encoded = EncodedSigningKey::<P>::try_from(private_key)
+        .map_err(|_| invalid_length("private key", private_key.len()))?;
+    Ok(SigningKey::decode(&encoded))
+}
+
+fn sign<P: MlDsaParams + KeyGen<KeyPair = KeyPair<P>>>(
+    private_key: &[u8],
+    data: &[u8],
+    context: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let signing_key = signing_key::<P>(private_key)?;
+
+    // Hedged signing.
+    let signature = signing_key
+        .sign_randomized(data, context, &mut OsRng)
+        .map_err(|e| {
+            CalError::failed_operation(
+                "Failed signing with ML-DSA.",
+                false,
+                Some(anyhow::anyhow!(e)),
+            )
+        })?;
+
+    Ok(signature.encode().to_vec())
+}
+
+fn verify<P: MlDsaParams>(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+    context: &[u8],
+) -> Result<bool, CalError> {
+    let encoded = EncodedVerifyingKey::<P>::try_from(public_key)
+        .map_err(|_| invalid_length("public key", public_key.len()))?;
+    let verifying_key = VerifyingKey::<P>::decode(&encoded);
+
+    let Some(signature) = EncodedSignature::<P>::try_from(signature)
+        .ok()
+        .and_then(|encoded| Signature::<P>::decode(&encoded))
+    else {
+        return Ok(false);
+    };
+
+    Ok(verifying_key.verify_with_context(data, context, &signature))
+}
+
+fn unsupported(spec: AsymmetricKeySpec) -> CalError {
+    CalError::unsupported_algorithm(format!("{:?} is not an ML-DSA parameter set", spec))
+}
+
+/// Generates an ML-DSA key pair and returns `(private_key, public_key)`.
+///
+/// The private key is the 32 byte seed, the public key is encoded as defined in FIPS 204.
+pub(super) fn generate_key_pair(spec: AsymmetricKeySpec) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+    match spec {
+        AsymmetricKeySpec::MlDsa44 => Ok(generate::<MlDsa44>()),
+        AsymmetricKeySpec::MlDsa65 => Ok(generate::<MlDsa65>()),
+        AsymmetricKeySpec::MlDsa87 => Ok(generate::<MlDsa87>()),
+        _ => Err(unsupported(spec)),
+    }
+}
+
+/// Signs `data` with the given context string, which is empty for plain ML-DSA signatures.
+pub(super) fn sign_data(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+    data: &[u8],
+    context: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    match spec {
+        AsymmetricKeySpec::MlDsa44 => sign::<MlDsa44>(private_key, data, context),
+        AsymmetricKeySpec::MlDsa65 => sign::<MlDsa65>(private_key, data, context),
+        AsymmetricKeySpec::MlDsa87 => sign::<MlDsa87>(private_key, data, context),
+        _ => Err(unsupported(spec)),
+    }
+}
+
+/// Returns `Ok(false)` for malformed signatures and an error for malformed public keys.
+pub(super) fn verify_signature(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+    context: &[u8],
+) -> Result<bool, CalError> {
+    match spec {
+        AsymmetricKeySpec::MlDsa44 => verify::<MlDsa44>(public_key, data, signature, context),
+        AsymmetricKeySpec::MlDsa65 => verify::<MlDsa65>(public_key, data, signature, context),
+        AsymmetricKeySpec::MlDsa87 => verify::<MlDsa87>(public_key, data, signature, context),
+        _ => Err(unsupported(spec)),
+    }
+}
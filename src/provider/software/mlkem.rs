@@ -0,0 +1,165 @@
+use anyhow::anyhow;
+use ml_kem::{
+    kem::{Decapsulate, Encapsulate},
+    Ciphertext, Encoded, EncodedSizeUser, KemCore, MlKem1024, MlKem512, MlKem768,
+};
+use p256::elliptic_curve::rand_core::OsRng;
+use zeroize::Zeroizing;
+
+use crate::common::{crypto::algorithms::encryption::AsymmetricKeySpec, error::CalError};
+
+/// The modulus `q` of ML-KEM.
+const Q: u16 = 3329;
+
+/// Length of the seed `rho` at the end of an encapsulation key.
+const RHO_LEN: usize = 32;
+
+fn invalid_length(name: &str, expected: usize, actual: usize) -> CalError {
+    CalError::bad_parameter(
+        format!("Invalid ML-KEM {name} length: expected {expected} bytes, got {actual}"),
+        true,
+        None,
+    )
+}
+
+fn generate<K: KemCore>() -> (Vec<u8>, Vec<u8>) {
+    let (decapsulation_key, encapsulation_key) = K::generate(&mut OsRng);
+    (
+        decapsulation_key.as_bytes().to_vec(),
+        encapsulation_key.as_bytes().to_vec(),
+    )
+}
+
+/// The encapsulation key check of FIPS 203 §7.2: the encoded polynomials in front of `rho` have
+/// to hold 12 bit coefficients smaller than `q`, otherwise decoding would silently reduce them.
+fn check_encapsulation_key<K: KemCore>(public_key: &[u8]) -> Result<(), CalError> {
+    let expected = Encoded::<K::EncapsulationKey>::default().len();
+    if public_key.len() != expected {
+        return Err(invalid_length("public key", expected, public_key.len()));
+    }
+
+    let reduced = public_key[..expected - RHO_LEN]
+        .chunks_exact(3)
+        .all(|bytes| {
+            let first = u16::from(bytes[0]) | (u16::from(bytes[1] & 0x0f) << 8);
+            let second = u16::from(bytes[1] >> 4) | (u16::from(bytes[2]) << 4);
+            first < Q && second < Q
+        });
+    if !reduced {
+        return Err(CalError::bad_parameter(
+            "Invalid ML-KEM public key: coefficient not reduced modulo q".to_string(),
+            true,
+            None,
+        ));
+    }
+
+    Ok(())
+}
+
+fn encapsulate<K: KemCore>(public_key: &[u8]) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), CalError> {
+    check_encapsulation_key::<K>(public_key)?;
+    let encoded = Encoded::<K::EncapsulationKey>::try_from(public_key).map_err(|_| {
+        invalid_length(
+            "public key",
+            Encoded::<K::EncapsulationKey>::default().len(),
+            public_key.len(),
+        )
+    })?;
+    let encapsulation_key = K::EncapsulationKey::from_bytes(&encoded);
+
+    let (ciphertext, shared_key) = encapsulation_key.encapsulate(&mut OsRng).map_err(|e| {
+        CalError::failed_operation(
+            "Failed ML-KEM encapsulation.",
+            false,
+            Some(anyhow!("{e:?}")),
+        )
+    })?;
+
+    Ok((ciphertext.to_vec(), Zeroizing::new(shared_key.to_vec())))
+}
+
+fn decapsulate<K: KemCore>(
+    private_key: &[u8],
+    ciphertext: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let encoded = Encoded::<K::DecapsulationKey>::try_from(private_key).map_err(|_| {
+        invalid_length(
+            "private key",
+            Encoded::<K::DecapsulationKey>::default().len(),
+            private_key.len(),
+        )
+    })?;
+    let decapsulation_key = K::DecapsulationKey::from_bytes(&encoded);
+
+    let ciphertext = Ciphertext::<K>::try_from(ciphertext).map_err(|_| {
+        invalid_length(
+            "ciphertext",
+            Ciphertext::<K>::default().len(),
+            ciphertext.len(),
+        )
+    })?;
+
+    // ML-KEM uses implicit rejection: an invalid ciphertext results in a pseudorandom shared key.
+    let shared_key = decapsulation_key.decapsulate(&ciphertext).map_err(|e| {
+        CalError::failed_operation(
+            "Failed ML-KEM decapsulation.",
+            false,
+            Some(anyhow!("{e:?}")),
+        )
+    })?;
+
+    Ok(Zeroizing::new(shared_key.to_vec()))
+}
+
+fn unsupported(spec: AsymmetricKeySpec) -> CalError {
+    CalError::unsupported_algorithm(format!("{:?} is not a key encapsulation mechanism", spec))
+}
+
+/// Generates an ML-KEM key pair and returns `(private_key, public_key)`.
+///
+/// The private key is the expanded decapsulation key defined in FIPS 203.
+pub(super) fn generate_key_pair(spec: AsymmetricKeySpec) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+    match spec {
+        AsymmetricKeySpec::MlKem512 => Ok(generate::<MlKem512>()),
+        AsymmetricKeySpec::MlKem768 => Ok(generate::<MlKem768>()),
+        AsymmetricKeySpec::MlKem1024 => Ok(generate::<MlKem1024>()),
+        _ => Err(unsupported(spec)),
+    }
+}
+
+/// Checks that `public_key` is a valid encapsulation key of `spec`.
+pub(super) fn check_public_key(spec: AsymmetricKeySpec, public_key: &[u8]) -> Result<(), CalError> {
+    match spec {
+        AsymmetricKeySpec::MlKem512 => check_encapsulation_key::<MlKem512>(public_key),
+        AsymmetricKeySpec::MlKem768 => check_encapsulation_key::<MlKem768>(public_key),
+        AsymmetricKeySpec::MlKem1024 => check_encapsulation_key::<MlKem1024>(public_key),
+        _ => Err(unsupported(spec)),
+    }
+}
+
+/// Encapsulates a fresh shared secret to `public_key` and returns `(ciphertext, shared_secret)`.
+pub(super) fn encapsulate_to(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), CalError> {
+    match spec {
+        AsymmetricKeySpec::MlKem512 => encapsulate::<MlKem512>(public_key),
+        AsymmetricKeySpec::MlKem768 => encapsulate::<MlKem768>(public_key),
+        AsymmetricKeySpec::MlKem1024 => encapsulate::<MlKem1024>(public_key),
+        _ => Err(unsupported(spec)),
+    }
+}
+
+/// Decapsulates the shared secret from `ciphertext` with `private_key`.
+pub(super) fn decapsulate_with(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+    ciphertext: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    match spec {
+        AsymmetricKeySpec::MlKem512 => decapsulate::<MlKem512>(private_key, ciphertext),
+        AsymmetricKeySpec::MlKem768 => decapsulate::<MlKem768>(private_key, ciphertext),
+        AsymmetricKeySpec::MlKem1024 => decapsulate::<MlKem1024>(private_key, ciphertext),
+        _ => Err(unsupported(spec)),
+    }
+}
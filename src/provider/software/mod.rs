@@ -1,8 +1,8 @@
 use crate::{
     common::{
-        config::{ProviderConfig, ProviderImplConfig, SecurityLevel},
+        config::{KeyPairSpec, ProviderConfig, ProviderImplConfig, SecurityLevel},
         crypto::algorithms::{
-            encryption::{AsymmetricKeySpec, Cipher},
+            encryption::{AsymmetricKeySpec, Cipher, SignatureEncoding},
             hashes::CryptoHash,
         },
         error::CalError,
@@ -14,16 +14,30 @@ use ring::{
     aead, agreement,
     signature::{
         EcdsaSigningAlgorithm, VerificationAlgorithm, ECDSA_P256_SHA256_ASN1,
-        ECDSA_P256_SHA256_ASN1_SIGNING, ECDSA_P384_SHA384_ASN1, ECDSA_P384_SHA384_ASN1_SIGNING,
+        ECDSA_P256_SHA256_ASN1_SIGNING, ECDSA_P256_SHA256_FIXED, ECDSA_P256_SHA256_FIXED_SIGNING,
+        ECDSA_P384_SHA384_ASN1, ECDSA_P384_SHA384_ASN1_SIGNING, ECDSA_P384_SHA384_FIXED,
+        ECDSA_P384_SHA384_FIXED_SIGNING,
     },
 };
 use serde::{Deserialize, Serialize};
 
 use std::collections::HashSet;
 
+mod aes;
+mod composite;
+mod encoding;
+mod hybrid;
 pub(crate) mod key_handle;
+mod mldsa;
+mod mlkem;
+mod password;
+mod pbes2;
 pub(crate) mod provider;
+mod rsa;
+mod slhdsa;
+mod usage;
 mod util;
+mod versions;
 
 #[derive(Default)]
 pub(crate) struct SoftwareProviderFactory {}
@@ -37,6 +51,32 @@ impl ProviderFactory for SoftwareProviderFactory {
         let mut supported_asym_specs = HashSet::new();
         supported_asym_specs.insert(AsymmetricKeySpec::P256);
         supported_asym_specs.insert(AsymmetricKeySpec::Curve25519);
+        supported_asym_specs.insert(AsymmetricKeySpec::RSA2048);
+        supported_asym_specs.insert(AsymmetricKeySpec::RSA3072);
+        supported_asym_specs.insert(AsymmetricKeySpec::RSA4096);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlKem512);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlKem768);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlKem1024);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa44);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa65);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa87);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaSha2_128s);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaSha2_128f);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaSha2_192s);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaSha2_192f);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaSha2_256s);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaSha2_256f);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaShake128s);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaShake128f);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaShake192s);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaShake192f);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaShake256s);
+        supported_asym_specs.insert(AsymmetricKeySpec::SlhDsaShake256f);
+        supported_asym_specs.insert(AsymmetricKeySpec::X25519MlKem768);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa44Ed25519);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa65Ed25519);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa44EcdsaP256);
+        supported_asym_specs.insert(AsymmetricKeySpec::MlDsa65EcdsaP256);
 
         let mut cipher_set = HashSet::new();
         cipher_set.insert(Cipher::AesGcm128);
@@ -80,16 +120,28 @@ pub(super) struct KeyPairMetadata {
     pub hash: CryptoHash,
 }
 
-impl From<AsymmetricKeySpec> for &'static dyn VerificationAlgorithm {
-    fn from(spec: AsymmetricKeySpec) -> Self {
-        match spec {
-            AsymmetricKeySpec::P256 => &ECDSA_P256_SHA256_ASN1,
-            AsymmetricKeySpec::P384 => &ECDSA_P384_SHA384_ASN1,
+impl From<KeyPairSpec> for &'static dyn VerificationAlgorithm {
+    fn from(spec: KeyPairSpec) -> Self {
+        match (spec.asym_spec, spec.signature_encoding) {
+            (AsymmetricKeySpec::P256, SignatureEncoding::Der) => &ECDSA_P256_SHA256_ASN1,
+            (AsymmetricKeySpec::P256, SignatureEncoding::P1363) => &ECDSA_P256_SHA256_FIXED,
+            (AsymmetricKeySpec::P384, SignatureEncoding::Der) => &ECDSA_P384_SHA384_ASN1,
+            (AsymmetricKeySpec::P384, SignatureEncoding::P1363) => &ECDSA_P384_SHA384_FIXED,
             _ => panic!("Unsupported ECC curve or scheme"),
         }
     }
 }
 
+impl From<KeyPairSpec> for &'static EcdsaSigningAlgorithm {
+    fn from(spec: KeyPairSpec) -> Self {
+        match (spec.asym_spec, spec.signature_encoding) {
+            (AsymmetricKeySpec::P256, SignatureEncoding::P1363) => &ECDSA_P256_SHA256_FIXED_SIGNING,
+            (AsymmetricKeySpec::P384, SignatureEncoding::P1363) => &ECDSA_P384_SHA384_FIXED_SIGNING,
+            (asym_spec, _) => asym_spec.into(),
+        }
+    }
+}
+
 impl From<AsymmetricKeySpec> for &'static EcdsaSigningAlgorithm {
     fn from(spec: AsymmetricKeySpec) -> Self {
         match spec {
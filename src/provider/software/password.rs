@@ -0,0 +1,243 @@
+use anyhow::anyhow;
+use argon2::{
+    password_hash::{
+        rand_core::OsRng, Error as PasswordHashError, PasswordHash, PasswordHasher,
+        PasswordVerifier, SaltString,
+    },
+    Argon2, Params,
+};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use sha2::{Sha256, Sha512};
+use tracing::error;
+
+use crate::{
+    common::{
+        crypto::algorithms::key_derivation::{Argon2Options, PasswordVerification},
+        error::CalError,
+    },
+    prelude::{CryptoHash, KDF},
+};
+
+fn argon2_algorithm_from_kdf(kdf: KDF) -> Option<(argon2::Algorithm, Argon2Options)> {
+    match kdf {
+        KDF::Argon2i(o) => Some((argon2::Algorithm::Argon2i, o)),
+        KDF::Argon2d(o) => Some((argon2::Algorithm::Argon2d, o)),
+        KDF::Argon2id(o) => Some((argon2::Algorithm::Argon2id, o)),
+        KDF::Scrypt(_) | KDF::Pbkdf2(_) => None,
+    }
+}
+
+fn unsupported_pbkdf2_hash() -> CalError {
+    CalError::bad_parameter(
+        "SoftwareProvider only supports Sha2_256 and Sha2_512 for PBKDF2.",
+        true,
+        None,
+    )
+}
+
+fn pbkdf2_algorithm_from_hash(hash: CryptoHash) -> Result<pbkdf2::Algorithm, CalError> {
+    match hash {
+        CryptoHash::Sha2_256 => Ok(pbkdf2::Algorithm::Pbkdf2Sha256),
+        CryptoHash::Sha2_512 => Ok(pbkdf2::Algorithm::Pbkdf2Sha512),
+        _ => Err(unsupported_pbkdf2_hash()),
+    }
+}
+
+fn scrypt_params(log_n: u8, r: u32, p: u32, len: usize) -> Result<scrypt::Params, CalError> {
+    scrypt::Params::new(log_n, r, p, len).map_err(|e| {
+        CalError::bad_parameter(
+            format!("Invalid scrypt parameters: {}", e),
+            true,
+            Some(anyhow!(e)),
+        )
+    })
+}
+
+/// Derives `key_length` bytes from a password with scrypt or PBKDF2.
+///
+/// Argon2 is handled by the provider itself.
+pub(super) fn derive_key_material(
+    password: &str,
+    salt: &[u8],
+    kdf: KDF,
+    key_length: usize,
+) -> Result<Vec<u8>, CalError> {
+    let mut derived_key = vec![0u8; key_length];
+
+    match kdf {
+        KDF::Scrypt(o) => {
+            let params = scrypt_params(o.log_n, o.r, o.p, key_length)?;
+            scrypt::scrypt(password.as_bytes(), salt, &params, &mut derived_key).map_err(|e| {
+                CalError::failed_operation(
+                    "Failed derivation of key with scrypt.",
+                    false,
+                    Some(anyhow!(e)),
+                )
+            })?;
+        }
+        KDF::Pbkdf2(o) => match o.hash {
+            CryptoHash::Sha2_256 => pbkdf2::pbkdf2_hmac::<Sha256>(
+                password.as_bytes(),
+                salt,
+                o.iterations,
+                &mut derived_key,
+            ),
+            CryptoHash::Sha2_512 => pbkdf2::pbkdf2_hmac::<Sha512>(
+                password.as_bytes(),
+                salt,
+                o.iterations,
+                &mut derived_key,
+            ),
+            _ => return Err(unsupported_pbkdf2_hash()),
+        },
+        _ => return Err(CalError::not_implemented()),
+    }
+
+    Ok(derived_key)
+}
+
+/// Hashes a password with a random salt and returns a PHC string.
+pub(super) fn hash_password(password: &str, kdf: KDF) -> Result<String, CalError> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    let password_hash = if let Some((algorithm, options)) = argon2_algorithm_from_kdf(kdf) {
+        let params = Params::new(
+            options.memory,
+            options.iterations,
+            options.parallelism,
+            None,
+        )
+        .map_err(|e| {
+            CalError::bad_parameter(
+                format!("Invalid Argon2 parameters: {}", e),
+                true,
+                Some(anyhow!(e)),
+            )
+        })?;
+
+        Argon2::new(algorithm, argon2::Version::V0x13, params)
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    } else {
+        match kdf {
+            KDF::Scrypt(o) => {
+                let params = scrypt_params(o.log_n, o.r, o.p, scrypt::Params::RECOMMENDED_LEN)?;
+                Scrypt
+                    .hash_password_customized(password.as_bytes(), None, None, params, &salt)
+                    .map(|hash| hash.to_string())
+            }
+            KDF::Pbkdf2(o) => {
+                let algorithm = pbkdf2_algorithm_from_hash(o.hash)?;
+                let params = pbkdf2::Params {
+                    rounds: o.iterations,
+                    ..Default::default()
+                };
+                Pbkdf2
+                    .hash_password_customized(
+                        password.as_bytes(),
+                        Some(algorithm.ident()),
+                        None,
+                        params,
+                        &salt,
+                    )
+                    .map(|hash| hash.to_string())
+            }
+            _ => unreachable!("Argon2 is handled above."),
+        }
+    };
+
+    password_hash.map_err(|e| {
+        error!("Failed hashing password.");
+        CalError::failed_operation("Failed hashing password.", false, Some(anyhow!(e)))
+    })
+}
+
+/// Returns `true` if `password_hash` was not created with the algorithm and parameters of `kdf`.
+fn needs_rehash(password_hash: &PasswordHash, kdf: KDF) -> Result<bool, PasswordHashError> {
+    if let Some((algorithm, options)) = argon2_algorithm_from_kdf(kdf) {
+        if password_hash.algorithm != algorithm.ident()
+            || password_hash.version != Some(argon2::Version::V0x13.into())
+        {
+            return Ok(true);
+        }
+
+        let params = Params::try_from(password_hash)?;
+        return Ok(params.m_cost() != options.memory
+            || params.t_cost() != options.iterations
+            || params.p_cost() != options.parallelism);
+    }
+
+    match kdf {
+        KDF::Scrypt(o) => {
+            if password_hash.algorithm != scrypt::ALG_ID {
+                return Ok(true);
+            }
+
+            let params = scrypt::Params::try_from(password_hash)?;
+            Ok(params.log_n() != o.log_n || params.r() != o.r || params.p() != o.p)
+        }
+        KDF::Pbkdf2(o) => {
+            let Ok(algorithm) = pbkdf2_algorithm_from_hash(o.hash) else {
+                return Ok(true);
+            };
+            if password_hash.algorithm != algorithm.ident() {
+                return Ok(true);
+            }
+
+            let params = pbkdf2::Params::try_from(password_hash)?;
+            Ok(params.rounds != o.iterations)
+        }
+        _ => unreachable!("Argon2 is handled above."),
+    }
+}
+
+/// Verifies a password against a PHC string.
+///
+/// The algorithm used for verification is taken from the PHC string, not from `kdf`.
+pub(super) fn verify_password(
+    password: &str,
+    password_hash: &str,
+    kdf: KDF,
+) -> Result<PasswordVerification, CalError> {
+    let parsed_hash = PasswordHash::new(password_hash).map_err(|e| {
+        CalError::bad_parameter(
+            "Password hash is not a valid PHC string.",
+            true,
+            Some(anyhow!(e)),
+        )
+    })?;
+
+    let argon2 = Argon2::default();
+    let verifier: &dyn PasswordVerifier = match parsed_hash.algorithm.as_str() {
+        "argon2i" | "argon2d" | "argon2id" => &argon2,
+        "scrypt" => &Scrypt,
+        "pbkdf2-sha256" | "pbkdf2-sha512" => &Pbkdf2,
+        algorithm => return Err(CalError::unsupported_algorithm(algorithm)),
+    };
+
+    let valid = match verifier.verify_password(password.as_bytes(), &parsed_hash) {
+        Ok(()) => true,
+        Err(PasswordHashError::Password) => false,
+        Err(e) => {
+            return Err(CalError::bad_parameter(
+                "Password hash contains invalid parameters.",
+                true,
+                Some(anyhow!(e)),
+            ))
+        }
+    };
+
+    let needs_rehash = needs_rehash(&parsed_hash, kdf).map_err(|e| {
+        CalError::bad_parameter(
+            "Password hash contains invalid parameters.",
+            true,
+            Some(anyhow!(e)),
+        )
+    })?;
+
+    Ok(PasswordVerification {
+        valid,
+        needs_rehash,
+    })
+}
@@ -0,0 +1,408 @@
+//! Password protected PKCS#8 `EncryptedPrivateKeyInfo` with PBES2 (RFC 8018).
+//!
+//! The key encryption key is derived with PBKDF2 or scrypt (RFC 7914), the private key info is
+//! encrypted with AES-256-CBC or AES-256-GCM (RFC 5084).
+
+use anyhow::anyhow;
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use pkcs8::{
+    der::{
+        asn1::{Any, AnyRef, Null, OctetStringRef},
+        Decode, Encode, Reader, Tag,
+    },
+    spki::AlgorithmIdentifierRef,
+};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use zeroize::Zeroizing;
+
+use super::{
+    aes::{self, BLOCK_LEN},
+    password,
+};
+use crate::{
+    common::{crypto::pkcs::standards::OidType, error::CalError},
+    prelude::{Cipher, CryptoHash, Pbkdf2Options, ScryptOptions, KDF},
+};
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const TAG_LEN: usize = 16;
+
+/// Limits of the KDF parameters of imported keys, which would otherwise let a crafted file
+/// exhaust the memory or block the caller.
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const MAX_SCRYPT_LOG_N: u32 = 20;
+/// Limit of the scrypt memory `128 * r * N`.
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+const MAX_SCRYPT_P: u32 = 16;
+
+fn encoding_failed(error: impl Into<anyhow::Error>) -> CalError {
+    CalError::failed_operation(
+        "Failed to encode encrypted private key".to_owned(),
+        true,
+        Some(error.into()),
+    )
+}
+
+fn invalid(error: impl Into<anyhow::Error>) -> CalError {
+    CalError::bad_parameter(
+        "Invalid encrypted private key".to_owned(),
+        true,
+        Some(error.into()),
+    )
+}
+
+fn unsupported(what: impl std::fmt::Display) -> CalError {
+    CalError::unsupported_algorithm(format!(
+        "{what} is not supported for encrypted private keys"
+    ))
+}
+
+fn check_limit(what: &str, value: u64, limit: u64) -> Result<(), CalError> {
+    if value > limit {
+        return Err(CalError::bad_parameter(
+            format!("{what} {value} of the encrypted private key exceeds the limit of {limit}"),
+            true,
+            None,
+        ));
+    }
+    Ok(())
+}
+
+fn decryption_failed() -> CalError {
+    CalError::bad_parameter(
+        "Failed to decrypt private key, the password may be wrong".to_owned(),
+        true,
+        None,
+    )
+}
+
+fn der(value: impl Encode) -> Result<Vec<u8>, CalError> {
+    value.to_der().map_err(encoding_failed)
+}
+
+fn sequence(elements: &[&[u8]]) -> Result<Vec<u8>, CalError> {
+    Any::new(Tag::Sequence, elements.concat())
+        .and_then(|sequence| sequence.to_der())
+        .map_err(encoding_failed)
+}
+
+fn algorithm(algorithm: OidType, parameters: &[u8]) -> Result<Vec<u8>, CalError> {
+    sequence(&[&der(algorithm.oid())?, parameters])
+}
+
+fn octet_string(bytes: &[u8]) -> Result<Vec<u8>, CalError> {
+    der(OctetStringRef::new(bytes).map_err(encoding_failed)?)
+}
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn kdf_algorithm(kdf: KDF, salt: &[u8]) -> Result<Vec<u8>, CalError> {
+    match kdf {
+        KDF::Pbkdf2(options) => {
+            let prf = match options.hash {
+                CryptoHash::Sha2_256 => OidType::HmacWithSha256,
+                CryptoHash::Sha2_512 => OidType::HmacWithSha512,
+                hash => return Err(unsupported(format!("PBKDF2 with {hash:?}"))),
+            };
+            let parameters = sequence(&[
+                &octet_string(salt)?,
+                &der(options.iterations)?,
+                &der(KEY_LEN as u32)?,
+                &algorithm(prf, &der(Null)?)?,
+            ])?;
+            algorithm(OidType::Pbkdf2, &parameters)
+        }
+        KDF::Scrypt(options) => {
+            let cost = 1u64.checked_shl(options.log_n.into()).ok_or_else(|| {
+                CalError::bad_parameter(
+                    format!("Invalid scrypt cost: 2^{}", options.log_n),
+                    true,
+                    None,
+                )
+            })?;
+            let parameters = sequence(&[
+                &octet_string(salt)?,
+                &der(cost)?,
+                &der(options.r)?,
+                &der(options.p)?,
+                &der(KEY_LEN as u32)?,
+            ])?;
+            algorithm(OidType::IdScrypt, &parameters)
+        }
+        kdf => Err(unsupported(format!("{kdf:?}"))),
+    }
+}
+
+fn gcm_key(key: &[u8]) -> Result<LessSafeKey, CalError> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|e| {
+            CalError::failed_operation("Invalid AES key".to_owned(), true, Some(anyhow!(e)))
+        })
+}
+
+/// Encrypts the DER encoded `private_key_info` with a key derived from `password`.
+///
+/// `kdf` has to be [KDF::Pbkdf2] or [KDF::Scrypt], `cipher` [Cipher::AesCbc256] or
+/// [Cipher::AesGcm256].
+pub(super) fn encrypt(
+    private_key_info: &[u8],
+    password: &str,
+    kdf: KDF,
+    cipher: Cipher,
+) -> Result<Vec<u8>, CalError> {
+    let salt = random::<SALT_LEN>();
+    let kdf_algorithm = kdf_algorithm(kdf, &salt)?;
+    if !matches!(cipher, Cipher::AesCbc256 | Cipher::AesGcm256) {
+        return Err(unsupported(format!("{cipher:?}")));
+    }
+    let key = Zeroizing::new(password::derive_key_material(
+        password, &salt, kdf, KEY_LEN,
+    )?);
+
+    let (scheme, encrypted) = match cipher {
+        Cipher::AesCbc256 => {
+            let iv = random::<BLOCK_LEN>();
+            (
+                algorithm(OidType::Aes256Cbc, &octet_string(&iv)?)?,
+                aes::cbc_encrypt(&key, &iv, private_key_info)?,
+            )
+        }
+        _ => {
+            let nonce = random::<NONCE_LEN>();
+            let mut in_out = private_key_info.to_vec();
+            gcm_key(&key)?
+                .seal_in_place_append_tag(
+                    Nonce::assume_unique_for_key(nonce),
+                    Aad::empty(),
+                    &mut in_out,
+                )
+                .map_err(|e| {
+                    CalError::failed_operation(
+                        "Encryption failed".to_owned(),
+                        true,
+                        Some(anyhow!(e)),
+                    )
+                })?;
+            let parameters = sequence(&[&octet_string(&nonce)?, &der(TAG_LEN as u8)?])?;
+            (algorithm(OidType::Aes256Gcm, &parameters)?, in_out)
+        }
+    };
+
+    let pbes2 = algorithm(OidType::Pbes2, &sequence(&[&kdf_algorithm, &scheme])?)?;
+    sequence(&[&pbes2, &octet_string(&encrypted)?])
+}
+
+fn parse_kdf(kdf: AlgorithmIdentifierRef<'_>) -> Result<(KDF, Vec<u8>), CalError> {
+    let parameters = kdf
+        .parameters
+        .ok_or_else(|| invalid(anyhow!("Missing KDF parameters")))?;
+
+    let (kdf_type, salt, key_len) = if kdf.oid == OidType::Pbkdf2.oid() {
+        parameters
+            .sequence(|reader| {
+                let salt = reader.decode::<OctetStringRef<'_>>()?;
+                let iterations = reader.decode::<u32>()?;
+                let key_len = reader.decode::<Option<u32>>()?;
+                let prf = reader.decode::<Option<AlgorithmIdentifierRef<'_>>>()?;
+                Ok((salt, iterations, key_len, prf.map(|prf| prf.oid)))
+            })
+            .map_err(invalid)
+            .and_then(|(salt, iterations, key_len, prf)| {
+                let hash = match prf {
+                    Some(prf) if prf == OidType::HmacWithSha256.oid() => CryptoHash::Sha2_256,
+                    Some(prf) if prf == OidType::HmacWithSha512.oid() => CryptoHash::Sha2_512,
+                    prf => {
+                        return Err(unsupported(format!(
+                            "PBKDF2 pseudo random function {}",
+                            prf.map_or("HMAC-SHA1".to_owned(), |prf| prf.to_string())
+                        )))
+                    }
+                };
+                check_limit(
+                    "PBKDF2 iteration count",
+                    iterations.into(),
+                    MAX_PBKDF2_ITERATIONS.into(),
+                )?;
+                Ok((
+                    KDF::Pbkdf2(Pbkdf2Options { iterations, hash }),
+                    salt.as_bytes().to_vec(),
+                    key_len,
+                ))
+            })?
+    } else if kdf.oid == OidType::IdScrypt.oid() {
+        let (salt, cost, r, p, key_len) = parameters
+            .sequence(|reader| {
+                Ok((
+                    reader.decode::<OctetStringRef<'_>>()?,
+                    reader.decode::<u64>()?,
+                    reader.decode::<u32>()?,
+                    reader.decode::<u32>()?,
+                    reader.decode::<Option<u32>>()?,
+                ))
+            })
+            .map_err(invalid)?;
+        if !cost.is_power_of_two() {
+            return Err(invalid(anyhow!("scrypt cost {cost} is not a power of two")));
+        }
+        let log_n = cost.trailing_zeros();
+        check_limit(
+            "scrypt cost exponent",
+            log_n.into(),
+            MAX_SCRYPT_LOG_N.into(),
+        )?;
+        check_limit(
+            "scrypt memory",
+            128 * u64::from(r) * cost,
+            MAX_SCRYPT_MEMORY,
+        )?;
+        check_limit("scrypt parallelization", p.into(), MAX_SCRYPT_P.into())?;
+        (
+            KDF::Scrypt(ScryptOptions {
+                log_n: log_n as u8,
+                r,
+                p,
+            }),
+            salt.as_bytes().to_vec(),
+            key_len,
+        )
+    } else {
+        return Err(unsupported(format!("Key derivation {}", kdf.oid)));
+    };
+
+    if key_len.is_some_and(|key_len| key_len as usize != KEY_LEN) {
+        return Err(unsupported(format!(
+            "Key length {}",
+            key_len.unwrap_or_default()
+        )));
+    }
+
+    Ok((kdf_type, salt))
+}
+
+/// Decrypts an `EncryptedPrivateKeyInfo` and returns the DER encoded private key info.
+pub(super) fn decrypt(
+    encrypted_private_key_info: &[u8],
+    password: &str,
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let (pbes2, encrypted) = AnyRef::from_der(encrypted_private_key_info)
+        .and_then(|info| {
+            info.sequence(|reader| {
+                Ok((
+                    reader.decode::<AlgorithmIdentifierRef<'_>>()?,
+                    reader.decode::<OctetStringRef<'_>>()?,
+                ))
+            })
+        })
+        .map_err(invalid)?;
+
+    if pbes2.oid != OidType::Pbes2.oid() {
+        return Err(unsupported(format!("Encryption scheme {}", pbes2.oid)));
+    }
+    let (kdf, scheme) = pbes2
+        .parameters
+        .ok_or_else(|| invalid(anyhow!("Missing PBES2 parameters")))?
+        .sequence(|reader| {
+            Ok((
+                reader.decode::<AlgorithmIdentifierRef<'_>>()?,
+                reader.decode::<AlgorithmIdentifierRef<'_>>()?,
+            ))
+        })
+        .map_err(invalid)?;
+
+    let (kdf, salt) = parse_kdf(kdf)?;
+    let scheme_parameters = scheme
+        .parameters
+        .ok_or_else(|| invalid(anyhow!("Missing encryption scheme parameters")))?;
+    let key = Zeroizing::new(password::derive_key_material(
+        password, &salt, kdf, KEY_LEN,
+    )?);
+
+    if scheme.oid == OidType::Aes256Cbc.oid() {
+        let iv = scheme_parameters
+            .decode_as::<OctetStringRef<'_>>()
+            .map_err(invalid)?;
+        aes::cbc_decrypt(&key, iv.as_bytes(), encrypted.as_bytes()).map_err(|_| decryption_failed())
+    } else if scheme.oid == OidType::Aes256Gcm.oid() {
+        let (nonce, tag_len) = scheme_parameters
+            .sequence(|reader| {
+                Ok((
+                    reader.decode::<OctetStringRef<'_>>()?,
+                    reader.decode::<Option<u8>>()?,
+                ))
+            })
+            .map_err(invalid)?;
+        if tag_len != Some(TAG_LEN as u8) {
+            return Err(unsupported(format!(
+                "AES-GCM tag length {}",
+                tag_len.unwrap_or(12)
+            )));
+        }
+        let nonce =
+            Nonce::try_assume_unique_for_key(nonce.as_bytes()).map_err(|e| invalid(anyhow!(e)))?;
+
+        let mut in_out = Zeroizing::new(encrypted.as_bytes().to_vec());
+        let len = gcm_key(&key)?
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| decryption_failed())?
+            .len();
+        in_out.truncate(len);
+        Ok(in_out)
+    } else {
+        Err(unsupported(format!("Encryption scheme {}", scheme.oid)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::error::CalErrorKind;
+
+    fn parse(kdf: KDF) -> Result<(KDF, Vec<u8>), CalError> {
+        let kdf_algorithm = kdf_algorithm(kdf, &[0; SALT_LEN])?;
+        parse_kdf(AlgorithmIdentifierRef::from_der(&kdf_algorithm).map_err(invalid)?)
+    }
+
+    fn pbkdf2(iterations: u32) -> KDF {
+        KDF::Pbkdf2(Pbkdf2Options {
+            iterations,
+            hash: CryptoHash::Sha2_256,
+        })
+    }
+
+    fn scrypt(log_n: u8, r: u32, p: u32) -> KDF {
+        KDF::Scrypt(ScryptOptions { log_n, r, p })
+    }
+
+    #[test]
+    fn test_kdf_limits() {
+        for kdf in [
+            pbkdf2(MAX_PBKDF2_ITERATIONS),
+            scrypt(MAX_SCRYPT_LOG_N as u8, 8, 1),
+            scrypt(10, 8, MAX_SCRYPT_P),
+        ] {
+            assert!(parse(kdf).is_ok());
+        }
+
+        // Rejected before any key is derived, so these return immediately.
+        for kdf in [
+            pbkdf2(MAX_PBKDF2_ITERATIONS + 1),
+            pbkdf2(u32::MAX),
+            scrypt(MAX_SCRYPT_LOG_N as u8 + 1, 8, 1),
+            scrypt(63, 8, 1),
+            scrypt(MAX_SCRYPT_LOG_N as u8, 9, 1),
+            scrypt(10, u32::MAX, 1),
+            scrypt(10, 8, MAX_SCRYPT_P + 1),
+        ] {
+            assert!(matches!(
+                parse(kdf).unwrap_err().error_kind(),
+                CalErrorKind::BadParameter { .. }
+            ));
+        }
+    }
+}
@@ -1,26 +1,34 @@
 use super::{
+    composite, encoding, hybrid,
     key_handle::{SoftwareKeyHandle, SoftwareKeyPairHandle},
+    mldsa, mlkem, password, pbes2, rsa, slhdsa,
+    usage::KeyUsageCounter,
+    versions::{KeyVersions, VersionStorage},
     SoftwareProvider, SoftwareProviderFactory, StorageManager,
 };
 use crate::{
     common::{
-        config::{KeyPairSpec, KeySpec, ProviderConfig, Spec},
-        crypto::algorithms::encryption::{AsymmetricKeySpec, Cipher},
-        error::CalError,
+        config::{KeyPairSpec, KeySpec, KeyUsage, ProviderConfig, Spec},
+        crypto::{
+            algorithms::encryption::{AsymmetricKeySpec, Cipher},
+            hkdf,
+            pkcs::standards::KeyFormat,
+        },
+        error::{CalError, KeyType},
         traits::{
             key_handle::DHKeyExchangeImpl,
             module_provider::{ProviderFactory, ProviderImpl},
         },
         DHExchange, KeyHandle, KeyPairHandle,
     },
-    prelude::{CryptoHash, KDF},
+    prelude::{CryptoHash, PasswordVerification, KDF},
     storage::KeyData,
 };
 use anyhow::anyhow;
 use argon2::{
     password_hash::SaltString, Argon2, Params, PasswordHasher, MAX_SALT_LEN, MIN_SALT_LEN,
 };
-use blake2::{Blake2b512, Digest};
+use blake2::{Blake2b512, Blake2s256, Digest};
 use itertools::Itertools;
 use nanoid::nanoid;
 use p256::{
@@ -30,12 +38,14 @@ use p256::{
 use ring::{
     aead::Algorithm,
     digest::{digest, SHA256, SHA384, SHA512, SHA512_256},
+    hmac,
     rand::{SecureRandom, SystemRandom},
     signature::{EcdsaKeyPair, EcdsaSigningAlgorithm, KeyPair},
 };
 use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
 use tracing::{error, info};
 use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroizing;
 
 impl ProviderImpl for SoftwareProvider {
     fn create_key(&mut self, spec: KeySpec) -> Result<KeyHandle, CalError> {
@@ -51,38 +61,7 @@ impl ProviderImpl for SoftwareProvider {
 
         let key_id = nanoid!(10);
 
-        // Initialize the system random generator
-        let rng = SystemRandom::new();
-
-        // Handle XChaCha20Poly1305 specially since it's not supported by ring
-        let key_data = if spec.cipher == Cipher::XChaCha20Poly1305 {
-            // XChaCha20Poly1305 requires a 256-bit (32-byte) key
-            let mut key_data = vec![0u8; 32];
-            rng.fill(&mut key_data).map_err(|e| {
-                error!("Failed to generate XChaCha20Poly1305 key");
-                CalError::failed_operation(
-                    "Failed to generate XChaCha20Poly1305 key".to_owned(),
-                    false,
-                    Some(anyhow!(e)),
-                )
-            })?;
-            key_data
-        } else {
-            // For ring-supported ciphers, use the existing From implementation
-            let algo: &Algorithm = spec.cipher.into();
-
-            // Generate the symmetric key data
-            let mut key_data = vec![0u8; algo.key_len()];
-            rng.fill(&mut key_data).map_err(|e| {
-                error!("Failed to generate symmetric key");
-                CalError::failed_operation(
-                    "Failed to generate symmetric key".to_owned(),
-                    false,
-                    Some(anyhow!(e)),
-                )
-            })?;
-            key_data
-        };
+        let key_data = Self::generate_key(spec)?;
 
         let storage_data = KeyData {
             id: key_id.clone(),
@@ -106,12 +85,25 @@ impl ProviderImpl for SoftwareProvider {
             self.storage_manager.clone()
         };
 
+        let usage = storage_manager
+            .as_ref()
+            .map(|storage_manager| {
+                KeyUsageCounter::persistent(
+                    storage_manager,
+                    &key_id,
+                    KeyVersions::default().current,
+                    spec,
+                )
+            })
+            .unwrap_or_default();
+
         // Initialize SoftwareKeyHandle with the key data
         let handle = SoftwareKeyHandle {
             key_id,
             key: key_data,
             storage_manager: storage_manager.clone(),
             spec,
+            usage,
         };
 
         Ok(KeyHandle {
@@ -140,6 +132,7 @@ impl ProviderImpl for SoftwareProvider {
             ));
         };
 
+        let version = KeyVersions::from_key_data(&storage_data)?.current;
         let Some(key_data) = storage_data.secret_data else {
             error!("No sensitive data for key found.");
             return Err(CalError::failed_operation(
@@ -149,12 +142,20 @@ impl ProviderImpl for SoftwareProvider {
             ));
         };
 
+        let usage = KeyUsageCounter::persistent(
+            self.storage_manager.as_ref().unwrap(),
+            &key_id,
+            version,
+            spec,
+        );
+
         // Initialize SoftwareKeyHandle with the LessSafeKey
         let handle = SoftwareKeyHandle {
             key_id,
             key: key_data,
             storage_manager: self.storage_manager.clone(),
             spec,
+            usage,
         };
 
         Ok(KeyHandle {
@@ -175,48 +176,55 @@ impl ProviderImpl for SoftwareProvider {
 
         let key_id = nanoid!(10);
 
-        let storage_data = if let AsymmetricKeySpec::Curve25519 = spec.asym_spec {
-            let keypair = ed25519_compact::KeyPair::from_seed(ed25519_compact::Seed::default());
-            KeyData {
-                id: key_id.clone(),
-                secret_data: Some(keypair.sk.to_vec()),
-                public_data: Some(keypair.pk.to_vec()),
-                additional_data: None,
-                spec: Spec::KeyPairSpec(spec),
+        let (private_key, public_key) = match spec.asym_spec {
+            AsymmetricKeySpec::Curve25519 => {
+                let keypair = ed25519_compact::KeyPair::from_seed(ed25519_compact::Seed::default());
+                (keypair.sk.to_vec(), keypair.pk.to_vec())
             }
-        } else {
-            // Generate ECC key pair using ring's SystemRandom for asymmetric keys
-            let rng = SystemRandom::new();
-            let algorithm: &EcdsaSigningAlgorithm = spec.asym_spec.into();
-            let pkcs8_bytes = EcdsaKeyPair::generate_pkcs8(algorithm, &rng).map_err(|e| {
-                error!("Failed to generate private key");
-                CalError::failed_operation(
-                    "Failed to generate private key".to_owned(),
-                    false,
-                    Some(anyhow!(e)),
-                )
-            })?;
-
-            // Create an EcdsaKeyPair from the PKCS#8-encoded private key
-            let key_pair = EcdsaKeyPair::from_pkcs8(algorithm, pkcs8_bytes.as_ref(), &rng)
-                .map_err(|e| {
-                    error!("Failed to parse key pair");
+            asym_spec if asym_spec.is_kem() => mlkem::generate_key_pair(asym_spec)?,
+            asym_spec if asym_spec.is_ml_dsa() => mldsa::generate_key_pair(asym_spec)?,
+            asym_spec if asym_spec.is_slh_dsa() => slhdsa::generate_key_pair(asym_spec)?,
+            asym_spec if asym_spec.is_composite() => composite::generate_key_pair(asym_spec)?,
+            asym_spec if asym_spec.is_rsa() => rsa::generate_key_pair(asym_spec)?,
+            _ => {
+                // Generate ECC key pair using ring's SystemRandom for asymmetric keys
+                let rng = SystemRandom::new();
+                let algorithm: &EcdsaSigningAlgorithm = spec.asym_spec.into();
+                let pkcs8_bytes = EcdsaKeyPair::generate_pkcs8(algorithm, &rng).map_err(|e| {
+                    error!("Failed to generate private key");
                     CalError::failed_operation(
-                        "Failed to parse key pair".to_owned(),
+                        "Failed to generate private key".to_owned(),
                         false,
                         Some(anyhow!(e)),
                     )
                 })?;
 
-            KeyData {
-                id: key_id.clone(),
-                secret_data: Some(pkcs8_bytes.as_ref().to_vec()),
-                public_data: Some(key_pair.public_key().as_ref().to_vec()),
-                additional_data: None,
-                spec: Spec::KeyPairSpec(spec),
+                // Create an EcdsaKeyPair from the PKCS#8-encoded private key
+                let key_pair = EcdsaKeyPair::from_pkcs8(algorithm, pkcs8_bytes.as_ref(), &rng)
+                    .map_err(|e| {
+                        error!("Failed to parse key pair");
+                        CalError::failed_operation(
+                            "Failed to parse key pair".to_owned(),
+                            false,
+                            Some(anyhow!(e)),
+                        )
+                    })?;
+
+                (
+                    pkcs8_bytes.as_ref().to_vec(),
+                    key_pair.public_key().as_ref().to_vec(),
+                )
             }
         };
 
+        let storage_data = KeyData {
+            id: key_id.clone(),
+            secret_data: Some(private_key),
+            public_data: Some(public_key),
+            additional_data: None,
+            spec: Spec::KeyPairSpec(spec),
+        };
+
         if self.storage_manager.is_some() && !spec.ephemeral {
             self.storage_manager
                 .as_ref()
@@ -308,8 +316,16 @@ impl ProviderImpl for SoftwareProvider {
         };
 
         // Initialize SoftwareKeyHandle with the raw key data
-        let handle =
+        let mut handle =
             SoftwareKeyHandle::new(key_id.clone(), spec, data.to_vec(), storage_manager.clone())?;
+        if let Some(storage_manager) = &storage_manager {
+            handle.usage = KeyUsageCounter::persistent(
+                storage_manager,
+                &key_id,
+                KeyVersions::default().current,
+                spec,
+            );
+        }
 
         // store key
         let storage_data = KeyData {
@@ -347,6 +363,10 @@ impl ProviderImpl for SoftwareProvider {
             ));
         }
 
+        // Ed25519 secret keys are 64 bytes, X25519 private keys 32 bytes.
+        let x25519 = private_key.len() != ed25519_compact::SecretKey::BYTES;
+        let public_key = &encoding::validate_public_key(spec.asym_spec, public_key, x25519)?;
+
         let key_id = nanoid!(10);
 
         let storage_manager = if spec.ephemeral {
@@ -384,52 +404,56 @@ impl ProviderImpl for SoftwareProvider {
         })
     }
 
+    /// Raw Ed25519 public keys are expected for `Curve25519`.
     fn import_public_key(
         &mut self,
         spec: KeyPairSpec,
         public_key: &[u8],
     ) -> Result<KeyPairHandle, CalError> {
-        if self.storage_manager.is_none() && !spec.ephemeral {
-            error!("This is an ephemeral provider, it cannot import non-ephemeral keys");
-            return Err(CalError::failed_operation(
-                "This is an ephemeral provider, it cannot import non-ephemeral keys".to_owned(),
-                true,
-                None,
-            ));
-        }
-
-        let key_id = nanoid!(10);
-
-        let storage_manager = if spec.ephemeral {
-            None
-        } else {
-            self.storage_manager.clone()
-        };
+        let public_key = encoding::validate_public_key(spec.asym_spec, public_key, false)?;
+        self.public_key_handle(spec, public_key)
+    }
 
-        let handle = SoftwareKeyPairHandle {
-            key_id: key_id.clone(),
-            spec,
-            public_key: public_key.to_vec(),
-            signing_key: None,
-            storage_manager: storage_manager.clone(),
-        };
+    fn import_key_pair_with_format(
+        &mut self,
+        spec: KeyPairSpec,
+        private_key: &[u8],
+        format: KeyFormat,
+    ) -> Result<KeyPairHandle, CalError> {
+        let (private_key, public_key) =
+            encoding::import_private_key(spec.asym_spec, private_key, format)?;
+        self.import_key_pair(spec, &public_key, &private_key)
+    }
 
-        let storage_data = KeyData {
-            id: key_id.clone(),
-            secret_data: None,
-            public_data: Some(public_key.to_vec()),
-            additional_data: None,
-            spec: Spec::KeyPairSpec(spec),
-        };
+    fn import_public_key_with_format(
+        &mut self,
+        spec: KeyPairSpec,
+        public_key: &[u8],
+        format: KeyFormat,
+    ) -> Result<KeyPairHandle, CalError> {
+        let public_key = encoding::import_public_key(spec.asym_spec, public_key, format)?;
+        self.public_key_handle(spec, public_key)
+    }
 
-        storage_manager
-            .as_ref()
-            .map(|s| s.store(key_id.clone(), storage_data))
-            .transpose()?;
+    fn import_encrypted_key(
+        &mut self,
+        spec: KeySpec,
+        data: &[u8],
+        password: &str,
+    ) -> Result<KeyHandle, CalError> {
+        let private_key_info = pbes2::decrypt(data, password)?;
+        let key = encoding::import_symmetric_key(spec.cipher, &private_key_info)?;
+        self.import_key(spec, &key)
+    }
 
-        Ok(KeyPairHandle {
-            implementation: handle.into(),
-        })
+    fn import_encrypted_key_pair(
+        &mut self,
+        spec: KeyPairSpec,
+        data: &[u8],
+        password: &str,
+    ) -> Result<KeyPairHandle, CalError> {
+        let private_key_info = pbes2::decrypt(data, password)?;
+        self.import_key_pair_with_format(spec, &private_key_info, KeyFormat::PKCS8)
     }
 
     fn start_ephemeral_dh_exchange(&mut self, spec: KeyPairSpec) -> Result<DHExchange, CalError> {
@@ -499,8 +523,6 @@ impl ProviderImpl for SoftwareProvider {
     }
 
     /// Derives a high-entropy key from a low-entropy password and a unique salt.
-    ///
-    /// Only Argon2 is currently supported.
     fn derive_key_from_password(
         &self,
         password: &str,
@@ -508,13 +530,6 @@ impl ProviderImpl for SoftwareProvider {
         algorithm: KeySpec,
         kdf: KDF,
     ) -> Result<KeyHandle, CalError> {
-        let (argo2_algorithm, argon2_option) = match kdf {
-            KDF::Argon2i(o) => (argon2::Algorithm::Argon2i, o),
-            KDF::Argon2d(o) => (argon2::Algorithm::Argon2d, o),
-            KDF::Argon2id(o) => (argon2::Algorithm::Argon2id, o),
-            // _ => return Err(CalError::not_implemented()),
-        };
-
         if salt.len() < 8 || salt.len() > 64 {
             return Err(CalError::bad_parameter(
                 format!(
@@ -541,6 +556,16 @@ impl ProviderImpl for SoftwareProvider {
             }
         };
 
+        let (argo2_algorithm, argon2_option) = match kdf {
+            KDF::Argon2i(o) => (argon2::Algorithm::Argon2i, o),
+            KDF::Argon2d(o) => (argon2::Algorithm::Argon2d, o),
+            KDF::Argon2id(o) => (argon2::Algorithm::Argon2id, o),
+            KDF::Scrypt(_) | KDF::Pbkdf2(_) => {
+                let derived_key = password::derive_key_material(password, salt, kdf, key_length)?;
+                return Ok(self.derived_key_handle(algorithm, derived_key));
+            }
+        };
+
         // Create Argon2 with specified algorithm
         let argon2 = Argon2::new(
             argo2_algorithm,
@@ -574,17 +599,20 @@ impl ProviderImpl for SoftwareProvider {
         // Extract the raw hash output and truncate to the required key length
         let derived_key = password_hash.hash.unwrap().as_bytes()[..key_length].to_vec();
 
-        let key_id = nanoid!(10);
-        let handle = SoftwareKeyHandle {
-            key_id: key_id.clone(),
-            spec: algorithm,
-            key: derived_key,
-            storage_manager: self.storage_manager.clone(),
-        };
+        Ok(self.derived_key_handle(algorithm, derived_key))
+    }
 
-        Ok(KeyHandle {
-            implementation: handle.into(),
-        })
+    fn hash_password(&self, password: &str, kdf: KDF) -> Result<String, CalError> {
+        password::hash_password(password, kdf)
+    }
+
+    fn verify_password(
+        &self,
+        password: &str,
+        password_hash: &str,
+        kdf: KDF,
+    ) -> Result<PasswordVerification, CalError> {
+        password::verify_password(password, password_hash, kdf)
     }
 
     fn derive_key_from_base(
@@ -637,6 +665,7 @@ impl ProviderImpl for SoftwareProvider {
             spec: algorithm,
             key: derived_key,
             storage_manager: self.storage_manager.clone(),
+            usage: KeyUsageCounter::default(),
         };
 
         Ok(KeyHandle {
@@ -667,11 +696,258 @@ impl ProviderImpl for SoftwareProvider {
                 hasher.update(input);
                 hasher.finalize().to_vec()
             }
+            CryptoHash::Blake2s => Blake2s256::digest(input).to_vec(),
             _ => unimplemented!(),
         };
 
         Ok(result)
     }
+
+    fn store_secret_data(&mut self, id: String, data: &[u8]) -> Result<(), CalError> {
+        let storage_data = KeyData {
+            id: id.clone(),
+            secret_data: Some(data.to_vec()),
+            public_data: None,
+            additional_data: None,
+            // Not used for secret data.
+            spec: Spec::KeySpec(KeySpec::default()),
+        };
+
+        self.secret_data_storage()?.store(id, storage_data)?;
+        Ok(())
+    }
+
+    fn load_secret_data(&self, id: String) -> Result<Vec<u8>, CalError> {
+        self.secret_data_storage()?
+            .get(id.clone())?
+            .secret_data
+            .ok_or_else(|| {
+                CalError::failed_operation(
+                    format!("No secret data found for id '{id}'"),
+                    true,
+                    None,
+                )
+            })
+    }
+
+    fn delete_secret_data(&mut self, id: String) -> Result<(), CalError> {
+        self.secret_data_storage()?.delete(id)?;
+        Ok(())
+    }
+
+    fn rotate_key(&mut self, key_id: String) -> Result<KeyHandle, CalError> {
+        let storage_manager = self.key_storage("rotate keys")?;
+        let key_data = storage_manager.get(key_id.clone())?;
+
+        let Spec::KeySpec(spec) = key_data.spec else {
+            return Err(CalError::bad_parameter(
+                "Only symmetric keys can be rotated".to_owned(),
+                true,
+                None,
+            ));
+        };
+        let mut versions = KeyVersions::from_key_data(&key_data)?;
+        let previous_key = key_data
+            .secret_data
+            .ok_or_else(|| CalError::missing_key(key_id.clone(), KeyType::Symmetric))?;
+        let next_version = versions.current.checked_add(1).ok_or_else(|| {
+            CalError::failed_operation(format!("Key {key_id} has no versions left"), true, None)
+        })?;
+
+        // The previous version is stored first, so that it is never lost.
+        VersionStorage::new(&storage_manager).store(
+            &key_id,
+            versions.current,
+            spec,
+            previous_key,
+        )?;
+        versions.retained.push(versions.current);
+        versions.current = next_version;
+
+        let storage_data = KeyData {
+            id: key_id.clone(),
+            secret_data: Some(Self::generate_key(spec)?),
+            public_data: None,
+            additional_data: Some(versions.to_bytes()?),
+            spec: Spec::KeySpec(spec),
+        };
+        // The new version is counted by a counter of its own, the counter of the previous
+        // version is kept so that handles loaded before the rotation never restart it.
+        storage_manager.store(key_id.clone(), storage_data)?;
+
+        self.load_key(key_id)
+    }
+
+    fn retire_key_version(&mut self, key_id: String, version: u32) -> Result<(), CalError> {
+        let storage_manager = self.key_storage("retire key versions")?;
+        let key_data = storage_manager.get(key_id.clone())?;
+        let mut versions = KeyVersions::from_key_data(&key_data)?;
+        let spec = key_data.spec;
+
+        if version == versions.current {
+            return Err(CalError::bad_parameter(
+                format!("Version {version} is the current version of key {key_id}"),
+                true,
+                None,
+            ));
+        }
+        let Some(position) = versions.retained.iter().position(|&v| v == version) else {
+            return Err(CalError::missing_key(
+                format!("{key_id} version {version}"),
+                KeyType::Symmetric,
+            ));
+        };
+        versions.retained.remove(position);
+
+        // The metadata is updated first, so that a retired version is never referenced.
+        storage_manager.store(
+            key_id.clone(),
+            KeyData {
+                additional_data: Some(versions.to_bytes()?),
+                ..key_data
+            },
+        )?;
+        VersionStorage::new(&storage_manager).delete(&key_id, version)?;
+        let Spec::KeySpec(spec) = spec else {
+            return Ok(());
+        };
+        KeyUsageCounter::persistent(&storage_manager, &key_id, version, spec).delete()
+    }
+
+    fn key_versions(&self, key_id: String) -> Result<Vec<u32>, CalError> {
+        let key_data = self.key_storage("load key versions")?.get(key_id)?;
+        Ok(KeyVersions::from_key_data(&key_data)?.all())
+    }
+}
+
+impl SoftwareProvider {
+    /// Storage for [ProviderImpl::store_secret_data], separated from the keys.
+    fn secret_data_storage(&self) -> Result<StorageManager, CalError> {
+        self.storage_manager
+            .as_ref()
+            .map(|storage_manager| storage_manager.sub_scope("SecretData"))
+            .ok_or_else(|| {
+                CalError::failed_operation(
+                    "This is an ephemeral provider, it cannot store data".to_owned(),
+                    true,
+                    None,
+                )
+            })
+    }
+
+    /// Storage of the keys, failing for ephemeral providers.
+    fn key_storage(&self, operation: &str) -> Result<StorageManager, CalError> {
+        self.storage_manager.clone().ok_or_else(|| {
+            error!("This is an ephemeral provider, it cannot {operation}");
+            CalError::failed_operation(
+                format!("This is an ephemeral provider, it cannot {operation}"),
+                true,
+                None,
+            )
+        })
+    }
+
+    /// Generates the material of a symmetric key.
+    fn generate_key(spec: KeySpec) -> Result<Vec<u8>, CalError> {
+        // Initialize the system random generator
+        let rng = SystemRandom::new();
+
+        // Handle XChaCha20Poly1305 and AES-CBC specially since they are not supported by ring
+        if matches!(
+            spec.cipher,
+            Cipher::XChaCha20Poly1305 | Cipher::AesCbc128 | Cipher::AesCbc256
+        ) {
+            let mut key_data = vec![0u8; spec.cipher.len()];
+            rng.fill(&mut key_data).map_err(|e| {
+                error!("Failed to generate {:?} key", spec.cipher);
+                CalError::failed_operation(
+                    format!("Failed to generate {:?} key", spec.cipher),
+                    false,
+                    Some(anyhow!(e)),
+                )
+            })?;
+            Ok(key_data)
+        } else {
+            // For ring-supported ciphers, use the existing From implementation
+            let algo: &Algorithm = spec.cipher.into();
+
+            // Generate the symmetric key data
+            let mut key_data = vec![0u8; algo.key_len()];
+            rng.fill(&mut key_data).map_err(|e| {
+                error!("Failed to generate symmetric key");
+                CalError::failed_operation(
+                    "Failed to generate symmetric key".to_owned(),
+                    false,
+                    Some(anyhow!(e)),
+                )
+            })?;
+            Ok(key_data)
+        }
+    }
+
+    /// Creates a handle for an already validated public key.
+    fn public_key_handle(
+        &mut self,
+        spec: KeyPairSpec,
+        public_key: Vec<u8>,
+    ) -> Result<KeyPairHandle, CalError> {
+        if self.storage_manager.is_none() && !spec.ephemeral {
+            error!("This is an ephemeral provider, it cannot import non-ephemeral keys");
+            return Err(CalError::failed_operation(
+                "This is an ephemeral provider, it cannot import non-ephemeral keys".to_owned(),
+                true,
+                None,
+            ));
+        }
+
+        let key_id = nanoid!(10);
+
+        let storage_manager = if spec.ephemeral {
+            None
+        } else {
+            self.storage_manager.clone()
+        };
+
+        let handle = SoftwareKeyPairHandle {
+            key_id: key_id.clone(),
+            spec,
+            public_key: public_key.clone(),
+            signing_key: None,
+            storage_manager: storage_manager.clone(),
+        };
+
+        let storage_data = KeyData {
+            id: key_id.clone(),
+            secret_data: None,
+            public_data: Some(public_key),
+            additional_data: None,
+            spec: Spec::KeyPairSpec(spec),
+        };
+
+        storage_manager
+            .as_ref()
+            .map(|s| s.store(key_id.clone(), storage_data))
+            .transpose()?;
+
+        Ok(KeyPairHandle {
+            implementation: handle.into(),
+        })
+    }
+
+    // Create a key handle from key material derived from a password
+    fn derived_key_handle(&self, spec: KeySpec, derived_key: Vec<u8>) -> KeyHandle {
+        let handle = SoftwareKeyHandle {
+            key_id: nanoid!(10),
+            spec,
+            key: derived_key,
+            storage_manager: self.storage_manager.clone(),
+            usage: KeyUsageCounter::default(),
+        };
+
+        KeyHandle {
+            implementation: handle.into(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -681,6 +957,8 @@ pub(crate) struct SoftwareDHExchange {
     public_key_bytes: Vec<u8>,
     storage_manager: Option<StorageManager>,
     spec: KeyPairSpec,
+    /// Key confirmation key and role, set once session keys are derived.
+    confirmation: Option<(Zeroizing<Vec<u8>>, bool)>,
 }
 
 impl SoftwareDHExchange {
@@ -690,6 +968,7 @@ impl SoftwareDHExchange {
         storage_manager: Option<StorageManager>,
         spec: KeyPairSpec,
     ) -> Result<Self, CalError> {
+        spec.key_usage.require(KeyUsage::AGREE, &key_id)?;
         match spec.asym_spec {
             AsymmetricKeySpec::Curve25519 => {
                 // Generate a new Curve25519 private key using a cryptographically secure RNG
@@ -703,6 +982,7 @@ impl SoftwareDHExchange {
                     public_key_bytes: public.as_bytes().to_vec(),
                     storage_manager,
                     spec,
+                    confirmation: None,
                 })
             }
             AsymmetricKeySpec::P256 => {
@@ -717,6 +997,21 @@ impl SoftwareDHExchange {
                     public_key_bytes: public.to_sec1_bytes().to_vec(),
                     storage_manager,
                     spec,
+                    confirmation: None,
+                })
+            }
+            AsymmetricKeySpec::X25519MlKem768 => {
+                // The public key starts out as the client share and is replaced by the
+                // server share once this exchange derives server session keys.
+                let (private_key_bytes, public_key_bytes) = hybrid::generate_key_pair()?;
+
+                Ok(Self {
+                    key_id,
+                    private_key_bytes,
+                    public_key_bytes,
+                    storage_manager,
+                    spec,
+                    confirmation: None,
                 })
             }
             _ => Err(CalError::failed_operation(
@@ -735,6 +1030,7 @@ impl SoftwareDHExchange {
         storage_manager: Option<StorageManager>,
         spec: KeyPairSpec,
     ) -> Result<Self, CalError> {
+        spec.key_usage.require(KeyUsage::AGREE, &key_id)?;
         // Validate that the provided key pair is valid
         match spec.asym_spec {
             AsymmetricKeySpec::Curve25519 => {
@@ -768,6 +1064,18 @@ impl SoftwareDHExchange {
                     ));
                 }
             }
+            AsymmetricKeySpec::X25519MlKem768 => {
+                if private_key.len() != hybrid::PRIVATE_KEY_LEN
+                    || (public_key.len() != hybrid::CLIENT_SHARE_LEN
+                        && public_key.len() != hybrid::SERVER_SHARE_LEN)
+                {
+                    return Err(CalError::failed_operation(
+                        "Invalid X25519MLKEM768 key length".to_string(),
+                        true,
+                        None,
+                    ));
+                }
+            }
             _ => {
                 return Err(CalError::failed_operation(
                     "Unsupported algorithm".to_string(),
@@ -784,91 +1092,41 @@ impl SoftwareDHExchange {
             public_key_bytes: public_key.to_vec(),
             storage_manager,
             spec,
+            confirmation: None,
         })
     }
 
-    /// Computes the shared secret between the local private key and a peer's public key.
-    fn compute_shared_secret(&self, peer_public_key: &[u8]) -> Result<Vec<u8>, CalError> {
-        match self.spec.asym_spec {
-            AsymmetricKeySpec::Curve25519 => {
-                // Convert our private key bytes to a 32-byte array
-                let private_key_bytes: [u8; 32] =
-                    self.private_key_bytes.as_slice().try_into().map_err(|_| {
-                        CalError::failed_operation(
-                            "Invalid private key length".to_owned(),
-                            true,
-                            None,
-                        )
-                    })?;
-                // Create a StaticSecret from the private key bytes
-                let private = StaticSecret::from(private_key_bytes);
-
-                // Convert peer public key bytes to a 32-byte array
-                let peer_public_bytes: [u8; 32] = peer_public_key.try_into().map_err(|_| {
-                    CalError::failed_operation(
-                        "Invalid peer public key length".to_owned(),
-                        true,
-                        None,
-                    )
-                })?;
-                // Create a PublicKey from the peer public key bytes
-                let peer_public = X25519PublicKey::from(peer_public_bytes);
-
-                // Perform Diffie-Hellman key exchange
-                let shared_secret = private.diffie_hellman(&peer_public);
-                Ok(shared_secret.as_bytes().to_vec())
-            }
-            AsymmetricKeySpec::P256 => {
-                // Deserialize our P-256 private key
-                let private_key_bytes: [u8; 32] =
-                    self.private_key_bytes.as_slice().try_into().map_err(|_| {
-                        CalError::failed_operation(
-                            "Invalid private key length".to_owned(),
-                            true,
-                            None,
-                        )
-                    })?;
-
-                let private =
-                    P256SecretKey::from_bytes((&private_key_bytes).into()).map_err(|e| {
-                        CalError::failed_operation(
-                            "Failed to create P-256 private key".to_owned(),
-                            false,
-                            Some(anyhow!(e)),
-                        )
-                    })?;
-
-                // Deserialize the peer's P-256 public key (in SEC1 format)
-                let peer_public = P256PublicKey::from_sec1_bytes(peer_public_key).map_err(|e| {
-                    CalError::failed_operation(
-                        "Invalid P-256 public key format".to_owned(),
-                        true,
-                        Some(anyhow!(e)),
-                    )
-                })?;
-
-                // Perform ECDH using the low-level diffie_hellman function
-                let shared_secret =
-                    diffie_hellman(private.to_nonzero_scalar(), peer_public.as_affine());
-
-                Ok(shared_secret.raw_secret_bytes().to_vec())
-            }
-            _ => Err(CalError::failed_operation(
-                "Unsupported algorithm".to_string(),
-                true,
-                None,
-            )),
-        }
-    }
-
     /// Generates session keys from the shared secret, matching libsodium's behavior.
+    ///
+    /// For `X25519MlKem768` the server encapsulates to the client share and replaces its own
+    /// public key with the resulting server share. Hashing both shares together with the
+    /// concatenated secrets combines the two key exchanges.
     fn generate_session_keys(
-        &self,
+        &mut self,
         peer_public_key: &[u8],
         is_client: bool,
     ) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+        // Compressed and uncompressed peer keys derive the same session keys.
+        let peer_public_key = match self.spec.asym_spec {
+            AsymmetricKeySpec::P256 => {
+                &encoding::validate_public_key(AsymmetricKeySpec::P256, peer_public_key, true)?
+            }
+            _ => peer_public_key,
+        };
+
         // Compute the shared secret first
-        let shared_secret = self.compute_shared_secret(peer_public_key)?;
+        let shared_secret = match self.spec.asym_spec {
+            AsymmetricKeySpec::X25519MlKem768 if is_client => {
+                hybrid::finish(&self.private_key_bytes, peer_public_key)?
+            }
+            AsymmetricKeySpec::X25519MlKem768 => {
+                let (server_share, shared_secret) =
+                    hybrid::respond(&self.private_key_bytes, peer_public_key)?;
+                self.public_key_bytes = server_share;
+                shared_secret
+            }
+            _ => Zeroizing::new(self.compute_shared_secret(peer_public_key)?),
+        };
 
         // Initialize Blake2b hasher (512-bit/64-byte output)
         let mut hasher = Blake2b512::new();
@@ -884,9 +1142,28 @@ impl SoftwareDHExchange {
         }
 
         // Finalize and obtain the 64-byte key material
-        let key_material = hasher.finalize();
+        let key_material = Zeroizing::new(hasher.finalize().to_vec());
         let keys = key_material.as_slice();
 
+        let (client_public_key, server_public_key) = if is_client {
+            (self.public_key_bytes.as_slice(), peer_public_key)
+        } else {
+            (peer_public_key, self.public_key_bytes.as_slice())
+        };
+        let mut confirmation_input = b"crypto-layer key confirmation".to_vec();
+        for public_key in [client_public_key, server_public_key] {
+            confirmation_input.extend_from_slice(&(public_key.len() as u64).to_be_bytes());
+            confirmation_input.extend_from_slice(public_key);
+        }
+        let confirmation_key = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, &key_material),
+            &confirmation_input,
+        );
+        self.confirmation = Some((
+            Zeroizing::new(confirmation_key.as_ref().to_vec()),
+            is_client,
+        ));
+
         // Split into receive (rx) and transmit (tx) keys, consistent with libsodium's implementation
         let (rx, tx) = if is_client {
             (keys[0..32].to_vec(), keys[32..64].to_vec())
@@ -897,6 +1174,32 @@ impl SoftwareDHExchange {
         Ok((rx, tx))
     }
 
+    /// MAC over the role and `transcript` with the key confirmation key.
+    fn key_confirmation_tag(
+        &self,
+        is_client: bool,
+        transcript: Option<&[u8]>,
+    ) -> Result<hmac::Tag, CalError> {
+        let (confirmation_key, _) = self.confirmation.as_ref().ok_or_else(|| {
+            CalError::failed_operation(
+                "Session keys have to be derived before key confirmation".to_owned(),
+                true,
+                None,
+            )
+        })?;
+
+        let mut input = if is_client {
+            b"client".to_vec()
+        } else {
+            b"server".to_vec()
+        };
+        input.extend_from_slice(transcript.unwrap_or_default());
+        Ok(hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, confirmation_key),
+            &input,
+        ))
+    }
+
     // Create a key handle from derived key material
     fn create_key_handle(
         &self,
@@ -926,7 +1229,12 @@ impl SoftwareDHExchange {
                 ephemeral: self.spec.ephemeral,
                 signing_hash: self.spec.signing_hash,
                 non_exportable: self.spec.non_exportable,
+                not_before: None,
+                not_after: self.spec.not_after,
+                process_not_after: self.spec.process_not_after,
+                key_usage: KeyUsage::DATA,
             },
+            usage: KeyUsageCounter::default(),
         };
 
         // Convert to KeyHandle
@@ -943,7 +1251,120 @@ impl DHKeyExchangeImpl for SoftwareDHExchange {
 
     /// Returns the public key as bytes for sharing with the peer
     fn get_public_key(&self) -> Result<Vec<u8>, CalError> {
-        Ok(self.public_key_bytes.clone())
+        Ok(encoding::encode_public_key(
+            self.spec.asym_spec,
+            &self.public_key_bytes,
+            self.spec.point_encoding,
+        ))
+    }
+
+    /// Computes the shared secret between the local private key and a peer's public key.
+    ///
+    /// The peer's public key has to be a point of the prime order subgroup, `P256` points may be
+    /// compressed.
+    fn compute_shared_secret(&self, peer_public_key: &[u8]) -> Result<Vec<u8>, CalError> {
+        match self.spec.asym_spec {
+            AsymmetricKeySpec::Curve25519 => {
+                // Convert our private key bytes to a 32-byte array
+                let private_key_bytes: [u8; 32] =
+                    self.private_key_bytes.as_slice().try_into().map_err(|_| {
+                        CalError::failed_operation(
+                            "Invalid private key length".to_owned(),
+                            true,
+                            None,
+                        )
+                    })?;
+                // Create a StaticSecret from the private key bytes
+                let private = StaticSecret::from(private_key_bytes);
+
+                // Rejects low order points and points on the twist
+                encoding::validate_public_key(
+                    AsymmetricKeySpec::Curve25519,
+                    peer_public_key,
+                    true,
+                )?;
+
+                // Convert peer public key bytes to a 32-byte array
+                let peer_public_bytes: [u8; 32] = peer_public_key.try_into().map_err(|_| {
+                    CalError::failed_operation(
+                        "Invalid peer public key length".to_owned(),
+                        true,
+                        None,
+                    )
+                })?;
+                // Create a PublicKey from the peer public key bytes
+                let peer_public = X25519PublicKey::from(peer_public_bytes);
+
+                // Perform Diffie-Hellman key exchange
+                let shared_secret = private.diffie_hellman(&peer_public);
+                Ok(shared_secret.as_bytes().to_vec())
+            }
+            AsymmetricKeySpec::P256 => {
+                // Deserialize our P-256 private key
+                let private_key_bytes: [u8; 32] =
+                    self.private_key_bytes.as_slice().try_into().map_err(|_| {
+                        CalError::failed_operation(
+                            "Invalid private key length".to_owned(),
+                            true,
+                            None,
+                        )
+                    })?;
+
+                let private =
+                    P256SecretKey::from_bytes((&private_key_bytes).into()).map_err(|e| {
+                        CalError::failed_operation(
+                            "Failed to create P-256 private key".to_owned(),
+                            false,
+                            Some(anyhow!(e)),
+                        )
+                    })?;
+
+                // Deserialize the peer's P-256 public key (in SEC1 format), which checks that it
+                // is on the curve and not the identity. The cofactor is 1.
+                let peer_public = P256PublicKey::from_sec1_bytes(peer_public_key).map_err(|e| {
+                    CalError::failed_operation(
+                        "Invalid P-256 public key format".to_owned(),
+                        true,
+                        Some(anyhow!(e)),
+                    )
+                })?;
+
+                // Perform ECDH using the low-level diffie_hellman function
+                let shared_secret =
+                    diffie_hellman(private.to_nonzero_scalar(), peer_public.as_affine());
+
+                Ok(shared_secret.raw_secret_bytes().to_vec())
+            }
+            _ => Err(CalError::failed_operation(
+                "Unsupported algorithm".to_string(),
+                true,
+                None,
+            )),
+        }
+    }
+
+    fn key_confirmation(&self, transcript: Option<&[u8]>) -> Result<Vec<u8>, CalError> {
+        let is_client = self
+            .confirmation
+            .as_ref()
+            .is_some_and(|(_, is_client)| *is_client);
+        Ok(self
+            .key_confirmation_tag(is_client, transcript)?
+            .as_ref()
+            .to_vec())
+    }
+
+    fn verify_key_confirmation(
+        &self,
+        peer_confirmation: &[u8],
+        transcript: Option<&[u8]>,
+    ) -> Result<bool, CalError> {
+        let is_client = self
+            .confirmation
+            .as_ref()
+            .is_some_and(|(_, is_client)| *is_client);
+        let expected = self.key_confirmation_tag(!is_client, transcript)?;
+        Ok(hkdf::constant_time_eq(expected.as_ref(), peer_confirmation))
     }
 
     fn derive_client_session_keys(
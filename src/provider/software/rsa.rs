@@ -0,0 +1,238 @@
+//! RSA key pairs, signatures and OAEP encryption.
+//!
+//! Private keys are stored as PKCS#8 documents, public keys as PKCS#1 `RSAPublicKey`. Private
+//! key operations are blinded, the decryption of the `rsa` crate is nevertheless not constant
+//! time (RUSTSEC-2023-0071).
+
+use ::rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey, EncodeRsaPublicKey},
+    pkcs8::{AssociatedOid, DecodePrivateKey, EncodePrivateKey},
+    traits::PublicKeyParts,
+    Oaep, Pkcs1v15Sign, Pss, RsaPrivateKey, RsaPublicKey,
+};
+use anyhow::anyhow;
+use digest::{Digest, DynDigest};
+use p256::elliptic_curve::rand_core::OsRng;
+use sha2::{Sha256, Sha384, Sha512};
+use zeroize::Zeroizing;
+
+use crate::common::{
+    crypto::algorithms::{
+        encryption::{AsymmetricKeySpec, RsaPadding},
+        hashes::CryptoHash,
+    },
+    error::CalError,
+};
+
+/// Modulus length in bits, the `rsa` crate does not support keys larger than 4096 bits.
+fn key_size(spec: AsymmetricKeySpec) -> Result<usize, CalError> {
+    match spec {
+        AsymmetricKeySpec::RSA1024 => Ok(1024),
+        AsymmetricKeySpec::RSA2048 => Ok(2048),
+        AsymmetricKeySpec::RSA3072 => Ok(3072),
+        AsymmetricKeySpec::RSA4096 => Ok(4096),
+        _ => Err(CalError::unsupported_algorithm(format!(
+            "{spec:?} is not a supported RSA key size"
+        ))),
+    }
+}
+
+fn invalid(what: &str, error: ::rsa::Error) -> CalError {
+    CalError::bad_parameter(format!("Invalid RSA {what}"), true, Some(anyhow!(error)))
+}
+
+fn check_size(spec: AsymmetricKeySpec, key: &impl PublicKeyParts) -> Result<(), CalError> {
+    let bits = key.n().bits();
+    if bits != key_size(spec)? {
+        return Err(CalError::bad_parameter(
+            format!("RSA key with {bits} bit modulus does not match {spec:?}"),
+            true,
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// Accepts the internal PKCS#8 document or a PKCS#1 `RSAPrivateKey`.
+pub(super) fn private_key(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+) -> Result<RsaPrivateKey, CalError> {
+    let key = RsaPrivateKey::from_pkcs8_der(private_key)
+        .or_else(|_| RsaPrivateKey::from_pkcs1_der(private_key))
+        .map_err(|e| {
+            CalError::bad_parameter("Invalid RSA private key".to_owned(), true, Some(anyhow!(e)))
+        })?;
+    key.validate().map_err(|e| invalid("private key", e))?;
+    check_size(spec, &key)?;
+    Ok(key)
+}
+
+pub(super) fn public_key(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+) -> Result<RsaPublicKey, CalError> {
+    let key = RsaPublicKey::from_pkcs1_der(public_key).map_err(|e| {
+        CalError::bad_parameter("Invalid RSA public key".to_owned(), true, Some(anyhow!(e)))
+    })?;
+    check_size(spec, &key)?;
+    Ok(key)
+}
+
+fn encoding_failed(error: impl Into<anyhow::Error>) -> CalError {
+    CalError::failed_operation(
+        "Failed to encode RSA key".to_owned(),
+        true,
+        Some(error.into()),
+    )
+}
+
+/// Returns the internal encodings of the private and public key.
+pub(super) fn encode_key_pair(
+    key: &RsaPrivateKey,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), CalError> {
+    let private_key = key.to_pkcs8_der().map_err(encoding_failed)?;
+    let public_key = key
+        .to_public_key()
+        .to_pkcs1_der()
+        .map_err(encoding_failed)?;
+    Ok((
+        Zeroizing::new(private_key.as_bytes().to_vec()),
+        public_key.into_vec(),
+    ))
+}
+
+/// PKCS#1 `RSAPrivateKey` of the internal private key.
+pub(super) fn pkcs1_private_key(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let document = self::private_key(spec, private_key)?
+        .to_pkcs1_der()
+        .map_err(encoding_failed)?;
+    Ok(Zeroizing::new(document.as_bytes().to_vec()))
+}
+
+/// Generates an RSA key pair with public exponent 65537 and returns `(private_key, public_key)`.
+pub(super) fn generate_key_pair(spec: AsymmetricKeySpec) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+    let key = RsaPrivateKey::new(&mut OsRng, key_size(spec)?).map_err(|e| {
+        CalError::failed_operation(
+            "Failed to generate RSA key pair".to_owned(),
+            false,
+            Some(anyhow!(e)),
+        )
+    })?;
+    let (private_key, public_key) = encode_key_pair(&key)?;
+    Ok((private_key.to_vec(), public_key))
+}
+
+fn unsupported_hash(hash: CryptoHash) -> CalError {
+    CalError::unsupported_algorithm(format!("RSA signatures with {hash:?}"))
+}
+
+fn sign<D: Digest + DynDigest + AssociatedOid + Send + Sync + 'static>(
+    key: &RsaPrivateKey,
+    padding: RsaPadding,
+    data: &[u8],
+) -> ::rsa::Result<Vec<u8>> {
+    let hashed = D::digest(data);
+    match padding {
+        RsaPadding::Pkcs1v15 => key.sign_with_rng(&mut OsRng, Pkcs1v15Sign::new::<D>(), &hashed),
+        RsaPadding::Pss => key.sign_with_rng(&mut OsRng, Pss::new::<D>(), &hashed),
+    }
+}
+
+fn verify<D: Digest + DynDigest + AssociatedOid + Send + Sync + 'static>(
+    key: &RsaPublicKey,
+    padding: RsaPadding,
+    data: &[u8],
+    signature: &[u8],
+) -> bool {
+    let hashed = D::digest(data);
+    match padding {
+        RsaPadding::Pkcs1v15 => key.verify(Pkcs1v15Sign::new::<D>(), &hashed, signature),
+        RsaPadding::Pss => key.verify(Pss::new::<D>(), &hashed, signature),
+    }
+    .is_ok()
+}
+
+pub(super) fn sign_data(
+    spec: AsymmetricKeySpec,
+    hash: CryptoHash,
+    padding: RsaPadding,
+    private_key: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let key = self::private_key(spec, private_key)?;
+    match hash {
+        CryptoHash::Sha2_256 => sign::<Sha256>(&key, padding, data),
+        CryptoHash::Sha2_384 => sign::<Sha384>(&key, padding, data),
+        CryptoHash::Sha2_512 => sign::<Sha512>(&key, padding, data),
+        _ => return Err(unsupported_hash(hash)),
+    }
+    .map_err(|e| CalError::failed_operation("Failed signing with RSA.", false, Some(anyhow!(e))))
+}
+
+/// Returns `Ok(false)` for invalid signatures and an error for malformed public keys.
+pub(super) fn verify_signature(
+    spec: AsymmetricKeySpec,
+    hash: CryptoHash,
+    padding: RsaPadding,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, CalError> {
+    let key = self::public_key(spec, public_key)?;
+    match hash {
+        CryptoHash::Sha2_256 => Ok(verify::<Sha256>(&key, padding, data, signature)),
+        CryptoHash::Sha2_384 => Ok(verify::<Sha384>(&key, padding, data, signature)),
+        CryptoHash::Sha2_512 => Ok(verify::<Sha512>(&key, padding, data, signature)),
+        _ => Err(unsupported_hash(hash)),
+    }
+}
+
+fn oaep(hash: CryptoHash) -> Result<Oaep, CalError> {
+    match hash {
+        CryptoHash::Sha2_256 => Ok(Oaep::new::<Sha256>()),
+        CryptoHash::Sha2_384 => Ok(Oaep::new::<Sha384>()),
+        CryptoHash::Sha2_512 => Ok(Oaep::new::<Sha512>()),
+        _ => Err(CalError::unsupported_algorithm(format!(
+            "RSA-OAEP with {hash:?}"
+        ))),
+    }
+}
+
+/// Encrypts `data` with RSA-OAEP, using `hash` for OAEP and MGF1 and an empty label.
+pub(super) fn encrypt_data(
+    spec: AsymmetricKeySpec,
+    hash: CryptoHash,
+    public_key: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    let key = self::public_key(spec, public_key)?;
+    key.encrypt(&mut OsRng, oaep(hash)?, data).map_err(|e| {
+        CalError::bad_parameter(
+            "Failed encrypting with RSA-OAEP, the data may be too long".to_owned(),
+            true,
+            Some(anyhow!(e)),
+        )
+    })
+}
+
+pub(super) fn decrypt_data(
+    spec: AsymmetricKeySpec,
+    hash: CryptoHash,
+    private_key: &[u8],
+    encrypted_data: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CalError> {
+    let key = self::private_key(spec, private_key)?;
+    key.decrypt_blinded(&mut OsRng, oaep(hash)?, encrypted_data)
+        .map(Zeroizing::new)
+        .map_err(|e| {
+            CalError::failed_operation(
+                "Failed decrypting with RSA-OAEP".to_owned(),
+                true,
+                Some(anyhow!(e)),
+            )
+        })
+}
@@ -0,0 +1,109 @@
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use slh_dsa::{
+    ParameterSet, Sha2_128f, Sha2_128s, Sha2_192f, Sha2_192s, Sha2_256f, Sha2_256s, Shake128f,
+    Shake128s, Shake192f, Shake192s, Shake256f, Shake256s, Signature, SigningKey, VerifyingKey,
+};
+use zeroize::Zeroizing;
+
+use crate::common::{crypto::algorithms::encryption::AsymmetricKeySpec, error::CalError};
+
+fn invalid_length(name: &str, actual: usize) -> CalError {
+    CalError::bad_parameter(
+        format!("Invalid SLH-DSA {name} length: {actual} bytes"),
+        true,
+        None,
+    )
+}
+
+fn generate<P: ParameterSet>() -> (Vec<u8>, Vec<u8>) {
+    let signing_key = SigningKey::<P>::new(&mut OsRng);
+    let verifying_key: &VerifyingKey<P> = signing_key.as_ref();
+    (signing_key.to_vec(), verifying_key.to_vec())
+}
+
+fn sign<P: ParameterSet>(private_key: &[u8], data: &[u8]) -> Result<Vec<u8>, CalError> {
+    let signing_key = SigningKey::<P>::try_from(private_key)
+        .map_err(|_| invalid_length("private key", private_key.len()))?;
+
+    // The randomizer has the length of the security parameter `n`, which is half of the public key.
+    let mut randomizer = Zeroizing::new(vec![0u8; private_key.len() / 4]);
+    OsRng.fill_bytes(&mut randomizer);
+
+    let signature = signing_key
+        .try_sign_with_context(data, &[], Some(&randomizer))
+        .map_err(|e| {
+            CalError::failed_operation(
+                "Failed signing with SLH-DSA.",
+                false,
+                Some(anyhow::anyhow!(e)),
+            )
+        })?;
+
+    Ok(signature.to_vec())
+}
+
+fn verify<P: ParameterSet>(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, CalError> {
+    let verifying_key = VerifyingKey::<P>::try_from(public_key)
+        .map_err(|_| invalid_length("public key", public_key.len()))?;
+
+    let Ok(signature) = Signature::<P>::try_from(signature) else {
+        return Ok(false);
+    };
+
+    Ok(verifying_key
+        .try_verify_with_context(data, &[], &signature)
+        .is_ok())
+}
+
+fn unsupported(spec: AsymmetricKeySpec) -> CalError {
+    CalError::unsupported_algorithm(format!("{:?} is not an SLH-DSA parameter set", spec))
+}
+
+macro_rules! dispatch {
+    ($spec:expr, $function:ident($($arg:expr),*)) => {
+        match $spec {
+            AsymmetricKeySpec::SlhDsaSha2_128s => $function::<Sha2_128s>($($arg),*),
+            AsymmetricKeySpec::SlhDsaSha2_128f => $function::<Sha2_128f>($($arg),*),
+            AsymmetricKeySpec::SlhDsaSha2_192s => $function::<Sha2_192s>($($arg),*),
+            AsymmetricKeySpec::SlhDsaSha2_192f => $function::<Sha2_192f>($($arg),*),
+            AsymmetricKeySpec::SlhDsaSha2_256s => $function::<Sha2_256s>($($arg),*),
+            AsymmetricKeySpec::SlhDsaSha2_256f => $function::<Sha2_256f>($($arg),*),
+            AsymmetricKeySpec::SlhDsaShake128s => $function::<Shake128s>($($arg),*),
+            AsymmetricKeySpec::SlhDsaShake128f => $function::<Shake128f>($($arg),*),
+            AsymmetricKeySpec::SlhDsaShake192s => $function::<Shake192s>($($arg),*),
+            AsymmetricKeySpec::SlhDsaShake192f => $function::<Shake192f>($($arg),*),
+            AsymmetricKeySpec::SlhDsaShake256s => $function::<Shake256s>($($arg),*),
+            AsymmetricKeySpec::SlhDsaShake256f => $function::<Shake256f>($($arg),*),
+            spec => return Err(unsupported(spec)),
+        }
+    };
+}
+
+/// Generates an SLH-DSA key pair and returns `(private_key, public_key)`.
+///
+/// Both keys are encoded as defined in FIPS 205.
+pub(super) fn generate_key_pair(spec: AsymmetricKeySpec) -> Result<(Vec<u8>, Vec<u8>), CalError> {
+    Ok(dispatch!(spec, generate()))
+}
+
+pub(super) fn sign_data(
+    spec: AsymmetricKeySpec,
+    private_key: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CalError> {
+    dispatch!(spec, sign(private_key, data))
+}
+
+/// Returns `Ok(false)` for malformed signatures and an error for malformed public keys.
+pub(super) fn verify_signature(
+    spec: AsymmetricKeySpec,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, CalError> {
+    dispatch!(spec, verify(public_key, data, signature))
+}
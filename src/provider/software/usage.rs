@@ -0,0 +1,172 @@
+//! Usage accounting of symmetric keys, so that keys are rotated before reaching the safety limit
+//! of their cipher.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::{versions::version_id, StorageManager};
+use crate::{
+    common::{
+        config::{KeySpec, Spec},
+        error::CalError,
+    },
+    storage::KeyData,
+};
+
+const SCOPE: &str = "KeyUsage";
+/// Number of encryptions reserved in storage at once, so that storage is not written for every encryption.
+const RESERVATION_BLOCK: u64 = 1024;
+
+/// Serializes reservations, as a stored key might be loaded into multiple handles.
+static RESERVATION_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default)]
+struct UsageState {
+    loaded: bool,
+    /// Index of the next encryption.
+    next: u64,
+    /// Encryptions below this index are reserved in storage.
+    reserved: u64,
+}
+
+#[derive(Debug, Clone)]
+struct UsageStorage {
+    storage_manager: StorageManager,
+    key_id: String,
+    version: u32,
+    spec: KeySpec,
+}
+
+/// Counts encryptions with a key, clones of a handle share the counter.
+///
+/// The counter of a stored key is persisted in blocks of reserved encryptions. After a restart
+/// counting continues after the last reserved block, so a counter value is never used twice.
+/// Every version of a rotated key has a counter of its own.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KeyUsageCounter {
+    storage: Option<UsageStorage>,
+    state: Arc<Mutex<UsageState>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, CalError> {
+    mutex.lock().map_err(|_| {
+        CalError::failed_operation("Key usage counter is poisoned".to_owned(), true, None)
+    })
+}
+
+impl UsageStorage {
+    fn id(&self) -> String {
+        version_id(&self.key_id, self.version)
+    }
+
+    fn load(&self) -> Result<u64, CalError> {
+        match self.storage_manager.get(self.id()) {
+            Ok(key_data) => key_data
+                .additional_data
+                .as_deref()
+                .and_then(|data| data.try_into().ok())
+                .map(u64::from_be_bytes)
+                .ok_or_else(|| {
+                    CalError::failed_operation(
+                        format!("Invalid usage counter of key {}", self.key_id),
+                        true,
+                        None,
+                    )
+                }),
+            // Keys created before usage accounting have no counter yet.
+            Err(e) if e.is_not_found() => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn store(&self, reserved: u64) -> Result<(), CalError> {
+        let key_data = KeyData {
+            id: self.id(),
+            secret_data: None,
+            public_data: None,
+            additional_data: Some(reserved.to_be_bytes().to_vec()),
+            spec: Spec::KeySpec(self.spec),
+        };
+        Ok(self.storage_manager.store(self.id(), key_data)?)
+    }
+}
+
+impl KeyUsageCounter {
+    /// Creates a counter persisted for `version` of the stored key `key_id`.
+    pub(crate) fn persistent(
+        storage_manager: &StorageManager,
+        key_id: &str,
+        version: u32,
+        spec: KeySpec,
+    ) -> Self {
+        Self {
+            storage: Some(UsageStorage {
+                storage_manager: storage_manager.sub_scope(SCOPE),
+                key_id: key_id.to_owned(),
+                version,
+                spec,
+            }),
+            state: Arc::default(),
+        }
+    }
+
+    pub(crate) fn is_persistent(&self) -> bool {
+        self.storage.is_some()
+    }
+
+    /// The key version counted by a persisted counter.
+    pub(crate) fn version(&self) -> Option<u32> {
+        self.storage.as_ref().map(|storage| storage.version)
+    }
+
+    fn loaded_state(&self) -> Result<MutexGuard<'_, UsageState>, CalError> {
+        let mut state = lock(&self.state)?;
+        if !state.loaded {
+            if let Some(storage) = &self.storage {
+                state.next = storage.load()?;
+                state.reserved = state.next;
+            }
+            state.loaded = true;
+        }
+        Ok(state)
+    }
+
+    /// Counts an encryption with the key `key_id` and returns its index.
+    ///
+    /// Fails once `limit` encryptions were counted.
+    pub(crate) fn reserve(&self, key_id: &str, limit: u64) -> Result<u64, CalError> {
+        let mut state = self.loaded_state()?;
+
+        if let Some(storage) = self
+            .storage
+            .as_ref()
+            .filter(|_| state.next >= state.reserved)
+        {
+            let _guard = lock(&RESERVATION_LOCK)?;
+            let start = state.next.max(storage.load()?);
+            let reserved = start.saturating_add(RESERVATION_BLOCK);
+            storage.store(reserved)?;
+            state.next = start;
+            state.reserved = reserved;
+        }
+
+        if state.next >= limit {
+            return Err(CalError::key_usage_limit_reached(key_id));
+        }
+        let index = state.next;
+        state.next += 1;
+        Ok(index)
+    }
+
+    /// Number of counted encryptions, including reserved but unused encryptions of earlier sessions.
+    pub(crate) fn count(&self) -> Result<u64, CalError> {
+        Ok(self.loaded_state()?.next)
+    }
+
+    /// Deletes the persisted counter, only to be used once the key version is deleted.
+    pub(crate) fn delete(&self) -> Result<(), CalError> {
+        if let Some(storage) = &self.storage {
+            storage.storage_manager.delete(storage.id())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Versions of rotated symmetric keys.
+//!
+//! The newest version of a key is stored under the key id as usual, with its [KeyVersions] in
+//! `additional_data`. Older versions are kept in a separate scope until they are retired.
+
+use serde::{Deserialize, Serialize};
+
+use super::StorageManager;
+use crate::{
+    common::{
+        config::{KeySpec, Spec},
+        error::{CalError, KeyType},
+    },
+    storage::KeyData,
+};
+
+const SCOPE: &str = "KeyVersions";
+
+/// Version metadata of a key, keys without metadata are in their first version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct KeyVersions {
+    pub(crate) current: u32,
+    /// Older versions which were not retired yet.
+    pub(crate) retained: Vec<u32>,
+}
+
+impl Default for KeyVersions {
+    fn default() -> Self {
+        Self {
+            current: 1,
+            retained: Vec::new(),
+        }
+    }
+}
+
+impl KeyVersions {
+    pub(crate) fn from_key_data(key_data: &KeyData) -> Result<Self, CalError> {
+        match key_data.additional_data.as_deref() {
+            Some(data) => rmp_serde::from_slice(data).map_err(|e| {
+                CalError::failed_operation(
+                    format!("Invalid version metadata of key {}", key_data.id),
+                    true,
+                    Some(anyhow::anyhow!(e)),
+                )
+            }),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, CalError> {
+        rmp_serde::to_vec(self).map_err(|e| {
+            CalError::failed_operation(
+                "Failed to serialize key version metadata".to_owned(),
+                true,
+                Some(anyhow::anyhow!(e)),
+            )
+        })
+    }
+
+    /// All available versions, oldest first.
+    pub(crate) fn all(&self) -> Vec<u32> {
+        let mut versions = self.retained.clone();
+        versions.push(self.current);
+        versions
+    }
+}
+
+/// Storage id of `version` of the key `key_id`.
+pub(crate) fn version_id(key_id: &str, version: u32) -> String {
+    format!("{key_id}/{version}")
+}
+
+/// Storage of the older versions of keys.
+pub(crate) struct VersionStorage {
+    storage_manager: StorageManager,
+}
+
+impl VersionStorage {
+    pub(crate) fn new(storage_manager: &StorageManager) -> Self {
+        Self {
+            storage_manager: storage_manager.sub_scope(SCOPE),
+        }
+    }
+
+    pub(crate) fn store(
+        &self,
+        key_id: &str,
+        version: u32,
+        spec: KeySpec,
+        key: Vec<u8>,
+    ) -> Result<(), CalError> {
+        let id = version_id(key_id, version);
+        let key_data = KeyData {
+            id: id.clone(),
+            secret_data: Some(key),
+            public_data: None,
+            additional_data: None,
+            spec: Spec::KeySpec(spec),
+        };
+        Ok(self.storage_manager.store(id, key_data)?)
+    }
+
+    pub(crate) fn load(&self, key_id: &str, version: u32) -> Result<Vec<u8>, CalError> {
+        self.storage_manager
+            .get(version_id(key_id, version))
+            .ok()
+            .and_then(|key_data| key_data.secret_data)
+            .ok_or_else(|| {
+                CalError::missing_key(format!("{key_id} version {version}"), KeyType::Symmetric)
+            })
+    }
+
+    pub(crate) fn delete(&self, key_id: &str, version: u32) -> Result<(), CalError> {
+        Ok(self.storage_manager.delete(version_id(key_id, version))?)
+    }
+}
@@ -106,6 +106,7 @@ mod test {
             signing_hash: CryptoHash::Sha2_512,
             ephemeral: true,
             non_exportable: false,
+            ..Default::default()
         };
 
         provider.create_key(key_spec).unwrap()
@@ -121,6 +122,7 @@ mod test {
             signing_hash: CryptoHash::Sha2_512,
             ephemeral: true,
             non_exportable: false,
+            ..Default::default()
         };
 
         provider.create_key_pair(key_pair_spec).unwrap()
@@ -44,6 +44,13 @@ pub enum StorageManagerError {
     GetKeys { source: StorageBackendError },
 }
 
+impl StorageManagerError {
+    /// Returns `true` if the requested entry does not exist.
+    pub(crate) fn is_not_found(&self) -> bool {
+        matches!(self, Self::Get { source } if source.is_not_found())
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum StorageManagerInitializationError {
     #[error("Failed to get the signature backend scope for the storage manager.")]
@@ -112,6 +119,14 @@ impl StorageManager {
         }))
     }
 
+    /// Returns a storage manager using the same backends, whose entries are separated
+    /// from the entries of this storage manager by `scope`.
+    pub(crate) fn sub_scope(&self, scope: &str) -> Self {
+        let mut storage_manager = self.clone();
+        storage_manager.scope.provider_scope = format!("{}/{}", self.scope.provider_scope, scope);
+        storage_manager
+    }
+
     fn encrypt_key_data(&self, key_data: KeyData) -> Result<KeyDataEncrypted, StorageManagerError> {
         let encrypted_sensitive_data = key_data
             .secret_data
@@ -283,7 +298,10 @@ mod test {
     use nanoid::nanoid;
     use rstest::{fixture, rstest};
 
-    use crate::{prelude::KeySpec, tests::TestStore};
+    use crate::{
+        prelude::{KeySpec, KeyUsage},
+        tests::TestStore,
+    };
 
     static TEST_KV_STORE: LazyLock<TestStore> = LazyLock::new(TestStore::new);
 
@@ -292,6 +310,10 @@ mod test {
         signing_hash: crate::prelude::CryptoHash::Sha2_512,
         ephemeral: true,
         non_exportable: false,
+        not_before: None,
+        not_after: None,
+        process_not_after: None,
+        key_usage: KeyUsage::ALL,
     });
 
     #[test]
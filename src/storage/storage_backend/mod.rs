@@ -29,6 +29,17 @@ pub enum StorageBackendError {
     Sqlite(#[from] SqliteBackendError),
 }
 
+impl StorageBackendError {
+    /// Returns `true` if the requested key does not exist.
+    pub(crate) fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::KvStore(KvStorageBackendError::Get)
+                | Self::Sqlite(SqliteBackendError::NoKeyError)
+        )
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum StorageBackendInitializationError {
     #[error(transparent)]
@@ -4,18 +4,23 @@ mod provider;
 
 use std::collections::HashMap;
 use std::sync::Once;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, LazyLock, RwLock};
 use std::{io, vec};
 
 use color_eyre::install;
 use color_eyre::owo_colors::OwoColorize;
 use tracing_subscriber::{filter::EnvFilter, fmt};
 
-use crate::common::config::{AdditionalConfig, ProviderImplConfig};
-use crate::common::KeyPairHandle;
+use crate::common::config::{AdditionalConfig, KeyPairSpec, ProviderImplConfig};
+use crate::common::crypto::algorithms::{encryption::AsymmetricKeySpec, hashes::CryptoHash};
+use crate::common::error::CalError;
+use crate::common::factory::create_provider_from_name;
+use crate::common::{KeyPairHandle, Provider};
 
 static SETUP_INITIALIZATION: Once = Once::new();
 
+static SOFTWARE_STORE: LazyLock<TestStore> = LazyLock::new(TestStore::new);
+
 /// When going out of scope, deletes the key pair it holds.
 struct CleanupKeyPair {
     key_pair_handle: KeyPairHandle,
@@ -65,6 +70,32 @@ fn setup() {
     });
 }
 
+/// Software provider with a store shared by all tests using it.
+#[cfg(feature = "software")]
+pub(crate) fn software_provider() -> Provider {
+    SOFTWARE_STORE.software_provider()
+}
+
+/// Spec of an ephemeral key pair signing with the hash usually paired with `asym_spec`.
+pub(crate) fn key_pair_spec(asym_spec: AsymmetricKeySpec) -> KeyPairSpec {
+    KeyPairSpec {
+        asym_spec,
+        signing_hash: match asym_spec {
+            AsymmetricKeySpec::P384 => CryptoHash::Sha2_384,
+            AsymmetricKeySpec::Curve25519 => CryptoHash::Sha2_512,
+            _ => CryptoHash::Sha2_256,
+        },
+        ephemeral: true,
+        ..Default::default()
+    }
+}
+
+/// Creates a key pair with [key_pair_spec] using [software_provider].
+#[cfg(feature = "software")]
+pub(crate) fn create_key_pair(asym_spec: AsymmetricKeySpec) -> Result<KeyPairHandle, CalError> {
+    software_provider().create_key_pair(key_pair_spec(asym_spec))
+}
+
 pub(crate) struct TestStore {
     store: Arc<RwLock<HashMap<String, Vec<u8>>>>,
 }
@@ -76,6 +107,12 @@ impl TestStore {
         }
     }
 
+    /// Software provider storing its keys in this store.
+    #[cfg(feature = "software")]
+    pub(crate) fn software_provider(&'static self) -> Provider {
+        create_provider_from_name("SoftwareProvider", self.impl_config()).unwrap()
+    }
+
     pub(crate) fn impl_config<'a: 'static>(&'a self) -> ProviderImplConfig {
         let kv_store = AdditionalConfig::KVStoreConfig {
             get_fn: Arc::new(|key| Box::pin(self.get(key))),
@@ -40,6 +40,7 @@ fn test_create_key_with_provider() -> Result<()> {
         signing_hash: CryptoHash::Sha2_256,
         ephemeral: false,
         non_exportable: true,
+        ..Default::default()
     };
 
     let _key = provider.create_key_pair(key_spec)?;
@@ -70,6 +71,7 @@ fn test_create_key_pair_sign_and_verify_data() -> Result<()> {
             signing_hash: hash,
             ephemeral: false,
             non_exportable: true,
+            ..Default::default()
         };
 
         let key = provider.create_key_pair(key_spec)?;
@@ -101,6 +103,7 @@ fn test_load_key_pair() -> Result<()> {
             signing_hash: CryptoHash::Sha2_256,
             ephemeral: false,
             non_exportable: true,
+            ..Default::default()
         };
 
         let key = provider.create_key_pair(key_spec)?;
@@ -132,6 +135,7 @@ fn spec_from_cipher_hash(cipher: Option<Cipher>, hash: CryptoHash) -> KeyPairSpe
         signing_hash: hash,
         ephemeral: false,
         non_exportable: true,
+        ..Default::default()
     }
 }
 
@@ -166,6 +170,7 @@ fn test_secured_software_provider(mut provider: Provider) -> Result<()> {
         signing_hash: CryptoHash::Sha2_512,
         ephemeral: false,
         non_exportable: true,
+        ..Default::default()
     };
 
     let key = provider.create_key_pair(spec)?;
@@ -190,6 +195,7 @@ fn test_secured_software_provider(mut provider: Provider) -> Result<()> {
             signing_hash: CryptoHash::Sha2_256,
             ephemeral: false,
             non_exportable: true,
+            ..Default::default()
         };
 
         let software_key = software_provider.create_key(symmetric_spec)?;
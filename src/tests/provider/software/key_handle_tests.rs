@@ -6,9 +6,10 @@ mod tests {
     use crate::common::{
         config::{KeyPairSpec, KeySpec},
         crypto::algorithms::{
-            encryption::{AsymmetricKeySpec, Cipher},
+            encryption::{AsymmetricKeySpec, Cipher, PointEncoding, RsaPadding, SignatureEncoding},
             hashes::CryptoHash,
         },
+        crypto::{ecdsa, pkcs::standards::KeyFormat},
         error::CalError,
         factory, KeyHandle, KeyPairHandle,
     };
@@ -20,6 +21,7 @@ mod tests {
     mod key_pair_handle {
         use super::*;
         use crate::{common::Provider, tests::TestStore};
+        use test_case::test_case;
 
         static mut STORE: std::sync::LazyLock<TestStore> = std::sync::LazyLock::new(TestStore::new);
 
@@ -46,6 +48,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             // Create a new key pair and get the SoftwareKeyPairHandle
@@ -74,6 +77,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             // Create a new key pair and get the SoftwareKeyPairHandle
@@ -106,6 +110,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             // Create two key pairs
@@ -138,6 +143,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             // Create a new key pair and get the SoftwareKeyPairHandle
@@ -161,6 +167,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             let impl_config = unsafe { STORE.impl_config().clone() };
@@ -201,6 +208,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             let impl_config = unsafe { STORE.impl_config().clone() };
@@ -239,6 +247,7 @@ mod tests {
                 signing_hash: CryptoHash::Sha2_256,
                 ephemeral: true,
                 non_exportable: false,
+                ..Default::default()
             };
 
             // Create a new key pair and get the SoftwareKeyPairHandle
@@ -250,9 +259,504 @@ mod tests {
             assert!(!key_id.is_empty(), "Key ID should not be empty");
             Ok(())
         }
+
+        fn ml_kem_spec(asym_spec: AsymmetricKeySpec, ephemeral: bool) -> KeyPairSpec {
+            KeyPairSpec {
+                asym_spec,
+                cipher: Some(Cipher::AesGcm256),
+                signing_hash: CryptoHash::Sha2_256,
+                ephemeral,
+                non_exportable: false,
+                ..Default::default()
+            }
+        }
+
+        #[test_case(AsymmetricKeySpec::MlKem512, 800, 768)]
+        #[test_case(AsymmetricKeySpec::MlKem768, 1184, 1088)]
+        #[test_case(AsymmetricKeySpec::MlKem1024, 1568, 1568)]
+        #[instrument]
+        fn test_encapsulate_decapsulate(
+            asym_spec: AsymmetricKeySpec,
+            public_key_len: usize,
+            ciphertext_len: usize,
+        ) -> Result<()> {
+            setup();
+            let spec = ml_kem_spec(asym_spec, true);
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            let recipient = provider.create_key_pair(spec)?;
+            let public_key = recipient.get_public_key()?;
+            assert_eq!(public_key.len(), public_key_len);
+
+            let sender = provider.import_public_key(spec, &public_key)?;
+            let (ciphertext, sender_key) = sender.encapsulate(&public_key)?;
+            assert_eq!(ciphertext.len(), ciphertext_len);
+
+            let recipient_key = recipient.decapsulate(&ciphertext)?;
+            assert_eq!(sender_key.extract_key()?, recipient_key.extract_key()?);
+
+            let data = b"Harvest now, decrypt later";
+            let (encrypted_data, iv) = sender_key.encrypt(data)?;
+            let decrypted_data = recipient_key.decrypt_data(&encrypted_data, &iv)?;
+            assert_eq!(data.as_slice(), decrypted_data.as_slice());
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_decapsulate_with_loaded_key_pair() -> Result<()> {
+            setup();
+            let spec = ml_kem_spec(AsymmetricKeySpec::MlKem768, false);
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            let key_pair = provider.create_key_pair(spec)?;
+            let (ciphertext, sender_key) = key_pair.encapsulate(&key_pair.get_public_key()?)?;
+
+            let loaded_key_pair = provider.load_key_pair(key_pair.id()?)?;
+            let recipient_key = loaded_key_pair.decapsulate(&ciphertext)?;
+
+            assert_eq!(sender_key.extract_key()?, recipient_key.extract_key()?);
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_decapsulate_with_public_only_key() -> Result<()> {
+            setup();
+            let spec = ml_kem_spec(AsymmetricKeySpec::MlKem768, true);
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            let key_pair = provider.create_key_pair(spec)?;
+            let public_key = key_pair.get_public_key()?;
+            let public_only_key_pair = provider.import_public_key(spec, &public_key)?;
+
+            let (ciphertext, _) = public_only_key_pair.encapsulate(&public_key)?;
+
+            assert!(
+                public_only_key_pair.decapsulate(&ciphertext).is_err(),
+                "Decapsulation should fail with public-only key"
+            );
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_encapsulate_with_invalid_input() -> Result<()> {
+            setup();
+            let spec = ml_kem_spec(AsymmetricKeySpec::MlKem512, true);
+
+            let key_pair = create_key_pair_handle(spec)?;
+            let public_key = key_pair.get_public_key()?;
+
+            assert!(key_pair.encapsulate(&public_key[1..]).is_err());
+            assert!(key_pair.decapsulate(&[0u8; 32]).is_err());
+
+            let p256_key_pair = create_key_pair_handle(KeyPairSpec {
+                asym_spec: AsymmetricKeySpec::P256,
+                ..spec
+            })?;
+            assert!(p256_key_pair.encapsulate(&public_key).is_err());
+            Ok(())
+        }
+
+        #[test_case(AsymmetricKeySpec::MlKem512)]
+        #[test_case(AsymmetricKeySpec::MlKem768)]
+        #[test_case(AsymmetricKeySpec::MlKem1024)]
+        #[instrument]
+        fn test_unreduced_encapsulation_key(asym_spec: AsymmetricKeySpec) -> Result<()> {
+            setup();
+            let spec = ml_kem_spec(asym_spec, true);
+
+            let key_pair = create_key_pair_handle(spec)?;
+            let mut public_key = key_pair.get_public_key()?;
+            // The first coefficient becomes 4095, which is not reduced modulo 3329.
+            public_key[0] = 0xff;
+            public_key[1] |= 0x0f;
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            assert!(provider.import_public_key(spec, &public_key).is_err());
+            assert!(key_pair.encapsulate(&public_key).is_err());
+            Ok(())
+        }
+
+        fn pq_signature_spec(asym_spec: AsymmetricKeySpec) -> KeyPairSpec {
+            KeyPairSpec {
+                asym_spec,
+                cipher: None,
+                signing_hash: CryptoHash::Sha2_256,
+                ephemeral: true,
+                non_exportable: false,
+                ..Default::default()
+            }
+        }
+
+        #[test_case(AsymmetricKeySpec::MlDsa44, 1312, 2420)]
+        #[test_case(AsymmetricKeySpec::MlDsa65, 1952, 3309)]
+        #[test_case(AsymmetricKeySpec::MlDsa87, 2592, 4627)]
+        #[test_case(AsymmetricKeySpec::SlhDsaSha2_128f, 32, 17088)]
+        #[test_case(AsymmetricKeySpec::SlhDsaShake128f, 32, 17088)]
+        #[instrument]
+        fn test_pq_sign_and_verify(
+            asym_spec: AsymmetricKeySpec,
+            public_key_len: usize,
+            signature_len: usize,
+        ) -> Result<()> {
+            setup();
+            let key_pair = create_key_pair_handle(pq_signature_spec(asym_spec))?;
+            assert_eq!(key_pair.get_public_key()?.len(), public_key_len);
+
+            let data = b"Data to sign";
+            let mut signature = key_pair.sign_data(data)?;
+            assert_eq!(signature.len(), signature_len);
+
+            assert!(key_pair.verify_signature(data, &signature)?);
+            assert!(!key_pair.verify_signature(b"Other data", &signature)?);
+
+            signature[0] ^= 1;
+            assert!(!key_pair.verify_signature(data, &signature)?);
+            assert!(!key_pair.verify_signature(data, &signature[1..])?);
+            Ok(())
+        }
+
+        #[test_case(AsymmetricKeySpec::MlDsa44Ed25519, 1312 + 32, 2420)]
+        #[test_case(AsymmetricKeySpec::MlDsa65Ed25519, 1952 + 32, 3309)]
+        #[test_case(AsymmetricKeySpec::MlDsa44EcdsaP256, 1312 + 65, 2420)]
+        #[test_case(AsymmetricKeySpec::MlDsa65EcdsaP256, 1952 + 65, 3309)]
+        #[instrument]
+        fn test_composite_sign_and_verify(
+            asym_spec: AsymmetricKeySpec,
+            public_key_len: usize,
+            ml_dsa_signature_len: usize,
+        ) -> Result<()> {
+            setup();
+            let key_pair = create_key_pair_handle(pq_signature_spec(asym_spec))?;
+            let other_key_pair = create_key_pair_handle(pq_signature_spec(asym_spec))?;
+            assert_eq!(key_pair.get_public_key()?.len(), public_key_len);
+
+            let data = b"Data to sign";
+            let signature = key_pair.sign_data(data)?;
+            assert!(signature.len() > ml_dsa_signature_len);
+
+            assert!(key_pair.verify_signature(data, &signature)?);
+            assert!(!key_pair.verify_signature(b"Other data", &signature)?);
+            assert!(!other_key_pair.verify_signature(data, &signature)?);
+
+            // Both components have to be valid.
+            let other_signature = other_key_pair.sign_data(data)?;
+            let (ml_dsa_signature, traditional_signature) =
+                signature.split_at(ml_dsa_signature_len);
+            let (other_ml_dsa_signature, other_traditional_signature) =
+                other_signature.split_at(ml_dsa_signature_len);
+
+            let mixed = [ml_dsa_signature, other_traditional_signature].concat();
+            assert!(!key_pair.verify_signature(data, &mixed)?);
+            let mixed = [other_ml_dsa_signature, traditional_signature].concat();
+            assert!(!key_pair.verify_signature(data, &mixed)?);
+
+            assert!(!key_pair.verify_signature(data, ml_dsa_signature)?);
+            Ok(())
+        }
+
+        #[test_case(RsaPadding::Pkcs1v15, CryptoHash::Sha2_256)]
+        #[test_case(RsaPadding::Pss, CryptoHash::Sha2_512)]
+        #[instrument]
+        fn test_rsa_sign_and_verify(
+            rsa_padding: RsaPadding,
+            signing_hash: CryptoHash,
+        ) -> Result<()> {
+            setup();
+            let spec = KeyPairSpec {
+                asym_spec: AsymmetricKeySpec::RSA2048,
+                signing_hash,
+                rsa_padding,
+                ephemeral: true,
+                ..Default::default()
+            };
+            let key_pair = create_key_pair_handle(spec)?;
+
+            let data = b"Data to sign";
+            let mut signature = key_pair.sign_data(data)?;
+            assert_eq!(signature.len(), 256);
+            assert!(key_pair.verify_signature(data, &signature)?);
+            assert!(!key_pair.verify_signature(b"Other data", &signature)?);
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+            let public_key = provider.import_public_key_with_format(
+                spec,
+                &key_pair.export_public_key(KeyFormat::SPKI)?,
+                KeyFormat::SPKI,
+            )?;
+            assert!(public_key.verify_signature(data, &signature)?);
+
+            let other_padding = match rsa_padding {
+                RsaPadding::Pkcs1v15 => RsaPadding::Pss,
+                RsaPadding::Pss => RsaPadding::Pkcs1v15,
+            };
+            let public_key = provider.import_public_key(
+                KeyPairSpec {
+                    rsa_padding: other_padding,
+                    ..spec
+                },
+                &key_pair.get_public_key()?,
+            )?;
+            assert!(!public_key.verify_signature(data, &signature)?);
+
+            signature[0] ^= 1;
+            assert!(!key_pair.verify_signature(data, &signature)?);
+            assert!(provider
+                .import_public_key_with_format(
+                    KeyPairSpec {
+                        asym_spec: AsymmetricKeySpec::RSA3072,
+                        ..spec
+                    },
+                    &key_pair.get_public_key()?,
+                    KeyFormat::Raw,
+                )
+                .is_err());
+            Ok(())
+        }
+
+        #[test_case(CryptoHash::Sha2_256)]
+        #[test_case(CryptoHash::Sha2_512)]
+        #[instrument]
+        fn test_rsa_encrypt_decrypt(signing_hash: CryptoHash) -> Result<()> {
+            setup();
+            let spec = KeyPairSpec {
+                asym_spec: AsymmetricKeySpec::RSA2048,
+                signing_hash,
+                ephemeral: true,
+                ..Default::default()
+            };
+            let key_pair = create_key_pair_handle(spec)?;
+
+            let data = b"Data to encrypt";
+            let encrypted = key_pair.encrypt_data(data)?;
+            assert_eq!(encrypted.len(), 256);
+            assert_eq!(key_pair.decrypt_data(&encrypted)?, data);
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+            let public_key = provider.import_public_key(spec, &key_pair.get_public_key()?)?;
+            let encrypted = public_key.encrypt_data(data)?;
+            assert_eq!(key_pair.decrypt_data(&encrypted)?, data);
+            assert!(public_key.decrypt_data(&encrypted).is_err());
+
+            let mut tampered = encrypted.clone();
+            tampered[0] ^= 1;
+            assert!(key_pair.decrypt_data(&tampered).is_err());
+            Ok(())
+        }
+
+        #[test_case(AsymmetricKeySpec::P256, CryptoHash::Sha2_256)]
+        #[test_case(AsymmetricKeySpec::P384, CryptoHash::Sha2_384)]
+        #[instrument]
+        fn test_ecdsa_signature_encoding(
+            asym_spec: AsymmetricKeySpec,
+            signing_hash: CryptoHash,
+        ) -> Result<()> {
+            setup();
+            let der_spec = KeyPairSpec {
+                asym_spec,
+                signing_hash,
+                ephemeral: true,
+                ..Default::default()
+            };
+            let p1363_spec = KeyPairSpec {
+                signature_encoding: SignatureEncoding::P1363,
+                ..der_spec
+            };
+            let scalar_len = asym_spec.ecdsa_scalar_len().unwrap();
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            let data = b"Data to sign";
+            let key_pair = create_key_pair_handle(p1363_spec)?;
+            let signature = key_pair.sign_data(data)?;
+            assert_eq!(signature.len(), 2 * scalar_len);
+            assert!(key_pair.verify_signature(data, &signature)?);
+
+            // The same public key imported with DER encoding only accepts converted signatures.
+            let der_key = provider.import_public_key(der_spec, &key_pair.get_public_key()?)?;
+            assert!(!der_key.verify_signature(data, &signature)?);
+            let der_signature = ecdsa::p1363_to_der(&signature, asym_spec)?;
+            assert!(der_key.verify_signature(data, &der_signature)?);
+            assert!(!key_pair.verify_signature(data, &der_signature)?);
+
+            let key_pair = create_key_pair_handle(der_spec)?;
+            let der_signature = key_pair.sign_data(data)?;
+            let p1363_key = provider.import_public_key(p1363_spec, &key_pair.get_public_key()?)?;
+            let signature = ecdsa::der_to_p1363(&der_signature, asym_spec)?;
+            assert!(p1363_key.verify_signature(data, &signature)?);
+            assert!(!p1363_key.verify_signature(data, &signature[1..])?);
+            Ok(())
+        }
+
+        #[test_case(AsymmetricKeySpec::P256, CryptoHash::Sha2_256, 33)]
+        #[test_case(AsymmetricKeySpec::P384, CryptoHash::Sha2_384, 49)]
+        #[instrument]
+        fn test_point_encoding(
+            asym_spec: AsymmetricKeySpec,
+            signing_hash: CryptoHash,
+            compressed_len: usize,
+        ) -> Result<()> {
+            setup();
+            let spec = KeyPairSpec {
+                asym_spec,
+                signing_hash,
+                ephemeral: true,
+                ..Default::default()
+            };
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            let key_pair = create_key_pair_handle(KeyPairSpec {
+                point_encoding: PointEncoding::Compressed,
+                ..spec
+            })?;
+            let compressed = key_pair.get_public_key()?;
+            assert_eq!(compressed.len(), compressed_len);
+            assert!(matches!(compressed[0], 0x02 | 0x03));
+            assert_eq!(key_pair.export_public_key(KeyFormat::Raw)?, compressed);
+
+            // SubjectPublicKeyInfo keeps the uncompressed point.
+            let spki = key_pair.export_public_key(KeyFormat::SPKI)?;
+            let uncompressed_key =
+                provider.import_public_key_with_format(spec, &spki, KeyFormat::SPKI)?;
+            let uncompressed = uncompressed_key.get_public_key()?;
+            assert_eq!(uncompressed.len(), 2 * compressed_len - 1);
+            assert_eq!(uncompressed[0], 0x04);
+            assert_eq!(uncompressed[1..compressed_len], compressed[1..]);
+
+            let data = b"Data to sign";
+            let signature = key_pair.sign_data(data)?;
+            assert!(uncompressed_key.verify_signature(data, &signature)?);
+
+            // Compressed imports are decompressed.
+            let imported = provider.import_public_key(spec, &compressed)?;
+            assert_eq!(imported.get_public_key()?, uncompressed);
+            assert!(imported.verify_signature(data, &signature)?);
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_import_invalid_public_key() -> Result<()> {
+            use curve25519_dalek::edwards::CompressedEdwardsY;
+
+            setup();
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            for (asym_spec, signing_hash) in [
+                (AsymmetricKeySpec::P256, CryptoHash::Sha2_256),
+                (AsymmetricKeySpec::P384, CryptoHash::Sha2_384),
+            ] {
+                let spec = KeyPairSpec {
+                    asym_spec,
+                    signing_hash,
+                    ephemeral: true,
+                    ..Default::default()
+                };
+                let key_pair = create_key_pair_handle(spec)?;
+                let public_key = key_pair.get_public_key()?;
+                assert!(provider.import_public_key(spec, &public_key).is_ok());
+
+                let mut off_curve = public_key.clone();
+                *off_curve.last_mut().unwrap() ^= 1;
+                assert!(provider.import_public_key(spec, &off_curve).is_err());
+                assert!(provider.import_public_key(spec, &[0x00]).is_err());
+                assert!(provider
+                    .import_key_pair(spec, &off_curve, &key_pair.extract_key()?)
+                    .is_err());
+            }
+
+            let spec = KeyPairSpec {
+                asym_spec: AsymmetricKeySpec::Curve25519,
+                ephemeral: true,
+                ..Default::default()
+            };
+            let public_key = create_key_pair_handle(spec)?.get_public_key()?;
+            assert!(provider.import_public_key(spec, &public_key).is_ok());
+
+            // The neutral element and a point with a component of order 4.
+            let mut identity = [0; 32];
+            identity[0] = 1;
+            assert!(provider.import_public_key(spec, &identity).is_err());
+            let point = CompressedEdwardsY(public_key.try_into().unwrap())
+                .decompress()
+                .unwrap();
+            let torsion = CompressedEdwardsY([0; 32]).decompress().unwrap();
+            let mixed_order = (point + torsion).compress();
+            assert!(provider
+                .import_public_key(spec, mixed_order.as_bytes())
+                .is_err());
+            Ok(())
+        }
+
+        #[test_case(AsymmetricKeySpec::MlDsa65)]
+        #[test_case(AsymmetricKeySpec::SlhDsaSha2_128f)]
+        #[test_case(AsymmetricKeySpec::MlDsa65EcdsaP256)]
+        #[instrument]
+        fn test_pq_import_key_pair(asym_spec: AsymmetricKeySpec) -> Result<()> {
+            setup();
+            let spec = pq_signature_spec(asym_spec);
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider: Provider =
+                factory::create_provider_from_name("SoftwareProvider", impl_config)
+                    .expect("Failed initializing SoftwareProvider");
+
+            let key_pair = provider.create_key_pair(spec)?;
+            let public_key = key_pair.get_public_key()?;
+            let private_key = key_pair.extract_key()?;
+
+            let imported_key_pair = provider.import_key_pair(spec, &public_key, &private_key)?;
+            let public_only_key_pair = provider.import_public_key(spec, &public_key)?;
+
+            let data = b"Data to sign";
+            let signature = imported_key_pair.sign_data(data)?;
+
+            assert!(key_pair.verify_signature(data, &signature)?);
+            assert!(public_only_key_pair.verify_signature(data, &signature)?);
+            Ok(())
+        }
     }
     mod key_handle {
-        use crate::{prelude::CalErrorKind, tests::TestStore};
+        use crate::{
+            common::config::{KeyUsage, Spec},
+            prelude::{CalErrorKind, ProviderImplConfig},
+            storage::{KeyData, StorageManager},
+            tests::TestStore,
+        };
         use test_case::test_case;
 
         use super::*;
@@ -676,5 +1180,332 @@ mod tests {
 
             Ok(())
         }
+
+        #[test_case(Cipher::AesGcm256)]
+        #[test_case(Cipher::XChaCha20Poly1305)]
+        #[instrument]
+        fn test_encrypt_with_counter_nonce(cipher: Cipher) -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                cipher,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+
+            let (first, first_iv) = key.encrypt_with_counter_nonce(b"first")?;
+            let (second, second_iv) = key.encrypt_with_counter_nonce(b"second")?;
+            assert_eq!(first_iv.len(), cipher.iv_len());
+            assert_eq!(first_iv[first_iv.len() - 8..], 0u64.to_be_bytes());
+            assert_eq!(second_iv[second_iv.len() - 8..], 1u64.to_be_bytes());
+            assert_eq!(key.decrypt_data(&first, &first_iv)?, b"first");
+            assert_eq!(key.decrypt_data(&second, &second_iv)?, b"second");
+
+            key.encrypt(b"random")?;
+            let usage = key.encryption_usage()?;
+            assert_eq!(usage.encryptions, 3);
+            assert_eq!(usage.counter_nonce_limit, u64::MAX);
+
+            // A reloaded key continues after the reserved counters, never reusing a nonce.
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider = factory::create_provider_from_name("SoftwareProvider", impl_config)
+                .expect("Failed initializing SoftwareProvider");
+            let loaded = provider.load_key(key.id()?)?;
+            let (_, loaded_iv) = loaded.encrypt_with_counter_nonce(b"third")?;
+            let loaded_counter = u64::from_be_bytes(loaded_iv[loaded_iv.len() - 8..].try_into()?);
+            assert!(loaded_counter >= 3);
+
+            let (_, next_iv) = key.encrypt_with_counter_nonce(b"fourth")?;
+            assert_ne!(next_iv, loaded_iv);
+
+            let id = key.id()?;
+            loaded.delete()?;
+            assert!(provider.load_key(id).is_err());
+
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_counter_nonce_ephemeral_key() -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                ephemeral: true,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+
+            assert!(key.encrypt_with_counter_nonce(b"data").is_err());
+            key.encrypt(b"data")?;
+            key.clone().encrypt(b"data")?;
+            assert_eq!(key.encryption_usage()?.encryptions, 2);
+
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_usage_limit() -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                cipher: Cipher::AesGcm256,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+            let limit = spec.cipher.usage_limit(false);
+            assert_eq!(key.encryption_usage()?.random_nonce_limit, limit);
+
+            // Simulate a key which was used up to the limit of random nonces.
+            let storage_manager = StorageManager::new("SoftwareProvider".to_owned(), unsafe {
+                &STORE.impl_config().additional_config
+            })?
+            .expect("Failed initializing StorageManager")
+            .sub_scope("KeyUsage");
+            let counter_id = format!("{}/1", key.id()?);
+            storage_manager.store(
+                counter_id.clone(),
+                KeyData {
+                    id: counter_id,
+                    secret_data: None,
+                    public_data: None,
+                    additional_data: Some(limit.to_be_bytes().to_vec()),
+                    spec: Spec::KeySpec(spec),
+                },
+            )?;
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider = factory::create_provider_from_name("SoftwareProvider", impl_config)
+                .expect("Failed initializing SoftwareProvider");
+            let key = provider.load_key(key.id()?)?;
+
+            let error = key.encrypt(b"data").unwrap_err();
+            assert!(matches!(
+                error.error_kind(),
+                CalErrorKind::KeyUsageLimitReached { .. }
+            ));
+            assert!(key.encrypt_with_iv(b"data", &[0; 12]).is_err());
+
+            let (ciphertext, iv) = key.encrypt_with_counter_nonce(b"data")?;
+            assert_eq!(key.decrypt_data(&ciphertext, &iv)?, b"data");
+
+            // The new version of a rotated key is counted from zero.
+            let key = provider.rotate_key(key.id()?)?;
+            key.encrypt(b"data")?;
+            assert_eq!(key.encryption_usage()?.encryptions, 1);
+
+            Ok(())
+        }
+
+        #[test_case(Cipher::AesGcm256)]
+        #[test_case(Cipher::XChaCha20Poly1305)]
+        #[instrument]
+        fn test_rotate_key(cipher: Cipher) -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                cipher,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+            let id = key.id()?;
+            let first = key.encrypt_versioned(b"first")?;
+            assert_eq!(first[..4], 1u32.to_be_bytes());
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider = factory::create_provider_from_name("SoftwareProvider", impl_config)
+                .expect("Failed initializing SoftwareProvider");
+            let rotated = provider.rotate_key(id.clone())?;
+            assert_eq!(rotated.id()?, id);
+            assert_eq!(provider.key_versions(id.clone())?, vec![1, 2]);
+            assert_ne!(rotated.extract_key()?, key.extract_key()?);
+
+            // Handles loaded before the rotation encrypt with the newest version as well.
+            let second = rotated.encrypt_versioned(b"second")?;
+            let stale = key.encrypt_versioned(b"stale")?;
+            assert_eq!(second[..4], 2u32.to_be_bytes());
+            assert_eq!(stale[..4], 2u32.to_be_bytes());
+
+            let loaded = provider.load_key(id.clone())?;
+            for handle in [&key, &rotated, &loaded] {
+                assert_eq!(handle.decrypt_versioned(&first)?, b"first");
+                assert_eq!(handle.decrypt_versioned(&second)?, b"second");
+                assert_eq!(handle.decrypt_versioned(&stale)?, b"stale");
+            }
+
+            // Old versions are only removed explicitly.
+            assert!(provider.retire_key_version(id.clone(), 2).is_err());
+            provider.retire_key_version(id.clone(), 1)?;
+            assert!(provider.retire_key_version(id.clone(), 1).is_err());
+            assert_eq!(provider.key_versions(id.clone())?, vec![2]);
+            assert!(loaded.decrypt_versioned(&first).is_err());
+            assert_eq!(loaded.decrypt_versioned(&second)?, b"second");
+
+            let mut third = provider
+                .rotate_key(id.clone())?
+                .encrypt_versioned(b"third")?;
+            assert_eq!(third[..4], 3u32.to_be_bytes());
+            third[..4].copy_from_slice(&4u32.to_be_bytes());
+            assert!(loaded.decrypt_versioned(&third).is_err());
+
+            loaded.delete()?;
+            assert!(provider.load_key(id.clone()).is_err());
+            assert!(provider.key_versions(id).is_err());
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_rotate_key_keeps_nonces_of_stale_handles() -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                cipher: Cipher::AesGcm256,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+            let id = key.id()?;
+            key.encrypt_with_counter_nonce(b"first")?;
+
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider = factory::create_provider_from_name("SoftwareProvider", impl_config)
+                .expect("Failed initializing SoftwareProvider");
+            let stale = provider.load_key(id.clone())?;
+            let rotated = provider.rotate_key(id.clone())?;
+
+            // Handles loaded before the rotation must not encrypt with the previous version.
+            assert!(key.encrypt_with_counter_nonce(b"data").is_err());
+            assert!(stale.encrypt_with_counter_nonce(b"data").is_err());
+            assert!(stale.encrypt(b"data").is_err());
+
+            // The newest version continues its own counter, whichever handle encrypts with it.
+            let iv_len = spec.cipher.iv_len();
+            let mut ivs = vec![];
+            for _ in 0..3 {
+                ivs.push(rotated.encrypt_with_counter_nonce(b"data")?.1);
+                ivs.push(stale.encrypt_versioned(b"data")?[4..4 + iv_len].to_vec());
+                ivs.push(key.encrypt_versioned(b"data")?[4..4 + iv_len].to_vec());
+            }
+            ivs.push(
+                provider
+                    .load_key(id.clone())?
+                    .encrypt_with_counter_nonce(b"data")?
+                    .1,
+            );
+            ivs.sort();
+            ivs.dedup();
+            assert_eq!(ivs.len(), 10);
+
+            // Usage of stale handles is charged to their own version, not to the new one.
+            assert_eq!(rotated.encryption_usage()?.encryptions, 3);
+            assert_eq!(key.encryption_usage()?.encryptions, 1);
+
+            rotated.delete()?;
+            Ok(())
+        }
+
+        // RFC 3394, sections 4.1 and 4.6.
+        #[test_case(
+            "000102030405060708090A0B0C0D0E0F",
+            Cipher::AesGcm128,
+            "00112233445566778899AABBCCDDEEFF",
+            "1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5"
+        )]
+        #[test_case(
+            "000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F",
+            Cipher::AesGcm256,
+            "00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F",
+            "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21"
+        )]
+        #[instrument]
+        fn test_wrap_key(kek: &str, cipher: Cipher, key: &str, wrapped: &str) -> Result<()> {
+            setup();
+            let impl_config = unsafe { STORE.impl_config().clone() };
+            let mut provider = factory::create_provider_from_name("SoftwareProvider", impl_config)
+                .expect("Failed initializing SoftwareProvider");
+            let spec = KeySpec {
+                cipher,
+                ephemeral: true,
+                ..Default::default()
+            };
+            let kek = provider.import_key(spec, &hex::decode(kek)?)?;
+
+            let mut wrapped = hex::decode(wrapped)?;
+            assert_eq!(kek.wrap_key(&hex::decode(key)?)?, wrapped);
+            assert_eq!(kek.unwrap_key(&wrapped)?, hex::decode(key)?);
+
+            wrapped[0] ^= 1;
+            assert!(kek.unwrap_key(&wrapped).is_err());
+            assert!(kek.wrap_key(&[0; 12]).is_err());
+            assert!(kek.unwrap_key(&wrapped[..16]).is_err());
+
+            let kek = provider.import_key(
+                KeySpec {
+                    key_usage: KeyUsage::ENCRYPT | KeyUsage::DECRYPT,
+                    ..spec
+                },
+                &kek.extract_key()?,
+            )?;
+            let error = kek.wrap_key(&[0; 16]).unwrap_err();
+            assert!(matches!(
+                error.error_kind(),
+                CalErrorKind::KeyUsageNotAllowed { .. }
+            ));
+            Ok(())
+        }
+
+        #[test_case(Cipher::AesCbc128)]
+        #[test_case(Cipher::AesCbc256)]
+        #[instrument]
+        fn test_encrypt_decrypt_cbc(cipher: Cipher) -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                cipher,
+                ephemeral: true,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+
+            for len in [0, 15, 16, 33] {
+                let plaintext = vec![0x2a; len];
+                let ciphertext = key.encrypt_cbc(&plaintext, &[7; 16])?;
+                assert_eq!(ciphertext.len(), (len / 16 + 1) * 16);
+                assert_eq!(key.decrypt_cbc(&ciphertext, &[7; 16])?, plaintext);
+            }
+
+            let ciphertext = key.encrypt_cbc(b"data", &[7; 16])?;
+            assert!(key.encrypt_cbc(b"data", &[7; 12]).is_err());
+            assert!(key.decrypt_cbc(&ciphertext, &[7; 12]).is_err());
+
+            // Unauthenticated CBC is not reachable through the public API.
+            assert!(key.encrypt(b"data").is_err());
+            assert!(key.encrypt_with_iv(b"data", &[7; 16]).is_err());
+            assert!(key.decrypt_data(&ciphertext, &[7; 16]).is_err());
+            assert!(key.encrypt_with_counter_nonce(b"data").is_err());
+            Ok(())
+        }
+
+        #[test]
+        #[instrument]
+        fn test_versioned_ephemeral_key() -> Result<()> {
+            setup();
+            let spec = KeySpec {
+                ephemeral: true,
+                ..Default::default()
+            };
+            let key = create_software_key_handle(spec)?;
+
+            let encrypted = key.encrypt_versioned(b"data")?;
+            assert_eq!(encrypted[..4], 1u32.to_be_bytes());
+            assert_eq!(key.decrypt_versioned(&encrypted)?, b"data");
+            assert!(key.decrypt_versioned(&encrypted[..4]).is_err());
+
+            let mut provider = factory::create_provider_from_name(
+                "SoftwareProvider",
+                ProviderImplConfig {
+                    additional_config: vec![],
+                },
+            )
+            .expect("Failed initializing SoftwareProvider");
+            assert!(provider.rotate_key(key.id()?).is_err());
+
+            Ok(())
+        }
     }
 }
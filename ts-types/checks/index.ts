@@ -11,8 +11,11 @@ import type {
 	KeyPairSpec,
 	KeySpec,
 	KeyType,
+	PasswordVerification,
+	Pbkdf2Options,
 	ProviderConfig,
 	ProviderImplConfig,
+	ScryptOptions,
 	SecurityLevel,
 	Spec,
 } from "../generated/index.js";
@@ -75,11 +78,21 @@ export const assertSpec = createAssertGuard<Spec>();
 export const isKeyType = createIs<KeyType>();
 export const assertKeyType = createAssertGuard<KeyType>();
 
+export const isPasswordVerification = createIs<PasswordVerification>();
+export const assertPasswordVerification =
+	createAssertGuard<PasswordVerification>();
+
+export const isPbkdf2Options = createIs<Pbkdf2Options>();
+export const assertPbkdf2Options = createAssertGuard<Pbkdf2Options>();
+
 export const isProviderConfig = createIs<ProviderConfig>();
 export const assertProviderConfig = createAssertGuard<ProviderConfig>();
 
 export const isProviderImplConfig = createIs<ProviderImplConfig>();
 export const assertProviderImplConfig = createAssertGuard<ProviderImplConfig>();
 
+export const isScryptOptions = createIs<ScryptOptions>();
+export const assertScryptOptions = createAssertGuard<ScryptOptions>();
+
 export const isSecurityLevel = createIs<SecurityLevel>();
 export const assertSecurityLevel = createAssertGuard<SecurityLevel>();
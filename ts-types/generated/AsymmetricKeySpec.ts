@@ -29,4 +29,27 @@ export type AsymmetricKeySpec =
   | "BrainpoolP638"
   | "Curve25519"
   | "Curve448"
-  | "Frp256v1";
+  | "Frp256v1"
+  | "MlKem512"
+  | "MlKem768"
+  | "MlKem1024"
+  | "MlDsa44"
+  | "MlDsa65"
+  | "MlDsa87"
+  | "SlhDsaSha2_128s"
+  | "SlhDsaSha2_128f"
+  | "SlhDsaSha2_192s"
+  | "SlhDsaSha2_192f"
+  | "SlhDsaSha2_256s"
+  | "SlhDsaSha2_256f"
+  | "SlhDsaShake128s"
+  | "SlhDsaShake128f"
+  | "SlhDsaShake192s"
+  | "SlhDsaShake192f"
+  | "SlhDsaShake256s"
+  | "SlhDsaShake256f"
+  | "X25519MlKem768"
+  | "MlDsa44Ed25519"
+  | "MlDsa65Ed25519"
+  | "MlDsa44EcdsaP256"
+  | "MlDsa65EcdsaP256";
@@ -46,4 +46,8 @@ export type CalErrorKind =
   | "NonExportable"
   | { "UnsupportedAlgorithm": string }
   | "EphemeralKeyError"
+  | { "KeyUsageLimitReached": { key_id: string } }
+  | { "KeyUsageNotAllowed": { key_id: string; usage: string } }
+  | { "KeyNotYetValid": { key_id: string } }
+  | { "KeyExpired": { key_id: string } }
   | "Other";
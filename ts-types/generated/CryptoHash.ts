@@ -22,4 +22,5 @@ export type CryptoHash =
   | "Sha3_256"
   | "Sha3_384"
   | "Sha3_512"
-  | "Blake2b";
+  | "Blake2b"
+  | "Blake2s";
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Encryptions done with a symmetric key and the safety limits of its cipher.
+ *
+ * See [KeyHandle::encryption_usage](crate::common::KeyHandle::encryption_usage).
+ */
+export type EncryptionUsage = {
+  /**
+   * Number of encryptions, which may include encryptions reserved but not used before a restart.
+   */
+  encryptions: bigint;
+  /**
+   * Maximum number of encryptions with random nonces.
+   */
+  random_nonce_limit: bigint;
+  /**
+   * Maximum number of encryptions with counter nonces.
+   */
+  counter_nonce_limit: bigint;
+};
@@ -1,5 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Argon2Options } from "./Argon2Options";
+import type { Pbkdf2Options } from "./Pbkdf2Options";
+import type { ScryptOptions } from "./ScryptOptions";
 
 /**
  * Enum representing different secure key derivation functions
@@ -31,4 +33,6 @@ import type { Argon2Options } from "./Argon2Options";
 export type KDF =
   | { "Argon2d": Argon2Options }
   | { "Argon2id": Argon2Options }
-  | { "Argon2i": Argon2Options };
+  | { "Argon2i": Argon2Options }
+  | { "Scrypt": ScryptOptions }
+  | { "Pbkdf2": Pbkdf2Options };
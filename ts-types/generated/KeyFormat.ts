@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PkiStandards } from "./PkiStandards";
+
+/**
+ * Encoding of imported and exported asymmetric keys.
+ *
+ * [KeyFormat::Raw] is the bare key material of the algorithm:
+ * - elliptic curve private keys are the big-endian scalar, public keys the SEC 1 point in the
+ *   [PointEncoding](crate::common::crypto::algorithms::encryption::PointEncoding) of the spec,
+ * - `Curve25519` private keys are the 32 byte Ed25519 seed (or X25519 scalar), public keys 32 bytes,
+ * - `RSA` keys are the PKCS#1 `RSAPrivateKey` and `RSAPublicKey` structures,
+ * - other algorithms use the encoding of their specification.
+ *
+ * The supported standards are PKCS#8 v1 and v2 and SEC 1 for private keys and
+ * `SubjectPublicKeyInfo` for public keys, all DER encoded. [KeyFormat::PEM] exports private
+ * keys as PKCS#8 and public keys as `SubjectPublicKeyInfo`, on import the format is taken from
+ * the PEM label.
+ *
+ * # Examples
+ *
+ * ```rust
+ * use crypto_layer::common::crypto::pkcs::standards::{KeyFormat, PkcsType, PkiStandards};
+ *
+ * let format = KeyFormat::Pki(PkiStandards::Pkcs(PkcsType::Pkcs8));
+ * assert_eq!(format, KeyFormat::PKCS8);
+ * ```
+ */
+export type KeyFormat = "Raw" | { "Pki": PkiStandards };
@@ -2,6 +2,10 @@
 import type { AsymmetricKeySpec } from "./AsymmetricKeySpec";
 import type { Cipher } from "./Cipher";
 import type { CryptoHash } from "./CryptoHash";
+import type { KeyUsage } from "./KeyUsage";
+import type { PointEncoding } from "./PointEncoding";
+import type { RsaPadding } from "./RsaPadding";
+import type { SignatureEncoding } from "./SignatureEncoding";
 
 /**
  * Struct used to configure key pairs.
@@ -22,6 +26,20 @@ export type KeyPairSpec = {
    * Hash function used for signing and encrypting.
    */
   signing_hash: CryptoHash;
+  /**
+   * Padding of signatures with `RSA` key pairs, ignored for other algorithms.
+   */
+  rsa_padding: RsaPadding;
+  /**
+   * Encoding of signatures with ECDSA key pairs, ignored for other algorithms.
+   */
+  signature_encoding: SignatureEncoding;
+  /**
+   * Encoding of the elliptic curve points returned as public key, ignored for other
+   * algorithms. Imports accept both encodings. Providers that cannot compress points refuse
+   * [PointEncoding::Compressed].
+   */
+  point_encoding: PointEncoding;
   /**
    * If set to true, the key pair will be discarded after the handle is dropped.
    */
@@ -30,4 +48,23 @@ export type KeyPairSpec = {
    * If set to true, the key can't be exported (also software keys)
    */
   non_exportable: boolean;
+  /**
+   * Unix time in seconds before which the key pair cannot be used.
+   */
+  not_before: bigint | null;
+  /**
+   * Unix time in seconds after which the key pair cannot be used to protect data, e.g. sign.
+   */
+  not_after: bigint | null;
+  /**
+   * Unix time in seconds after which the key pair cannot be used to process data either,
+   * e.g. verify signatures.
+   *
+   * If unset, data protected with the key pair can be processed after [KeyPairSpec::not_after].
+   */
+  process_not_after: bigint | null;
+  /**
+   * Operations the key pair may be used for.
+   */
+  key_usage: KeyUsage;
 };
@@ -1,6 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Cipher } from "./Cipher";
 import type { CryptoHash } from "./CryptoHash";
+import type { KeyUsage } from "./KeyUsage";
 
 /**
  * Struct used to configure keys.
@@ -27,4 +28,22 @@ export type KeySpec = {
    * Some providers do not allow exporting keys at all, even if set to `false`.
    */
   non_exportable: boolean;
+  /**
+   * Unix time in seconds before which the key cannot be used.
+   */
+  not_before: bigint | null;
+  /**
+   * Unix time in seconds after which the key cannot be used to protect data, e.g. encrypt.
+   */
+  not_after: bigint | null;
+  /**
+   * Unix time in seconds after which the key cannot be used to process data either, e.g. decrypt.
+   *
+   * If unset, data protected with the key can be processed after [KeySpec::not_after].
+   */
+  process_not_after: bigint | null;
+  /**
+   * Operations the key may be used for.
+   */
+  key_usage: KeyUsage;
 };
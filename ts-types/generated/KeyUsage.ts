@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Operations a key or key pair may be used for, as a bitset.
+ *
+ * Usages are combined with `|`, e.g. `KeyUsage::ENCRYPT | KeyUsage::DECRYPT`.
+ * The default allows all operations.
+ */
+export type KeyUsage = number;
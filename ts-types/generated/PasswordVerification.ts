@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of verifying a password against a stored PHC string.
+ */
+export type PasswordVerification = {
+  /**
+   * `true` if the password matches the stored hash.
+   */
+  valid: boolean;
+  /**
+   * `true` if the stored hash was not created with the requested [KDF] and parameters.
+   *
+   * If the password is valid, the password should be hashed again and the stored hash replaced.
+   */
+  needs_rehash: boolean;
+};
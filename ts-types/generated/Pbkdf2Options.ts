@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CryptoHash } from "./CryptoHash";
+
+/**
+ * Configuration for KDF with PBKDF2
+ *
+ * When in doubt use the default.
+ */
+export type Pbkdf2Options = {
+  /**
+   * Number of iterations
+   */
+  iterations: number;
+  /**
+   * Hash function used with HMAC as pseudo random function
+   */
+  hash: CryptoHash;
+};
@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Enumerates the PKCS (Public-Key Cryptography Standards) types.
+ *
+ * Specifies the different PKCS standards, covering a wide array of cryptographic
+ * functionalities, including key exchange, encryption, and digital signatures.
+ *
+ * # Examples
+ *
+ * Selecting a PKCS type for RSA cryptography:
+ *
+ * ```rust
+ * use crypto_layer::common::crypto::pkcs::standards::PkcsType;
+ *
+ * let pkcs_type = PkcsType::Pkcs1;
+ * ```
+ *
+ * # Note
+ *
+ * `#[repr(C)]` attribute for C compatibility.
+ */
+export type PkcsType =
+  | "Pkcs1"
+  | "Pkcs3"
+  | "Pkcs5"
+  | "Pkcs7"
+  | "Pkcs8"
+  | "Pkcs9"
+  | "Pkcs10"
+  | "Pkcs11"
+  | "Pkcs12"
+  | "Pkcs8V2";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Encoding of elliptic curve points in public keys of the SEC 1 curves `P256` and `P384`.
+ */
+export type PointEncoding = "Uncompressed" | "Compressed";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Padding scheme of RSA signatures.
+ *
+ * RSA encryption always uses OAEP with the hash of the key pair.
+ */
+export type RsaPadding = "Pkcs1v15" | "Pss";
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Configuration for KDF with scrypt
+ *
+ * When in doubt use the default.
+ */
+export type ScryptOptions = {
+  /**
+   * Binary logarithm of the CPU/memory cost `N`
+   */
+  log_n: number;
+  /**
+   * Block size
+   */
+  r: number;
+  /**
+   * Degree of parallelism
+   */
+  p: number;
+};
@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Enumerates the types within the X.509 standard.
+ *
+ * Specifies the different elements associated with X.509, a standard for creating
+ * a public key infrastructure for digital certificates and public-key encryption.
+ *
+ * # Examples
+ *
+ * Selecting an X.509 type for certificates:
+ *
+ * ```rust
+ * use crypto_layer::common::crypto::pkcs::standards::X509Type;
+ *
+ * let x509_type = X509Type::Certificate;
+ * ```
+ *
+ * # Note
+ *
+ * Uses `#[repr(C)]` for compatibility with C language standards, facilitating use
+ * in systems where interoperability with C is required.
+ */
+export type X509Type =
+  | "Certificate"
+  | "CertificateRevocationList"
+  | "CertificateSigningRequest"
+  | "SubjectPublicKeyInfo";
@@ -6,14 +6,26 @@ export * from './CalErrorKind';
 export * from './Cipher';
 export * from './CryptoHash';
 export * from './DHExchange';
+export * from './EncryptionUsage';
 export * from './KDF';
+export * from './KeyFormat';
 export * from './KeyHandle';
 export * from './KeyPairHandle';
 export * from './KeyPairSpec';
 export * from './KeySpec';
 export * from './KeyType';
+export * from './KeyUsage';
+export * from './PasswordVerification';
+export * from './Pbkdf2Options';
+export * from './PkcsType';
+export * from './PkiStandards';
+export * from './PointEncoding';
 export * from './Provider';
 export * from './ProviderConfig';
 export * from './ProviderImplConfig';
+export * from './RsaPadding';
+export * from './ScryptOptions';
 export * from './SecurityLevel';
+export * from './SignatureEncoding';
 export * from './Spec';
+export * from './X509Type';
@@ -3,6 +3,7 @@ import type { KeyHandle } from "./index.ts";
 // biome-ignore lint/style/useNamingConvention: <explanation>
 export type DHExchange = {
 	getPublicKey: () => Promise<Uint8Array>;
+	computeSharedSecret: (peerPublicKey: Uint8Array) => Promise<Uint8Array>;
 	// addExternal: (externalKey: Uint8Array) => Promise<Uint8Array>;
 	// addExternalFinal: (externalKey: Uint8Array) => Promise<KeyHandle>;
 	deriveClientSessionKeys: (
@@ -17,4 +18,9 @@ export type DHExchange = {
 	deriveServerKeyHandles: (
 		clientPk: Uint8Array,
 	) => Promise<[KeyHandle, KeyHandle]>;
+	keyConfirmation: (transcript?: Uint8Array) => Promise<Uint8Array>;
+	verifyKeyConfirmation: (
+		peerConfirmation: Uint8Array,
+		transcript?: Uint8Array,
+	) => Promise<boolean>;
 };
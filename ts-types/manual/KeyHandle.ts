@@ -1,15 +1,36 @@
-import type { KeySpec } from "../generated/index.ts";
+import type { Cipher, EncryptionUsage, KDF, KeySpec } from "../generated/index.ts";
 
 export type KeyHandle = {
 	extractKey: () => Promise<Uint8Array>;
+	exportEncryptedKey: (
+		password: string,
+		kdf: KDF,
+		cipher: Cipher,
+	) => Promise<Uint8Array>;
 	/** @deprecated In favor of the more explicit {@link KeyHandle.encrypt} and {@link KeyHandle.encryptWithIv} methods. */
 	encryptData: (data: Uint8Array, iv: Uint8Array) => Promise<[Uint8Array, Uint8Array]>;
 	encrypt: (data: Uint8Array) => Promise<[Uint8Array, Uint8Array]>;
 	encryptWithIv: (data: Uint8Array, iv: Uint8Array) => Promise<Uint8Array>;
+	encryptWithCounterNonce: (
+		data: Uint8Array,
+	) => Promise<[Uint8Array, Uint8Array]>;
+	encryptionUsage: () => Promise<EncryptionUsage>;
+	encryptVersioned: (data: Uint8Array) => Promise<Uint8Array>;
+	decryptVersioned: (encryptedData: Uint8Array) => Promise<Uint8Array>;
 	decryptData: (
 		encryptedData: Uint8Array,
 		iv: Uint8Array,
 	) => Promise<Uint8Array>;
+	encryptWithAad: (
+		data: Uint8Array,
+		iv: Uint8Array,
+		aad: Uint8Array,
+	) => Promise<Uint8Array>;
+	decryptWithAad: (
+		encryptedData: Uint8Array,
+		iv: Uint8Array,
+		aad: Uint8Array,
+	) => Promise<Uint8Array>;
 	id: () => Promise<string>;
 	delete: () => Promise<void>;
 	spec: () => Promise<KeySpec>;
@@ -1,5 +1,6 @@
-import type { KeyPairSpec } from "../generated/index.ts";
+import type { Cipher, KDF, KeyFormat, KeyPairSpec } from "../generated/index.ts";
 import type { DHExchange } from "./DHExchange.ts";
+import type { KeyHandle } from "./index.ts";
 
 export type KeyPairHandle = {
 	encryptData: (data: Uint8Array) => Promise<Uint8Array>;
@@ -11,6 +12,15 @@ export type KeyPairHandle = {
 	) => Promise<boolean>;
 	getPublicKey: () => Promise<Uint8Array>;
 	extractKey: () => Promise<Uint8Array>;
+	exportPrivateKey: (format: KeyFormat) => Promise<Uint8Array>;
+	exportPublicKey: (format: KeyFormat) => Promise<Uint8Array>;
+	exportEncryptedPrivateKey: (
+		password: string,
+		kdf: KDF,
+		cipher: Cipher,
+	) => Promise<Uint8Array>;
+	encapsulate: (publicKey: Uint8Array) => Promise<[Uint8Array, KeyHandle]>;
+	decapsulate: (ciphertext: Uint8Array) => Promise<KeyHandle>;
 	id: () => Promise<string>;
 	delete: () => Promise<void>;
 	spec: () => Promise<KeyPairSpec>;
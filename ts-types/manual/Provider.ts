@@ -1,8 +1,10 @@
 import type {
 	CryptoHash,
 	KDF,
+	KeyFormat,
 	KeyPairSpec,
 	KeySpec,
+	PasswordVerification,
 	ProviderConfig,
 	Spec,
 } from "../generated/index.ts";
@@ -23,6 +25,26 @@ export type Provider = {
 		spec: KeyPairSpec,
 		publicKey: Uint8Array,
 	) => Promise<KeyPairHandle>;
+	importKeyPairWithFormat: (
+		spec: KeyPairSpec,
+		privateKey: Uint8Array,
+		format: KeyFormat,
+	) => Promise<KeyPairHandle>;
+	importPublicKeyWithFormat: (
+		spec: KeyPairSpec,
+		publicKey: Uint8Array,
+		format: KeyFormat,
+	) => Promise<KeyPairHandle>;
+	importEncryptedKey: (
+		spec: KeySpec,
+		data: Uint8Array,
+		password: string,
+	) => Promise<KeyHandle>;
+	importEncryptedKeyPair: (
+		spec: KeyPairSpec,
+		data: Uint8Array,
+		password: string,
+	) => Promise<KeyPairHandle>;
 	startEphemeralDhExchange: (spec: KeyPairSpec) => Promise<DHExchange>;
 	/** @deprecated Non ephemeral dh exchange might be insecure. */
 	dhExchangeFromKeys: (
@@ -38,6 +60,12 @@ export type Provider = {
 		algorithm: KeySpec,
 		kdf: KDF,
 	) => Promise<KeyHandle>;
+	hashPassword: (password: string, kdf: KDF) => Promise<string>;
+	verifyPassword: (
+		password: string,
+		passwordHash: string,
+		kdf: KDF,
+	) => Promise<PasswordVerification>;
 	/** @deprecated Deprecated in favor of {@link KeyHandle.deriveKey} */
 	deriveKeyFromBase(
 		baseKey: Uint8Array,
@@ -48,4 +76,11 @@ export type Provider = {
 	getRandom: (len: number) => Promise<Uint8Array>;
 	hash: (input: Uint8Array, hash: CryptoHash) => Promise<Uint8Array>;
 	getAllKeys: () => Promise<[string, Spec][]>;
+	getExpiringKeys: (within: bigint) => Promise<[string, Spec][]>;
+	storeSecretData: (id: string, data: Uint8Array) => Promise<void>;
+	loadSecretData: (id: string) => Promise<Uint8Array>;
+	deleteSecretData: (id: string) => Promise<void>;
+	rotateKey: (keyId: string) => Promise<KeyHandle>;
+	retireKeyVersion: (keyId: string, version: number) => Promise<void>;
+	keyVersions: (keyId: string) => Promise<number[]>;
 };